@@ -0,0 +1,470 @@
+//! A headless game that exercises every feature of the crate against a scripted fake editor.
+//!
+//! The fake editor runs on a background thread and talks to the game over UDP, the same as a
+//! real editor would. It waits for the handshake and the first snapshots, then edits components
+//! and resources, creates and destroys entities, and checks that each edit is acknowledged and
+//! reflected in later snapshots. The demo exits with an error if any step fails, so it doubles
+//! as a smoke test: compare the messages your editor receives against the ones checked here.
+//!
+//! Run it with `cargo run --example editor_demo`.
+
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+#[macro_use]
+extern crate log;
+extern crate serde;
+#[macro_use]
+extern crate serde_json;
+extern crate tap;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use serde::*;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tap::*;
+
+/// How long the fake editor waits for each expected message before giving up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Player {
+    name: String,
+
+    // Redacted, so it should never show up in the editor.
+    token: String,
+}
+
+impl Component for Player {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Score {
+    points: u32,
+}
+
+/// Read-only resource, since it can't be deserialized.
+#[derive(Debug, Clone, Serialize)]
+struct GameInfo {
+    title: &'static str,
+    player: Option<SerializableEntity>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tick {
+    frame: u64,
+}
+
+struct DemoState {
+    connection: EditorConnection,
+    done: Arc<AtomicBool>,
+    frame: u64,
+}
+
+impl SimpleState for DemoState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        let world = data.world;
+        world.register::<Position>();
+        world.register::<Player>();
+
+        let player = world
+            .create_entity()
+            .with(Position { x: 0.0, y: 0.0 })
+            .with(Player {
+                name: "player one".into(),
+                token: "super secret".into(),
+            })
+            .build();
+        for index in 0..10 {
+            world
+                .create_entity()
+                .with(Position {
+                    x: index as f32,
+                    y: 0.0,
+                })
+                .build();
+        }
+
+        world.add_resource(Score::default());
+        world.add_resource(GameInfo {
+            title: "Editor demo",
+            player: Some(player.into()),
+        });
+    }
+
+    fn update(&mut self, data: &mut StateData<GameData>) -> SimpleTrans {
+        data.data.update(&data.world);
+
+        self.frame += 1;
+        if self.frame % 60 == 0 {
+            info!("Frame {}", self.frame);
+            self.connection
+                .send_message("demo_tick", Tick { frame: self.frame });
+        }
+
+        // Don't spin faster than a real game would.
+        thread::sleep(Duration::from_millis(5));
+
+        if self.done.load(Ordering::SeqCst) {
+            Trans::Quit
+        } else {
+            Trans::None
+        }
+    }
+}
+
+fn main() -> amethyst::Result<()> {
+    let editor_socket = UdpSocket::bind("127.0.0.1:0")?;
+    let transport = Transport::udp("127.0.0.1:0")?.with_editor_address(editor_socket.local_addr()?);
+    let game_address = transport.local_addr()?;
+
+    let editor_bundle = SyncEditorBundle::new()
+        .tap(|bundle| sync_components!(bundle, Position, Player))
+        .tap(|bundle| sync_resources!(bundle, Score))
+        .tap(|bundle| read_resources!(bundle, GameInfo))
+        .tap(|bundle| bundle.redact_fields("Player", &["token"]))
+        .tap(|bundle| bundle.register_message("demo_tick", Tick { frame: 0 }))
+        .tap(|bundle| bundle.alert_on_entity_count(1000))
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(50)))
+        .tap(|bundle| bundle.transport(transport));
+    let connection = editor_bundle.connection();
+    EditorLogger::new(&editor_bundle).start();
+
+    let done = Arc::new(AtomicBool::new(false));
+    let editor_done = done.clone();
+    let editor = thread::spawn(move || {
+        let result = FakeEditor::new(editor_socket, game_address).run_script();
+        editor_done.store(true, Ordering::SeqCst);
+        result
+    });
+
+    let state = DemoState {
+        connection,
+        done,
+        frame: 0,
+    };
+    let game_data = GameDataBuilder::default().with_bundle(editor_bundle)?;
+    let mut game = Application::build(".", state)?.build(game_data)?;
+    game.run();
+
+    match editor.join().expect("Fake editor panicked") {
+        Ok(()) => {
+            println!("All checks passed");
+            Ok(())
+        }
+
+        Err(error) => {
+            eprintln!("Check failed: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// A minimal editor that follows a fixed script.
+struct FakeEditor {
+    socket: UdpSocket,
+    game_address: SocketAddr,
+    incoming: Vec<u8>,
+    next_message_id: u32,
+
+    // The chunks received so far of messages that haven't fully arrived, by message ID.
+    chunks: HashMap<u32, Vec<Option<Vec<u8>>>>,
+
+    // Every snapshot and message received so far.
+    snapshots: Vec<Value>,
+    messages: Vec<Value>,
+}
+
+impl FakeEditor {
+    fn new(socket: UdpSocket, game_address: SocketAddr) -> Self {
+        socket
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .expect("Failed to set read timeout");
+        FakeEditor {
+            socket,
+            game_address,
+            incoming: Vec::new(),
+            next_message_id: 0,
+            chunks: HashMap::new(),
+            snapshots: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    fn run_script(&mut self) -> Result<(), String> {
+        let handshake = self.wait_for_message("handshake", |_| true)?;
+        check(
+            handshake["components"] == json!(["Position", "Player"]),
+            "handshake lists the registered components",
+        )?;
+        check(
+            handshake["redacted"]["Player"] == json!(["token"]),
+            "handshake lists redacted fields",
+        )?;
+        check(
+            handshake["messages"]["demo_tick"].is_object(),
+            "handshake lists custom messages",
+        )?;
+
+        self.wait_for_message("system_graph", |_| true)?;
+        self.wait_for_message("log", |_| true)?;
+        self.wait_for_message("demo_tick", |_| true)?;
+
+        // Find the player in a snapshot, and make sure its token was redacted.
+        let snapshot = self.wait_for_snapshot("player", |snapshot| {
+            !component_data(snapshot, "Player").is_empty()
+        })?;
+        let (player_id, player) = component_data(&snapshot, "Player")
+            .into_iter()
+            .next()
+            .unwrap();
+        check(player.get("token").is_none(), "redacted fields aren't sent")?;
+        check(
+            resource_data(&snapshot, "GameInfo").is_some(),
+            "read-only resources are sent",
+        )?;
+        let generation = snapshot["entities"]
+            .as_array()
+            .and_then(|entities| entities.iter().find(|entity| entity["id"] == player_id))
+            .map(|entity| entity["generation"].clone())
+            .ok_or("player missing from the entity list")?;
+        let player_entity = json!({ "id": player_id, "generation": generation });
+
+        // Edit a component, a redacted component, and a resource.
+        self.send(json!({
+            "type": "ComponentUpdate",
+            "id": "Position",
+            "entity": player_entity,
+            "data": { "x": 42.0, "y": -1.0 },
+            "request_id": 1,
+        }));
+        self.wait_for_ack(1, "applied")?;
+        self.wait_for_snapshot("moved player", |snapshot| {
+            component_data(snapshot, "Position")
+                .iter()
+                .any(|(id, position)| *id == player_id && position["x"] == 42.0)
+        })?;
+
+        self.send(json!({
+            "type": "ComponentUpdate",
+            "id": "Player",
+            "entity": player_entity,
+            "data": { "name": "renamed" },
+            "request_id": 2,
+        }));
+        self.wait_for_ack(2, "applied")?;
+
+        self.send(json!({
+            "type": "ResourceUpdate",
+            "id": "Score",
+            "data": { "points": 99 },
+            "request_id": 3,
+        }));
+        self.wait_for_ack(3, "applied")?;
+        self.wait_for_snapshot("updated score", |snapshot| {
+            resource_data(snapshot, "Score").map_or(false, |score| score["points"] == 99)
+        })?;
+
+        // Create and destroy entities.
+        self.send(json!({ "type": "CreateEntities", "amount": 3, "request_id": 4 }));
+        self.wait_for_ack(4, "applied")?;
+
+        self.send(json!({
+            "type": "DestroyEntities",
+            "entities": [player_entity],
+            "request_id": 5,
+        }));
+        self.wait_for_ack(5, "applied")?;
+
+        // Edits to the destroyed entity should now be rejected.
+        self.send(json!({
+            "type": "ComponentUpdate",
+            "id": "Position",
+            "entity": player_entity,
+            "data": { "x": 0.0, "y": 0.0 },
+            "request_id": 6,
+        }));
+        self.wait_for_ack(6, "stale_entity")?;
+
+        Ok(())
+    }
+
+    fn send(&mut self, message: Value) {
+        let serialized = serde_json::to_vec(&message).expect("Failed to serialize message");
+
+        // Every message fits in a single packet, so its chunk header says it's chunk 0 of 1.
+        let id = self.next_message_id;
+        self.next_message_id += 1;
+        let mut bytes = vec![
+            (id >> 24) as u8,
+            (id >> 16) as u8,
+            (id >> 8) as u8,
+            id as u8,
+            0,
+            0,
+            0,
+            1,
+        ];
+        Framing::LengthPrefixed.encode(&serialized, &mut bytes);
+        self.socket
+            .send_to(&bytes, self.game_address)
+            .expect("Failed to send message to game");
+    }
+
+    /// Adds a packet to the message it's a chunk of, which is moved to `incoming` once all of its
+    /// chunks have arrived.
+    fn push_chunk(&mut self, packet: &[u8]) {
+        if packet.len() < 8 {
+            return;
+        }
+
+        let read_be = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .fold(0, |value, &byte| value << 8 | u32::from(byte))
+        };
+        let id = read_be(&packet[0..4]);
+        let index = read_be(&packet[4..6]) as usize;
+        let count = read_be(&packet[6..8]) as usize;
+        let complete = {
+            let chunks = self.chunks.entry(id).or_insert_with(|| vec![None; count]);
+            if index >= chunks.len() {
+                return;
+            }
+            chunks[index] = Some(packet[8..].to_vec());
+            chunks.iter().all(Option::is_some)
+        };
+
+        if complete {
+            for chunk in self.chunks.remove(&id).into_iter().flatten().flatten() {
+                self.incoming.extend_from_slice(&chunk);
+            }
+        }
+    }
+
+    /// Reads any pending packets, recording the snapshots and messages they complete.
+    fn poll(&mut self) {
+        let mut buf = [0; 64 * 1024];
+        while let Ok(bytes_read) = self.socket.recv(&mut buf) {
+            self.push_chunk(&buf[..bytes_read]);
+        }
+
+        while let Some((message, consumed)) = Framing::LengthPrefixed.decode(&self.incoming) {
+            let envelope = serde_json::from_slice::<Value>(&self.incoming[message]);
+            self.incoming.drain(..consumed);
+
+            let data = match envelope {
+                Ok(mut envelope) => envelope["data"].take(),
+                Err(error) => {
+                    eprintln!("Received invalid JSON: {}", error);
+                    continue;
+                }
+            };
+            if let Some(messages) = data["messages"].as_array() {
+                self.messages.extend(messages.iter().cloned());
+            }
+            if data.get("components").is_some() {
+                self.snapshots.push(data);
+            }
+        }
+    }
+
+    fn wait_for_message<F>(&mut self, message_type: &str, predicate: F) -> Result<Value, String>
+    where
+        F: Fn(&Value) -> bool,
+    {
+        self.wait_for(&format!("{:?} message", message_type), |editor| {
+            editor
+                .messages
+                .iter()
+                .find(|message| message["type"] == message_type && predicate(&message["data"]))
+                .map(|message| message["data"].clone())
+        })
+    }
+
+    fn wait_for_snapshot<F>(&mut self, description: &str, predicate: F) -> Result<Value, String>
+    where
+        F: Fn(&Value) -> bool,
+    {
+        self.wait_for(&format!("snapshot with {}", description), |editor| {
+            editor
+                .snapshots
+                .iter()
+                .rev()
+                .find(|s| predicate(s))
+                .cloned()
+        })
+    }
+
+    fn wait_for_ack(&mut self, request_id: u64, status: &str) -> Result<(), String> {
+        let ack = self.wait_for_message("edit_ack", |ack| ack["request_id"] == request_id)?;
+        check(
+            ack["status"] == status,
+            &format!("request {} is acknowledged as {}", request_id, status),
+        )
+    }
+
+    fn wait_for<F>(&mut self, description: &str, find: F) -> Result<Value, String>
+    where
+        F: Fn(&FakeEditor) -> Option<Value>,
+    {
+        let start = Instant::now();
+        while start.elapsed() < TIMEOUT {
+            self.poll();
+            if let Some(found) = find(self) {
+                println!("ok: received {}", description);
+                return Ok(found);
+            }
+        }
+
+        Err(format!("timed out waiting for {}", description))
+    }
+}
+
+fn check(condition: bool, description: &str) -> Result<(), String> {
+    if condition {
+        println!("ok: {}", description);
+        Ok(())
+    } else {
+        Err(description.to_owned())
+    }
+}
+
+/// The serialized components of a type in a snapshot, keyed by entity ID.
+fn component_data(snapshot: &Value, name: &str) -> Vec<(u64, Value)> {
+    snapshot["components"]
+        .as_array()
+        .and_then(|components| components.iter().find(|c| c["name"] == name))
+        .and_then(|component| component["data"].as_object())
+        .map(|data| {
+            data.iter()
+                .filter_map(|(id, value)| Some((id.parse().ok()?, value.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn resource_data<'a>(snapshot: &'a Value, name: &str) -> Option<&'a Value> {
+    snapshot["resources"]
+        .as_array()?
+        .iter()
+        .find(|resource| resource["name"] == name)
+        .map(|resource| &resource["data"])
+}
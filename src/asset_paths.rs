@@ -0,0 +1,42 @@
+use amethyst::assets::{Asset, Handle};
+use std::collections::HashMap;
+
+/// The paths that assets were loaded from, shown in the editor alongside the assets synced with
+/// [`SyncEditorBundle::sync_assets`].
+///
+/// Amethyst doesn't keep track of where assets were loaded from, so the game has to record the
+/// path of each asset it wants the editor to show by name. Assets without a recorded path are
+/// only listed by their handle ID. Since handle IDs are reused once all handles to an asset have
+/// been dropped, paths should be removed when the game unloads an asset.
+///
+/// [`SyncEditorBundle::sync_assets`]: ./struct.SyncEditorBundle.html#method.sync_assets
+#[derive(Debug, Clone, Default)]
+pub struct AssetPaths {
+    paths: HashMap<(&'static str, u32), String>,
+}
+
+impl AssetPaths {
+    /// Records the path that the asset behind `handle` was loaded from.
+    pub fn insert<A, S>(&mut self, handle: &Handle<A>, path: S)
+    where
+        A: Asset,
+        S: Into<String>,
+    {
+        self.paths.insert((A::NAME, handle.id()), path.into());
+    }
+
+    /// Removes the path recorded for the asset behind `handle`.
+    pub fn remove<A: Asset>(&mut self, handle: &Handle<A>) {
+        self.paths.remove(&(A::NAME, handle.id()));
+    }
+
+    /// The path recorded for the asset behind `handle`, if any.
+    pub fn get<A: Asset>(&self, handle: &Handle<A>) -> Option<&str> {
+        self.paths.get(&(A::NAME, handle.id())).map(String::as_str)
+    }
+
+    /// Whether any asset was recorded as loaded from `path`.
+    pub(crate) fn contains_path(&self, path: &str) -> bool {
+        self.paths.values().any(|recorded| recorded == path)
+    }
+}
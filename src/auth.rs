@@ -0,0 +1,173 @@
+use crate::types::{EditStatus, IncomingMessage};
+use std::fmt;
+
+/// A shared secret that the editor has to send in an `Authenticate` message before the game
+/// accepts any other commands from it.
+///
+/// See [`SyncEditorBundle::require_auth_token`] for details.
+///
+/// [`SyncEditorBundle::require_auth_token`]: ../struct.SyncEditorBundle.html#method.require_auth_token
+#[derive(Clone)]
+pub(crate) struct AuthToken(String);
+
+impl AuthToken {
+    pub(crate) fn new(token: String) -> Self {
+        AuthToken(token)
+    }
+
+    /// Whether `token` is the expected token.
+    ///
+    /// NOTE: The comparison takes the same time wherever the first mismatch is, and only
+    /// depends on the length of the token that was sent, so that how long the check takes
+    /// doesn't reveal how much of the expected token was guessed correctly, or how long it is.
+    pub(crate) fn matches(&self, token: &str) -> bool {
+        let expected = self.0.as_bytes();
+        let token = token.as_bytes();
+        if expected.is_empty() {
+            return token.is_empty();
+        }
+
+        let length_diff = (expected.len() ^ token.len()) as u64;
+        let diff = token.iter().enumerate().fold(0, |diff, (index, byte)| {
+            diff | (expected[index % expected.len()] ^ byte)
+        });
+        (u64::from(diff) | length_diff) == 0
+    }
+}
+
+// NOTE: The token is left out so that it doesn't end up in logs.
+impl fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("AuthToken(..)")
+    }
+}
+
+/// Whether the editor has authenticated in the current session, shared by the bundle's receiver
+/// system and [`EditorClient`].
+///
+/// Until the editor has authenticated, only the messages that start a session are accepted. A
+/// `Handshake` starts a new session that has to be authenticated again, and is held back until
+/// the editor has authenticated.
+///
+/// [`EditorClient`]: ../struct.EditorClient.html
+#[derive(Debug, Default)]
+pub(crate) struct AuthSession {
+    token: Option<AuthToken>,
+    authenticated: bool,
+
+    // The capabilities from a handshake that's waiting for the editor to authenticate.
+    pending_handshake: Option<Option<Vec<String>>>,
+}
+
+impl AuthSession {
+    /// Requires the editor to send `token` before any other commands are accepted.
+    pub(crate) fn require(&mut self, token: AuthToken) {
+        self.token = Some(token);
+    }
+
+    /// Whether `message` should be handled, which is only the case for messages that start a
+    /// session until the editor has authenticated, if a token is required.
+    pub(crate) fn accepts(&self, message: &IncomingMessage) -> bool {
+        if self.token.is_none() || self.authenticated {
+            return true;
+        }
+
+        match *message {
+            IncomingMessage::Authenticate { .. }
+            | IncomingMessage::Handshake { .. }
+            | IncomingMessage::Heartbeat => true,
+            _ => false,
+        }
+    }
+
+    /// Forgets that the editor has authenticated, so that the next session has to authenticate
+    /// again.
+    pub(crate) fn end(&mut self) {
+        if self.authenticated {
+            debug!("Editor session ended, authentication required again");
+        }
+        self.authenticated = false;
+        self.pending_handshake = None;
+    }
+
+    /// Starts a new session with a handshake listing the editor's `capabilities`, returning them
+    /// if the handshake can be answered right away. Otherwise it's answered once the editor has
+    /// authenticated.
+    pub(crate) fn handshake(
+        &mut self,
+        capabilities: Option<Vec<String>>,
+    ) -> Option<Option<Vec<String>>> {
+        if self.token.is_none() {
+            return Some(capabilities);
+        }
+
+        self.end();
+        self.pending_handshake = Some(capabilities);
+        None
+    }
+
+    /// Checks the `token` sent by the editor, returning the status to acknowledge it with and the
+    /// capabilities of the handshake to answer now that the editor has authenticated, if any.
+    pub(crate) fn authenticate(
+        &mut self,
+        token: &str,
+    ) -> (EditStatus, Option<Option<Vec<String>>>) {
+        match self.token {
+            Some(ref expected) if !expected.matches(token) => {
+                warn!("Editor sent the wrong authentication token");
+                (EditStatus::Unauthorized, None)
+            }
+            Some(_) => {
+                info!("Editor authenticated");
+                self.authenticated = true;
+                (EditStatus::Applied, self.pending_handshake.take())
+            }
+            None => (EditStatus::Applied, None),
+        }
+    }
+
+    /// Whether the editor only has to authenticate to answer handshakes, as opposed to answering
+    /// them right away.
+    pub(crate) fn is_required(&self) -> bool {
+        self.token.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_only_the_same_token() {
+        let token = AuthToken::new("secret".into());
+        assert!(token.matches("secret"));
+        assert!(!token.matches("secreT"));
+        assert!(!token.matches("secret2"));
+        assert!(!token.matches(""));
+        assert!(!token.matches("secretsecret"));
+        assert!(!AuthToken::new(String::new()).matches("secret"));
+        assert_eq!(format!("{:?}", token), "AuthToken(..)");
+    }
+
+    #[test]
+    fn handshake_waits_for_authentication() {
+        let mut session = AuthSession::default();
+        session.require(AuthToken::new("secret".into()));
+        assert!(!session.accepts(&IncomingMessage::RequestFullSync));
+        assert_eq!(session.handshake(Some(vec!["deltas".into()])), None);
+
+        assert_eq!(
+            session.authenticate("wrong"),
+            (EditStatus::Unauthorized, None)
+        );
+        assert_eq!(
+            session.authenticate("secret"),
+            (EditStatus::Applied, Some(Some(vec!["deltas".into()])))
+        );
+        assert!(session.accepts(&IncomingMessage::RequestFullSync));
+
+        // A new handshake starts a new session.
+        assert_eq!(session.handshake(None), None);
+        assert!(!session.accepts(&IncomingMessage::RequestFullSync));
+    }
+}
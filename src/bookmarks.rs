@@ -0,0 +1,63 @@
+use amethyst::ecs::Entity;
+use std::collections::BTreeMap;
+
+/// Entities bookmarked under a name, e.g. `"player"` or `"boss"`, so that entities that are
+/// inspected often can be found again quickly. Bookmarks are kept in sync with the editor in
+/// both directions.
+///
+/// The editor sets bookmarks with the `BookmarkEntity` message, and the game can set them as
+/// well. Any change is sent to the editor as a `"bookmarks"` message. If the bookmarked entity
+/// has a `Named` component, the bookmark also remembers its name, so that when the entity is
+/// deleted and replaced by a new entity with the same name (e.g. when a level is reloaded), the
+/// bookmark moves to the new entity. Bookmarks of deleted entities without a name are removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bookmarks {
+    entries: BTreeMap<String, Bookmark>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Bookmark {
+    /// The bookmarked entity, or `None` while a named entity is waiting to be found again.
+    pub entity: Option<Entity>,
+
+    /// The `Named` name of the entity, used to find it again once it's been replaced.
+    pub named: Option<String>,
+}
+
+impl Bookmarks {
+    /// The entity bookmarked as `name`, if it exists.
+    pub fn get(&self, name: &str) -> Option<Entity> {
+        self.entries.get(name).and_then(|bookmark| bookmark.entity)
+    }
+
+    /// The names of all bookmarks along with their entities, in order of name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Entity)> {
+        self.entries
+            .iter()
+            .filter_map(|(name, bookmark)| Some((name.as_str(), bookmark.entity?)))
+    }
+
+    /// Bookmarks `entity` as `name`, replacing any entity bookmarked under that name.
+    pub fn insert<S: Into<String>>(&mut self, name: S, entity: Entity) {
+        self.entries.insert(
+            name.into(),
+            Bookmark {
+                entity: Some(entity),
+                named: None,
+            },
+        );
+    }
+
+    /// Removes the bookmark called `name`.
+    pub fn remove(&mut self, name: &str) {
+        self.entries.remove(name);
+    }
+
+    pub(crate) fn entries_mut(&mut self) -> &mut BTreeMap<String, Bookmark> {
+        &mut self.entries
+    }
+
+    pub(crate) fn entries(&self) -> &BTreeMap<String, Bookmark> {
+        &self.entries
+    }
+}
@@ -0,0 +1,134 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// How important it is to send a component or resource to the editor when snapshots don't fit
+/// within the byte budget.
+///
+/// Types are sent in order of priority until the budget is used up, and types that don't fit
+/// are deferred to the next snapshot. Each type's priority rises by one level for every four
+/// snapshots in a row it's left out of, even past `High`, so that a steady stream of higher
+/// priority data can't keep low priority types from ever being sent. See
+/// [`SyncEditorBundle::byte_budget`] for details.
+///
+/// [`SyncEditorBundle::byte_budget`]: ./struct.SyncEditorBundle.html#method.byte_budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SyncPriority {
+    /// Sent only when there's room left once everything else has been sent.
+    Low,
+
+    /// The priority of types that haven't been given one.
+    Normal,
+
+    /// Sent before anything else.
+    High,
+}
+
+impl Default for SyncPriority {
+    fn default() -> Self {
+        SyncPriority::Normal
+    }
+}
+
+/// The number of consecutive snapshots a type has to be deferred for its priority to rise by one
+/// level.
+pub(crate) const PRIORITY_AGE: usize = 4;
+
+impl SyncPriority {
+    fn level(self) -> usize {
+        match self {
+            SyncPriority::Low => 0,
+            SyncPriority::Normal => 1,
+            SyncPriority::High => 2,
+        }
+    }
+}
+
+/// Picks which of the serialized types to include in each snapshot so that it stays within a
+/// maximum size.
+#[derive(Debug)]
+pub(crate) struct ByteBudget {
+    bytes: usize,
+    priorities: HashMap<&'static str, SyncPriority>,
+
+    // The number of consecutive snapshots each type has been left out of.
+    deferred: HashMap<&'static str, usize>,
+}
+
+impl ByteBudget {
+    pub(crate) fn new(bytes: usize, priorities: HashMap<&'static str, SyncPriority>) -> Self {
+        ByteBudget {
+            bytes,
+            priorities,
+            deferred: HashMap::new(),
+        }
+    }
+
+    /// Decides which of `entries`, given as their name and serialized size, are sent in this
+    /// snapshot, returning whether to send each entry.
+    ///
+    /// Entries are picked in order of their priority, raised by how long they've been deferred,
+    /// with the ones deferred the longest going first when that's equal.
+    ///
+    /// The first entry picked is always sent, even if it's larger than the whole budget, so that
+    /// a single huge type doesn't stop everything from being sent.
+    pub(crate) fn select(&mut self, entries: &[(&'static str, usize)]) -> Vec<bool> {
+        let mut order = (0..entries.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&index| {
+            let name = entries[index].0;
+            let priority = self.priorities.get(name).cloned().unwrap_or_default();
+            let deferred = self.deferred.get(name).cloned().unwrap_or(0);
+            (
+                Reverse(priority.level() * PRIORITY_AGE + deferred),
+                Reverse(deferred),
+                index,
+            )
+        });
+
+        let mut send = vec![false; entries.len()];
+        let mut used = 0;
+        for index in order {
+            let (name, size) = entries[index];
+            if used == 0 || used + size <= self.bytes {
+                used += size;
+                send[index] = true;
+                self.deferred.remove(name);
+            } else {
+                *self.deferred.entry(name).or_insert(0) += 1;
+            }
+        }
+
+        send
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cycles_deferred_types() {
+        let mut priorities = HashMap::new();
+        priorities.insert("important", SyncPriority::High);
+        let mut budget = ByteBudget::new(100, priorities);
+        let entries = [("a", 40), ("b", 40), ("important", 60)];
+
+        assert_eq!(budget.select(&entries), vec![true, false, true]);
+        assert_eq!(budget.select(&entries), vec![false, true, true]);
+        assert_eq!(budget.select(&entries), vec![true, false, true]);
+    }
+
+    #[test]
+    fn low_priority_types_are_not_starved() {
+        let mut priorities = HashMap::new();
+        priorities.insert("important", SyncPriority::High);
+        priorities.insert("minor", SyncPriority::Low);
+        let mut budget = ByteBudget::new(100, priorities);
+
+        // The high priority type alone fills the budget in every snapshot.
+        let entries = [("important", 100), ("minor", 10)];
+        let sent = (0..3 * PRIORITY_AGE)
+            .filter(|_| budget.select(&entries)[1])
+            .count();
+        assert!(sent > 0);
+    }
+}
@@ -1,28 +1,134 @@
+use crate::auth::AuthToken;
+use crate::budget::SyncPriority;
+use crate::capabilities::{Capabilities, Capability};
+use crate::codec::Codec;
+use crate::editor_meta::EditorMeta;
+use crate::entity_map::EntityIdMapping;
+use crate::entity_names::EntityNaming;
+use crate::ext::{Registration, SyncSystemExt, SyncSystems};
+use crate::handshake::Handshake;
+#[cfg(feature = "renderer")]
+use crate::interest::InterestArea;
+use crate::names;
+use crate::profiler::{SystemProfiler, SystemTiming};
+use crate::registry::Registry;
+use crate::resource_chunks::ResourceChunker;
+#[cfg(feature = "screen-capture")]
+use crate::screen_capture::{CaptureFormat, CaptureSettings};
+use crate::snapshot::Snapshot;
+use crate::subscriptions::SyncLevel;
+use crate::sync_strategy::SyncStrategy;
+use crate::system_graph::SystemGraph;
 use crate::systems::*;
-use crate::types::IncomingComponent;
+use crate::transport::Transport;
+use crate::types::{IncomingComponent, IncomingResource};
 use crate::types::*;
+use crate::unknown_type::{UnknownTypePolicy, UnknownTypes};
+use crate::validation::ComponentValidator;
+use crate::worker::SerializationPool;
+use amethyst::assets::Asset;
 use amethyst::core::{Result as BundleResult, SystemBundle};
-use amethyst::ecs::{Component, DispatcherBuilder};
+use amethyst::ecs::storage::Tracked;
+use amethyst::ecs::{Component, DispatcherBuilder, World};
 use amethyst::shred::Resource;
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{Receiver, Sender};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::HashMap;
+#[cfg(feature = "physics")]
+use specs_physics::Position;
+use std::collections::{HashMap, HashSet};
+use std::io;
 use std::marker::PhantomData;
-use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Bundles all necessary systems for serializing all registered components and resources and
 /// sending them to the editor.
 pub struct SyncEditorBundle<'a> {
-    send_interval: Duration,
+    sender_settings: SenderSettings,
+    watchdog_settings: WatchdogSettings,
+    storage_stats_interval: Option<Duration>,
     read_systems: Vec<Box<dyn RegisterReadSystem>>,
     write_systems: Vec<Box<dyn RegisterWriteSystem>>,
+    sync_systems: Vec<Box<dyn SyncSystemExt>>,
+    entity_id_mapping: Option<Box<dyn RegisterReadSystem>>,
+    entity_naming: Option<EntityNaming>,
+    entity_uuids: bool,
+    auth_token: Option<String>,
+    incoming_chunk_headers: Option<bool>,
+    receive_buffer_size: Option<usize>,
+    #[cfg(feature = "encryption")]
+    encryption_key: Option<[u8; 32]>,
+    unknown_type_policy: UnknownTypePolicy,
+    heartbeat_timeout: Option<Duration>,
+    entity_limits: EntityLimits,
+    apply_edits_at: SyncPoint,
+    prefab_spawner: Option<(Sender<PrefabSpawn>, Box<dyn RegisterWriteSystem>)>,
+    console_handler: Option<ConsoleHandler>,
+    audio: bool,
+    dynamic_components: bool,
+    history: Option<usize>,
+    #[cfg(feature = "physics")]
+    physics: Option<(
+        Sender<PhysicsCommand>,
+        Box<dyn RegisterReadSystem>,
+        Box<dyn RegisterWriteSystem>,
+    )>,
+    #[cfg(feature = "screen-capture")]
+    screen_capture: Option<CaptureSettings>,
+    #[cfg(feature = "renderer")]
+    interest_area: Option<InterestArea>,
     sender: EditorConnection,
     receiver: Receiver<SerializedData>,
     component_map: ComponentMap,
     resource_map: ResourceMap,
+    chunk_map: ChannelMap<ChunkRequest>,
+    registry: Registry,
+
+    // Whether types registered with the `*_dev_only` methods are synced, which they are in
+    // debug builds only. The IDs of the types left out are kept, so that edits for them can be
+    // acknowledged without going through the unknown type policy.
+    dev_builds: bool,
+    dev_only_components: HashSet<&'static str>,
+    dev_only_resources: HashSet<&'static str>,
+
+    system_graph: SystemGraph,
+    profiler: Option<(SystemProfiler, Receiver<SystemTiming>)>,
+    editor_meta_path: Option<PathBuf>,
+    scene_export_dir: Option<PathBuf>,
     bind_address: &'a str,
+    transport: Option<Transport>,
+    disable_on_error: bool,
+    serialization_threads: usize,
+    background_components: bool,
+}
+
+/// The point in each frame at which the bundle's systems run, relative to the game's systems.
+///
+/// The bundle's systems are split into two parts, separated from each other and from the
+/// game's systems by barriers: reading and sending the game state to the editor, and receiving
+/// and applying the edits made in the editor. The sync point controls which of those comes
+/// first. See [`SyncEditorBundle::apply_edits_at`] for details.
+///
+/// [`SyncEditorBundle::apply_edits_at`]: ./struct.SyncEditorBundle.html#method.apply_edits_at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPoint {
+    /// Edits are applied before the game state is sent. Add the bundle before the game's own
+    /// systems, so that edits are applied at the start of the frame and the game's systems see
+    /// them in the same frame.
+    FrameStart,
+
+    /// Edits are applied after the game state is sent. Add the bundle after the game's own
+    /// systems, so that edits are applied at the end of the frame and the game's systems see
+    /// them in the next frame.
+    FrameEnd,
+}
+
+impl Default for SyncPoint {
+    fn default() -> Self {
+        SyncPoint::FrameEnd
+    }
 }
 
 /// Registers one or more components to be syncronized with the editor.
@@ -91,21 +197,139 @@ macro_rules! read_resources {
 
 impl<'a> SyncEditorBundle<'a> {
     /// Construct an empty bundle.
+    ///
+    /// The socket used to communicate with the editor is bound when the bundle is built. If
+    /// that fails, e.g. because the game runs in a sandbox that doesn't allow binding sockets,
+    /// an error is logged and the bundle doesn't add any systems, so that the game keeps
+    /// running without the editor. Use [`try_new`] to handle the error instead.
+    ///
+    /// [`try_new`]: #method.try_new
     pub fn new() -> Self {
         let (sender, receiver) = crossbeam_channel::unbounded();
 
         SyncEditorBundle {
-            send_interval: Duration::from_millis(200),
+            sender_settings: SenderSettings::default(),
+            watchdog_settings: WatchdogSettings::default(),
+            storage_stats_interval: None,
             read_systems: Vec::new(),
             write_systems: Vec::new(),
+            sync_systems: Vec::new(),
+            entity_id_mapping: None,
+            entity_naming: None,
+            entity_uuids: false,
+            auth_token: None,
+            incoming_chunk_headers: None,
+            receive_buffer_size: None,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+            unknown_type_policy: UnknownTypePolicy::default(),
+            heartbeat_timeout: None,
+            entity_limits: EntityLimits::default(),
+            apply_edits_at: SyncPoint::default(),
+            prefab_spawner: None,
+            console_handler: None,
+            audio: false,
+            dynamic_components: false,
+            history: None,
+            #[cfg(feature = "physics")]
+            physics: None,
+            #[cfg(feature = "screen-capture")]
+            screen_capture: None,
+            #[cfg(feature = "renderer")]
+            interest_area: None,
             sender: EditorConnection::new(sender),
             receiver,
             component_map: HashMap::new(),
             resource_map: HashMap::new(),
-            bind_address: "127.0.0.1:0"
+            chunk_map: HashMap::new(),
+            registry: Registry::default(),
+            dev_builds: cfg!(debug_assertions),
+            dev_only_components: HashSet::new(),
+            dev_only_resources: HashSet::new(),
+            system_graph: SystemGraph::default(),
+            profiler: None,
+            editor_meta_path: None,
+            scene_export_dir: None,
+            bind_address: "127.0.0.1:0",
+            transport: None,
+            disable_on_error: true,
+            serialization_threads: 1,
+            background_components: false,
         }
     }
 
+    /// Construct an empty bundle, binding the socket used to communicate with the editor right
+    /// away.
+    ///
+    /// Returns an error if the socket can't be bound to `127.0.0.1:0`. Unlike a bundle created
+    /// with [`new`], a bundle created with this doesn't disable itself if the connection to the
+    /// editor can't be opened when it's built, and the error is returned from `build` instead.
+    ///
+    /// Since the socket is already bound, the [bind address] is ignored. To bind to another
+    /// address, pass a transport created with [`Transport::udp`] to [`transport`].
+    ///
+    /// [`new`]: #method.new
+    /// [bind address]: #method.bind_address
+    /// [`Transport::udp`]: ./struct.Transport.html#method.udp
+    /// [`transport`]: #method.transport
+    pub fn try_new() -> io::Result<Self> {
+        let mut bundle = Self::new();
+        bundle.transport = Some(Transport::udp(bundle.bind_address)?);
+        bundle.disable_on_error = false;
+        Ok(bundle)
+    }
+
+    /// Construct a bundle with the least setup needed to inspect a game in the editor.
+    ///
+    /// Only the entity list, `Transform`, and `Named` are synced, and the state is sent once
+    /// per second. This keeps the cost of running with the editor attached low, and is a good
+    /// starting point before registering the game's own types.
+    pub fn minimal() -> Self {
+        use amethyst::core::{Named, Transform};
+
+        let mut bundle = Self::new();
+        sync_components!(bundle, Transform, Named);
+        bundle.send_interval(Duration::from_secs(1));
+        bundle
+    }
+
+    /// Construct a bundle suitable for most games.
+    ///
+    /// All of the types supported by [`sync_default_types`] are synced, entities are named
+    /// after their dominant component, and only data that changed is sent if the editor
+    /// supports it.
+    ///
+    /// [`sync_default_types`]: #method.sync_default_types
+    pub fn standard() -> Self {
+        let mut bundle = Self::new();
+        bundle.sync_default_types();
+        bundle.generate_entity_names(EntityNaming::DominantComponent);
+        bundle.sender_settings.delta_only = true;
+        bundle
+    }
+
+    /// Construct a bundle that sends as much information to the editor as possible.
+    ///
+    /// All of the types supported by [`sync_default_types`] are synced, along with any types
+    /// submitted with [`submit_registrations`] if the `inventory` feature is enabled. Entities
+    /// are named and list their components, the full state is sent in every snapshot along with
+    /// size statistics, and snapshots are sent every frame. This is useful for debugging, but is
+    /// expensive for games with many entities.
+    ///
+    /// [`sync_default_types`]: #method.sync_default_types
+    /// [`submit_registrations`]: ./macro.submit_registrations.html
+    pub fn everything() -> Self {
+        let mut bundle = Self::new();
+        bundle.sync_default_types();
+        #[cfg(feature = "inventory")]
+        bundle.sync_registered_types();
+        bundle.generate_entity_names(EntityNaming::DominantComponent);
+        bundle.send_stats(true);
+        bundle.send_entity_components(true);
+        bundle.send_interval(Duration::from_millis(0));
+        bundle
+    }
+
     /// Synchronize amethyst types.
     ///
     /// Currently only a small set is supported. This will be expanded in the future.
@@ -136,6 +360,47 @@ impl<'a> SyncEditorBundle<'a> {
         read_components!(self, UiText);
         sync_resources!(self, AmbientColor, HideCursor);
         read_resources!(self, WindowFocus);
+        self.sync_audio();
+    }
+
+    /// Sends the state of the game's audio to the editor, to help debug sound issues.
+    ///
+    /// Whenever it changes, the state is sent as an `"audio"` message containing whether an
+    /// output device is available, the volume and state of the `AudioSink` resource, and the
+    /// number of audio emitters, listeners, and sound sources attached to entities. The editor
+    /// can change the volume of the `AudioSink` with a `SetMasterVolume` message.
+    ///
+    /// This is enabled by [`sync_default_types`].
+    ///
+    /// [`sync_default_types`]: #method.sync_default_types
+    pub fn sync_audio(&mut self) {
+        self.audio = true;
+    }
+
+    /// Sends the components in the [`DynamicComponentStore`] resource to the editor, for
+    /// components defined at runtime, e.g. by a scripting language.
+    ///
+    /// Each name in the store is sent as if it was a registered component type, and the editor
+    /// can edit the components with `ComponentUpdate` and `ComponentPatch` messages. Edits to
+    /// names that aren't registered as a typed component are passed on to the store.
+    ///
+    /// [`DynamicComponentStore`]: ./struct.DynamicComponentStore.html
+    pub fn sync_dynamic_components(&mut self) {
+        self.dynamic_components = true;
+    }
+
+    /// Records the state of all registered components and resources over the last `frames`
+    /// frames, for time-travel debugging.
+    ///
+    /// The editor can fetch recorded frames with a `RequestHistory` message, which is answered
+    /// with a `"history"` message, and restore the editable components and resources to the
+    /// state of a recorded frame with a `RewindTo` message. Only components on entities that
+    /// still exist are restored.
+    ///
+    /// Every registered type is serialized every frame while history is enabled, whether or not
+    /// an editor is connected, so this is expensive for large worlds.
+    pub fn history(&mut self, frames: usize) {
+        self.history = Some(frames);
     }
 
     /// Register a component for synchronizing with the editor. This will result in a
@@ -143,36 +408,209 @@ impl<'a> SyncEditorBundle<'a> {
     pub fn sync_component<C>(&mut self, name: &'static str)
     where
         C: Component + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.read_component::<C>(name);
+        self.write_component::<C>(name);
+    }
+
+    /// Register a component for synchronizing with the editor, with the given priority for
+    /// when snapshots don't fit within the [byte budget].
+    ///
+    /// [byte budget]: #method.byte_budget
+    pub fn sync_component_with_priority<C>(&mut self, name: &'static str, priority: SyncPriority)
+    where
+        C: Component + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.sync_component::<C>(name);
+        self.priority(name, priority);
+    }
+
+    /// Register a component for synchronizing with the editor in debug builds only.
+    ///
+    /// In release builds (i.e. without `debug_assertions`) the component isn't registered at
+    /// all, so heavyweight types don't cost anything to ship while the rest of the bundle's
+    /// configuration stays the same across build profiles. Edits that the editor sends for the
+    /// component in a release build are dropped and acknowledged with an `unknown_type` status,
+    /// without going through the [unknown type policy].
+    ///
+    /// [unknown type policy]: #method.unknown_type_policy
+    pub fn sync_component_dev_only<C>(&mut self, name: &'static str)
+    where
+        C: Component + Serialize + DeserializeOwned + Send + Sync,
+    {
+        if self.dev_builds {
+            self.sync_component::<C>(name);
+        } else {
+            self.dev_only_components.insert(name);
+        }
+    }
+
+    /// Register a component to be sent to the editor as read-only data in debug builds only.
+    ///
+    /// See [`sync_component_dev_only`] for details.
+    ///
+    /// [`sync_component_dev_only`]: #method.sync_component_dev_only
+    pub fn read_component_dev_only<C>(&mut self, name: &'static str)
+    where
+        C: Component + Serialize + Send,
+    {
+        if self.dev_builds {
+            self.read_component::<C>(name);
+        } else {
+            self.dev_only_components.insert(name);
+        }
+    }
+
+    pub fn read_component<C>(&mut self, name: &'static str)
+    where
+        C: Component + Serialize + Send,
     {
         let read_component = ReadComponent::<C> {
             name,
             _marker: Default::default(),
         };
+        self.registry.register_component::<C>(name);
+        self.read_systems
+            .push(Box::new(read_component) as Box<dyn RegisterReadSystem>);
+    }
 
-        let (sender, receiver) = crossbeam_channel::unbounded();
-        self.component_map.insert(name, sender);
-        let write_component = WriteComponent::<C> {
+    /// Register a component with a tracked storage (e.g. a `FlaggedStorage`) for synchronizing
+    /// with the editor.
+    ///
+    /// This is the same as [`sync_component`], except that the serialized components are
+    /// cached and reused as long as no components of the type have been inserted, modified, or
+    /// removed. This avoids serializing large storages that rarely change. Note that changes
+    /// made through interior mutability aren't detected by tracked storages.
+    ///
+    /// [`sync_component`]: #method.sync_component
+    pub fn sync_tracked_component<C>(&mut self, name: &'static str)
+    where
+        C: Component + Serialize + DeserializeOwned + Send + Sync,
+        C::Storage: Tracked,
+    {
+        self.read_tracked_component::<C>(name);
+        self.write_component::<C>(name);
+    }
+
+    /// Register a component with a tracked storage to be displayed as read-only in the editor.
+    ///
+    /// See [`sync_tracked_component`] for details on caching.
+    ///
+    /// [`sync_tracked_component`]: #method.sync_tracked_component
+    pub fn read_tracked_component<C>(&mut self, name: &'static str)
+    where
+        C: Component + Serialize + Send,
+        C::Storage: Tracked,
+    {
+        let read_component = ReadTrackedComponent::<C> {
             name,
-            receiver,
             _marker: Default::default(),
         };
-
+        self.registry.register_component::<C>(name);
         self.read_systems
             .push(Box::new(read_component) as Box<dyn RegisterReadSystem>);
-        self.write_systems
-            .push(Box::new(write_component) as Box<dyn RegisterWriteSystem>);
     }
 
-    pub fn read_component<C>(&mut self, name: &'static str)
+    /// Register a component for synchronizing with the editor, serializing it on a background
+    /// thread.
+    ///
+    /// This is the same as [`sync_component`], except that the read system only copies the
+    /// components, and the copies are serialized on one of the bundle's [serialization
+    /// threads]. This keeps the cost of syncing huge storages during the dispatch down to the
+    /// cost of cloning them, which still grows with the number of components, so it pays off
+    /// for components that are cheap to clone but expensive to serialize. The serialized
+    /// components reach the editor with the first snapshot sent after they're ready, so they
+    /// may lag a frame or more behind the rest of the state.
+    ///
+    /// A panic while serializing is logged, and the components are copied again on the next
+    /// frame. If the serialization threads aren't running, components are serialized during the
+    /// dispatch instead.
+    ///
+    /// [`sync_component`]: #method.sync_component
+    /// [serialization threads]: #method.serialization_threads
+    pub fn sync_component_in_background<C>(&mut self, name: &'static str)
     where
-        C: Component + Serialize + Send,
+        C: Component + Clone + Serialize + DeserializeOwned + Send + Sync,
     {
-        let read_component = ReadComponent::<C> {
+        self.read_component_in_background::<C>(name);
+        self.write_component::<C>(name);
+    }
+
+    /// Register a component to be displayed as read-only in the editor, serializing it on a
+    /// background thread.
+    ///
+    /// See [`sync_component_in_background`] for details.
+    ///
+    /// [`sync_component_in_background`]: #method.sync_component_in_background
+    pub fn read_component_in_background<C>(&mut self, name: &'static str)
+    where
+        C: Component + Clone + Serialize + Send,
+    {
+        let read_component = ReadBackgroundComponent::<C> {
             name,
             _marker: Default::default(),
         };
+        self.registry.register_component::<C>(name);
         self.read_systems
             .push(Box::new(read_component) as Box<dyn RegisterReadSystem>);
+        self.background_components = true;
+    }
+
+    /// Sets the number of threads used to serialize components registered with
+    /// [`sync_component_in_background`].
+    ///
+    /// The threads are only started if any components are serialized in the background.
+    /// Defaults to 1.
+    ///
+    /// [`sync_component_in_background`]: #method.sync_component_in_background
+    pub fn serialization_threads(&mut self, threads: usize) {
+        self.serialization_threads = threads;
+    }
+
+    /// Adds the system that applies updates from the editor to components of type `C`.
+    fn write_component<C>(&mut self, name: &'static str)
+    where
+        C: Component + Serialize + DeserializeOwned + Send + Sync,
+    {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        self.component_map.insert(name, sender);
+        self.registry.register_writable_component::<C>(name);
+        let write_component = WriteComponent::<C> {
+            name,
+            receiver,
+            _marker: Default::default(),
+        };
+        self.write_systems
+            .push(Box::new(write_component) as Box<dyn RegisterWriteSystem>);
+    }
+
+    /// Registers a component for synchronizing with the editor, grouped under `category`.
+    ///
+    /// Categories allow the editor to group large numbers of registered types into sections,
+    /// e.g. putting all physics components under `"Physics"`. The category is sent along with
+    /// the component's data. Otherwise this is the same as [`sync_component`].
+    ///
+    /// [`sync_component`]: #method.sync_component
+    pub fn sync_component_in_category<C>(&mut self, category: &'static str, name: &'static str)
+    where
+        C: Component + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.sync_component::<C>(name);
+        self.registry.set_category(name, category);
+    }
+
+    /// Registers a component to be displayed as read-only in the editor, grouped under
+    /// `category`.
+    ///
+    /// See [`sync_component_in_category`] for details on categories.
+    ///
+    /// [`sync_component_in_category`]: #method.sync_component_in_category
+    pub fn read_component_in_category<C>(&mut self, category: &'static str, name: &'static str)
+    where
+        C: Component + Serialize + Send,
+    {
+        self.read_component::<C>(name);
+        self.registry.set_category(name, category);
     }
 
     /// Registers a resource type to be synchronized with the editor.
@@ -192,6 +630,8 @@ impl<'a> SyncEditorBundle<'a> {
             name,
             _marker: Default::default(),
         };
+        self.registry.register_resource::<R>(name);
+        self.registry.register_writable_resource::<R>(name);
 
         let (sender, receiver) = crossbeam_channel::unbounded();
         self.resource_map.insert(name, sender);
@@ -207,6 +647,50 @@ impl<'a> SyncEditorBundle<'a> {
             .push(Box::new(write_resource) as Box<dyn RegisterWriteSystem>);
     }
 
+    /// Registers a resource type to be synchronized with the editor, with the given priority
+    /// for when snapshots don't fit within the [byte budget].
+    ///
+    /// [byte budget]: #method.byte_budget
+    pub fn sync_resource_with_priority<R>(&mut self, name: &'static str, priority: SyncPriority)
+    where
+        R: Resource + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.sync_resource::<R>(name);
+        self.priority(name, priority);
+    }
+
+    /// Registers a resource type to be synchronized with the editor in debug builds only.
+    ///
+    /// See [`sync_component_dev_only`] for details.
+    ///
+    /// [`sync_component_dev_only`]: #method.sync_component_dev_only
+    pub fn sync_resource_dev_only<R>(&mut self, name: &'static str)
+    where
+        R: Resource + Serialize + DeserializeOwned + Send + Sync,
+    {
+        if self.dev_builds {
+            self.sync_resource::<R>(name);
+        } else {
+            self.dev_only_resources.insert(name);
+        }
+    }
+
+    /// Registers a resource to be sent to the editor as read-only data in debug builds only.
+    ///
+    /// See [`sync_component_dev_only`] for details.
+    ///
+    /// [`sync_component_dev_only`]: #method.sync_component_dev_only
+    pub fn read_resource_dev_only<R>(&mut self, name: &'static str)
+    where
+        R: Resource + Serialize + Send,
+    {
+        if self.dev_builds {
+            self.read_resource::<R>(name);
+        } else {
+            self.dev_only_resources.insert(name);
+        }
+    }
+
     /// Registers a resource to be sent to the editor as read-only data.
     ///
     /// At runtime, the state data for `R` will be sent to the editor for viewing, however
@@ -224,115 +708,1599 @@ impl<'a> SyncEditorBundle<'a> {
             name,
             _marker: Default::default(),
         };
+        self.registry.register_resource::<R>(name);
 
         self.read_systems
             .push(Box::new(read_resource) as Box<dyn RegisterReadSystem>);
     }
 
-    /// Sets the interval at which the current game state will be sent to the editor.
+    /// Registers a resource type to be synchronized with the editor, grouped under `category`.
     ///
-    /// In order to reduce the amount of work the editor has to do to keep track of the latest
-    /// game state, the rate at which the game state is sent can be reduced. This defaults to
-    /// sending updated data every 200 ms. Setting this to 0 will ensure that data is sent every
-    /// frame.
+    /// See [`sync_component_in_category`] for details on categories.
     ///
-    /// Note that log output is sent every frame regardless of this interval, the interval only
-    /// controls how often the game's state is sent.
-    pub fn send_interval(&mut self, send_interval: Duration) {
-        self.send_interval = send_interval;
+    /// [`sync_component_in_category`]: #method.sync_component_in_category
+    pub fn sync_resource_in_category<R>(&mut self, category: &'static str, name: &'static str)
+    where
+        R: Resource + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.sync_resource::<R>(name);
+        self.registry.set_category(name, category);
     }
 
-    /// Sets the address that the UDP port will bind to.
+    /// Registers a resource to be sent to the editor as read-only data, grouped under
+    /// `category`.
     ///
-    /// Format should be `address:port`, defaults to `127.0.0.1:0`
-    pub fn bind_address(&mut self, bind_address: &'a str) {
-        self.bind_address = bind_address;
+    /// See [`sync_component_in_category`] for details on categories.
+    ///
+    /// [`sync_component_in_category`]: #method.sync_component_in_category
+    pub fn read_resource_in_category<R>(&mut self, category: &'static str, name: &'static str)
+    where
+        R: Resource + Serialize + Send,
+    {
+        self.read_resource::<R>(name);
+        self.registry.set_category(name, category);
     }
 
-    /// Retrieve a connection to send messages to the editor via the [`SyncEditorSystem`].
-    pub(crate) fn connection(&self) -> EditorConnection {
-        self.sender.clone()
-    }
-}
+    /// Registers a resource that's too large to send in full, e.g. a tile map, to be sent to
+    /// the editor in chunks as read-only data.
+    ///
+    /// `chunker` splits the resource into chunks, see [`ResourceChunker`]. Whenever the set of
+    /// chunks changes, their IDs are sent as a `"resource_chunk_index"` message. The editor
+    /// fetches chunks with a `RequestChunks` message, e.g. for the regions of a map that are
+    /// visible, after which the requested chunks are sent as `"resource_chunks"` messages
+    /// whenever their data changes.
+    ///
+    /// [`ResourceChunker`]: ./trait.ResourceChunker.html
+    pub fn sync_chunked_resource<R, C>(&mut self, name: &'static str, chunker: C)
+    where
+        R: Resource,
+        C: ResourceChunker<R>,
+    {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        self.chunk_map.insert(name, sender);
+        let read_chunks = ReadChunkedResource::<R, C> {
+            name,
+            chunker,
+            receiver,
+            _marker: Default::default(),
+        };
 
-impl<'a> Default for SyncEditorBundle<'a> {
-    fn default() -> Self {
-        Self::new()
+        self.read_systems
+            .push(Box::new(read_chunks) as Box<dyn RegisterReadSystem>);
     }
-}
-
-impl<'a, 'b, 'c> SystemBundle<'a, 'b> for SyncEditorBundle<'c> {
-    fn build(self, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> BundleResult<()> {
-        let socket = UdpSocket::bind(self.bind_address).expect("Failed to bind socket");
-        socket
-            .set_nonblocking(true)
-            .expect("Failed to make editor socket nonblocking");
 
-        // Ensure that all previous systems are done before syncing.
-        dispatcher.add_barrier();
-
-        // Register the systems for serializing each of the component/resource types.
-        for read_system in self.read_systems {
-            read_system.register(dispatcher, &self.sender);
+    /// Applies registrations produced by another crate.
+    ///
+    /// See the [`ext`] module for how crates can provide registrations for their own types.
+    ///
+    /// [`ext`]: ./ext/index.html
+    pub fn register<I>(&mut self, registrations: I)
+    where
+        I: IntoIterator<Item = Registration>,
+    {
+        for registration in registrations {
+            registration.apply(self);
         }
+    }
 
-        // Ensure all components/resources are read before sending.
-        dispatcher.add_barrier();
-
-        // Create the sender system, which will update the editor on all tracked
-        // components/resources/entities.
-        let sender_system = EditorSenderSystem::from_channel(
-            self.receiver,
-            self.send_interval,
-            socket.try_clone().expect("failed to clone socket"),
-        );
-        dispatcher.add(sender_system, "editor_sender_system", &[]);
-
-        // Ensure all components/resources are sent before writing.
-        dispatcher.add_barrier();
-
-        // Create the receiver system, which will read any incoming messages from the
-        // editor and pass them to the corresponding systems for applying changes to
-        // components/resources/entities.
-        let (entity_sender, entity_receiver) = crossbeam_channel::unbounded::<EntityMessage>();
-        let receiver_system = EditorReceiverSystem::new(
-            self.component_map.clone(),
-            self.resource_map.clone(),
-            entity_sender,
-            socket,
-        );
-        dispatcher.add(receiver_system, "editor_receiver_system", &[]);
-
-        // Register the system that applies entity changes (creates/destroys entities).
-        // This must also depend on the editor receiver system so that it can apply
-        // an entity changes specified by the editor.
-        dispatcher.add(
-            EntityHandlerSystem::new(entity_receiver),
-            "entity_creator",
-            &["editor_receiver_system"],
-        );
+    /// Adds systems provided by another crate to sync data of its own with the editor.
+    ///
+    /// See [`SyncSystemExt`] for how the systems are added.
+    ///
+    /// [`SyncSystemExt`]: ./ext/trait.SyncSystemExt.html
+    pub fn add_sync_systems<S>(&mut self, systems: S)
+    where
+        S: SyncSystemExt + 'static,
+    {
+        self.sync_systems.push(Box::new(systems));
+    }
 
-        // Register the systems for each of the component/resource types that support
-        // being edited at runtime. Internally these declare a dependency on the
-        // editor receiver system.
-        for write_system in self.write_systems {
-            write_system.register(dispatcher);
+    /// Applies all registrations submitted with [`submit_registrations`].
+    ///
+    /// Only available with the `inventory` feature. See the [`ext`] module for details.
+    ///
+    /// [`submit_registrations`]: ./macro.submit_registrations.html
+    /// [`ext`]: ./ext/index.html
+    #[cfg(feature = "inventory")]
+    pub fn sync_registered_types(&mut self) {
+        for registration in inventory::iter::<Registration> {
+            registration.apply(self);
         }
+    }
 
-        // Ensure all components/resources/entities are written before continuing the dispatch
-        dispatcher.add_barrier();
+    /// Registers a component that provides stable IDs for entities.
+    ///
+    /// Once registered, the mapped ID of each entity is included in the entity list sent to the
+    /// editor, and the editor may address entities by `{ "mapped_id": ... }` instead of by their
+    /// ID and generation. This keeps editor sessions stable across events that recreate
+    /// entities, such as a networked client reconnecting.
+    ///
+    /// Only one mapping component can be registered; registering another replaces the previous
+    /// one.
+    pub fn map_entity_ids<C>(&mut self)
+    where
+        C: EntityIdMapping,
+    {
+        let map_entity_ids = MapEntityIds::<C> {
+            _marker: Default::default(),
+        };
+        self.entity_id_mapping = Some(Box::new(map_entity_ids) as Box<dyn RegisterReadSystem>);
+    }
 
-        Ok(())
+    /// Generates names for entities, which are included in the entity list sent to the editor.
+    ///
+    /// Without names, entities show up in the editor as bare IDs. Entities with a `Named`
+    /// component use that name, and other entities are named according to `naming` the first
+    /// time they're seen. The names are only kept by the editor sync, so no `Named` components
+    /// are added to the world.
+    pub fn generate_entity_names(&mut self, naming: EntityNaming) {
+        self.entity_naming = Some(naming);
     }
-}
 
-struct ReadComponent<T> {
-    name: &'static str,
-    _marker: PhantomData<T>,
-}
+    /// Gives every entity an [`EditorUuid`] component, which is included in the entity list sent
+    /// to the editor so that it can recognize the same entity after the game is restarted.
+    ///
+    /// Entities that already have an `EditorUuid` keep it, so UUIDs stay the same across runs as
+    /// long as the game saves them along with the entities, e.g. in prefabs or save files.
+    ///
+    /// [`EditorUuid`]: ./struct.EditorUuid.html
+    pub fn assign_entity_uuids(&mut self) {
+        self.entity_uuids = true;
+    }
 
-struct ReadResource<T> {
-    name: &'static str,
+    /// Lists the assets of type `A` that are attached to entities in the editor.
+    ///
+    /// Whenever the list changes, it's sent to the editor as an `"assets"` message containing
+    /// the handle ID of each asset, whether it has finished loading, and the number of entities
+    /// using it. Amethyst doesn't keep track of where assets were loaded from, so paths are only
+    /// included for assets recorded in the [`AssetPaths`] resource.
+    ///
+    /// The editor can also send a `ReloadAsset` message to hot-reload assets whose files have
+    /// changed. This requires the game to add a `HotReloadStrategy` resource, e.g. with
+    /// `HotReloadBundle`, and works best with `HotReloadStrategy::when_triggered`.
+    ///
+    /// [`AssetPaths`]: ./struct.AssetPaths.html
+    pub fn sync_assets<A: Asset>(&mut self) {
+        let read_assets = ReadAssets::<A> {
+            _marker: Default::default(),
+        };
+        self.read_systems
+            .push(Box::new(read_assets) as Box<dyn RegisterReadSystem>);
+    }
+
+    /// Sends the rigid bodies and colliders of specs-physics to the editor, where `P` is the
+    /// game's position component. Only available with the `physics` feature.
+    ///
+    /// Every snapshot includes a `"physics"` section listing each entity with a `PhysicsBody`
+    /// or `PhysicsCollider`, along with its position, velocity, and the bounding box of its
+    /// collider. The editor can push bodies around with `ApplyImpulse` messages and move them
+    /// with `Teleport` messages, which are picked up by specs-physics on its next step.
+    ///
+    /// Only one position type can be registered; registering another replaces the previous
+    /// one.
+    #[cfg(feature = "physics")]
+    pub fn sync_physics<P: Position<f32>>(&mut self) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let read_physics = ReadPhysics::<P> {
+            _marker: Default::default(),
+        };
+        let control_physics = ControlPhysics::<P> {
+            receiver,
+            _marker: Default::default(),
+        };
+        self.physics = Some((
+            sender,
+            Box::new(read_physics) as Box<dyn RegisterReadSystem>,
+            Box::new(control_physics) as Box<dyn RegisterWriteSystem>,
+        ));
+    }
+
+    /// Allows the editor to spawn prefabs with data type `T` into the running game.
+    ///
+    /// The editor can send a `SpawnPrefab` message with the path of a prefab asset, which will
+    /// be loaded as a `Prefab<T>` in RON format and attached to a newly created entity. Once
+    /// the prefab has been instantiated, the IDs of all of its entities are sent back to the
+    /// editor in a `"prefab_spawned"` message and the request is acknowledged. If it fails to
+    /// load, the request is acknowledged as failed and the entity is deleted again. Note that
+    /// the prefab is only instantiated if the game registers a `PrefabLoaderSystem<T>`.
+    ///
+    /// Only one prefab type can be registered; registering another replaces the previous one.
+    pub fn spawn_prefabs<T>(&mut self)
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let spawn_prefabs = SpawnPrefabs::<T> {
+            receiver,
+            _marker: Default::default(),
+        };
+        self.prefab_spawner = Some((
+            sender,
+            Box::new(spawn_prefabs) as Box<dyn RegisterWriteSystem>,
+        ));
+    }
+
+    /// Sets the function that evaluates input typed into the editor's console.
+    ///
+    /// The editor sends console input with an `Eval` message, and the string returned by the
+    /// handler is sent back in a `"console_result"` message. What the input means is up to the
+    /// game, e.g. it could be a list of debug commands or code for an embedded scripting
+    /// language. The handler is given mutable access to the world, and runs when the world is
+    /// maintained at the end of the frame.
+    ///
+    /// Setting another handler replaces the previous one.
+    pub fn console_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&mut World, &str) -> String + Send + Sync + 'static,
+    {
+        self.console_handler = Some(Arc::new(handler));
+    }
+
+    /// Streams the game's rendered frames to the editor, so that it can show a live viewport.
+    ///
+    /// A frame is requested from the game every `interval` while the editor is connected,
+    /// through the [`ScreenCapture`] resource. Each frame the game submits is encoded in
+    /// `format` and sent as a series of `"screen_capture"` messages, each containing part of the
+    /// base64-encoded image. Only available with the `screen-capture` feature.
+    ///
+    /// [`ScreenCapture`]: ./struct.ScreenCapture.html
+    #[cfg(feature = "screen-capture")]
+    pub fn capture_screen(&mut self, interval: Duration, format: CaptureFormat) {
+        self.screen_capture = Some(CaptureSettings { interval, format });
+    }
+
+    /// Only serializes the components of entities within `area` around the camera, for games
+    /// whose worlds are too large to sync in full. Only available with the `renderer` feature.
+    ///
+    /// The area is centered on the active camera, or the first camera found if there's no
+    /// `ActiveCamera` resource. Entities outside of it still appear in the entity list, but
+    /// their components are left out of snapshots. Entities without a `GlobalTransform` are
+    /// always synced. The editor can keep specific entities synced wherever they are with a
+    /// `ForceSync` message, and learns which entities were synced from the `"interest"` section
+    /// of each snapshot.
+    #[cfg(feature = "renderer")]
+    pub fn interest_area(&mut self, area: InterestArea) {
+        self.interest_area = Some(area);
+    }
+
+    /// Prevents fields of the component or resource registered as `name` from being sent to the
+    /// editor.
+    ///
+    /// Use this for data that must never leave the process, such as auth tokens or personal
+    /// information. Fields are named by their serialized name, and nested fields can be named
+    /// with a dot-separated path, e.g. `account.email`. Redacted fields are removed from the
+    /// data before it's sent, and are listed in the handshake so that the editor can show them
+    /// as hidden. They're also left out of exported scenes.
+    ///
+    /// The type can still be edited, since redacted fields keep their current value when an
+    /// update from the editor is applied. Calling this again for the same type replaces its
+    /// redacted fields.
+    pub fn redact_fields(&mut self, name: &'static str, fields: &[&str]) {
+        let fields = fields.iter().map(|&field| field.to_owned()).collect();
+        self.registry.redact(name, fields);
+    }
+
+    /// Register a component for synchronizing with the editor, where the editor can't change
+    /// the given fields.
+    ///
+    /// Use this for components that are mostly editable but contain data that must not be
+    /// overwritten, such as an asset handle. Masked fields are still sent to the editor and
+    /// listed as read-only in the handshake. Updates from the editor are merged into the current
+    /// component, rather than replacing it, skipping any masked fields. Fields are named the same
+    /// way as for [`redact_fields`].
+    ///
+    /// [`redact_fields`]: #method.redact_fields
+    pub fn sync_component_masked<C>(&mut self, name: &'static str, fields: &[&str])
+    where
+        C: Component + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.sync_component::<C>(name);
+        let fields = fields.iter().map(|&field| field.to_owned()).collect();
+        self.registry.mask(name, fields);
+    }
+
+    /// Register a resource for synchronizing with the editor, where the editor can't change
+    /// the given fields.
+    ///
+    /// See [`sync_component_masked`] for details.
+    ///
+    /// [`sync_component_masked`]: #method.sync_component_masked
+    pub fn sync_resource_masked<R>(&mut self, name: &'static str, fields: &[&str])
+    where
+        R: Resource + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.sync_resource::<R>(name);
+        let fields = fields.iter().map(|&field| field.to_owned()).collect();
+        self.registry.mask(name, fields);
+    }
+
+    /// Marks the component or resource registered as `name` as fixed-step data, which is only
+    /// updated during fixed updates (e.g. physics state).
+    ///
+    /// Fixed-step types are listed in the handshake. Together with the fixed step count and
+    /// interpolation alpha included in each snapshot, this lets the editor interpolate the data
+    /// between fixed steps instead of showing it jump once every few frames.
+    pub fn fixed_step(&mut self, name: &'static str) {
+        self.registry.set_fixed_step(name);
+    }
+
+    /// Sets the priority of the component, resource or section registered as `name` for when
+    /// snapshots don't fit within the [byte budget].
+    ///
+    /// [byte budget]: #method.byte_budget
+    pub fn priority(&mut self, name: &'static str, priority: SyncPriority) {
+        self.registry.set_priority(name, priority);
+    }
+
+    /// Checks every synced component for NaN and infinite floats as it's read, catching e.g. a
+    /// transform that went NaN.
+    ///
+    /// Each invalid component is logged and reported to the editor as an issue in the
+    /// `"validation"` category, linked to its entity. A component is only reported again once it
+    /// has been valid in between. This has to serialize every component an extra time, so it's
+    /// off by default.
+    pub fn validate_components(&mut self) {
+        self.registry.set_check_floats();
+    }
+
+    /// Adds a check that every component of type `C` is run through as it's read, returning a
+    /// description of the problem if the component is invalid, e.g. if a value is out of range.
+    ///
+    /// Invalid components are reported the same way as with [`validate_components`], which
+    /// doesn't need to be enabled for the check to run. Several checks can be added for the same
+    /// type. Components read by the bundle's own systems are checked whichever way they were
+    /// registered.
+    ///
+    /// [`validate_components`]: #method.validate_components
+    pub fn add_validator<C, F>(&mut self, validator: F)
+    where
+        C: Component,
+        F: Fn(&C) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.registry.add_validator::<C>(Arc::new(validator));
+    }
+
+    /// Serializes all registered components and resources in `world`, without connecting to the
+    /// editor.
+    ///
+    /// The data is serialized the same way as when it's sent to the editor, so this can be used
+    /// by tests and CI tooling to check the state of a game. Components whose storage hasn't been
+    /// registered in the world and resources that haven't been added are left out.
+    pub fn capture_snapshot(&self, world: &World) -> Snapshot {
+        Snapshot::capture(world, &self.registry)
+    }
+
+    /// Sets the maximum nesting depth of components and resources sent to the editor.
+    ///
+    /// Objects and arrays nested deeper than this are replaced with a
+    /// `{ "truncated_depth": depth }` marker, and a `"diagnostic"` message is sent to the editor
+    /// the first time each type is truncated. This keeps deeply nested (e.g. recursive) types
+    /// from producing data that can't be parsed when the editor sends it back. Defaults to 64.
+    pub fn max_depth(&mut self, depth: usize) {
+        self.registry.set_max_depth(depth);
+    }
+
+    /// Registers a custom message type that the game sends with
+    /// [`EditorConnection::send_message`], along with a sample payload describing its shape.
+    ///
+    /// Registered message types and their samples are listed in the handshake, so that the
+    /// editor knows which messages to expect and can validate them. In debug builds, each
+    /// message of a registered type is also checked against the sample before it's sent:
+    ///
+    /// * Fields that are null in the sample are optional and may hold any value.
+    /// * Objects must have exactly the fields in the sample, apart from optional fields.
+    /// * Array elements must match the first element of the sample array.
+    /// * Any other value only needs to be of the same JSON type as in the sample.
+    ///
+    /// [`EditorConnection::send_message`]: ./struct.EditorConnection.html#method.send_message
+    pub fn register_message<T: Serialize>(&mut self, message_type: &'static str, sample: T) {
+        match serde_json::to_value(sample) {
+            Ok(sample) => self.sender.schemas().register(message_type, sample),
+            Err(error) => error!(
+                "Failed to serialize sample for message {:?}: {:?}",
+                message_type, error
+            ),
+        }
+    }
+
+    /// Reports a system added by the game, so that it's included in the system graph sent to
+    /// the editor.
+    ///
+    /// When the bundle is built, the names and dependencies of all systems it adds are sent to
+    /// the editor in a `"system_graph"` message, which the editor can use to visualize the
+    /// execution order. The bundle can't see systems added outside of it, so any systems that
+    /// should also be displayed need to be reported with their name and dependencies.
+    pub fn report_system(&mut self, name: &str, dependencies: &[&str]) {
+        self.system_graph.report(name, dependencies);
+    }
+
+    /// Enables profiling of the game's systems, returning a profiler used to wrap the systems
+    /// that should be profiled.
+    ///
+    /// See [`SystemProfiler`] for details. Calling this multiple times returns handles to the
+    /// same profiler.
+    ///
+    /// [`SystemProfiler`]: ./struct.SystemProfiler.html
+    pub fn profiler(&mut self) -> SystemProfiler {
+        let (profiler, _) = self.profiler.get_or_insert_with(|| {
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            (SystemProfiler::new(sender), receiver)
+        });
+        profiler.clone()
+    }
+
+    /// Sets the interval at which the current game state will be sent to the editor.
+    ///
+    /// In order to reduce the amount of work the editor has to do to keep track of the latest
+    /// game state, the rate at which the game state is sent can be reduced. This defaults to
+    /// sending updated data every 200 ms. Setting this to 0 will ensure that data is sent every
+    /// frame.
+    ///
+    /// Note that log output is sent every frame regardless of this interval, the interval only
+    /// controls how often the game's state is sent. The editor can change the interval while the
+    /// game is running by sending a `SetSendInterval` message.
+    pub fn send_interval(&mut self, send_interval: Duration) {
+        self.sender_settings.send_interval = send_interval;
+    }
+
+    /// Sets the number of frames over which each state snapshot is transmitted.
+    ///
+    /// Sending the full game state can take a significant amount of time for large worlds,
+    /// causing a spike in frame time every [send interval]. Setting this to a value greater than
+    /// 1 smooths out the spike at the cost of the editor receiving the snapshot slightly later.
+    ///
+    /// If the editor supports partial snapshots (the `"deltas"` capability), the components,
+    /// resources and sections are split into parts of roughly equal size, and each part is
+    /// assembled, serialized and sent on its own frame, marked with `"delta": true`. Only the
+    /// first part carries the entity list and [size statistics]. If the next snapshot is due
+    /// before every part has been sent, the rest are sent with it.
+    ///
+    /// Other editors need each snapshot in one piece, so it's still serialized in a single frame,
+    /// but sending the resulting packets is spread evenly across the given number of frames. Log
+    /// output and other messages are queued behind any snapshot still being transmitted.
+    ///
+    /// Defaults to 1.
+    ///
+    /// [send interval]: #method.send_interval
+    /// [size statistics]: #method.send_stats
+    pub fn send_frames(&mut self, send_frames: usize) {
+        self.sender_settings.send_frames = send_frames;
+    }
+
+    /// Sends packets to the editor from a background thread.
+    ///
+    /// Snapshots are still assembled by the sender system, but the resulting packets are handed
+    /// over to a dedicated thread that encrypts them, if encryption is enabled, and writes them
+    /// to the socket, retrying if the OS send buffer is full. This keeps socket I/O and retries
+    /// out of the dispatch. Disabled by default.
+    pub fn send_in_background(&mut self, enabled: bool) {
+        self.sender_settings.send_in_background = enabled;
+    }
+
+    /// Sets the maximum size in bytes of the packets sent to the editor.
+    ///
+    /// Messages larger than this are split across multiple packets. By default this is 32 KB
+    /// for an editor on the same machine, and 1200 bytes for an editor at any other address,
+    /// which keeps packets below the MTU of almost any network so that they aren't fragmented
+    /// or dropped along the way.
+    pub fn max_packet_size(&mut self, max_packet_size: usize) {
+        self.sender_settings.max_packet_size = Some(max_packet_size);
+    }
+
+    /// Limits the size of the components, resources and sections in each snapshot to `bytes`.
+    ///
+    /// By default every snapshot carries all of the synced data, which can add up to megabytes
+    /// in a large scene. With a budget set, types are added to the snapshot in order of their
+    /// [priority] until the budget is used up, and the rest are deferred to the next snapshot.
+    /// A deferred type's priority rises by one level for every four snapshots in a row it's left
+    /// out of, so every type still reaches the editor every few snapshots even when higher
+    /// priority types would fill the budget on their own. A single type larger than the whole
+    /// budget is sent on its own. The entity list and messages don't count towards the budget.
+    ///
+    /// [priority]: ./enum.SyncPriority.html
+    pub fn byte_budget(&mut self, bytes: usize) {
+        self.sender_settings.byte_budget = Some(bytes);
+    }
+
+    /// Sets which component types are serialized and sent with each snapshot.
+    ///
+    /// By default every registered component type is serialized every frame and sent with each
+    /// snapshot, which can take a while in games with many registered types. With
+    /// [`SyncStrategy::RoundRobin(n)`], only `n` component types are serialized for each
+    /// snapshot, taking turns through all registered types, which spreads the cost out evenly.
+    /// The editor only gets each type every few snapshots, so snapshots are marked with
+    /// `"delta": true` and the editor keeps showing the last data it received for the types
+    /// left out. If the editor doesn't support deltas, every type is sent with each snapshot.
+    ///
+    /// [`SyncStrategy::RoundRobin(n)`]: ./enum.SyncStrategy.html#variant.RoundRobin
+    pub fn sync_strategy(&mut self, strategy: SyncStrategy) {
+        self.sender_settings.strategy = strategy;
+    }
+
+    /// Sets how much of the game state is sent to the editor.
+    ///
+    /// At [`SyncLevel::Minimal`], components and resources aren't serialized at all, and
+    /// snapshots only include the number of entities (as `"entity_count"`) and the timing
+    /// information. Logs, profiler data and other messages are still sent. This is useful for
+    /// soak tests on headless servers, where only metrics and logs are of interest. The editor
+    /// can change the level at runtime with a `SetSyncLevel` message, after which the full state
+    /// is sent again.
+    ///
+    /// Defaults to [`SyncLevel::Full`].
+    ///
+    /// [`SyncLevel::Minimal`]: ./enum.SyncLevel.html#variant.Minimal
+    /// [`SyncLevel::Full`]: ./enum.SyncLevel.html#variant.Full
+    pub fn sync_level(&mut self, level: SyncLevel) {
+        self.sender.subscriptions().set_level(level);
+    }
+
+    /// Sends at most `page_size` components of each type per snapshot, instead of all of them.
+    ///
+    /// A type with many instances otherwise produces one huge array of components, which is
+    /// slow to send and for the editor to display. With pagination, each component type is sent
+    /// with a `"page"` object giving the page's `index`, the page `size`, and the `total`
+    /// number of components of the type. The first page is sent until the editor requests
+    /// another one with a `RequestComponentPage` message, e.g. as the user scrolls through the
+    /// components. Components are paged in order of entity ID.
+    pub fn component_page_size(&mut self, page_size: usize) {
+        self.sender.pages().set_page_size(page_size);
+    }
+
+    /// Sets whether each packet starts with a header identifying the message it belongs to.
+    ///
+    /// Without headers the editor reassembles messages by concatenating packets in the order
+    /// they arrive, so a single lost or reordered packet corrupts the message. With headers,
+    /// each packet starts with the message's ID, the packet's index within the message, and
+    /// the number of packets in the message, which lets the editor put messages back together
+    /// regardless of the order packets arrive in, and discard messages that are missing
+    /// packets.
+    ///
+    /// Enabled by default for UDP transports, unless they use the [`Framing::NodeIpc`]
+    /// compatibility mode, since editors that delimit messages that way don't expect headers.
+    /// IPC transports never lose or reorder data, so they don't send headers by default.
+    ///
+    /// [`Framing::NodeIpc`]: ./enum.Framing.html#variant.NodeIpc
+    pub fn chunk_headers(&mut self, enabled: bool) {
+        self.sender_settings.chunk_headers = Some(enabled);
+    }
+
+    /// Sets how the packets sent to the editor are encoded. Defaults to [`Codec::Json`].
+    ///
+    /// The game advertises binary codecs in its handshake, but keeps sending JSON until the
+    /// editor lists the codec among its own capabilities, so that editors which can't decode it
+    /// keep working.
+    ///
+    /// [`Codec::Json`]: ./enum.Codec.html#variant.Json
+    pub fn codec(&mut self, codec: Codec) {
+        self.sender_settings.codec = codec;
+    }
+
+    /// Sets whether each packet received from the editor starts with a chunk header, in the
+    /// same format as the headers enabled with [`chunk_headers`].
+    ///
+    /// With headers, the game puts each message back together from its chunks before decoding
+    /// it, and discards messages that are missing chunks. Without them, messages from the
+    /// editor are reassembled by concatenating packets in the order they arrive, so a large
+    /// message split across several packets is lost if any of them are reordered.
+    ///
+    /// Enabled by default for UDP transports, unless they use the [`Framing::NodeIpc`]
+    /// compatibility mode, since editors that delimit messages that way don't send headers.
+    /// IPC transports never lose or reorder data, so they don't expect headers by default.
+    ///
+    /// [`chunk_headers`]: #method.chunk_headers
+    /// [`Framing::NodeIpc`]: ./enum.Framing.html#variant.NodeIpc
+    pub fn incoming_chunk_headers(&mut self, enabled: bool) {
+        self.incoming_chunk_headers = Some(enabled);
+    }
+
+    /// Sets the size in bytes of the buffer that each packet from the editor is read into.
+    ///
+    /// UDP packets that don't fit in the buffer are dropped with a warning, so this has to be
+    /// larger than the largest packet the editor sends. Defaults to 64 KB, which fits any UDP
+    /// packet; a smaller buffer saves memory if the editor is known to only send small packets.
+    /// Sizes below 1 KB are raised to 1 KB.
+    pub fn receive_buffer_size(&mut self, size: usize) {
+        self.receive_buffer_size = Some(size);
+    }
+
+    /// Sets whether each snapshot includes size statistics for the registered types.
+    ///
+    /// When enabled, snapshots have a `"stats"` section listing the number of components and
+    /// the size in bytes of the serialized data for each registered component and resource,
+    /// which helps find the types that dominate bandwidth. Sizes are reported even for types
+    /// that weren't sent because they didn't change. Enabled by default.
+    pub fn send_stats(&mut self, enabled: bool) {
+        self.sender_settings.stats = enabled;
+    }
+
+    /// Sets whether each entry in the entity list includes the components that the entity has.
+    ///
+    /// When enabled, each entity has a `"components"` field listing the names of the registered
+    /// components attached to it, so that the editor doesn't have to search every component
+    /// list to find them. The lists are gathered from the storages while the components are
+    /// serialized. Disabled by default.
+    pub fn send_entity_components(&mut self, enabled: bool) {
+        self.sender_settings.entity_components = enabled;
+    }
+
+    /// Holds back log output on frames that send a state snapshot, for at most `max_delay`.
+    ///
+    /// By default log output is sent every frame, so a frame spike that causes a burst of log
+    /// records makes the spike worse if the same frame also sends a snapshot. With a maximum
+    /// delay set, logs received on a snapshot frame are deferred and then trickled out over the
+    /// following frames, at most one packet's worth per frame. Logs that have waited for
+    /// `max_delay` are always sent, so log output is never delayed for longer than that.
+    pub fn defer_logs(&mut self, max_delay: Duration) {
+        self.sender_settings.max_log_delay = Some(max_delay);
+    }
+
+    /// Configures the bundle for editing over slow or constrained networks.
+    ///
+    /// This reduces bandwidth usage as much as possible at the cost of latency and detail:
+    ///
+    /// * Packets are limited to 512 bytes, so that they fit within the MTU of even very
+    ///   constrained networks (e.g. mobile hotspots).
+    /// * The full state is sent once per second, spread across 4 frames.
+    /// * Only components and resources whose data changed since they were last sent are
+    ///   included in each snapshot, which is marked with `"delta": true`.
+    /// * Only the number of entities is sent (as `"entity_count"`), rather than the full list.
+    /// * Size statistics are not sent (see [`send_stats`](#method.send_stats)).
+    /// * Packets are encoded with MessagePack instead of JSON, once the editor says that it
+    ///   supports it (see [`codec`](#method.codec)).
+    ///
+    /// Individual settings can still be adjusted after applying the profile.
+    ///
+    /// Note that since only changes are sent, any snapshot dropped by the network will leave
+    /// the editor with stale data until the affected types change again.
+    pub fn low_bandwidth_profile(&mut self) {
+        self.sender_settings = SenderSettings {
+            send_interval: Duration::from_secs(1),
+            send_frames: 4,
+            max_packet_size: Some(512),
+            delta_only: true,
+            entity_summary: true,
+            stats: false,
+            codec: Codec::MessagePack,
+            ..SenderSettings::default()
+        };
+    }
+
+    /// Alerts the editor when the number of live entities exceeds `threshold`.
+    ///
+    /// The entity count is checked once per second. When it goes over the threshold an
+    /// `"alert"` message is sent to the editor and a warning is logged, which helps catch
+    /// runaway spawners during long playtests. Another alert is only sent if the count drops
+    /// back to the threshold and then exceeds it again.
+    pub fn alert_on_entity_count(&mut self, threshold: usize) {
+        self.watchdog_settings.max_entities = Some(threshold);
+    }
+
+    /// Sends a `"storage_stats"` message every `interval`, with the number of live entities and,
+    /// for each registered component, the number of entities that have it and the kind of
+    /// storage it uses (e.g. `"DenseVecStorage"`).
+    ///
+    /// This helps spot leaks such as thousands of components left behind by entities that
+    /// should have been deleted. Like other messages, the report is still sent at
+    /// [`SyncLevel::Minimal`].
+    ///
+    /// [`SyncLevel::Minimal`]: ./enum.SyncLevel.html#variant.Minimal
+    pub fn report_storage_stats(&mut self, interval: Duration) {
+        self.storage_stats_interval = Some(interval);
+    }
+
+    /// Limits the number of entities the editor can create and destroy in a single frame.
+    ///
+    /// A `CreateEntities` or `DestroyEntities` message asking for more entities than the limit
+    /// is rejected with an `"error"` message, and its request is acknowledged with the
+    /// `limit_exceeded` status. Requests within the limit that don't fit in the current frame
+    /// are handled on the following frames, except for those in a `Transaction`, which are
+    /// rejected in the same way since transactions are always applied right away.
+    ///
+    /// Defaults to 10,000 entities of each.
+    pub fn limit_entity_commands(&mut self, created_per_frame: usize, destroyed_per_frame: usize) {
+        self.entity_limits.created_per_frame = created_per_frame;
+        self.entity_limits.destroyed_per_frame = destroyed_per_frame;
+    }
+
+    /// Stops the editor from creating entities once there are `max` live entities.
+    ///
+    /// `CreateEntities` messages that would go over the maximum are rejected in the same way as
+    /// the ones that go over the limits set with [`limit_entity_commands`]. Entities created by
+    /// the game itself aren't limited.
+    ///
+    /// [`limit_entity_commands`]: #method.limit_entity_commands
+    pub fn limit_entity_count(&mut self, max: usize) {
+        self.entity_limits.max_entities = Some(max);
+    }
+
+    /// Alerts the editor when the number of entities with the component registered as `name`
+    /// exceeds `threshold`.
+    ///
+    /// The component must also be registered with the bundle. See
+    /// [`alert_on_entity_count`](#method.alert_on_entity_count) for how alerts are sent.
+    pub fn alert_on_component_count(&mut self, name: &'static str, threshold: usize) {
+        self.watchdog_settings
+            .max_components
+            .retain(|&(existing, _)| existing != name);
+        self.watchdog_settings
+            .max_components
+            .push((name, threshold));
+    }
+
+    /// Sets the file used to persist settings that the editor stores for this game.
+    ///
+    /// The editor can store arbitrary settings tied to this game (e.g. panel layouts or the
+    /// last camera position), which are sent back to the editor in the handshake. By default
+    /// these only last for the current session; setting a path saves them to that file so that
+    /// they survive across sessions.
+    pub fn editor_meta_path<P: Into<PathBuf>>(&mut self, path: P) {
+        self.editor_meta_path = Some(path.into());
+    }
+
+    /// Sets the directory that the editor can export scenes to.
+    ///
+    /// Paths in `ExportScene` commands are resolved relative to `dir`. Absolute paths and paths
+    /// that use `..` are rejected, so that the editor can't overwrite files elsewhere. Until a
+    /// directory is set every export is rejected.
+    pub fn scene_export_dir<P: Into<PathBuf>>(&mut self, dir: P) {
+        self.scene_export_dir = Some(dir.into());
+    }
+
+    /// Sets an ID that distinguishes this bundle from other bundles in the same process, e.g.
+    /// when a game runs both a server and a client world.
+    ///
+    /// The ID is included as `"instance"` in every message sent to the editor, and in the
+    /// handshake. Messages from the editor that include an `"instance"` are ignored unless it
+    /// matches the ID, while messages without one are handled by every bundle. This lets
+    /// several bundles share the same editor.
+    pub fn instance_id<S: Into<String>>(&mut self, id: S) {
+        self.sender_settings.instance_id = Some(id.into());
+    }
+
+    /// Enables heartbeats, which let the game stop sending state while no editor is connected.
+    ///
+    /// The game sends a `"heartbeat"` message every `interval`, and expects the editor to send
+    /// a `Heartbeat` message (or any other message) at least once every `timeout`. While the
+    /// editor is silent, no state is serialized or sent apart from the heartbeats, which let
+    /// the editor find the game. Once the editor is heard from again, the handshake and the full
+    /// state are sent again.
+    ///
+    /// Only enable this if the editor sends heartbeats, since otherwise the game won't send it
+    /// any state until it sends a message of its own.
+    pub fn heartbeat(&mut self, interval: Duration, timeout: Duration) {
+        self.sender_settings.heartbeat_interval = Some(interval);
+        self.heartbeat_timeout = Some(timeout);
+    }
+
+    /// Sends a `"transport_stats"` message every `interval`, with the number of packets and
+    /// bytes sent, dropped and retried so far, the current packet and byte rates, and the
+    /// number of packets waiting to be sent.
+    ///
+    /// This helps diagnose bandwidth issues when the editor runs on another machine. The same
+    /// totals are available to the game through the [`SyncStats`] resource.
+    ///
+    /// [`SyncStats`]: ./struct.SyncStats.html
+    pub fn send_transport_stats(&mut self, interval: Duration) {
+        self.sender_settings.transport_stats_interval = Some(interval);
+    }
+
+    /// Limits how many bytes are sent to the editor each second, in order to test how the game
+    /// and the editor behave over a low-bandwidth connection.
+    ///
+    /// Packets over the limit stay queued until the limit allows them to be sent, so snapshots
+    /// arrive late and pile up if they're larger than the limit allows for the [send interval].
+    /// The editor can change or remove the limit while the game is running by sending a
+    /// `SetSendRateLimit` message.
+    ///
+    /// This is meant for testing, and shouldn't be enabled otherwise.
+    ///
+    /// [send interval]: #method.send_interval
+    pub fn limit_send_rate(&mut self, bytes_per_second: u64) {
+        self.sender_settings.rate_limit = Some(bytes_per_second);
+    }
+
+    /// Requires the editor to send `token` in an `Authenticate` message before any of its
+    /// commands are accepted.
+    ///
+    /// Without a token, any process that can reach the game's socket can edit the world, which
+    /// is a risk on shared machines or over a LAN. With a token, all messages other than
+    /// `Authenticate`, `Handshake` and `Heartbeat` are ignored until the editor has sent the
+    /// right token, and a `Handshake` is only answered once it has.
+    ///
+    /// Authentication lasts for a single session. A `Handshake` starts a new session, so the
+    /// editor should send it before `Authenticate`. The editor also has to authenticate again
+    /// after it stops sending for longer than the connection timeout, or after timing out when
+    /// [heartbeats] are enabled. The handshake sent when the game starts tells the editor
+    /// whether a token is required.
+    ///
+    /// Note that the token is sent in plaintext, and the game state is sent to the editor
+    /// address regardless. Unless the connection is encrypted with [`encryption_key`], the token
+    /// only guards against connecting the wrong editor by accident, not against anyone who can
+    /// see the traffic.
+    ///
+    /// [heartbeats]: #method.heartbeat
+    /// [`encryption_key`]: #method.encryption_key
+    pub fn require_auth_token<S: Into<String>>(&mut self, token: S) {
+        self.auth_token = Some(token.into());
+    }
+
+    /// Sets where the systems that apply edits from the editor are placed in the dispatcher.
+    ///
+    /// Edits are always applied by the bundle's own systems in a stage of their own, so game
+    /// systems never run at the same time as them. This controls whether that stage runs before
+    /// or after the stages that read and send the game state. Since the bundle can only order
+    /// its systems relative to systems added before it, the bundle should be added before the
+    /// game's systems when using [`SyncPoint::FrameStart`] and after them when using
+    /// [`SyncPoint::FrameEnd`]. The names of the bundle's systems are listed in the [`names`]
+    /// module for ordering game systems around them more precisely.
+    ///
+    /// With [`SyncPoint::FrameEnd`], changes to the values of components and resources are
+    /// deferred until the world is maintained at the end of the frame, so that the systems
+    /// applying them only borrow the storages on frames where the editor has sent something.
+    /// With [`SyncPoint::FrameStart`] they're applied right away, so that the game's systems
+    /// see them in the same frame.
+    ///
+    /// Defaults to [`SyncPoint::FrameEnd`].
+    ///
+    /// [`SyncPoint::FrameStart`]: ./enum.SyncPoint.html#variant.FrameStart
+    /// [`SyncPoint::FrameEnd`]: ./enum.SyncPoint.html#variant.FrameEnd
+    /// [`names`]: ./names/index.html
+    pub fn apply_edits_at(&mut self, point: SyncPoint) {
+        self.apply_edits_at = point;
+    }
+
+    /// Sets how the game responds to updates from the editor for components or resources that
+    /// aren't registered as editable.
+    ///
+    /// Defaults to [`UnknownTypePolicy::WarnOnce`].
+    ///
+    /// [`UnknownTypePolicy::WarnOnce`]: ./enum.UnknownTypePolicy.html#variant.WarnOnce
+    pub fn unknown_type_policy(&mut self, policy: UnknownTypePolicy) {
+        self.unknown_type_policy = policy;
+    }
+
+    /// Sets the address that the UDP port will bind to.
+    ///
+    /// Format should be `address:port`, defaults to `127.0.0.1:0`. Has no effect on bundles
+    /// created with [`try_new`], which bind their socket when they're created.
+    ///
+    /// [`try_new`]: #method.try_new
+    pub fn bind_address(&mut self, bind_address: &'a str) {
+        self.bind_address = bind_address;
+    }
+
+    /// Sets the transport used to communicate with the editor.
+    ///
+    /// By default a UDP transport bound to the [bind address] is created when the bundle is
+    /// built. Providing a transport explicitly allows it to be configured further, e.g. with
+    /// [`Transport::with_conditioning`] to simulate a bad network, or to use [`Transport::ipc`]
+    /// instead of UDP for an editor running on the same machine. When a transport is provided
+    /// the bind address is ignored.
+    ///
+    /// [bind address]: #method.bind_address
+    /// [`Transport::with_conditioning`]: ./struct.Transport.html#method.with_conditioning
+    /// [`Transport::ipc`]: ./struct.Transport.html#method.ipc
+    pub fn transport(&mut self, transport: Transport) {
+        self.transport = Some(transport);
+    }
+
+    /// Encrypts the connection to the editor with `key`, which the editor has to be configured
+    /// with as well.
+    ///
+    /// This is meant for editing a game running on a remote devkit over an untrusted network,
+    /// so that component data and commands aren't sent in plaintext. The key applies to the
+    /// transport the bundle creates as well as one passed to [`transport`]. See
+    /// [`Transport::with_encryption`] for how packets are encrypted. Only UDP transports can be
+    /// encrypted, so building the bundle with an IPC transport fails.
+    ///
+    /// [`transport`]: #method.transport
+    /// [`Transport::with_encryption`]: ./struct.Transport.html#method.with_encryption
+    #[cfg(feature = "encryption")]
+    pub fn encryption_key(&mut self, key: [u8; 32]) {
+        self.encryption_key = Some(key);
+    }
+
+    /// Retrieve a connection to send messages to the editor via the [`SyncEditorSystem`].
+    ///
+    /// The connection can be moved into the game's own systems in order to send custom
+    /// messages with [`EditorConnection::send_message`].
+    ///
+    /// [`EditorConnection::send_message`]: ./struct.EditorConnection.html#method.send_message
+    pub fn connection(&self) -> EditorConnection {
+        self.sender.clone()
+    }
+}
+
+impl<'a> Default for SyncEditorBundle<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, 'b, 'c> SystemBundle<'a, 'b> for SyncEditorBundle<'c> {
+    fn build(mut self, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> BundleResult<()> {
+        let graph = &mut self.system_graph;
+        let transport = match self.transport {
+            Some(transport) => Ok(transport),
+            None => Transport::udp(self.bind_address),
+        };
+        #[cfg(feature = "encryption")]
+        let transport = match self.encryption_key {
+            Some(key) => transport.and_then(|transport| transport.with_encryption(key)),
+            None => transport,
+        };
+        let transports = transport.and_then(|transport| Ok((transport.try_clone()?, transport)));
+        let (sender_transport, transport) = match transports {
+            Ok(transports) => transports,
+            Err(error) if self.disable_on_error => {
+                error!(
+                    "Failed to open the connection to the editor, editor sync is disabled: {}",
+                    error
+                );
+                return Ok(());
+            }
+            Err(error) => {
+                return Err(
+                    format!("Failed to open the connection to the editor: {}", error).into(),
+                );
+            }
+        };
+
+        #[cfg(feature = "physics")]
+        let physics_sender = match self.physics {
+            Some((sender, read_system, write_system)) => {
+                self.read_systems.push(read_system);
+                self.write_systems.push(write_system);
+                Some(sender)
+            }
+            None => None,
+        };
+
+        let registry = Arc::new(self.registry);
+        let pool = if self.background_components {
+            Some(SerializationPool::new(self.serialization_threads))
+        } else {
+            None
+        };
+        let context = RegisterContext {
+            connection: &self.sender,
+            registry: &registry,
+            pool: pool.as_ref(),
+            apply_edits_at: self.apply_edits_at,
+        };
+
+        // Advertise the optional features that are enabled. These stay enabled until the editor
+        // tells us which ones it supports.
+        let mut capabilities = Capabilities::default();
+        capabilities.insert(Capability::EditAcks);
+        if self.sender_settings.delta_only || self.sender_settings.strategy != SyncStrategy::Full {
+            capabilities.insert(Capability::Deltas);
+        }
+        if self.sender_settings.entity_summary {
+            capabilities.insert(Capability::EntitySummary);
+        }
+        if self.sender_settings.stats {
+            capabilities.insert(Capability::Stats);
+        }
+        if self.profiler.is_some() {
+            capabilities.insert(Capability::Profiler);
+        }
+        if self.prefab_spawner.is_some() {
+            capabilities.insert(Capability::Prefabs);
+        }
+        if self.console_handler.is_some() {
+            capabilities.insert(Capability::Console);
+        }
+        #[cfg(feature = "screen-capture")]
+        {
+            if self.screen_capture.is_some() {
+                capabilities.insert(Capability::ScreenCapture);
+            }
+        }
+        if cfg!(feature = "renderer") {
+            capabilities.insert(Capability::Picking);
+        }
+        if self.history.is_some() {
+            capabilities.insert(Capability::History);
+        }
+        self.sender.capabilities().set(capabilities);
+
+        // NOTE: A binary codec is only advertised, and isn't used until the editor has listed it.
+        if self.sender_settings.codec == Codec::MessagePack {
+            capabilities.insert(Capability::MessagePack);
+        }
+        let instance_id = self.sender_settings.instance_id.clone();
+
+        // With heartbeats enabled, nothing is sent until the editor is heard from.
+        if self.heartbeat_timeout.is_some() {
+            self.sender.presence().set_active(false);
+        }
+
+        // Create the sender system, which will update the editor on all tracked
+        // components/resources/entities.
+        let (interval_sender, interval_receiver) = crossbeam_channel::unbounded::<Duration>();
+        let (rate_limit_sender, rate_limit_receiver) = crossbeam_channel::unbounded();
+        self.sender_settings.priorities = registry.priorities();
+        if let SyncStrategy::RoundRobin(per_turn) = self.sender_settings.strategy {
+            self.sender
+                .schedule()
+                .round_robin(registry.component_names(), per_turn);
+        }
+        let sender_system = EditorSenderSystem::from_channel(
+            self.receiver,
+            interval_receiver,
+            rate_limit_receiver,
+            self.sender_settings,
+            &self.sender,
+            sender_transport,
+        );
+        match sender_system.crash_reporter() {
+            Ok(reporter) => self.sender.set_crash_reporter(reporter),
+            Err(error) => warn!(
+                "Failed to clone the transport, panics won't be reported to the editor: {}",
+                error
+            ),
+        }
+        let mut sync_systems = self.sync_systems;
+        let mut send_stages = Some(SendStages {
+            #[cfg(feature = "renderer")]
+            interest: self
+                .interest_area
+                .map(|area| InterestSystem::new(area, self.sender.clone())),
+            read_systems: self.read_systems,
+            entity_id_mapping: self.entity_id_mapping,
+            entity_names: self
+                .entity_naming
+                .map(|naming| EntityNameSystem::new(naming, registry.clone())),
+            entity_uuids: if self.entity_uuids {
+                Some(EntityUuidSystem)
+            } else {
+                None
+            },
+            audio: if self.audio {
+                Some(AudioSyncSystem::new(self.sender.clone()))
+            } else {
+                None
+            },
+            dynamic_components: if self.dynamic_components {
+                Some(DynamicComponentSyncSystem::new(self.sender.clone()))
+            } else {
+                None
+            },
+            debug_draw: DebugDrawSystem::new(self.sender.clone()),
+            issues: IssuesSystem::new(self.sender.clone()),
+            sender_system,
+        });
+
+        // Ensure that all previous systems are done before syncing.
+        graph.add_barrier(dispatcher);
+
+        // When edits are applied at the end of the frame, the state is sent first.
+        if self.apply_edits_at == SyncPoint::FrameEnd {
+            if let Some(stages) = send_stages.take() {
+                stages.register(dispatcher, graph, &context, &mut sync_systems);
+            }
+
+            // Ensure all components/resources are sent before writing.
+            graph.add_barrier(dispatcher);
+        }
+
+        // Create the receiver system, which will read any incoming messages from the
+        // editor and pass them to the corresponding systems for applying changes to
+        // components/resources/entities.
+        let (entity_sender, entity_receiver) = crossbeam_channel::unbounded::<EntityMessage>();
+        let (scene_sender, scene_receiver) = crossbeam_channel::unbounded::<SceneExport>();
+        let (clone_sender, clone_receiver) = crossbeam_channel::unbounded::<CloneEntities>();
+        let (selection_sender, selection_receiver) = crossbeam_channel::unbounded::<SetSelection>();
+        let (bookmark_sender, bookmark_receiver) = crossbeam_channel::unbounded::<BookmarkEntity>();
+        let (query_sender, query_receiver) = crossbeam_channel::unbounded::<EntityQuery>();
+        let (orphan_sender, orphan_receiver) = crossbeam_channel::unbounded::<OrphanRequest>();
+        let (watch_sender, watch_receiver) = crossbeam_channel::unbounded::<WatchMessage>();
+        let (pin_sender, pin_receiver) = crossbeam_channel::unbounded::<PinMessage>();
+        let (breakpoint_sender, breakpoint_receiver) =
+            crossbeam_channel::unbounded::<BreakpointMessage>();
+        let (time_sender, time_receiver) = crossbeam_channel::unbounded::<TimeControl>();
+        let (gizmo_sender, gizmo_receiver) = crossbeam_channel::unbounded::<GizmoMessage>();
+        let (reload_sender, reload_receiver) = crossbeam_channel::unbounded::<AssetReload>();
+        #[cfg(feature = "renderer")]
+        let (picking_sender, picking_receiver) = crossbeam_channel::unbounded::<PickRequest>();
+        let handshake = Handshake {
+            instance: instance_id,
+            components: registry.component_names(),
+            resources: registry.resource_names(),
+            redacted: registry.redacted_fields(),
+            read_only: registry.masked_fields(),
+            fixed_step: registry.fixed_step_names(),
+            capabilities,
+            messages: self.sender.schemas().samples(),
+            editor_meta: EditorMeta::load(self.editor_meta_path),
+            auth_required: self.auth_token.is_some(),
+        };
+        self.sender.send_message("handshake", &handshake);
+        let (prefab_sender, prefab_system) = match self.prefab_spawner {
+            Some((sender, system)) => (Some(sender), Some(system)),
+            None => (None, None),
+        };
+        let (volume_sender, volume_receiver) = if self.audio {
+            let (sender, receiver) = crossbeam_channel::unbounded::<SetMasterVolume>();
+            (Some(sender), Some(receiver))
+        } else {
+            (None, None)
+        };
+        let (dynamic_sender, dynamic_receiver) = if self.dynamic_components {
+            let (sender, receiver) = crossbeam_channel::unbounded::<DynamicComponentEdit>();
+            (Some(sender), Some(receiver))
+        } else {
+            (None, None)
+        };
+        let (history_sender, history_system) = match self.history {
+            Some(frames) => {
+                let (sender, receiver) = crossbeam_channel::unbounded::<HistoryRequest>();
+                let system =
+                    HistorySystem::new(frames, receiver, registry.clone(), self.sender.clone());
+                (Some(sender), Some(system))
+            }
+            None => (None, None),
+        };
+        let (console_sender, console_system) = match self.console_handler {
+            Some(handler) => {
+                let (sender, receiver) = crossbeam_channel::unbounded::<ConsoleInput>();
+                let system = ConsoleSystem::new(receiver, handler, self.sender.clone());
+                (Some(sender), Some(system))
+            }
+            None => (None, None),
+        };
+        let routes = MessageRoutes {
+            component_map: self.component_map.clone(),
+            resource_map: self.resource_map.clone(),
+            chunk_map: self.chunk_map.clone(),
+            dev_only_components: self.dev_only_components,
+            dev_only_resources: self.dev_only_resources,
+            entity_handler: entity_sender.clone(),
+            scene_export: scene_sender,
+            clone_entities: clone_sender,
+            selection: selection_sender,
+            bookmarks: bookmark_sender,
+            query: query_sender,
+            orphans: orphan_sender,
+            watch: watch_sender,
+            pin: pin_sender,
+            breakpoint: breakpoint_sender,
+            time_control: time_sender,
+            gizmo: gizmo_sender,
+            send_interval: interval_sender,
+            rate_limit: rate_limit_sender,
+            asset_reload: reload_sender,
+            #[cfg(feature = "renderer")]
+            picking: picking_sender,
+            prefab_spawn: prefab_sender,
+            console: console_sender,
+            dynamic_components: dynamic_sender,
+            audio_volume: volume_sender,
+            history: history_sender,
+            #[cfg(feature = "physics")]
+            physics: physics_sender,
+        };
+        let incoming_chunk_headers = self
+            .incoming_chunk_headers
+            .unwrap_or_else(|| transport.uses_chunk_headers());
+        let mut receiver_system = EditorReceiverSystem::new(
+            routes,
+            self.sender.clone(),
+            handshake,
+            UnknownTypes::new(self.unknown_type_policy),
+            registry.clone(),
+            self.heartbeat_timeout,
+            self.entity_limits.clone(),
+            transport,
+        );
+        if let Some(token) = self.auth_token {
+            receiver_system.require_auth_token(AuthToken::new(token));
+        }
+        receiver_system.reassemble_chunks(incoming_chunk_headers);
+        if let Some(size) = self.receive_buffer_size {
+            receiver_system.receive_buffer_size(size);
+        }
+        graph.add(dispatcher, receiver_system, names::RECEIVER, &[]);
+
+        // Register the system that applies entity changes (creates/destroys entities).
+        // This must also depend on the editor receiver system so that it can apply
+        // an entity changes specified by the editor.
+        graph.add(
+            dispatcher,
+            EntityHandlerSystem::new(
+                entity_receiver,
+                self.sender.clone(),
+                self.entity_limits.clone(),
+            ),
+            names::ENTITY_HANDLER,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that duplicates entities at the editor's request.
+        graph.add(
+            dispatcher,
+            CloneEntitiesSystem::new(clone_receiver, registry.clone(), self.sender.clone()),
+            names::CLONE_ENTITIES,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that keeps the selection in sync with the editor.
+        graph.add(
+            dispatcher,
+            SelectionSystem::new(selection_receiver, self.sender.clone()),
+            names::SELECTION,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that keeps bookmarked entities in sync with the editor.
+        graph.add(
+            dispatcher,
+            BookmarkSystem::new(bookmark_receiver, self.sender.clone()),
+            names::BOOKMARKS,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that exports entities selected in the editor to prefab files.
+        graph.add(
+            dispatcher,
+            SceneExportSystem::new(
+                scene_receiver,
+                registry.clone(),
+                self.sender.clone(),
+                self.scene_export_dir,
+            ),
+            names::SCENE_EXPORT,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that answers entity queries from the editor.
+        graph.add(
+            dispatcher,
+            EntityQuerySystem::new(query_receiver, registry.clone(), self.sender.clone()),
+            names::ENTITY_QUERY,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that finds and purges orphaned entities at the editor's request.
+        graph.add(
+            dispatcher,
+            OrphanSystem::new(
+                orphan_receiver,
+                registry.clone(),
+                self.sender.clone(),
+                entity_sender,
+            ),
+            names::ORPHANS,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that sends the values of fields watched by the editor.
+        graph.add(
+            dispatcher,
+            WatchSystem::new(watch_receiver, registry.clone(), self.sender.clone()),
+            names::WATCH,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that holds fields pinned by the editor at their pinned values.
+        graph.add(
+            dispatcher,
+            PinEnforcementSystem::new(pin_receiver, registry.clone(), self.sender.clone()),
+            names::PIN,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that sends the timings of profiled systems, if profiling is enabled.
+        if let Some((_, timing_receiver)) = self.profiler {
+            graph.add(
+                dispatcher,
+                ProfilerSystem::new(timing_receiver, self.sender.clone()),
+                names::PROFILER,
+                &[],
+            );
+        }
+
+        // Register the system that alerts the editor of runaway entity counts, if configured.
+        if !self.watchdog_settings.is_empty() {
+            graph.add(
+                dispatcher,
+                WatchdogSystem::new(
+                    self.watchdog_settings,
+                    registry.clone(),
+                    self.sender.clone(),
+                ),
+                names::WATCHDOG,
+                &[],
+            );
+        }
+
+        // Register the system that reports component storage statistics, if enabled.
+        if let Some(interval) = self.storage_stats_interval {
+            graph.add(
+                dispatcher,
+                StorageStatsSystem::new(interval, registry.clone(), self.sender.clone()),
+                names::STORAGE_STATS,
+                &[],
+            );
+        }
+
+        // Register the system that hot-reloads assets at the editor's request.
+        graph.add(
+            dispatcher,
+            AssetReloadSystem::new(reload_receiver, self.sender.clone()),
+            names::ASSET_RELOAD,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that changes the time scale and fixed step at the editor's request.
+        graph.add(
+            dispatcher,
+            TimeControlSystem::new(time_receiver, self.sender.clone()),
+            names::TIME_CONTROL,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that applies transform gizmo drags from the editor.
+        graph.add(
+            dispatcher,
+            GizmoWriteSystem::new(gizmo_receiver, self.sender.clone()),
+            names::GIZMO_WRITE,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that answers requests to pick entities in the editor's viewport.
+        #[cfg(feature = "renderer")]
+        graph.add(
+            dispatcher,
+            PickingSystem::new(picking_receiver, self.sender.clone()),
+            names::PICKING,
+            &[names::RECEIVER],
+        );
+
+        // Register the system that spawns prefabs requested by the editor, if any.
+        if let Some(prefab_system) = prefab_system {
+            prefab_system.register(dispatcher, graph, &context);
+        }
+
+        // Register the system that streams the game's rendered frames, if screen capture is
+        // enabled.
+        #[cfg(feature = "screen-capture")]
+        {
+            if let Some(settings) = self.screen_capture {
+                graph.add(
+                    dispatcher,
+                    ScreenCaptureSystem::new(settings, self.sender.clone()),
+                    names::SCREEN_CAPTURE,
+                    &[],
+                );
+            }
+        }
+
+        // Register the system that applies edits to dynamic components, if they're synced.
+        if let Some(dynamic_receiver) = dynamic_receiver {
+            graph.add(
+                dispatcher,
+                DynamicComponentWriteSystem::new(dynamic_receiver, self.sender.clone()),
+                names::WRITE_DYNAMIC_COMPONENTS,
+                &[names::ENTITY_HANDLER],
+            );
+        }
+
+        // Register the system that changes the audio volume at the editor's request, if audio is
+        // synced.
+        if let Some(volume_receiver) = volume_receiver {
+            graph.add(
+                dispatcher,
+                AudioVolumeSystem::new(volume_receiver, self.sender.clone()),
+                names::AUDIO_VOLUME,
+                &[names::RECEIVER],
+            );
+        }
+
+        // Register the system that evaluates console input from the editor, if a handler is set.
+        if let Some(console_system) = console_system {
+            graph.add(
+                dispatcher,
+                console_system,
+                names::CONSOLE,
+                &[names::RECEIVER],
+            );
+        }
+
+        // Register the systems for each of the component/resource types that support
+        // being edited at runtime. Internally these declare a dependency on the
+        // editor receiver system.
+        for write_system in self.write_systems {
+            write_system.register(dispatcher, graph, &context);
+        }
+
+        // Register the systems added by other crates that change the game state.
+        {
+            let mut systems = SyncSystems::new(dispatcher, graph, &self.sender);
+            for sync_system in &mut sync_systems {
+                sync_system.register_write_systems(&mut systems);
+            }
+        }
+
+        // Ensure all components/resources/entities are written before continuing the dispatch
+        graph.add_barrier(dispatcher);
+
+        // Register the system that pauses the game when a breakpoint set by the editor is hit,
+        // which needs to see the edits applied this frame.
+        graph.add(
+            dispatcher,
+            BreakpointSystem::new(breakpoint_receiver, registry.clone(), self.sender.clone()),
+            names::BREAKPOINTS,
+            &[],
+        );
+
+        // Register the system that records the state of registered types, if history is enabled.
+        if let Some(history_system) = history_system {
+            graph.add(
+                dispatcher,
+                history_system,
+                names::HISTORY,
+                &[names::RECEIVER],
+            );
+        }
+
+        // When edits are applied at the start of the frame, the state is sent afterwards, so
+        // that the editor sees the result of its edits as soon as possible.
+        if let Some(stages) = send_stages {
+            graph.add_barrier(dispatcher);
+            stages.register(dispatcher, graph, &context, &mut sync_systems);
+
+            // Ensure the state is read before the game's systems change it.
+            graph.add_barrier(dispatcher);
+        }
+
+        self.sender.send_message("system_graph", &self.system_graph);
+
+        Ok(())
+    }
+}
+
+/// The systems that read the game state and send it to the editor, which are registered either
+/// before or after the systems that apply edits depending on the bundle's [`SyncPoint`].
+///
+/// [`SyncPoint`]: ./enum.SyncPoint.html
+struct SendStages {
+    #[cfg(feature = "renderer")]
+    interest: Option<InterestSystem>,
+    read_systems: Vec<Box<dyn RegisterReadSystem>>,
+    entity_id_mapping: Option<Box<dyn RegisterReadSystem>>,
+    entity_names: Option<EntityNameSystem>,
+    entity_uuids: Option<EntityUuidSystem>,
+    audio: Option<AudioSyncSystem>,
+    dynamic_components: Option<DynamicComponentSyncSystem>,
+    debug_draw: DebugDrawSystem,
+    issues: IssuesSystem,
+    sender_system: EditorSenderSystem,
+}
+
+impl SendStages {
+    fn register(
+        self,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+        sync_systems: &mut [Box<dyn SyncSystemExt>],
+    ) {
+        // Register the system that picks the entities whose components are serialized, if an
+        // interest area is set. It has to finish before any components are read.
+        #[cfg(feature = "renderer")]
+        {
+            if let Some(interest) = self.interest {
+                graph.add(dispatcher, interest, names::INTEREST, &[]);
+                graph.add_barrier(dispatcher);
+            }
+        }
+
+        // Register the systems for serializing each of the component/resource types.
+        for read_system in self.read_systems {
+            read_system.register(dispatcher, graph, context);
+        }
+
+        // Register the system that maps entities to their stable IDs, if any.
+        if let Some(entity_id_mapping) = self.entity_id_mapping {
+            entity_id_mapping.register(dispatcher, graph, context);
+        }
+
+        // Register the system that names entities, if enabled.
+        if let Some(entity_names) = self.entity_names {
+            graph.add(dispatcher, entity_names, names::ENTITY_NAMES, &[]);
+        }
+
+        // Register the system that gives entities UUIDs, if enabled.
+        if let Some(entity_uuids) = self.entity_uuids {
+            graph.add(dispatcher, entity_uuids, names::ENTITY_UUIDS, &[]);
+        }
+
+        // Register the system that sends the state of the game's audio, if enabled.
+        if let Some(audio) = self.audio {
+            graph.add(dispatcher, audio, names::AUDIO, &[]);
+        }
+
+        // Register the system that sends dynamic components, if they're synced.
+        if let Some(dynamic_components) = self.dynamic_components {
+            graph.add(
+                dispatcher,
+                dynamic_components,
+                names::DYNAMIC_COMPONENTS,
+                &[],
+            );
+        }
+
+        // Register the system that sends the debug shapes drawn during the frame.
+        graph.add(dispatcher, self.debug_draw, names::DEBUG_DRAW, &[]);
+
+        // Register the system that sends the issues reported during the frame.
+        graph.add(dispatcher, self.issues, names::ISSUES, &[]);
+
+        // Register the systems added by other crates that send their own data.
+        {
+            let mut systems = SyncSystems::new(dispatcher, graph, context.connection);
+            for sync_system in sync_systems {
+                sync_system.register_read_systems(&mut systems);
+            }
+        }
+
+        // Ensure all components/resources are read before sending.
+        graph.add_barrier(dispatcher);
+
+        graph.add(dispatcher, self.sender_system, names::SENDER, &[]);
+    }
+}
+
+struct ReadComponent<T> {
+    name: &'static str,
+    _marker: PhantomData<T>,
+}
+
+struct ReadAssets<A> {
+    _marker: PhantomData<A>,
+}
+
+#[cfg(feature = "physics")]
+struct ReadPhysics<P> {
+    _marker: PhantomData<P>,
+}
+
+#[cfg(feature = "physics")]
+struct ControlPhysics<P> {
+    receiver: Receiver<PhysicsCommand>,
+    _marker: PhantomData<P>,
+}
+
+struct ReadTrackedComponent<T> {
+    name: &'static str,
+    _marker: PhantomData<T>,
+}
+
+struct ReadBackgroundComponent<T> {
+    name: &'static str,
+    _marker: PhantomData<T>,
+}
+
+struct ReadResource<T> {
+    name: &'static str,
+    _marker: PhantomData<T>,
+}
+
+struct ReadChunkedResource<T, C> {
+    name: &'static str,
+    chunker: C,
+    receiver: Receiver<ChunkRequest>,
+    _marker: PhantomData<T>,
+}
+
+struct MapEntityIds<T> {
+    _marker: PhantomData<T>,
+}
+
+struct SpawnPrefabs<T> {
+    receiver: Receiver<PrefabSpawn>,
     _marker: PhantomData<T>,
 }
 
@@ -344,7 +2312,7 @@ struct WriteComponent<T> {
 
 struct WriteResource<T> {
     name: &'static str,
-    receiver: Receiver<serde_json::Value>,
+    receiver: Receiver<IncomingResource>,
     _marker: PhantomData<T>,
 }
 
@@ -355,11 +2323,121 @@ where
     fn register(
         self: Box<Self>,
         dispatcher: &mut DispatcherBuilder,
-        connection: &EditorConnection,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            ReadComponentSystem::<T>::new(
+                self.name,
+                context.connection.clone(),
+                context.registry.options(self.name),
+                context.validator::<T>(self.name),
+            ),
+            &names::read_component(self.name),
+            &[],
+        );
+    }
+}
+
+impl<A: Asset> RegisterReadSystem for ReadAssets<A> {
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            AssetSyncSystem::<A>::new(context.connection.clone()),
+            &names::read_assets(A::NAME),
+            &[],
+        );
+    }
+}
+
+#[cfg(feature = "physics")]
+impl<P: Position<f32>> RegisterReadSystem for ReadPhysics<P> {
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            PhysicsSyncSystem::<P>::new(context.connection.clone()),
+            names::PHYSICS,
+            &[],
+        );
+    }
+}
+
+#[cfg(feature = "physics")]
+impl<P: Position<f32>> RegisterWriteSystem for ControlPhysics<P> {
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            PhysicsControlSystem::<P>::new(self.receiver, context.connection.clone()),
+            names::PHYSICS_CONTROL,
+            &[names::RECEIVER],
+        );
+    }
+}
+
+impl<T> RegisterReadSystem for ReadTrackedComponent<T>
+where
+    T: Component + Serialize + Send,
+    T::Storage: Tracked,
+{
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            ReadTrackedComponentSystem::<T>::new(
+                self.name,
+                context.connection.clone(),
+                context.registry.options(self.name),
+                context.validator::<T>(self.name),
+            ),
+            &names::read_component(self.name),
+            &[],
+        );
+    }
+}
+
+impl<T> RegisterReadSystem for ReadBackgroundComponent<T>
+where
+    T: Component + Clone + Serialize + Send,
+{
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
     ) {
-        dispatcher.add(
-            ReadComponentSystem::<T>::new(self.name, connection.clone()),
-            "",
+        let pool = context
+            .pool
+            .expect("No serialization threads for background component");
+        graph.add(
+            dispatcher,
+            ReadBackgroundComponentSystem::<T>::new(
+                self.name,
+                context.connection.clone(),
+                context.registry.options(self.name),
+                pool.clone(),
+                context.validator::<T>(self.name),
+            ),
+            &names::read_component(self.name),
             &[],
         );
     }
@@ -372,26 +2450,117 @@ where
     fn register(
         self: Box<Self>,
         dispatcher: &mut DispatcherBuilder,
-        connection: &EditorConnection,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            ReadResourceSystem::<T>::new(
+                self.name,
+                context.connection.clone(),
+                context.registry.options(self.name),
+            ),
+            &names::read_resource(self.name),
+            &[],
+        );
+    }
+}
+
+impl<T, C> RegisterReadSystem for ReadChunkedResource<T, C>
+where
+    T: Resource,
+    C: ResourceChunker<T>,
+{
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            ChunkedResourceSystem::<T, C>::new(
+                self.name,
+                self.chunker,
+                self.receiver,
+                context.connection.clone(),
+            ),
+            &names::read_resource(self.name),
+            &[],
+        );
+    }
+}
+
+impl<T> RegisterReadSystem for MapEntityIds<T>
+where
+    T: EntityIdMapping,
+{
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        _context: &RegisterContext,
     ) {
-        dispatcher.add(
-            ReadResourceSystem::<T>::new(self.name, connection.clone()),
-            "",
+        graph.add(
+            dispatcher,
+            EntityIdMapSystem::<T>::new(),
+            names::ENTITY_ID_MAP,
             &[],
         );
     }
 }
 
+impl<T> RegisterWriteSystem for SpawnPrefabs<T>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        graph.add(
+            dispatcher,
+            PrefabSpawnSystem::<T>::new(self.receiver, context.connection.clone()),
+            names::PREFAB_SPAWN,
+            &[names::RECEIVER],
+        );
+    }
+}
+
 impl<T> RegisterWriteSystem for WriteComponent<T>
 where
     T: Component + Serialize + DeserializeOwned + Send + Sync,
 {
-    fn register(self: Box<Self>, dispatcher: &mut DispatcherBuilder) {
-        dispatcher.add(
-            WriteComponentSystem::<T>::new(self.name, self.receiver),
-            "",
-            &["entity_creator"],
-        );
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        let name = names::write_component(self.name);
+        let connection = context.connection.clone();
+        let options = context.registry.options(self.name);
+        match context.apply_edits_at {
+            SyncPoint::FrameStart => graph.add(
+                dispatcher,
+                WriteComponentSystem::<T>::new(self.name, self.receiver, connection, options),
+                &name,
+                &[names::ENTITY_HANDLER],
+            ),
+            SyncPoint::FrameEnd => graph.add(
+                dispatcher,
+                DeferredWriteComponentSystem::<T>::new(
+                    self.name,
+                    self.receiver,
+                    connection,
+                    options,
+                ),
+                &name,
+                &[names::ENTITY_HANDLER],
+            ),
+        }
     }
 }
 
@@ -399,21 +2568,82 @@ impl<T> RegisterWriteSystem for WriteResource<T>
 where
     T: Resource + Serialize + DeserializeOwned + Send + Sync,
 {
-    fn register(self: Box<Self>, dispatcher: &mut DispatcherBuilder) {
-        dispatcher.add(
-            WriteResourceSystem::<T>::new(self.name, self.receiver),
-            "",
-            &["entity_creator"],
-        );
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    ) {
+        let name = names::write_resource(self.name);
+        let connection = context.connection.clone();
+        let options = context.registry.options(self.name);
+        match context.apply_edits_at {
+            SyncPoint::FrameStart => graph.add(
+                dispatcher,
+                WriteResourceSystem::<T>::new(self.name, self.receiver, connection, options),
+                &name,
+                &[names::ENTITY_HANDLER],
+            ),
+            SyncPoint::FrameEnd => graph.add(
+                dispatcher,
+                DeferredWriteResourceSystem::<T>::new(
+                    self.name,
+                    self.receiver,
+                    connection,
+                    options,
+                ),
+                &name,
+                &[names::ENTITY_HANDLER],
+            ),
+        }
+    }
+}
+
+/// State shared by all of the per-type systems when they're registered.
+struct RegisterContext<'a> {
+    connection: &'a EditorConnection,
+    registry: &'a Registry,
+
+    // Only present if any components are serialized in the background.
+    pool: Option<&'a SerializationPool>,
+
+    // Edits to component and resource values are deferred until the world is maintained when
+    // they're applied at the end of the frame.
+    apply_edits_at: SyncPoint,
+}
+
+impl<'a> RegisterContext<'a> {
+    /// Creates the validator for components of type `T` registered as `name`, if they need to
+    /// be validated.
+    fn validator<T>(&self, name: &'static str) -> Option<ComponentValidator<T>>
+    where
+        T: Serialize + 'static,
+    {
+        ComponentValidator::new(
+            name,
+            self.connection.clone(),
+            self.registry.checks_floats(),
+            self.registry.validators::<T>(),
+        )
     }
 }
 
 trait RegisterReadSystem {
-    fn register(self: Box<Self>, dispatcher: &mut DispatcherBuilder, connection: &EditorConnection);
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    );
 }
 
 trait RegisterWriteSystem {
-    fn register(self: Box<Self>, dispatcher: &mut DispatcherBuilder);
+    fn register(
+        self: Box<Self>,
+        dispatcher: &mut DispatcherBuilder,
+        graph: &mut SystemGraph,
+        context: &RegisterContext,
+    );
 }
 
 #[cfg(test)]
@@ -440,4 +2670,45 @@ mod test {
         sync_resources!(bundle, AmbientColor,);
         read_resources!(bundle, AmbientColor,);
     }
+
+    /// Tests that dev-only types are registered like any other type in debug builds.
+    #[test]
+    fn dev_only_in_debug_builds() {
+        let mut bundle = SyncEditorBundle::default();
+        bundle.dev_builds = true;
+        bundle.sync_component_dev_only::<Light>("Light");
+        bundle.read_component_dev_only::<Camera>("Camera");
+        bundle.sync_resource_dev_only::<AmbientColor>("AmbientColor");
+        bundle.read_resource_dev_only::<AmbientColor>("ReadAmbientColor");
+
+        assert_eq!(bundle.read_systems.len(), 4);
+        assert!(bundle.component_map.contains_key("Light"));
+        assert!(bundle.resource_map.contains_key("AmbientColor"));
+        assert!(bundle.dev_only_components.is_empty());
+        assert!(bundle.dev_only_resources.is_empty());
+    }
+
+    /// Tests that dev-only types are left out of release builds, and remembered so that edits
+    /// for them can be acknowledged.
+    #[test]
+    fn dev_only_in_release_builds() {
+        let mut bundle = SyncEditorBundle::default();
+        bundle.dev_builds = false;
+        bundle.sync_component_dev_only::<Light>("Light");
+        bundle.read_component_dev_only::<Camera>("Camera");
+        bundle.sync_resource_dev_only::<AmbientColor>("AmbientColor");
+        bundle.read_resource_dev_only::<AmbientColor>("ReadAmbientColor");
+
+        assert!(bundle.read_systems.is_empty());
+        assert!(bundle.write_systems.is_empty());
+        assert!(bundle.component_map.is_empty());
+        assert!(bundle.resource_map.is_empty());
+
+        let mut components = bundle.dev_only_components.iter().collect::<Vec<_>>();
+        components.sort();
+        assert_eq!(components, vec![&"Camera", &"Light"]);
+        let mut resources = bundle.dev_only_resources.iter().collect::<Vec<_>>();
+        resources.sort();
+        assert_eq!(resources, vec![&"AmbientColor", &"ReadAmbientColor"]);
+    }
 }
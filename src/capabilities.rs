@@ -0,0 +1,194 @@
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// An optional feature of the editor protocol.
+///
+/// The game lists the features it uses in the handshake, and the editor replies with the ones it
+/// understands. Features that aren't supported by both sides are disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Capability {
+    /// Snapshots only contain the components and resources that changed.
+    Deltas,
+
+    /// Snapshots only contain the number of entities instead of the full entity list.
+    EntitySummary,
+
+    /// Snapshots contain size statistics for each registered type.
+    Stats,
+
+    /// Edits with a `request_id` are acknowledged with an `"edit_ack"` message.
+    EditAcks,
+
+    /// Per-system timings are sent as `"profiler"` messages.
+    Profiler,
+
+    /// Prefabs can be spawned with a `SpawnPrefab` message.
+    Prefabs,
+
+    /// Entities can be picked from the screen with a `PickRequest` message.
+    Picking,
+
+    /// Console input can be evaluated with an `Eval` message.
+    Console,
+
+    /// Frames rendered by the game are streamed as `"screen_capture"` messages.
+    ScreenCapture,
+
+    /// Recent frames can be requested with `RequestHistory` and restored with `RewindTo`.
+    History,
+
+    /// Packets are encoded with MessagePack instead of JSON.
+    ///
+    /// NOTE: Unlike the other capabilities, this is only used once the editor has listed it,
+    /// since an editor that can't decode the packets can't read anything else either.
+    MessagePack,
+}
+
+impl Capability {
+    const ALL: [Capability; 11] = [
+        Capability::Deltas,
+        Capability::EntitySummary,
+        Capability::Stats,
+        Capability::EditAcks,
+        Capability::Profiler,
+        Capability::Prefabs,
+        Capability::Picking,
+        Capability::Console,
+        Capability::ScreenCapture,
+        Capability::History,
+        Capability::MessagePack,
+    ];
+
+    /// The name of the capability in the handshake.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Capability::Deltas => "deltas",
+            Capability::EntitySummary => "entity_summary",
+            Capability::Stats => "stats",
+            Capability::EditAcks => "edit_acks",
+            Capability::Profiler => "profiler",
+            Capability::Prefabs => "prefabs",
+            Capability::Picking => "picking",
+            Capability::Console => "console",
+            Capability::ScreenCapture => "screen_capture",
+            Capability::History => "history",
+            Capability::MessagePack => "msgpack",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Capability> {
+        Capability::ALL
+            .iter()
+            .cloned()
+            .find(|capability| capability.name() == name)
+    }
+
+    fn bit(self) -> usize {
+        1 << self as usize
+    }
+}
+
+/// A set of capabilities.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Capabilities {
+    bits: usize,
+}
+
+impl Capabilities {
+    /// Builds a set from capability names, ignoring any names that aren't known.
+    ///
+    /// Newer editors may support capabilities that this version of the game doesn't know about,
+    /// which simply won't be used.
+    pub(crate) fn from_names<'a, I>(names: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut capabilities = Capabilities::default();
+        for capability in names.into_iter().filter_map(Capability::from_name) {
+            capabilities.insert(capability);
+        }
+        capabilities
+    }
+
+    pub(crate) fn insert(&mut self, capability: Capability) {
+        self.bits |= capability.bit();
+    }
+
+    pub(crate) fn contains(self, capability: Capability) -> bool {
+        self.bits & capability.bit() != 0
+    }
+
+    /// The capabilities contained in both sets.
+    pub(crate) fn intersection(self, other: Capabilities) -> Capabilities {
+        Capabilities {
+            bits: self.bits & other.bits,
+        }
+    }
+
+    fn iter(self) -> impl Iterator<Item = Capability> {
+        Capability::ALL
+            .iter()
+            .cloned()
+            .filter(move |&capability| self.contains(capability))
+    }
+}
+
+impl Serialize for Capabilities {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+        for capability in self.iter() {
+            seq.serialize_element(capability.name())?;
+        }
+        seq.end()
+    }
+}
+
+/// The capabilities negotiated with the editor, shared between all systems that talk to it.
+///
+/// Until the editor has sent its capabilities, this contains everything the game supports, so
+/// that editors which don't negotiate capabilities keep working as before.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedCapabilities {
+    bits: Arc<AtomicUsize>,
+}
+
+impl SharedCapabilities {
+    pub(crate) fn get(&self) -> Capabilities {
+        Capabilities {
+            bits: self.bits.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn set(&self, capabilities: Capabilities) {
+        self.bits.store(capabilities.bits, Ordering::Relaxed);
+    }
+
+    pub(crate) fn contains(&self, capability: Capability) -> bool {
+        self.get().contains(capability)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negotiate() {
+        let mut game = Capabilities::default();
+        game.insert(Capability::Deltas);
+        game.insert(Capability::Stats);
+        game.insert(Capability::EditAcks);
+
+        let editor = Capabilities::from_names(vec!["stats", "edit_acks", "picking", "blobs"]);
+        let negotiated = game.intersection(editor);
+        assert!(negotiated.contains(Capability::Stats));
+        assert!(negotiated.contains(Capability::EditAcks));
+        assert!(!negotiated.contains(Capability::Deltas));
+        assert!(!negotiated.contains(Capability::Picking));
+        assert_eq!(
+            serde_json::to_string(&negotiated).unwrap(),
+            r#"["stats","edit_acks"]"#,
+        );
+    }
+}
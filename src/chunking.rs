@@ -0,0 +1,240 @@
+//! Headers identifying the chunks that large messages are split into.
+//!
+//! Without headers, the editor reassembles messages by concatenating packets in the order they
+//! arrive, which only works if no packet is lost or reordered on the way. With headers enabled,
+//! every packet starts with the ID of the message it belongs to, its index within the message,
+//! and the number of chunks in the message, all as big-endian integers:
+//!
+//! ```text
+//! | message ID (u32) | chunk index (u16) | chunk count (u16) | payload... |
+//! ```
+//!
+//! Concatenating the payloads of all chunks of a message in order gives the framed message.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The size in bytes of the header at the start of each chunk.
+pub(crate) const CHUNK_HEADER_LEN: usize = 8;
+
+/// The maximum number of chunks in a message, limited by the size of the chunk count.
+pub(crate) const MAX_CHUNKS: usize = 0xFFFF;
+
+/// How many messages older than the newest one are kept while waiting for their missing chunks.
+const MAX_PENDING_MESSAGES: u32 = 64;
+
+/// Appends the header for chunk `index` of `count` of message `id` to `out`.
+pub(crate) fn encode_header(id: u32, index: u16, count: u16, out: &mut Vec<u8>) {
+    out.extend_from_slice(&[
+        (id >> 24) as u8,
+        (id >> 16) as u8,
+        (id >> 8) as u8,
+        id as u8,
+        (index >> 8) as u8,
+        index as u8,
+        (count >> 8) as u8,
+        count as u8,
+    ]);
+}
+
+/// The source of the message IDs put in chunk headers, shared by everything that sends messages
+/// over the same connection so that no two messages get the same ID.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MessageIds {
+    next: Arc<AtomicUsize>,
+}
+
+impl MessageIds {
+    pub(crate) fn next(&self) -> u32 {
+        // NOTE: IDs wrap around, so truncating the counter is fine.
+        self.next.fetch_add(1, Ordering::Relaxed) as u32
+    }
+}
+
+/// Splits the framed message `bytes` into packets of at most `max_packet_size` bytes, each
+/// starting with a chunk header for message `id` if one is given.
+///
+/// Returns `None` if the message would take more than `MAX_CHUNKS` chunks, which the header
+/// can't count.
+pub(crate) fn split(bytes: &[u8], max_packet_size: usize, id: Option<u32>) -> Option<Vec<Vec<u8>>> {
+    let id = match id {
+        Some(id) => id,
+        None => return Some(bytes.chunks(max_packet_size).map(<[u8]>::to_vec).collect()),
+    };
+
+    let payload_size = max_packet_size - CHUNK_HEADER_LEN;
+    let count = (bytes.len() + payload_size - 1) / payload_size;
+    if count > MAX_CHUNKS {
+        return None;
+    }
+
+    let packets = bytes
+        .chunks(payload_size)
+        .enumerate()
+        .map(|(index, payload)| {
+            let mut packet = Vec::with_capacity(CHUNK_HEADER_LEN + payload.len());
+            encode_header(id, index as u16, count as u16, &mut packet);
+            packet.extend_from_slice(payload);
+            packet
+        })
+        .collect();
+    Some(packets)
+}
+
+/// Reassembles messages from chunks that may arrive out of order or not at all.
+#[derive(Debug, Default)]
+pub(crate) struct Reassembler {
+    pending: BTreeMap<u32, PendingMessage>,
+    newest: Option<u32>,
+}
+
+#[derive(Debug)]
+struct PendingMessage {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+impl Reassembler {
+    /// Adds a received chunk, returning the payload of its message once all of the message's
+    /// chunks have been received.
+    ///
+    /// Malformed chunks are ignored. Messages are given up on once too many newer messages have
+    /// arrived, and a chunk of a message older than that is taken as a sign that the sender
+    /// restarted, so reassembly starts over from it.
+    pub(crate) fn push(&mut self, packet: &[u8]) -> Option<Vec<u8>> {
+        if packet.len() < CHUNK_HEADER_LEN {
+            return None;
+        }
+
+        let id = (u32::from(packet[0]) << 24)
+            | (u32::from(packet[1]) << 16)
+            | (u32::from(packet[2]) << 8)
+            | u32::from(packet[3]);
+        let index = (usize::from(packet[4]) << 8) | usize::from(packet[5]);
+        let count = (usize::from(packet[6]) << 8) | usize::from(packet[7]);
+        if index >= count {
+            return None;
+        }
+
+        // NOTE: Message IDs wrap around, so they're compared by their distance from the newest.
+        // A distance of more than half the range of IDs means that the message is newer.
+        let newest = *self.newest.get_or_insert(id);
+        let age = newest.wrapping_sub(id);
+        if age > MAX_PENDING_MESSAGES && age < 1 << 31 {
+            // An ID this much older than the newest most likely means that the sender restarted
+            // and is counting from zero again, so everything still pending is dropped.
+            self.pending.clear();
+            self.newest = Some(id);
+        } else if age >= 1 << 31 {
+            self.newest = Some(id);
+            self.pending
+                .retain(|&pending, _| id.wrapping_sub(pending) <= MAX_PENDING_MESSAGES);
+        }
+
+        let complete = {
+            let message = self.pending.entry(id).or_insert_with(|| PendingMessage {
+                chunks: vec![None; count],
+                received: 0,
+            });
+            if message.chunks.len() != count || message.chunks[index].is_some() {
+                return None;
+            }
+
+            message.chunks[index] = Some(packet[CHUNK_HEADER_LEN..].to_owned());
+            message.received += 1;
+            message.received == count
+        };
+
+        if !complete {
+            return None;
+        }
+
+        let message = self.pending.remove(&id)?;
+        let mut payload = Vec::new();
+        for chunk in message.chunks.into_iter().flatten() {
+            payload.extend_from_slice(&chunk);
+        }
+        Some(payload)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chunk(id: u32, index: u16, count: u16, payload: &[u8]) -> Vec<u8> {
+        let mut packet = Vec::new();
+        encode_header(id, index, count, &mut packet);
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    #[test]
+    fn reassembles_out_of_order_chunks() {
+        let mut reassembler = Reassembler::default();
+        assert_eq!(reassembler.push(&chunk(7, 2, 3, b"ghi")), None);
+        assert_eq!(reassembler.push(&chunk(7, 0, 3, b"abc")), None);
+        assert_eq!(reassembler.push(&chunk(8, 0, 1, b"x")), Some(b"x".to_vec()));
+        assert_eq!(
+            reassembler.push(&chunk(7, 1, 3, b"def")),
+            Some(b"abcdefghi".to_vec())
+        );
+    }
+
+    #[test]
+    fn ignores_duplicate_and_malformed_chunks() {
+        let mut reassembler = Reassembler::default();
+        assert_eq!(reassembler.push(&chunk(1, 0, 2, b"a")), None);
+        assert_eq!(reassembler.push(&chunk(1, 0, 2, b"a")), None);
+        assert_eq!(reassembler.push(&chunk(1, 2, 2, b"c")), None);
+        assert_eq!(reassembler.push(&[0, 1]), None);
+        assert_eq!(
+            reassembler.push(&chunk(1, 1, 2, b"b")),
+            Some(b"ab".to_vec())
+        );
+    }
+
+    #[test]
+    fn split_round_trip() {
+        let packets = split(b"abcdefghij", CHUNK_HEADER_LEN + 4, Some(3)).unwrap();
+        assert_eq!(packets.len(), 3);
+        assert!(packets
+            .iter()
+            .all(|packet| packet.len() <= CHUNK_HEADER_LEN + 4));
+
+        let mut reassembler = Reassembler::default();
+        let mut message = None;
+        for packet in packets.iter().rev() {
+            message = reassembler.push(packet).or(message);
+        }
+        assert_eq!(message, Some(b"abcdefghij".to_vec()));
+
+        assert_eq!(
+            split(b"abc", 2, None).unwrap(),
+            vec![b"ab".to_vec(), b"c".to_vec()]
+        );
+    }
+
+    #[test]
+    fn gives_up_on_old_messages() {
+        let mut reassembler = Reassembler::default();
+        assert_eq!(reassembler.push(&chunk(0, 0, 2, b"a")), None);
+        assert_eq!(
+            reassembler.push(&chunk(MAX_PENDING_MESSAGES + 1, 0, 1, b"z")),
+            Some(b"z".to_vec())
+        );
+        assert_eq!(reassembler.push(&chunk(0, 1, 2, b"b")), None);
+    }
+
+    #[test]
+    fn restarted_sender_is_accepted() {
+        let mut reassembler = Reassembler::default();
+        assert_eq!(
+            reassembler.push(&chunk(500, 0, 1, b"a")),
+            Some(b"a".to_vec())
+        );
+        assert_eq!(reassembler.push(&chunk(0, 0, 1, b"b")), Some(b"b".to_vec()));
+        assert_eq!(reassembler.push(&chunk(1, 0, 1, b"c")), Some(b"c".to_vec()));
+    }
+}
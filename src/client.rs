@@ -0,0 +1,287 @@
+use crate::auth::{AuthSession, AuthToken};
+use crate::codec::Codec;
+use crate::connection_status::EditorConnectionStatus;
+use crate::message_reader::MessageReader;
+use crate::packet_queue::PacketQueue;
+use crate::protocol::{OutgoingMessage, Packet, PacketData};
+use crate::sync_stats::SyncStats;
+use crate::transport::Transport;
+use crate::types::{EditAck, IncomingMessage};
+use serde_json::Value;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// A connection to the editor for games that don't run on an Amethyst `Application`, e.g.
+/// dedicated servers built directly on specs.
+///
+/// The client speaks the same protocol as [`SyncEditorBundle`], but leaves it up to the game
+/// what state to send and how to apply the editor's commands. The game builds the contents of
+/// each packet with the types in the [`protocol`] module and sends them with [`send_state`],
+/// and polls for commands from the editor with [`poll_commands`], e.g. once per tick.
+///
+/// Packets go through the same pipeline as the bundle's: messages are framed and split into
+/// packets, sending is retried when the OS send buffer is full, the send rate can be limited,
+/// and the outcome is counted in [`stats`]. The editor can be required to authenticate the same
+/// way as well, including authenticating again after it has been silent for longer than the
+/// connection timeout. Everything else the bundle does, such as serializing the world, applying
+/// edits and negotiating capabilities, is left to the game.
+///
+/// ```no_run
+/// use amethyst_editor_sync::protocol::{IncomingMessage, PacketData};
+/// use amethyst_editor_sync::{EditorClient, Transport};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut client = EditorClient::new(Transport::udp("0.0.0.0:0")?);
+/// for tick in 0.. {
+///     for command in client.poll_commands() {
+///         match command {
+///             IncomingMessage::Heartbeat => {}
+///             command => println!("Unhandled command: {:?}", command),
+///         }
+///     }
+///
+///     let mut data = PacketData::default();
+///     data.entity_count = Some(0);
+///     client.send_state(tick, data)?;
+/// #   break;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`SyncEditorBundle`]: ./struct.SyncEditorBundle.html
+/// [`protocol`]: ./protocol/index.html
+/// [`send_state`]: #method.send_state
+/// [`stats`]: #method.stats
+/// [`poll_commands`]: #method.poll_commands
+pub struct EditorClient {
+    queue: PacketQueue,
+    reader: MessageReader,
+    auth: AuthSession,
+    status: EditorConnectionStatus,
+    instance_id: Option<String>,
+    max_packet_size: Option<usize>,
+    chunk_headers: bool,
+    stats: SyncStats,
+    messages_dropped: u64,
+
+    // Commands answered by the client itself, e.g. acknowledgements of `Authenticate`, which go
+    // out with the next state.
+    replies: Vec<OutgoingMessage>,
+
+    started: Instant,
+}
+
+impl EditorClient {
+    /// Creates a client that exchanges messages with the editor over `transport`.
+    pub fn new(transport: Transport) -> Self {
+        let chunk_headers = transport.uses_chunk_headers();
+        let mut reader = MessageReader::new();
+        reader.reassemble_chunks(chunk_headers);
+
+        EditorClient {
+            queue: PacketQueue::new(transport, None, chunk_headers),
+            reader,
+            auth: AuthSession::default(),
+            status: EditorConnectionStatus::default(),
+            instance_id: None,
+            max_packet_size: None,
+            chunk_headers,
+            stats: SyncStats::default(),
+            messages_dropped: 0,
+            replies: Vec::new(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Tags every packet with `id`, and ignores messages from the editor that are addressed to
+    /// other instances, like [`SyncEditorBundle::instance_id`].
+    ///
+    /// [`SyncEditorBundle::instance_id`]: ./struct.SyncEditorBundle.html#method.instance_id
+    pub fn with_instance_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.instance_id = Some(id.into());
+        self
+    }
+
+    /// Sets the maximum size in bytes of the packets sent to the editor, like
+    /// [`SyncEditorBundle::max_packet_size`].
+    ///
+    /// [`SyncEditorBundle::max_packet_size`]: ./struct.SyncEditorBundle.html#method.max_packet_size
+    pub fn with_max_packet_size(mut self, max_packet_size: usize) -> Self {
+        self.max_packet_size = Some(max_packet_size);
+        self.queue
+            .configure(self.max_packet_size, self.chunk_headers);
+        self
+    }
+
+    /// Sets whether each packet sent to the editor starts with a chunk header, like
+    /// [`SyncEditorBundle::chunk_headers`].
+    ///
+    /// [`SyncEditorBundle::chunk_headers`]: ./struct.SyncEditorBundle.html#method.chunk_headers
+    pub fn with_chunk_headers(mut self, enabled: bool) -> Self {
+        self.chunk_headers = enabled;
+        self.queue
+            .configure(self.max_packet_size, self.chunk_headers);
+        self
+    }
+
+    /// Sets whether each packet received from the editor starts with a chunk header, like
+    /// [`SyncEditorBundle::incoming_chunk_headers`].
+    ///
+    /// [`SyncEditorBundle::incoming_chunk_headers`]: ./struct.SyncEditorBundle.html#method.incoming_chunk_headers
+    pub fn with_incoming_chunk_headers(mut self, enabled: bool) -> Self {
+        self.reader.reassemble_chunks(enabled);
+        self
+    }
+
+    /// Limits how many bytes are sent to the editor each second, like
+    /// [`SyncEditorBundle::limit_send_rate`].
+    ///
+    /// [`SyncEditorBundle::limit_send_rate`]: ./struct.SyncEditorBundle.html#method.limit_send_rate
+    pub fn with_send_rate_limit(mut self, bytes_per_second: u64) -> Self {
+        self.queue
+            .set_rate_limit(Some(bytes_per_second), Instant::now());
+        self
+    }
+
+    /// Requires the editor to send `token` in an `Authenticate` message before any of its
+    /// commands are returned by [`poll_commands`], like [`SyncEditorBundle::require_auth_token`].
+    ///
+    /// The client acknowledges `Authenticate` messages itself. A `Handshake` is only returned
+    /// once the editor has authenticated, so that the game doesn't answer it before then.
+    ///
+    /// [`poll_commands`]: #method.poll_commands
+    /// [`SyncEditorBundle::require_auth_token`]: ./struct.SyncEditorBundle.html#method.require_auth_token
+    pub fn with_auth_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.auth.require(AuthToken::new(token.into()));
+        self
+    }
+
+    /// Sets how long the editor can go without sending anything before it's considered
+    /// disconnected, like the timeout passed to [`SyncEditorBundle::heartbeat`].
+    ///
+    /// An editor that reconnects after being disconnected may be a different process, so it has
+    /// to authenticate again if a token is required. Defaults to 5 seconds.
+    ///
+    /// [`SyncEditorBundle::heartbeat`]: ./struct.SyncEditorBundle.html#method.heartbeat
+    pub fn with_connection_timeout(mut self, timeout: Duration) -> Self {
+        self.status.set_timeout(timeout);
+        self
+    }
+
+    /// Whether the editor has sent anything within the connection timeout, and when it was
+    /// last heard from.
+    pub fn connection_status(&self) -> &EditorConnectionStatus {
+        &self.status
+    }
+
+    /// Statistics about the packets sent to the editor so far.
+    pub fn stats(&self) -> &SyncStats {
+        &self.stats
+    }
+
+    /// The number of messages that couldn't be sent to the editor in full, like
+    /// [`EditorConnectionStatus::messages_dropped`].
+    ///
+    /// [`EditorConnectionStatus::messages_dropped`]: ./struct.EditorConnectionStatus.html#method.messages_dropped
+    pub fn messages_dropped(&self) -> u64 {
+        self.messages_dropped
+    }
+
+    /// Sends a packet with the given contents to the editor, tagged with `frame` and the time
+    /// since the client was created.
+    ///
+    /// The packet is split into as many packets as needed to stay within the maximum packet
+    /// size, and as many of them as the send rate limit allows are sent right away. The rest,
+    /// along with packets that couldn't be sent because the OS send buffer was full, are sent
+    /// by later calls to `send_state` or [`poll_commands`]. Returns an error if the packet
+    /// couldn't be serialized.
+    ///
+    /// [`poll_commands`]: #method.poll_commands
+    pub fn send_state(&mut self, frame: u64, mut data: PacketData) -> io::Result<()> {
+        data.messages.extend(self.replies.drain(..));
+
+        let elapsed = self.started.elapsed();
+        let packet = Packet {
+            ty: "message".into(),
+            instance: self.instance_id.clone().map(Value::String),
+            frame,
+            absolute_time: Some(
+                elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) * 1e-9,
+            ),
+            data,
+        };
+        self.queue
+            .queue_encoded(Codec::Json, &packet)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        self.send_queued();
+        Ok(())
+    }
+
+    /// Reads everything the editor has sent since the last call, and returns the commands that
+    /// it completed, in the order they were sent.
+    ///
+    /// Never blocks. Messages that can't be parsed are logged and left out, as are commands from
+    /// an editor that hasn't authenticated, if a token is required.
+    pub fn poll_commands(&mut self) -> Vec<IncomingMessage> {
+        let editor_address = self.queue.transport().editor_address();
+        let was_connected = self.status.is_connected();
+        let status = &mut self.status;
+        self.reader.read_packets(self.queue.transport_mut(), || {
+            status.heard_from(editor_address)
+        });
+
+        // An editor that was silent for longer than the connection timeout may have been
+        // replaced by another process, so it starts a new session that has to be authenticated
+        // again.
+        if !was_connected && self.status.is_connected() {
+            self.auth.end();
+        }
+
+        // NOTE: Sending queued and delayed packets here means they go out even if no more state
+        // is sent.
+        self.send_queued();
+        if let Err(error) = self.queue.transport_mut().flush() {
+            debug!("Failed to send delayed packets: {}", error);
+        }
+
+        let framing = self.queue.transport().framing();
+        let instance = self.instance_id.as_ref().map(String::as_str);
+        let messages = self.reader.messages(framing, instance);
+
+        let mut commands = Vec::with_capacity(messages.len());
+        for message in messages {
+            match message {
+                ref message if !self.auth.accepts(message) => {
+                    warn_once!("Ignoring commands from unauthenticated editor");
+                }
+                IncomingMessage::Handshake { capabilities } => {
+                    if let Some(capabilities) = self.auth.handshake(capabilities) {
+                        commands.push(IncomingMessage::Handshake { capabilities });
+                    }
+                }
+                IncomingMessage::Authenticate { token, request_id } => {
+                    let (status, pending_handshake) = self.auth.authenticate(&token);
+                    if let Some(request_id) = request_id {
+                        self.replies.push(OutgoingMessage {
+                            ty: "edit_ack".into(),
+                            data: serde_json::to_value(EditAck { request_id, status })
+                                .expect("Failed to serialize acknowledgement"),
+                        });
+                    }
+                    if let Some(capabilities) = pending_handshake {
+                        commands.push(IncomingMessage::Handshake { capabilities });
+                    }
+                }
+                command => commands.push(command),
+            }
+        }
+        commands
+    }
+
+    /// Sends as many queued packets as possible.
+    fn send_queued(&mut self) {
+        let count = self.queue.len();
+        self.messages_dropped += self.queue.send(Instant::now(), count, &mut self.stats);
+    }
+}
@@ -0,0 +1,103 @@
+//! Encodings for the packets sent to the editor.
+
+use serde::Serialize;
+use std::fmt;
+
+/// How the packets sent to the editor are encoded, set with [`SyncEditorBundle::codec`].
+///
+/// Messages from the editor are always JSON, since they're small and infrequent. Crash reports
+/// are always sent as JSON as well, so editors should check the first byte of each message to
+/// tell the encodings apart: JSON packets start with `{`, MessagePack packets with a map marker.
+///
+/// [`SyncEditorBundle::codec`]: ./struct.SyncEditorBundle.html#method.codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// JSON text. This is the default.
+    Json,
+
+    /// [MessagePack], which encodes the same data as the JSON packets in fewer bytes.
+    ///
+    /// Editors that can decode it list the `"msgpack"` capability in their handshake. Until the
+    /// editor has done so, packets are still sent as JSON.
+    ///
+    /// [MessagePack]: https://msgpack.org
+    MessagePack,
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Codec::Json
+    }
+}
+
+impl Codec {
+    /// Serializes `message` and appends it to `out`.
+    ///
+    /// NOTE: Components and resources are embedded in packets as raw JSON, which binary
+    /// serializers can't write as is, so MessagePack packets are converted to a JSON value first.
+    pub(crate) fn encode<T: Serialize>(
+        self,
+        message: &T,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        match self {
+            // NOTE: The message is written on a single line, so that it can be framed as JSON
+            // lines.
+            Codec::Json => serde_json::to_writer(out, message).map_err(EncodeError::Json),
+
+            Codec::MessagePack => {
+                let value = serde_json::to_value(message).map_err(EncodeError::Json)?;
+                rmp_serde::encode::write(out, &value).map_err(EncodeError::MessagePack)
+            }
+        }
+    }
+}
+
+/// An error that prevented a message from being encoded.
+#[derive(Debug)]
+pub(crate) enum EncodeError {
+    Json(serde_json::Error),
+    MessagePack(rmp_serde::encode::Error),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EncodeError::Json(ref error) => write!(f, "{}", error),
+            EncodeError::MessagePack(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {
+    fn description(&self) -> &str {
+        "failed to encode message"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::value::{to_raw_value, RawValue};
+    use serde_json::{json, Value};
+
+    #[derive(Serialize)]
+    struct Snapshot {
+        components: Vec<Box<RawValue>>,
+    }
+
+    #[test]
+    fn message_pack_embeds_raw_values_as_data() {
+        let snapshot = Snapshot {
+            components: vec![to_raw_value(&json!({ "x": 1.5 })).unwrap()],
+        };
+
+        let mut bytes = Vec::new();
+        Codec::MessagePack.encode(&snapshot, &mut bytes).unwrap();
+        assert_ne!(bytes[0], b'{');
+        assert_eq!(
+            rmp_serde::from_slice::<Value>(&bytes).unwrap(),
+            json!({ "components": [{ "x": 1.5 }] })
+        );
+    }
+}
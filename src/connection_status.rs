@@ -0,0 +1,151 @@
+use std::net::SocketAddr;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long the editor can go without sending anything before it's considered disconnected, in
+/// seconds, unless a heartbeat timeout is configured.
+pub(crate) const CONNECTION_TIMEOUT_SECS: u64 = 5;
+
+/// Whether an editor is attached to the game, maintained by the bundle's receiver and sender
+/// systems.
+///
+/// Games can read this resource to enable features only while an editor is attached, such as
+/// cheats or debug overlays. Since the editor and the game communicate over UDP, there's no
+/// actual connection; the editor is considered connected as long as it has sent a packet within
+/// the last few seconds, or within the heartbeat timeout if one is configured with
+/// [`SyncEditorBundle::heartbeat`].
+///
+/// [`SyncEditorBundle::heartbeat`]: ./struct.SyncEditorBundle.html#method.heartbeat
+#[derive(Debug, Clone)]
+pub struct EditorConnectionStatus {
+    last_heard: Option<Instant>,
+    editor_address: Option<SocketAddr>,
+    timeout: Duration,
+    messages_dropped: u64,
+    errors: Vec<String>,
+}
+
+impl Default for EditorConnectionStatus {
+    fn default() -> Self {
+        EditorConnectionStatus {
+            last_heard: None,
+            editor_address: None,
+            timeout: Duration::from_secs(CONNECTION_TIMEOUT_SECS),
+            messages_dropped: 0,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl EditorConnectionStatus {
+    /// Returns `true` if the editor has sent a packet recently.
+    pub fn is_connected(&self) -> bool {
+        match self.last_heard {
+            Some(last_heard) => last_heard.elapsed() < self.timeout,
+            None => false,
+        }
+    }
+
+    /// The last time a packet was received from the editor, or `None` if the editor has never
+    /// connected.
+    pub fn last_heard(&self) -> Option<Instant> {
+        self.last_heard
+    }
+
+    /// The address of the editor, or `None` if the editor has never connected or is connected
+    /// through IPC.
+    pub fn editor_address(&self) -> Option<SocketAddr> {
+        self.editor_address
+    }
+
+    /// The number of messages that couldn't be sent to the editor in full, because sending
+    /// some of their packets failed.
+    ///
+    /// Failing to send doesn't stop the game; the messages are dropped and a warning is logged
+    /// instead. A growing count usually means the network is congested or the editor can't keep
+    /// up, in which case sending less often may help.
+    pub fn messages_dropped(&self) -> u64 {
+        self.messages_dropped
+    }
+
+    /// Takes the errors recorded since the last call, for the mismatches between the game and
+    /// the editor that the game opted into treating as failures, such as updates for unknown
+    /// types with [`UnknownTypePolicy::Strict`].
+    ///
+    /// These never stop the game on their own. Tests and CI can check for them each frame or
+    /// once the game has run, and fail with the errors that were found.
+    ///
+    /// [`UnknownTypePolicy::Strict`]: ./enum.UnknownTypePolicy.html#variant.Strict
+    pub fn take_errors(&mut self) -> Vec<String> {
+        mem::replace(&mut self.errors, Vec::new())
+    }
+
+    /// Records that a packet was received from the editor at `address`, if it has one.
+    pub(crate) fn heard_from(&mut self, address: Option<SocketAddr>) {
+        self.last_heard = Some(Instant::now());
+        self.editor_address = address;
+    }
+
+    /// Records errors to be returned by `take_errors`.
+    pub(crate) fn record_errors<I: IntoIterator<Item = String>>(&mut self, errors: I) {
+        self.errors.extend(errors);
+    }
+
+    /// Records that `count` messages couldn't be sent to the editor.
+    pub(crate) fn messages_dropped_by(&mut self, count: u64) {
+        self.messages_dropped += count;
+    }
+
+    /// Sets how long the editor can go without sending anything before it's considered
+    /// disconnected.
+    pub(crate) fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+}
+
+/// Whether state should currently be serialized and sent to the editor, shared between the
+/// systems that do so.
+///
+/// Always set unless heartbeats are enabled, in which case it's only set while the editor is
+/// connected.
+///
+/// Also counts the full resyncs requested by the editor, which systems that only send changes
+/// check to know when to send their full state again.
+#[derive(Debug, Clone)]
+pub(crate) struct EditorPresence {
+    active: Arc<AtomicBool>,
+    resyncs: Arc<AtomicUsize>,
+}
+
+impl Default for EditorPresence {
+    fn default() -> Self {
+        EditorPresence {
+            active: Arc::new(AtomicBool::new(true)),
+            resyncs: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl EditorPresence {
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_active(&self, active: bool) {
+        self.active.store(active, Ordering::Relaxed);
+    }
+
+    /// Asks every system that only sends changes to send its full state again.
+    pub(crate) fn request_resync(&self) {
+        self.resyncs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether a resync was requested since the last call with the same `seen` counter, which
+    /// each system keeps for itself starting at 0.
+    pub(crate) fn resync_requested(&self, seen: &mut usize) -> bool {
+        let resyncs = self.resyncs.load(Ordering::Relaxed);
+        mem::replace(seen, resyncs) != resyncs
+    }
+}
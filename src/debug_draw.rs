@@ -0,0 +1,80 @@
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// The most shapes kept for a single frame. Shapes drawn beyond this are dropped, so that a
+/// runaway loop can't flood the editor.
+const MAX_SHAPES_PER_FRAME: usize = 16 * 1024;
+
+/// A debug shape drawn by the game, sent to the editor to overlay on its own view.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub(crate) enum DebugShape {
+    Line {
+        start: [f32; 3],
+        end: [f32; 3],
+        color: [f32; 4],
+    },
+
+    Aabb {
+        min: [f32; 3],
+        max: [f32; 3],
+        color: [f32; 4],
+    },
+}
+
+/// The shapes drawn by the game during the current frame, shared by all clones of an
+/// [`EditorConnection`].
+///
+/// [`EditorConnection`]: ./struct.EditorConnection.html
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DebugDrawBuffer {
+    shapes: Arc<Mutex<Vec<DebugShape>>>,
+}
+
+impl DebugDrawBuffer {
+    pub(crate) fn push(&self, shape: DebugShape) {
+        let mut shapes = self.shapes.lock().expect("Debug draw buffer poisoned");
+        if shapes.len() < MAX_SHAPES_PER_FRAME {
+            shapes.push(shape);
+        } else {
+            warn_once!(
+                "More than {} debug shapes were drawn in one frame, dropping the rest",
+                MAX_SHAPES_PER_FRAME
+            );
+        }
+    }
+
+    /// Takes the shapes drawn since the last call.
+    pub(crate) fn take(&self) -> Vec<DebugShape> {
+        let mut shapes = self.shapes.lock().expect("Debug draw buffer poisoned");
+        mem::replace(&mut *shapes, Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn take_clears_the_frame() {
+        let buffer = DebugDrawBuffer::default();
+        buffer.push(DebugShape::Line {
+            start: [0.0; 3],
+            end: [1.0; 3],
+            color: [1.0; 4],
+        });
+        assert_eq!(buffer.take().len(), 1);
+        assert!(buffer.take().is_empty());
+    }
+
+    #[test]
+    fn serializes_shape_kind() {
+        let shape = DebugShape::Aabb {
+            min: [0.0; 3],
+            max: [1.0; 3],
+            color: [1.0; 4],
+        };
+        let value = serde_json::to_value(&shape).unwrap();
+        assert_eq!(value["shape"], "aabb");
+    }
+}
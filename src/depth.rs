@@ -0,0 +1,102 @@
+use serde_json::{json, Value};
+
+/// The default maximum nesting depth of a component or resource sent to the editor.
+///
+/// This leaves room for the message envelope below serde_json's recursion limit of 128, so that
+/// data sent to the editor can still be parsed when the editor sends it back.
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// The number of levels that serialized components are nested in, i.e. the object holding the
+/// entity, the `data` array, and the object holding the type's name.
+pub(crate) const COMPONENT_WRAPPER_DEPTH: usize = 3;
+
+/// The number of levels that serialized resources are nested in.
+pub(crate) const RESOURCE_WRAPPER_DEPTH: usize = 1;
+
+/// Returns the maximum nesting depth of objects and arrays in a JSON string.
+///
+/// This is much cheaper than parsing the string, so it's used to check serialized data before
+/// falling back to truncating it.
+pub(crate) fn json_depth(json: &str) -> usize {
+    let mut depth = 0;
+    let mut max_depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in json.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            b'}' | b']' => depth -= 1,
+            _ => {}
+        }
+    }
+    max_depth
+}
+
+/// Replaces objects and arrays nested deeper than `max_depth` with a marker object,
+/// `{ "truncated_depth": max_depth }`.
+///
+/// Returns `true` if anything was truncated.
+pub(crate) fn truncate(value: &mut Value, max_depth: usize) -> bool {
+    truncate_at(value, max_depth, 0)
+}
+
+fn truncate_at(value: &mut Value, max_depth: usize, depth: usize) -> bool {
+    let children: Vec<&mut Value> = match value {
+        Value::Object(object) => object.values_mut().collect(),
+        Value::Array(array) => array.iter_mut().collect(),
+        _ => return false,
+    };
+
+    if depth >= max_depth {
+        *value = json!({ "truncated_depth": max_depth });
+        return true;
+    }
+
+    let mut truncated = false;
+    for child in children {
+        truncated |= truncate_at(child, max_depth, depth + 1);
+    }
+    truncated
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn depth_of_string() {
+        assert_eq!(json_depth("3"), 0);
+        assert_eq!(json_depth(r#"{"a": [1, {"b": "}}{{[["}]}"#), 3);
+        assert_eq!(json_depth(r#"["\"[", ["\\"]]"#), 2);
+    }
+
+    #[test]
+    fn truncate_deep_values() {
+        let mut value = json!({ "a": { "b": { "c": 1 } }, "d": [[1], 2] });
+        assert!(!truncate(&mut value.clone(), 3));
+
+        assert!(truncate(&mut value, 2));
+        assert_eq!(
+            value,
+            json!({
+                "a": { "b": { "truncated_depth": 2 } },
+                "d": [{ "truncated_depth": 2 }, 2],
+            }),
+        );
+        assert_eq!(json_depth(&value.to_string()), 3);
+    }
+}
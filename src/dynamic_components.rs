@@ -0,0 +1,96 @@
+use amethyst::ecs::Entity;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Components that are defined at runtime rather than as Rust types, e.g. by a scripting
+/// language, stored as JSON and keyed by name.
+///
+/// Once [`SyncEditorBundle::sync_dynamic_components`] is enabled, the components in the store
+/// are sent to the editor alongside the typed components, and the editor can edit them with
+/// the same messages. Typed components take precedence over dynamic components with the same
+/// name.
+///
+/// The store doesn't know when entities are deleted, so the game should call
+/// [`remove_entity`] when deleting an entity with dynamic components. Components of deleted
+/// entities aren't sent to the editor either way.
+///
+/// [`SyncEditorBundle::sync_dynamic_components`]: ./struct.SyncEditorBundle.html#method.sync_dynamic_components
+/// [`remove_entity`]: #method.remove_entity
+#[derive(Debug, Clone, Default)]
+pub struct DynamicComponentStore {
+    components: BTreeMap<String, BTreeMap<Entity, Value>>,
+}
+
+impl DynamicComponentStore {
+    /// Attaches the component `name` to `entity`, returning the component it replaced, if any.
+    pub fn insert<S: Into<String>>(
+        &mut self,
+        entity: Entity,
+        name: S,
+        value: Value,
+    ) -> Option<Value> {
+        self.components
+            .entry(name.into())
+            .or_insert_with(BTreeMap::new)
+            .insert(entity, value)
+    }
+
+    /// The component `name` of `entity`, if it has one.
+    pub fn get(&self, entity: Entity, name: &str) -> Option<&Value> {
+        self.components
+            .get(name)
+            .and_then(|components| components.get(&entity))
+    }
+
+    /// The component `name` of `entity`, if it has one.
+    pub fn get_mut(&mut self, entity: Entity, name: &str) -> Option<&mut Value> {
+        self.components
+            .get_mut(name)
+            .and_then(|components| components.get_mut(&entity))
+    }
+
+    /// Removes the component `name` from `entity`, returning it if there was one.
+    pub fn remove(&mut self, entity: Entity, name: &str) -> Option<Value> {
+        self.components
+            .get_mut(name)
+            .and_then(|components| components.remove(&entity))
+    }
+
+    /// Removes all components of `entity`.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        for components in self.components.values_mut() {
+            components.remove(&entity);
+        }
+    }
+
+    /// Copies all components of `source` onto `target`.
+    pub(crate) fn clone_entity(&mut self, source: Entity, target: Entity) {
+        for components in self.components.values_mut() {
+            if let Some(value) = components.get(&source).cloned() {
+                components.insert(target, value);
+            }
+        }
+    }
+
+    /// The names of all components that have been added to the store, in order.
+    ///
+    /// Names stay in the store once all components with the name have been removed, so that the
+    /// editor is told that there are none left.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.components.keys().map(String::as_str)
+    }
+
+    /// Whether any component named `name` has ever been added to the store.
+    pub(crate) fn contains_name(&self, name: &str) -> bool {
+        self.components.contains_key(name)
+    }
+
+    /// The components named `name` along with their entities, sorted by entity ID.
+    pub(crate) fn iter(&self, name: &str) -> impl Iterator<Item = (Entity, &Value)> {
+        self.components
+            .get(name)
+            .into_iter()
+            .flat_map(|components| components.iter())
+            .map(|(&entity, value)| (entity, value))
+    }
+}
@@ -1,12 +1,22 @@
 use crate::bundle::SyncEditorBundle;
-use log::{Level, Log, Metadata, Record};
+use log::{Level, LevelFilter, Log, Metadata, Record};
 
 use crate::types::EditorConnection;
 
 /// A `Log` implementation that sends all incoming logs to the editor, which may allow more
 /// interactive filtering.
+///
+/// Only one logger can be set per process, so to keep logging to the terminal as well, either
+/// start the logger with [`start_with_fallback`], or add the logger to the chain of a logging
+/// framework that dispatches to several loggers, e.g. with `fern::Dispatch::chain`.
+///
+/// [`start_with_fallback`]: #method.start_with_fallback
 pub struct EditorLogger {
     editor_connection: EditorConnection,
+    fallback: Option<Box<dyn Log>>,
+
+    // The most verbose level sent to the editor for each module, see `filter_module`.
+    module_filters: Vec<(String, LevelFilter)>,
 }
 
 impl EditorLogger {
@@ -14,15 +24,60 @@ impl EditorLogger {
     pub fn new(bundle: &SyncEditorBundle) -> Self {
         Self {
             editor_connection: bundle.connection(),
+            fallback: None,
+            module_filters: Vec::new(),
         }
     }
 
+    /// Only sends records logged by `module` and its submodules to the editor if they're at
+    /// least as severe as `level`, e.g. to keep trace logs from `amethyst_renderer` from
+    /// flooding the editor.
+    ///
+    /// Records are matched by their target, which is the module path unless set otherwise.
+    /// When several filters match a record the one for the most specific module is used, so
+    /// `"amethyst_renderer::pass"` can be let through while the rest of `"amethyst_renderer"`
+    /// is filtered. Filtered records are dropped before they're serialized, but are still
+    /// passed on to the fallback logger.
+    pub fn filter_module(mut self, module: &str, level: LevelFilter) -> Self {
+        self.module_filters
+            .retain(|&(ref filtered, _)| filtered != module);
+        self.module_filters.push((module.to_owned(), level));
+        self
+    }
+
     /// Start this logger if no current logger is set.
     pub fn start(self) {
         log::set_max_level(log::LevelFilter::max());
         log::set_boxed_logger(Box::new(self))
             .unwrap_or_else(|_| warn!("Logger already set. The editor will not receive any logs."));
     }
+
+    /// Start this logger if no current logger is set, passing every record on to `fallback` as
+    /// well as sending it to the editor.
+    ///
+    /// This keeps the game's usual log output, e.g. to stdout, while the editor is connected.
+    /// Records that `fallback` isn't enabled for are still sent to the editor.
+    pub fn start_with_fallback(mut self, fallback: Box<dyn Log>) {
+        self.fallback = Some(fallback);
+        self.start();
+    }
+
+    /// Whether records with `metadata` pass the module filters.
+    fn sends_to_editor(&self, metadata: &Metadata) -> bool {
+        let target = metadata.target();
+        let filter = self
+            .module_filters
+            .iter()
+            .filter(|&&(ref module, _)| {
+                target.starts_with(module.as_str())
+                    && (target.len() == module.len() || target[module.len()..].starts_with("::"))
+            })
+            .max_by_key(|&&(ref module, _)| module.len());
+        match filter {
+            Some(&(_, level)) => metadata.level() <= level,
+            None => true,
+        }
+    }
 }
 
 impl Log for EditorLogger {
@@ -31,11 +86,25 @@ impl Log for EditorLogger {
     }
 
     fn log(&self, record: &Record) {
-        self.editor_connection
-            .send_message("log", SerializableLogRecord::from(record));
+        if self.sends_to_editor(record.metadata()) {
+            self.editor_connection.send_log(SerializableLogRecord::new(
+                record,
+                self.editor_connection.frame(),
+            ));
+        }
+
+        if let Some(ref fallback) = self.fallback {
+            if fallback.enabled(record.metadata()) {
+                fallback.log(record);
+            }
+        }
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        if let Some(ref fallback) = self.fallback {
+            fallback.flush();
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -67,10 +136,13 @@ struct SerializableLogRecord {
     file: Option<String>,
     line: Option<u32>,
     message: String,
+
+    /// The frame the record was logged during.
+    frame: u64,
 }
 
-impl<'a> From<&'a Record<'a>> for SerializableLogRecord {
-    fn from(record: &Record) -> Self {
+impl SerializableLogRecord {
+    fn new(record: &Record, frame: u64) -> Self {
         Self {
             level: record.level().into(),
             target: record.target().to_owned(),
@@ -78,6 +150,65 @@ impl<'a> From<&'a Record<'a>> for SerializableLogRecord {
             file: record.file().map(|s| s.to_owned()),
             line: record.line(),
             message: format!("{}", record.args()),
+            frame,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct CountingLogger {
+        count: Arc<AtomicUsize>,
+    }
+
+    impl Log for CountingLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Info
+        }
+
+        fn log(&self, _: &Record) {
+            self.count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn forwards_enabled_records_to_fallback() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut logger = EditorLogger::new(&SyncEditorBundle::default());
+        logger.fallback = Some(Box::new(CountingLogger {
+            count: count.clone(),
+        }));
+
+        for &level in &[Level::Error, Level::Info, Level::Debug] {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .args(format_args!("test"))
+                    .build(),
+            );
+        }
+        assert_eq!(count.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn most_specific_module_filter_wins() {
+        let logger = EditorLogger::new(&SyncEditorBundle::default())
+            .filter_module("amethyst_renderer", LevelFilter::Warn)
+            .filter_module("amethyst_renderer::pass", LevelFilter::Trace);
+
+        let passes = |target: &str, level: Level| {
+            logger.sends_to_editor(&Metadata::builder().target(target).level(level).build())
+        };
+        assert!(!passes("amethyst_renderer", Level::Info));
+        assert!(passes("amethyst_renderer", Level::Error));
+        assert!(passes("amethyst_renderer::pass::flat", Level::Trace));
+        assert!(passes("amethyst_renderer_extra", Level::Trace));
+        assert!(passes("game", Level::Trace));
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Key-value store of editor settings that are tied to this game.
+///
+/// The editor can store arbitrary preferences here (e.g. panel layouts or the last camera
+/// position) with the `SetEditorMeta` message, and the values are sent back to the editor as
+/// part of the handshake. If a path has been configured with
+/// [`SyncEditorBundle::editor_meta_path`], the values are persisted to disk so that they survive
+/// across sessions even when the editor's own storage is unavailable.
+///
+/// [`SyncEditorBundle::editor_meta_path`]: ./struct.SyncEditorBundle.html#method.editor_meta_path
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EditorMeta {
+    path: Option<PathBuf>,
+    values: BTreeMap<String, serde_json::Value>,
+}
+
+impl EditorMeta {
+    /// Loads the previously persisted values from `path`, if there are any.
+    pub(crate) fn load(path: Option<PathBuf>) -> EditorMeta {
+        let values = match path {
+            Some(ref path) => match fs::read_to_string(path) {
+                Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|error| {
+                    warn!("Failed to parse editor meta file {:?}: {:?}", path, error);
+                    BTreeMap::new()
+                }),
+
+                // It's fine for the file to not exist yet, since it's only created once the
+                // editor stores its first value.
+                Err(ref error) if error.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+
+                Err(error) => {
+                    warn!("Failed to read editor meta file {:?}: {:?}", path, error);
+                    BTreeMap::new()
+                }
+            },
+
+            None => BTreeMap::new(),
+        };
+
+        EditorMeta { path, values }
+    }
+
+    /// Sets the value for `key`, removing it if `value` is null, and persists the change.
+    pub(crate) fn set(&mut self, key: String, value: serde_json::Value) {
+        if value.is_null() {
+            self.values.remove(&key);
+        } else {
+            self.values.insert(key, value);
+        }
+
+        if let Some(ref path) = self.path {
+            let result = serde_json::to_string_pretty(&self.values)
+                .map_err(io::Error::from)
+                .and_then(|contents| fs::write(path, contents));
+            if let Err(error) = result {
+                warn!("Failed to save editor meta file {:?}: {:?}", path, error);
+            }
+        }
+    }
+}
+
+impl Serialize for EditorMeta {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.values.serialize(serializer)
+    }
+}
@@ -0,0 +1,152 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use serde::de::{Deserialize, Deserializer, Error as DeError};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A random ID that identifies an entity across runs of the game, unlike entity IDs, which are
+/// reused as entities are created and deleted and change every time the game is started.
+///
+/// When enabled with [`SyncEditorBundle::assign_entity_uuids`], every entity without an
+/// `EditorUuid` is given a new one, and the UUID is included in the entity list sent to the
+/// editor as the entity's `"uuid"`. The editor can use it to keep layouts and inspectors attached
+/// to the same entity after the game is restarted.
+///
+/// UUIDs are only stable across runs if they're saved along with the rest of the entity, e.g. as
+/// part of a prefab or save file. `EditorUuid` serializes as a hyphenated string (e.g.
+/// `"0b9c3a4e-7d1f-4c52-9a3e-5f0e2d8c1b7a"`), and an entity that's created with a saved
+/// `EditorUuid` keeps it instead of being given a new one.
+///
+/// [`SyncEditorBundle::assign_entity_uuids`]: ./struct.SyncEditorBundle.html#method.assign_entity_uuids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EditorUuid(u128);
+
+impl EditorUuid {
+    /// Generates a new random (version 4) UUID.
+    pub fn new() -> Self {
+        let bits = rand::random::<u128>();
+
+        // Set the version to 4 and the variant to RFC 4122, so that the UUID is recognized as a
+        // random one by other tools.
+        let bits = (bits & !(0xF << 76)) | (0x4 << 76);
+        let bits = (bits & !(0x3 << 62)) | (0x2 << 62);
+        EditorUuid(bits)
+    }
+
+    /// Creates a UUID from its 128 bits, e.g. to restore a UUID that was saved by the game in a
+    /// format of its own.
+    pub fn from_u128(bits: u128) -> Self {
+        EditorUuid(bits)
+    }
+
+    /// The 128 bits of the UUID.
+    pub fn as_u128(self) -> u128 {
+        self.0
+    }
+}
+
+impl Default for EditorUuid {
+    fn default() -> Self {
+        EditorUuid::new()
+    }
+}
+
+impl Component for EditorUuid {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl fmt::Display for EditorUuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits = self.0;
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            bits >> 96,
+            (bits >> 80) & 0xFFFF,
+            (bits >> 64) & 0xFFFF,
+            (bits >> 48) & 0xFFFF,
+            bits & 0xFFFF_FFFF_FFFF,
+        )
+    }
+}
+
+/// The error returned when parsing an [`EditorUuid`] from a string that isn't a hyphenated UUID.
+///
+/// [`EditorUuid`]: ./struct.EditorUuid.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseUuidError(String);
+
+impl fmt::Display for ParseUuidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} is not a valid UUID", self.0)
+    }
+}
+
+impl std::error::Error for ParseUuidError {
+    fn description(&self) -> &str {
+        "invalid UUID"
+    }
+}
+
+impl FromStr for EditorUuid {
+    type Err = ParseUuidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let groups = s.split('-').map(str::len).collect::<Vec<_>>();
+        let digits = s.chars().filter(|&c| c != '-').collect::<String>();
+        if groups != [8, 4, 4, 4, 12] {
+            return Err(ParseUuidError(s.into()));
+        }
+        u128::from_str_radix(&digits, 16)
+            .map(EditorUuid)
+            .map_err(|_| ParseUuidError(s.into()))
+    }
+}
+
+impl Serialize for EditorUuid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EditorUuid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_string() {
+        let uuid = EditorUuid::new();
+        let s = uuid.to_string();
+        assert_eq!(s.len(), 36);
+        assert_eq!(&s[14..15], "4");
+        assert_eq!(s.parse(), Ok(uuid));
+    }
+
+    #[test]
+    fn rejects_malformed_strings() {
+        assert!("0b9c3a4e7d1f4c529a3e5f0e2d8c1b7a"
+            .parse::<EditorUuid>()
+            .is_err());
+        assert!("0b9c3a4e-7d1f-4c52-9a3e-5f0e2d8c1b7g"
+            .parse::<EditorUuid>()
+            .is_err());
+        assert!("0b9c3a4e-7d1f-4c52-9a3e5-f0e2d8c1b7a"
+            .parse::<EditorUuid>()
+            .is_err());
+    }
+
+    #[test]
+    fn serializes_as_string() {
+        let uuid = EditorUuid::from_u128(0x0b9c3a4e_7d1f_4c52_9a3e_5f0e2d8c1b7a);
+        let json = serde_json::to_string(&uuid).unwrap();
+        assert_eq!(json, r#""0b9c3a4e-7d1f-4c52-9a3e-5f0e2d8c1b7a""#);
+        assert_eq!(serde_json::from_str::<EditorUuid>(&json).unwrap(), uuid);
+    }
+}
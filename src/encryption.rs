@@ -0,0 +1,337 @@
+use ring::aead::{self, OpeningKey, SealingKey, CHACHA20_POLY1305};
+use ring::digest::SHA256;
+use ring::hmac::{self, SigningKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEQUENCE_LEN: usize = 8;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// The number of bytes that encryption adds to each packet.
+pub(crate) const OVERHEAD: usize = SEQUENCE_LEN + NONCE_LEN + TAG_LEN;
+
+/// How far behind the newest packet received a packet can be and still be accepted, in sequence
+/// numbers. UDP can reorder packets, e.g. the chunks of a large message, so older packets are
+/// accepted as long as they haven't been received before.
+const REPLAY_WINDOW: u64 = 64;
+
+/// How much older than the time a [`Cipher`] was created a packet's sequence number can be and
+/// still be accepted, in microseconds. Sequence numbers are timestamps taken from the sender's
+/// clock, so this allows for the clocks of the game and the editor being slightly apart, while
+/// keeping packets recorded in earlier sessions from being replayed to a newly started end.
+const CLOCK_SKEW: u64 = 60 * 1_000_000;
+
+/// The labels used to derive the key for each direction from the shared key.
+const GAME_TO_EDITOR: &[u8] = b"amethyst-editor-sync game to editor";
+const EDITOR_TO_GAME: &[u8] = b"amethyst-editor-sync editor to game";
+
+/// Which end of the connection a [`Cipher`] is used by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Role {
+    Game,
+    Editor,
+}
+
+/// Encrypts and authenticates the packets exchanged with the editor with ChaCha20-Poly1305,
+/// using a key shared by the game and the editor.
+///
+/// Each direction uses its own key, derived from the shared key with HMAC-SHA256, so that a
+/// packet can't be reflected back to the end that sent it. Each packet is sealed separately, and
+/// is sent as an 8 byte big-endian sequence number, a random 12 byte nonce, the ciphertext and
+/// the 16 byte authentication tag. The sequence number is authenticated as associated data, and
+/// packets whose sequence number has already been received, or is too far behind the newest one,
+/// are rejected so that recorded packets can't be replayed.
+///
+/// Sequence numbers are the time the packet was sealed in microseconds, or one more than the
+/// previous sequence number if the clock hasn't moved on since, so that they keep increasing
+/// when the game or the editor is restarted with the same key. Since a newly created cipher
+/// hasn't received anything yet, it rejects packets sealed well before it was created, which
+/// keeps packets recorded in earlier sessions from being replayed; see [`CLOCK_SKEW`]. Clones
+/// share their sequence numbers, since they're used to send from several systems over the same
+/// connection.
+///
+/// [`CLOCK_SKEW`]: ./constant.CLOCK_SKEW.html
+pub(crate) struct Cipher {
+    key: [u8; 32],
+    role: Role,
+    sealing: SealingKey,
+    opening: OpeningKey,
+    rng: SystemRandom,
+    next_sequence: Arc<Mutex<u64>>,
+    received: Arc<Mutex<ReplayWindow>>,
+}
+
+impl Cipher {
+    pub(crate) fn new(key: [u8; 32], role: Role) -> Self {
+        Cipher::started_at(key, role, now_micros())
+    }
+
+    /// Creates a cipher as if it was created at `start`, in microseconds since the Unix epoch.
+    fn started_at(key: [u8; 32], role: Role, start: u64) -> Self {
+        let (sealing_label, opening_label) = match role {
+            Role::Game => (GAME_TO_EDITOR, EDITOR_TO_GAME),
+            Role::Editor => (EDITOR_TO_GAME, GAME_TO_EDITOR),
+        };
+
+        Cipher {
+            key,
+            role,
+            sealing: SealingKey::new(&CHACHA20_POLY1305, &derive_key(&key, sealing_label))
+                .expect("Invalid key length"),
+            opening: OpeningKey::new(&CHACHA20_POLY1305, &derive_key(&key, opening_label))
+                .expect("Invalid key length"),
+            rng: SystemRandom::new(),
+            next_sequence: Arc::new(Mutex::new(start)),
+            received: Arc::new(Mutex::new(ReplayWindow::starting_at(
+                start.saturating_sub(CLOCK_SKEW),
+            ))),
+        }
+    }
+
+    pub(crate) fn try_clone(&self) -> Self {
+        Cipher {
+            next_sequence: self.next_sequence.clone(),
+            received: self.received.clone(),
+            ..Cipher::new(self.key, self.role)
+        }
+    }
+
+    /// Encrypts `bytes` into a packet ready to be sent.
+    pub(crate) fn seal(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let sequence = {
+            let mut next_sequence = self.next_sequence.lock().expect("Sequence lock poisoned");
+            let sequence = (*next_sequence).max(now_micros());
+            *next_sequence = sequence + 1;
+            sequence
+        };
+
+        let mut packet = vec![0; SEQUENCE_LEN + NONCE_LEN];
+        write_sequence(sequence, &mut packet[..SEQUENCE_LEN]);
+        self.rng
+            .fill(&mut packet[SEQUENCE_LEN..])
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to generate a nonce"))?;
+        packet.extend_from_slice(bytes);
+        packet.resize(packet.len() + TAG_LEN, 0);
+
+        let (header, in_out) = packet.split_at_mut(SEQUENCE_LEN + NONCE_LEN);
+        let (sequence, nonce) = header.split_at(SEQUENCE_LEN);
+        aead::seal_in_place(&self.sealing, nonce, sequence, in_out, TAG_LEN)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to encrypt packet"))?;
+        Ok(packet)
+    }
+
+    /// Decrypts a received packet in place, moving the plaintext to the start of `packet` and
+    /// returning its length, or `None` if the packet wasn't sealed by the other end with the
+    /// same key, has been tampered with, or has been received before.
+    pub(crate) fn open(&self, packet: &mut [u8]) -> Option<usize> {
+        if packet.len() < OVERHEAD {
+            return None;
+        }
+
+        let sequence = read_sequence(&packet[..SEQUENCE_LEN]);
+        let mut received = self.received.lock().expect("Replay window lock poisoned");
+        if !received.is_fresh(sequence) {
+            return None;
+        }
+
+        let len = {
+            let (header, in_out) = packet.split_at_mut(SEQUENCE_LEN + NONCE_LEN);
+            let (sequence, nonce) = header.split_at(SEQUENCE_LEN);
+            aead::open_in_place(&self.opening, nonce, sequence, 0, in_out)
+                .ok()?
+                .len()
+        };
+
+        // NOTE: Only authentic packets move the window, so forged sequence numbers can't be used
+        // to get genuine packets rejected.
+        received.insert(sequence);
+        packet.rotate_left(SEQUENCE_LEN + NONCE_LEN);
+        Some(len)
+    }
+}
+
+/// Tracks the sequence numbers received recently, in order to reject replayed packets.
+#[derive(Debug, Default)]
+struct ReplayWindow {
+    // Sequence numbers below this are rejected until something newer has been received.
+    oldest: u64,
+
+    // The newest sequence number received, if any.
+    newest: Option<u64>,
+
+    // Bit `n` is set if `newest - n - 1` has been received.
+    older: u64,
+}
+
+impl ReplayWindow {
+    /// Creates a window that rejects sequence numbers below `oldest`.
+    fn starting_at(oldest: u64) -> Self {
+        ReplayWindow {
+            oldest,
+            ..ReplayWindow::default()
+        }
+    }
+
+    /// Whether a packet with `sequence` should be accepted.
+    fn is_fresh(&self, sequence: u64) -> bool {
+        let newest = match self.newest {
+            Some(newest) => newest,
+            None => return sequence >= self.oldest,
+        };
+        if sequence > newest {
+            return true;
+        }
+
+        let behind = newest - sequence;
+        behind != 0 && behind <= REPLAY_WINDOW && self.older & (1 << (behind - 1)) == 0
+    }
+
+    /// Records a sequence number that has been checked with `is_fresh`.
+    fn insert(&mut self, sequence: u64) {
+        match self.newest {
+            Some(newest) if sequence < newest => self.older |= 1 << (newest - sequence - 1),
+            Some(newest) => {
+                // The previous newest sequence number ends up `ahead` behind the new one.
+                let ahead = sequence - newest;
+                self.older = if ahead > REPLAY_WINDOW {
+                    0
+                } else {
+                    self.older.checked_shl(ahead as u32).unwrap_or(0) | 1 << (ahead - 1)
+                };
+                self.newest = Some(sequence);
+            }
+            None => self.newest = Some(sequence),
+        }
+    }
+}
+
+/// The current time in microseconds since the Unix epoch.
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() * 1_000_000 + u64::from(since.subsec_micros()))
+        .unwrap_or(0)
+}
+
+/// Derives the key for one direction of the connection from the shared key.
+fn derive_key(key: &[u8; 32], label: &[u8]) -> Vec<u8> {
+    let signature = hmac::sign(&SigningKey::new(&SHA256, key), label);
+    signature.as_ref().to_owned()
+}
+
+fn write_sequence(sequence: u64, bytes: &mut [u8]) {
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = (sequence >> (8 * (SEQUENCE_LEN - 1 - index))) as u8;
+    }
+}
+
+fn read_sequence(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0, |sequence, &byte| (sequence << 8) | u64::from(byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let game = Cipher::new([7; 32], Role::Game);
+        let editor = Cipher::new([7; 32], Role::Editor);
+        let mut packet = game.seal(b"hello editor").unwrap();
+        assert_eq!(packet.len(), b"hello editor".len() + OVERHEAD);
+        assert!(!packet.windows(5).any(|window| window == b"hello"));
+
+        let len = editor.open(&mut packet).unwrap();
+        assert_eq!(&packet[..len], b"hello editor");
+
+        let mut packet = editor.seal(b"hello game").unwrap();
+        let len = game.open(&mut packet).unwrap();
+        assert_eq!(&packet[..len], b"hello game");
+    }
+
+    #[test]
+    fn rejects_tampered_and_foreign_packets() {
+        let game = Cipher::new([7; 32], Role::Game);
+        let editor = Cipher::new([7; 32], Role::Editor);
+        let mut packet = game.seal(b"hello editor").unwrap();
+        let mut foreign = packet.clone();
+        let mut resequenced = packet.clone();
+        packet[OVERHEAD - TAG_LEN] ^= 1;
+        assert_eq!(editor.open(&mut packet), None);
+
+        resequenced[SEQUENCE_LEN - 1] ^= 1;
+        assert_eq!(editor.open(&mut resequenced), None);
+
+        assert_eq!(Cipher::new([8; 32], Role::Editor).open(&mut foreign), None);
+        assert_eq!(editor.open(&mut [0; 8]), None);
+    }
+
+    #[test]
+    fn rejects_reflected_packets() {
+        let game = Cipher::new([7; 32], Role::Game);
+        let mut packet = game.seal(b"hello editor").unwrap();
+        assert_eq!(game.open(&mut packet), None);
+    }
+
+    #[test]
+    fn rejects_replayed_packets() {
+        let game = Cipher::new([7; 32], Role::Game);
+        let editor = Cipher::new([7; 32], Role::Editor);
+        let first = game.seal(b"first").unwrap();
+        let second = game.seal(b"second").unwrap();
+
+        // Reordered packets are still accepted, but only once.
+        assert!(editor.open(&mut second.clone()).is_some());
+        assert!(editor.open(&mut first.clone()).is_some());
+        assert_eq!(editor.open(&mut first.clone()), None);
+        assert_eq!(editor.open(&mut second.clone()), None);
+    }
+
+    #[test]
+    fn rejects_packets_from_before_it_was_created() {
+        let game = Cipher::new([7; 32], Role::Game);
+        let packet = game.seal(b"recorded").unwrap();
+
+        // An editor started later than the clock skew allows rejects the recorded packet, but
+        // still accepts new ones.
+        let later = read_sequence(&packet[..SEQUENCE_LEN]) + CLOCK_SKEW + 1;
+        let editor = Cipher::started_at([7; 32], Role::Editor, later);
+        assert_eq!(editor.open(&mut packet.clone()), None);
+
+        let editor = Cipher::started_at([7; 32], Role::Editor, now_micros() + CLOCK_SKEW / 2);
+        assert!(editor.open(&mut packet.clone()).is_some());
+    }
+
+    #[test]
+    fn clones_share_sequence_numbers() {
+        let game = Cipher::new([7; 32], Role::Game);
+        let clone = game.try_clone();
+        let editor = Cipher::new([7; 32], Role::Editor);
+        assert!(editor.open(&mut game.seal(b"first").unwrap()).is_some());
+        assert!(editor.open(&mut clone.seal(b"second").unwrap()).is_some());
+    }
+
+    #[test]
+    fn replay_window_slides() {
+        let mut window = ReplayWindow::default();
+        window.insert(100);
+        window.insert(98);
+        assert!(!window.is_fresh(100));
+        assert!(!window.is_fresh(98));
+        assert!(window.is_fresh(99));
+        assert!(!window.is_fresh(100 - REPLAY_WINDOW - 1));
+
+        window.insert(100 + REPLAY_WINDOW);
+        assert!(!window.is_fresh(100));
+        assert!(window.is_fresh(100 + REPLAY_WINDOW - 1));
+
+        window.insert(1000);
+        assert!(window.is_fresh(999));
+        assert!(!window.is_fresh(1000));
+    }
+}
@@ -0,0 +1,75 @@
+use amethyst::ecs::{Component, Entity};
+use std::collections::HashMap;
+
+/// A component that provides a stable, game-defined identifier for its entity.
+///
+/// Entity IDs and generations are only meaningful within a single run of the game, so an
+/// editor that addresses entities by `{ id, generation }` loses track of them whenever the
+/// entities are recreated (e.g. when a networked client reconnects and respawns its replicated
+/// entities). Implementing `EntityIdMapping` for a component that already carries a stable
+/// identifier, such as a network ID, and registering it with
+/// [`SyncEditorBundle::map_entity_ids`] allows the editor to address entities by that
+/// identifier instead.
+///
+/// [`SyncEditorBundle::map_entity_ids`]: ./struct.SyncEditorBundle.html#method.map_entity_ids
+pub trait EntityIdMapping: Component + Send + Sync {
+    /// Returns the stable identifier for the entity that this component is attached to.
+    fn mapped_id(&self) -> u64;
+}
+
+/// Lookup table between entities and their mapped IDs.
+///
+/// Rebuilt every frame by [`EntityIdMapSystem`] if an [`EntityIdMapping`] component has been
+/// registered, and left empty otherwise.
+///
+/// Also holds the temporary IDs that the editor gave to the entities it created, which are kept
+/// until the entities are deleted.
+///
+/// [`EntityIdMapSystem`]: ./systems/struct.EntityIdMapSystem.html
+/// [`EntityIdMapping`]: ./trait.EntityIdMapping.html
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EntityIdMap {
+    by_mapped_id: HashMap<u64, Entity>,
+    by_entity: HashMap<u32, u64>,
+    temporary: HashMap<u64, Entity>,
+}
+
+impl EntityIdMap {
+    pub(crate) fn clear(&mut self) {
+        self.by_mapped_id.clear();
+        self.by_entity.clear();
+    }
+
+    pub(crate) fn insert(&mut self, entity: Entity, mapped_id: u64) {
+        self.by_mapped_id.insert(mapped_id, entity);
+        self.by_entity.insert(entity.id(), mapped_id);
+    }
+
+    /// Gets the entity currently associated with `mapped_id`, if any.
+    pub(crate) fn entity(&self, mapped_id: u64) -> Option<Entity> {
+        self.by_mapped_id.get(&mapped_id).cloned()
+    }
+
+    /// Gets the mapped ID for the entity with the given ID, if any.
+    pub(crate) fn mapped_id(&self, entity_id: u32) -> Option<u64> {
+        self.by_entity.get(&entity_id).cloned()
+    }
+
+    /// Associates an entity created by the editor with the temporary ID the editor gave it.
+    pub(crate) fn insert_temporary(&mut self, temporary_id: u64, entity: Entity) {
+        self.temporary.insert(temporary_id, entity);
+    }
+
+    /// Gets the entity that the editor gave `temporary_id`, which may have been deleted since.
+    pub(crate) fn temporary(&self, temporary_id: u64) -> Option<Entity> {
+        self.temporary.get(&temporary_id).cloned()
+    }
+
+    /// Forgets the temporary IDs of entities that no longer exist.
+    pub(crate) fn retain_temporary<F>(&mut self, mut is_alive: F)
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        self.temporary.retain(|_, entity| is_alive(*entity));
+    }
+}
@@ -0,0 +1,52 @@
+use amethyst::ecs::Entity;
+use std::collections::HashMap;
+
+/// How names are generated for entities, when enabled with
+/// [`SyncEditorBundle::generate_entity_names`].
+///
+/// Entities with a `Named` component always use that name instead.
+///
+/// [`SyncEditorBundle::generate_entity_names`]: ./struct.SyncEditorBundle.html#method.generate_entity_names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityNaming {
+    /// Name entities by their ID, e.g. `Entity 42`.
+    Id,
+
+    /// Name entities after the first registered component that they have, in the order the
+    /// components were registered, e.g. `Player 42`. Entities without any registered components
+    /// are named by their ID.
+    DominantComponent,
+}
+
+/// Names of entities shown in the editor, kept separately from the world so that no `Named`
+/// components need to be added to the game.
+///
+/// Filled in by [`EntityNameSystem`] if name generation is enabled, and left empty otherwise.
+///
+/// [`EntityNameSystem`]: ./systems/struct.EntityNameSystem.html
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EntityNames {
+    names: HashMap<Entity, String>,
+}
+
+impl EntityNames {
+    pub(crate) fn get(&self, entity: Entity) -> Option<&str> {
+        self.names.get(&entity).map(String::as_str)
+    }
+
+    pub(crate) fn contains(&self, entity: Entity) -> bool {
+        self.names.contains_key(&entity)
+    }
+
+    pub(crate) fn insert(&mut self, entity: Entity, name: String) {
+        self.names.insert(entity, name);
+    }
+
+    /// Removes the names of entities that no longer exist.
+    pub(crate) fn retain<F>(&mut self, mut alive: F)
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        self.names.retain(|&entity, _| alive(entity));
+    }
+}
@@ -0,0 +1,240 @@
+//! Extension points for crates that provide editor support for their own types.
+//!
+//! Crates that define components and resources (e.g. physics or UI plugins) can ship editor
+//! support for them without depending on the internals of `SyncEditorBundle`. The items in
+//! this module are the supported way of doing so, and will only change in a breaking way
+//! alongside a major version bump.
+//!
+//! The simplest approach is an extension trait implemented for [`SyncEditorBundle`] that uses
+//! the public registration methods:
+//!
+//! ```
+//! # extern crate amethyst;
+//! # extern crate amethyst_editor_sync;
+//! # extern crate serde;
+//! # use amethyst::ecs::*;
+//! # use amethyst_editor_sync::*;
+//! # use serde::*;
+//! # #[derive(Serialize, Deserialize)]
+//! # struct RigidBody;
+//! # impl Component for RigidBody { type Storage = DenseVecStorage<Self>; }
+//! pub trait SyncPhysicsExt {
+//!     fn sync_physics_types(&mut self);
+//! }
+//!
+//! impl<'a> SyncPhysicsExt for SyncEditorBundle<'a> {
+//!     fn sync_physics_types(&mut self) {
+//!         sync_components!(self, RigidBody);
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! When the set of types isn't known statically, or needs to be built up somewhere other than
+//! where the bundle is available, plugins can instead produce [`Registration`] values, which the
+//! game then passes to [`SyncEditorBundle::register`].
+//!
+//! With the `inventory` feature enabled, registrations can also be submitted from anywhere in
+//! the game (or its dependencies) with [`submit_registrations`], and are then all applied at
+//! once with [`SyncEditorBundle::sync_registered_types`]:
+//!
+//! ```ignore
+//! submit_registrations!(
+//!     Registration::sync_component::<RigidBody>("RigidBody"),
+//!     Registration::read_resource::<PhysicsWorld>("PhysicsWorld"),
+//! );
+//!
+//! let editor_bundle = SyncEditorBundle::new()
+//!     .tap(SyncEditorBundle::sync_registered_types);
+//! ```
+//!
+//! Crates that have data of their own to show in the editor, rather than components and
+//! resources, can add their own systems to the bundle by implementing [`SyncSystemExt`]. The
+//! systems send their data with [`EditorConnection::send_data`].
+//!
+//! [`SyncEditorBundle`]: ../struct.SyncEditorBundle.html
+//! [`Registration`]: ./struct.Registration.html
+//! [`SyncSystemExt`]: ./trait.SyncSystemExt.html
+//! [`EditorConnection::send_data`]: ../struct.EditorConnection.html#method.send_data
+//! [`SyncEditorBundle::register`]: ../struct.SyncEditorBundle.html#method.register
+//! [`submit_registrations`]: ../macro.submit_registrations.html
+//! [`SyncEditorBundle::sync_registered_types`]: ../struct.SyncEditorBundle.html#method.sync_registered_types
+
+use amethyst::ecs::{Component, DispatcherBuilder, System};
+use amethyst::shred::Resource;
+use crate::bundle::SyncEditorBundle;
+use crate::system_graph::SystemGraph;
+use crate::types::EditorConnection;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A type-erased registration of a component or resource with a [`SyncEditorBundle`].
+///
+/// Each constructor corresponds to the `SyncEditorBundle` method of the same name.
+///
+/// [`SyncEditorBundle`]: ../struct.SyncEditorBundle.html
+pub struct Registration {
+    register: Box<dyn for<'a> Fn(&mut SyncEditorBundle<'a>) + Send + Sync>,
+}
+
+impl Registration {
+    /// Registers a component to be synchronized with the editor.
+    pub fn sync_component<C>(name: &'static str) -> Registration
+    where
+        C: Component + Serialize + DeserializeOwned + Send + Sync,
+    {
+        Registration::new(move |bundle| bundle.sync_component::<C>(name))
+    }
+
+    /// Registers a component to be displayed as read-only in the editor.
+    pub fn read_component<C>(name: &'static str) -> Registration
+    where
+        C: Component + Serialize + Send,
+    {
+        Registration::new(move |bundle| bundle.read_component::<C>(name))
+    }
+
+    /// Registers a resource to be synchronized with the editor.
+    pub fn sync_resource<R>(name: &'static str) -> Registration
+    where
+        R: Resource + Serialize + DeserializeOwned + Send + Sync,
+    {
+        Registration::new(move |bundle| bundle.sync_resource::<R>(name))
+    }
+
+    /// Registers a resource to be displayed as read-only in the editor.
+    pub fn read_resource<R>(name: &'static str) -> Registration
+    where
+        R: Resource + Serialize + Send,
+    {
+        Registration::new(move |bundle| bundle.read_resource::<R>(name))
+    }
+
+    /// Creates a registration that performs arbitrary setup on the bundle.
+    ///
+    /// This allows a group of registrations (along with any other configuration) to be
+    /// bundled into a single value.
+    pub fn new<F>(register: F) -> Registration
+    where
+        F: for<'a> Fn(&mut SyncEditorBundle<'a>) + Send + Sync + 'static,
+    {
+        Registration {
+            register: Box::new(register),
+        }
+    }
+
+    pub(crate) fn apply(&self, bundle: &mut SyncEditorBundle) {
+        (self.register)(bundle);
+    }
+}
+
+/// Systems provided by another crate to sync data of its own with the editor, added with
+/// [`SyncEditorBundle::add_sync_systems`].
+///
+/// Both methods are called once when the bundle is built, and add their systems with
+/// [`SyncSystems::add`].
+///
+/// ```
+/// # extern crate amethyst;
+/// # extern crate amethyst_editor_sync;
+/// # use amethyst::ecs::*;
+/// # use amethyst_editor_sync::*;
+/// # use amethyst_editor_sync::ext::*;
+/// struct PathDebugSystem {
+///     connection: EditorConnection,
+/// }
+///
+/// impl<'a> System<'a> for PathDebugSystem {
+///     type SystemData = ();
+///
+///     fn run(&mut self, (): Self::SystemData) {
+///         let paths: Vec<Vec<[f32; 2]>> = Vec::new();
+///         self.connection.send_data("pathfinding", &paths);
+///     }
+/// }
+///
+/// struct PathDebugger;
+///
+/// impl SyncSystemExt for PathDebugger {
+///     fn register_read_systems(&mut self, systems: &mut SyncSystems) {
+///         let connection = systems.connection().clone();
+///         systems.add(PathDebugSystem { connection }, "path_debug", &[]);
+///     }
+/// }
+///
+/// let mut bundle = SyncEditorBundle::default();
+/// bundle.add_sync_systems(PathDebugger);
+/// ```
+///
+/// [`SyncEditorBundle::add_sync_systems`]: ../struct.SyncEditorBundle.html#method.add_sync_systems
+/// [`SyncSystems::add`]: ./struct.SyncSystems.html#method.add
+pub trait SyncSystemExt {
+    /// Adds the systems that read the game state and send it to the editor.
+    ///
+    /// These run alongside the bundle's own systems that read the game state, and finish before
+    /// the data for the frame is sent.
+    fn register_read_systems(&mut self, _systems: &mut SyncSystems) {}
+
+    /// Adds the systems that change the game state, e.g. in response to edits.
+    ///
+    /// These run alongside the bundle's own systems that apply edits from the editor. Depend on
+    /// [`names::RECEIVER`] to run after the messages from the editor have been read.
+    ///
+    /// [`names::RECEIVER`]: ../names/constant.RECEIVER.html
+    fn register_write_systems(&mut self, _systems: &mut SyncSystems) {}
+}
+
+/// Adds the systems of a [`SyncSystemExt`] to the bundle's part of the dispatcher.
+///
+/// [`SyncSystemExt`]: ./trait.SyncSystemExt.html
+pub struct SyncSystems<'s, 'a: 's, 'b: 's> {
+    dispatcher: &'s mut DispatcherBuilder<'a, 'b>,
+    graph: &'s mut SystemGraph,
+    connection: &'s EditorConnection,
+}
+
+impl<'s, 'a, 'b> SyncSystems<'s, 'a, 'b> {
+    pub(crate) fn new(
+        dispatcher: &'s mut DispatcherBuilder<'a, 'b>,
+        graph: &'s mut SystemGraph,
+        connection: &'s EditorConnection,
+    ) -> Self {
+        SyncSystems {
+            dispatcher,
+            graph,
+            connection,
+        }
+    }
+
+    /// The connection used to send data to the editor.
+    pub fn connection(&self) -> &EditorConnection {
+        self.connection
+    }
+
+    /// Adds a system to the dispatcher, which is also listed in the system graph sent to the
+    /// editor.
+    pub fn add<S>(&mut self, system: S, name: &str, dependencies: &[&str])
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        self.graph.add(self.dispatcher, system, name, dependencies);
+    }
+}
+
+#[cfg(feature = "inventory")]
+inventory::collect!(Registration);
+
+/// Submits registrations to be applied by [`SyncEditorBundle::sync_registered_types`].
+///
+/// Registrations can be submitted from any module of any crate linked into the game, removing
+/// the need to list every type where the bundle is created. Only available with the
+/// `inventory` feature.
+///
+/// [`SyncEditorBundle::sync_registered_types`]: ./struct.SyncEditorBundle.html#method.sync_registered_types
+#[cfg(feature = "inventory")]
+#[macro_export]
+macro_rules! submit_registrations {
+    ($( $registration:expr ),* $(,)*) => {
+        $( $crate::inventory::submit! { $registration } )*
+    };
+}
@@ -0,0 +1,646 @@
+//! A scripted stand-in for a real editor, used for soak testing.
+
+use crate::chunking::{self, Reassembler};
+#[cfg(feature = "encryption")]
+use crate::encryption::{Cipher, Role};
+use crate::framing::Framing;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// An editor that randomly exercises the protocol against a running game and checks that the
+/// game responds correctly.
+///
+/// The fake editor edits components and resources, creates entities, and destroys entities at
+/// configurable rates. Edits send back the exact data the game last sent for a component or
+/// resource, so any type whose `Serialize` and `Deserialize` implementations don't round-trip
+/// is caught. This is intended for soak-testing a game's registrations before hooking up a
+/// real editor, and can run in the same process as the game:
+///
+/// ```no_run
+/// # extern crate amethyst;
+/// # extern crate amethyst_editor_sync;
+/// # use amethyst_editor_sync::*;
+/// # use std::time::Duration;
+/// # fn main() -> std::io::Result<()> {
+/// let editor = FakeEditor::bind("127.0.0.1:0")?.with_edit_rate(20.0);
+/// let transport = Transport::udp("127.0.0.1:0")?.with_editor_address(editor.local_addr()?);
+/// let editor = editor.spawn();
+///
+/// // Create a `SyncEditorBundle` using the transport and run the game...
+///
+/// let report = editor.stop();
+/// assert!(report.is_ok(), "{:#?}", report);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Only entities that have none of the registered components are destroyed, which in practice
+/// means entities created by the fake editor, so that the game's own entities are left alone.
+///
+/// The following are reported as violations:
+///
+/// * Data that isn't valid JSON.
+/// * Packets that fail to decrypt, if the connection is encrypted.
+/// * Edits that the game fails to deserialize.
+/// * Requests that aren't acknowledged within the acknowledgement timeout.
+/// * The game going silent for longer than the acknowledgement timeout, e.g. because it
+///   panicked.
+pub struct FakeEditor {
+    socket: UdpSocket,
+
+    // The address of the game, learned from the first packet it sends.
+    game_address: Option<SocketAddr>,
+
+    edit_rate: f32,
+    create_rate: f32,
+    destroy_rate: f32,
+    ack_timeout: Duration,
+    framing: Framing,
+
+    // Sent in an `Authenticate` message once the game has been heard from, if set.
+    auth_token: Option<String>,
+
+    // Whether the game starts each packet with a chunk header, and whether packets sent to the
+    // game start with one, if set explicitly. Both default to what the framing implies.
+    chunk_headers: Option<bool>,
+    incoming_chunk_headers: Option<bool>,
+    reassembler: Reassembler,
+    next_message_id: u32,
+
+    // Only present if the connection is encrypted.
+    #[cfg(feature = "encryption")]
+    cipher: Option<Cipher>,
+
+    incoming: Vec<u8>,
+    latest_snapshot: Option<Value>,
+    last_received: Option<Instant>,
+    next_request_id: u64,
+    pending: HashMap<u64, PendingRequest>,
+
+    // Types that the game rejected as unknown, since read-only types can't be edited.
+    read_only: HashSet<String>,
+
+    report: SoakReport,
+}
+
+/// The results of running a [`FakeEditor`] against a game.
+///
+/// [`FakeEditor`]: ./struct.FakeEditor.html
+#[derive(Debug, Clone, Default)]
+pub struct SoakReport {
+    /// The number of state snapshots received.
+    pub snapshots: usize,
+
+    /// The number of messages received, not counting snapshots.
+    pub messages: usize,
+
+    /// The number of requests sent to the game.
+    pub requests: usize,
+
+    /// The number of requests that the game acknowledged.
+    pub acks: usize,
+
+    /// Errors reported by the game in `"error"` messages.
+    pub errors: Vec<String>,
+
+    /// Problems found with the game's responses.
+    pub violations: Vec<String>,
+}
+
+impl SoakReport {
+    /// Whether the soak test passed without any violations.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// A [`FakeEditor`] running on a background thread.
+///
+/// [`FakeEditor`]: ./struct.FakeEditor.html
+pub struct FakeEditorHandle {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<SoakReport>,
+}
+
+impl FakeEditorHandle {
+    /// Stops the fake editor, returning its report.
+    pub fn stop(self) -> SoakReport {
+        self.stop.store(true, Ordering::SeqCst);
+        self.thread.join().expect("Fake editor panicked")
+    }
+}
+
+struct PendingRequest {
+    sent: Instant,
+    description: String,
+
+    // The component or resource being edited, if the request is an edit.
+    type_name: Option<String>,
+}
+
+impl FakeEditor {
+    /// Creates a fake editor listening on `bind_address`.
+    ///
+    /// The game needs to be configured to send to the fake editor's [local address]. By
+    /// default the fake editor makes 10 edits, creates 1 entity, and destroys 1 entity per
+    /// second.
+    ///
+    /// [local address]: #method.local_addr
+    pub fn bind(bind_address: &str) -> io::Result<FakeEditor> {
+        let socket = UdpSocket::bind(bind_address)?;
+        socket.set_read_timeout(Some(Duration::from_millis(1)))?;
+
+        Ok(FakeEditor {
+            socket,
+            game_address: None,
+
+            edit_rate: 10.0,
+            create_rate: 1.0,
+            destroy_rate: 1.0,
+            ack_timeout: Duration::from_secs(5),
+            framing: Framing::default(),
+
+            auth_token: None,
+
+            chunk_headers: None,
+            incoming_chunk_headers: None,
+            reassembler: Reassembler::default(),
+            next_message_id: 0,
+
+            #[cfg(feature = "encryption")]
+            cipher: None,
+
+            incoming: Vec::new(),
+            latest_snapshot: None,
+            last_received: None,
+            next_request_id: 0,
+            pending: HashMap::new(),
+            read_only: HashSet::new(),
+
+            report: SoakReport::default(),
+        })
+    }
+
+    /// Returns the address that the fake editor is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Sets the number of component and resource edits sent per second.
+    pub fn with_edit_rate(mut self, per_second: f32) -> Self {
+        self.edit_rate = per_second;
+        self
+    }
+
+    /// Sets the number of entities created per second.
+    pub fn with_create_rate(mut self, per_second: f32) -> Self {
+        self.create_rate = per_second;
+        self
+    }
+
+    /// Sets the number of entities destroyed per second.
+    pub fn with_destroy_rate(mut self, per_second: f32) -> Self {
+        self.destroy_rate = per_second;
+        self
+    }
+
+    /// Sets how long the game has to acknowledge each request. Defaults to 5 seconds.
+    pub fn with_ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = ack_timeout;
+        self
+    }
+
+    /// Sets how messages are delimited, which must match the framing used by the game's
+    /// transport.
+    pub fn with_framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Authenticates with `token` before sending any other requests, which must match
+    /// [`SyncEditorBundle::require_auth_token`].
+    ///
+    /// [`SyncEditorBundle::require_auth_token`]: ./struct.SyncEditorBundle.html#method.require_auth_token
+    pub fn with_auth_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Sets whether the game starts each packet with a chunk header, which must match
+    /// [`SyncEditorBundle::chunk_headers`].
+    ///
+    /// Defaults to what the bundle does for a UDP editor, i.e. headers unless the framing is
+    /// [`Framing::NodeIpc`].
+    ///
+    /// [`SyncEditorBundle::chunk_headers`]: ./struct.SyncEditorBundle.html#method.chunk_headers
+    /// [`Framing::NodeIpc`]: ./enum.Framing.html#variant.NodeIpc
+    pub fn with_chunk_headers(mut self, enabled: bool) -> Self {
+        self.chunk_headers = Some(enabled);
+        self
+    }
+
+    /// Sets whether each packet sent to the game starts with a chunk header, which must match
+    /// [`SyncEditorBundle::incoming_chunk_headers`].
+    ///
+    /// Defaults to what the bundle expects from a UDP editor, i.e. headers unless the framing is
+    /// [`Framing::NodeIpc`].
+    ///
+    /// [`SyncEditorBundle::incoming_chunk_headers`]: ./struct.SyncEditorBundle.html#method.incoming_chunk_headers
+    /// [`Framing::NodeIpc`]: ./enum.Framing.html#variant.NodeIpc
+    pub fn with_incoming_chunk_headers(mut self, enabled: bool) -> Self {
+        self.incoming_chunk_headers = Some(enabled);
+        self
+    }
+
+    /// Encrypts the packets exchanged with the game with `key`, which must match
+    /// [`SyncEditorBundle::encryption_key`].
+    ///
+    /// [`SyncEditorBundle::encryption_key`]: ./struct.SyncEditorBundle.html#method.encryption_key
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption(mut self, key: [u8; 32]) -> Self {
+        self.cipher = Some(Cipher::new(key, Role::Editor));
+        self
+    }
+
+    /// Runs the fake editor on a background thread until it's stopped.
+    pub fn spawn(self) -> FakeEditorHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = thread::spawn(move || self.run_until(|| thread_stop.load(Ordering::SeqCst)));
+        FakeEditorHandle { stop, thread }
+    }
+
+    /// Runs the fake editor on the current thread for `duration`.
+    pub fn run_for(self, duration: Duration) -> SoakReport {
+        let start = Instant::now();
+        self.run_until(|| start.elapsed() >= duration)
+    }
+
+    fn run_until<F: Fn() -> bool>(mut self, stop: F) -> SoakReport {
+        let mut rng = rand::thread_rng();
+        let mut last_step = Instant::now();
+        let (mut edits, mut creates, mut destroys) = (0.0, 0.0, 0.0);
+
+        while !stop() {
+            self.poll();
+            self.check_timeouts();
+
+            let now = Instant::now();
+            let dt = duration_to_secs(now - last_step);
+            last_step = now;
+
+            // Nothing can be sent until the game has been heard from.
+            if self.game_address.is_none() {
+                continue;
+            }
+
+            if let Some(token) = self.auth_token.take() {
+                self.send_request(
+                    json!({ "type": "Authenticate", "token": token }),
+                    "Authenticate".into(),
+                    None,
+                );
+            }
+
+            edits += self.edit_rate * dt;
+            creates += self.create_rate * dt;
+            destroys += self.destroy_rate * dt;
+            while edits >= 1.0 {
+                edits -= 1.0;
+                self.edit(&mut rng);
+            }
+            while creates >= 1.0 {
+                creates -= 1.0;
+                let amount = rng.gen_range(1, 4);
+                self.send_request(
+                    json!({ "type": "CreateEntities", "amount": amount }),
+                    format!("CreateEntities of {} entities", amount),
+                    None,
+                );
+            }
+            while destroys >= 1.0 {
+                destroys -= 1.0;
+                self.destroy(&mut rng);
+            }
+        }
+
+        self.report
+    }
+
+    /// Sends back the data of a random component or resource from the latest snapshot.
+    fn edit<R: Rng>(&mut self, rng: &mut R) {
+        let snapshot = match self.latest_snapshot {
+            Some(ref snapshot) => snapshot,
+            None => return,
+        };
+
+        let mut candidates = Vec::new();
+        for component in snapshot["components"].as_array().into_iter().flatten() {
+            let name = component["name"].as_str().unwrap_or_default();
+            if self.read_only.contains(name) {
+                continue;
+            }
+
+            for entry in component["data"].as_array().into_iter().flatten() {
+                let message = json!({
+                    "type": "ComponentUpdate",
+                    "id": name,
+                    "entity": entry["entity"],
+                    "data": entry["data"],
+                });
+                candidates.push((message, name.to_owned()));
+            }
+        }
+        for resource in snapshot["resources"].as_array().into_iter().flatten() {
+            let name = resource["name"].as_str().unwrap_or_default();
+            if self.read_only.contains(name) {
+                continue;
+            }
+
+            let message = json!({
+                "type": "ResourceUpdate",
+                "id": name,
+                "data": resource["data"],
+            });
+            candidates.push((message, name.to_owned()));
+        }
+
+        if let Some((message, name)) = candidates.choose(rng).cloned() {
+            let description = format!(
+                "{} of {}",
+                message["type"].as_str().unwrap_or_default(),
+                name
+            );
+            self.send_request(message, description, Some(name));
+        }
+    }
+
+    /// Destroys a random entity that has none of the registered components.
+    fn destroy<R: Rng>(&mut self, rng: &mut R) {
+        let snapshot = match self.latest_snapshot {
+            Some(ref snapshot) => snapshot,
+            None => return,
+        };
+
+        let candidates = empty_entities(snapshot);
+        if let Some(entity) = candidates.choose(rng).cloned() {
+            self.send_request(
+                json!({ "type": "DestroyEntities", "entities": [entity] }),
+                format!("DestroyEntities of {}", entity),
+                None,
+            );
+        }
+    }
+
+    fn send_request(&mut self, mut message: Value, description: String, type_name: Option<String>) {
+        let game_address = match self.game_address {
+            Some(address) => address,
+            None => return,
+        };
+
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        message["request_id"] = json!(request_id);
+
+        let serialized = serde_json::to_vec(&message).expect("Failed to serialize request");
+        let mut bytes = Vec::new();
+        if self
+            .incoming_chunk_headers
+            .unwrap_or(self.framing != Framing::NodeIpc)
+        {
+            chunking::encode_header(self.next_message_id, 0, 1, &mut bytes);
+            self.next_message_id = self.next_message_id.wrapping_add(1);
+        }
+        self.framing.encode(&serialized, &mut bytes);
+        if let Err(error) = self
+            .seal(bytes)
+            .and_then(|bytes| self.socket.send_to(&bytes, game_address))
+        {
+            self.report
+                .violations
+                .push(format!("Failed to send {}: {}", description, error));
+            return;
+        }
+
+        self.report.requests += 1;
+        self.pending.insert(
+            request_id,
+            PendingRequest {
+                sent: Instant::now(),
+                description,
+                type_name,
+            },
+        );
+    }
+
+    /// Reads any pending packets from the game and handles the messages they complete.
+    fn poll(&mut self) {
+        let mut buf = [0; 64 * 1024];
+        while let Ok((bytes_read, address)) = self.socket.recv_from(&mut buf) {
+            if self
+                .game_address
+                .map_or(false, |game_address| game_address != address)
+            {
+                continue;
+            }
+
+            let bytes_read = match self.open(&mut buf[..bytes_read]) {
+                Some(bytes_read) => bytes_read,
+                None => {
+                    self.report
+                        .violations
+                        .push("Received a packet that failed to decrypt".into());
+                    continue;
+                }
+            };
+
+            self.game_address = Some(address);
+            self.last_received = Some(Instant::now());
+            if self
+                .chunk_headers
+                .unwrap_or(self.framing != Framing::NodeIpc)
+            {
+                if let Some(message) = self.reassembler.push(&buf[..bytes_read]) {
+                    self.incoming.extend_from_slice(&message);
+                }
+            } else {
+                self.incoming.extend_from_slice(&buf[..bytes_read]);
+            }
+        }
+
+        while let Some((message, consumed)) = self.framing.decode(&self.incoming) {
+            let envelope = serde_json::from_slice::<Value>(&self.incoming[message]);
+            self.incoming.drain(..consumed);
+
+            match envelope {
+                Ok(mut envelope) => self.handle_data(envelope["data"].take()),
+                Err(error) => self
+                    .report
+                    .violations
+                    .push(format!("Received invalid JSON: {}", error)),
+            }
+        }
+    }
+
+    /// Encrypts a packet before it's sent, if the connection is encrypted.
+    #[cfg(feature = "encryption")]
+    fn seal(&self, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+        match self.cipher {
+            Some(ref cipher) => cipher.seal(&bytes),
+            None => Ok(bytes),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn seal(&self, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+        Ok(bytes)
+    }
+
+    /// Decrypts a received packet in place, returning the length of the decrypted data or
+    /// `None` if it failed to decrypt.
+    #[cfg(feature = "encryption")]
+    fn open(&self, packet: &mut [u8]) -> Option<usize> {
+        match self.cipher {
+            Some(ref cipher) => cipher.open(packet),
+            None => Some(packet.len()),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn open(&self, packet: &mut [u8]) -> Option<usize> {
+        Some(packet.len())
+    }
+
+    fn handle_data(&mut self, data: Value) {
+        for message in data["messages"].as_array().into_iter().flatten() {
+            self.report.messages += 1;
+            match message["type"].as_str() {
+                Some("edit_ack") => self.handle_ack(&message["data"]),
+                Some("error") => self.report.errors.push(message["data"].to_string()),
+                _ => {}
+            }
+        }
+
+        if data.get("components").is_some() {
+            self.report.snapshots += 1;
+            self.latest_snapshot = Some(data);
+        }
+    }
+
+    fn handle_ack(&mut self, ack: &Value) {
+        let request = match ack["request_id"]
+            .as_u64()
+            .and_then(|request_id| self.pending.remove(&request_id))
+        {
+            Some(request) => request,
+            None => return,
+        };
+        self.report.acks += 1;
+
+        match ack["status"].as_str() {
+            Some("invalid_data") => self.report.violations.push(format!(
+                "{} failed to deserialize: {}",
+                request.description,
+                self.report.errors.last().map_or("", |error| &**error)
+            )),
+
+            // Read-only types can't be edited, so stop trying.
+            Some("unknown_type") => {
+                if let Some(name) = request.type_name {
+                    self.read_only.insert(name);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn check_timeouts(&mut self) {
+        let ack_timeout = self.ack_timeout;
+        let timed_out = self
+            .pending
+            .iter()
+            .filter(|(_, request)| request.sent.elapsed() > ack_timeout)
+            .map(|(&request_id, _)| request_id)
+            .collect::<Vec<_>>();
+        for request_id in timed_out {
+            let request = self.pending.remove(&request_id).unwrap();
+            self.report.violations.push(format!(
+                "{} wasn't acknowledged within {:?}",
+                request.description, ack_timeout
+            ));
+        }
+
+        if let Some(last_received) = self.last_received {
+            if last_received.elapsed() > ack_timeout {
+                self.report
+                    .violations
+                    .push(format!("The game stopped sending for {:?}", ack_timeout));
+                self.last_received = None;
+            }
+        }
+    }
+}
+
+/// Finds the entities in a snapshot that have none of the registered components.
+fn empty_entities(snapshot: &Value) -> Vec<Value> {
+    let with_components = snapshot["components"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|component| component["data"].as_array().into_iter().flatten())
+        .filter_map(|entry| entry["entity"]["id"].as_u64())
+        .collect::<HashSet<_>>();
+
+    snapshot["entities"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|entity| match entity["id"].as_u64() {
+            Some(id) => !with_components.contains(&id),
+            None => false,
+        })
+        .map(|entity| json!({ "id": entity["id"], "generation": entity["generation"] }))
+        .collect()
+}
+
+fn duration_to_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 * 1e-9
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_empty_entities() {
+        let snapshot = json!({
+            "entities": [
+                { "id": 0, "generation": 1 },
+                { "id": 1, "generation": 1 },
+                { "id": 2, "generation": 3 },
+            ],
+            "components": [{
+                "name": "Label",
+                "data": [{ "entity": { "id": 1, "generation": 1 }, "data": { "text": "a" } }],
+            }],
+            "resources": [],
+        });
+
+        assert_eq!(
+            empty_entities(&snapshot),
+            vec![
+                json!({ "id": 0, "generation": 1 }),
+                json!({ "id": 2, "generation": 3 }),
+            ],
+        );
+    }
+}
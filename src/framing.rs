@@ -0,0 +1,186 @@
+use std::ops::Range;
+
+/// The largest message accepted from the editor, in bytes.
+///
+/// A buffer holding more than this without a complete message means that the framing is out of
+/// sync, e.g. because the editor uses a different framing or a length prefix was corrupted, so
+/// the buffered bytes are discarded instead of waiting forever for the message to complete.
+pub(crate) const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+/// How messages are delimited in the stream of bytes exchanged with the editor, set with
+/// [`Transport::with_framing`].
+///
+/// Messages may be split across several packets, so the framing is what lets the receiving side
+/// find where each message ends. The same framing is used for messages in both directions.
+///
+/// [`Transport::with_framing`]: ./struct.Transport.html#method.with_framing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Each message is preceded by its length in bytes, as a 4-byte big-endian integer. This is
+    /// the default.
+    ///
+    /// Unlike the delimited framings, this doesn't rely on a byte that never appears inside a
+    /// message, and a message can be rejected as soon as its length is known. Messages from the
+    /// editor larger than 16MB are discarded.
+    LengthPrefixed,
+
+    /// Each message is followed by a form feed character (`0x0C`), which is what node-ipc
+    /// expects.
+    ///
+    /// This is a compatibility mode for editors that still use the delimiter, which breaks if
+    /// the delimiter appears inside a message. Those editors don't use chunk headers either, so
+    /// UDP transports with this framing don't send or expect them unless
+    /// [`SyncEditorBundle::chunk_headers`] or [`SyncEditorBundle::incoming_chunk_headers`] say
+    /// otherwise.
+    ///
+    /// [`SyncEditorBundle::chunk_headers`]: ./struct.SyncEditorBundle.html#method.chunk_headers
+    /// [`SyncEditorBundle::incoming_chunk_headers`]: ./struct.SyncEditorBundle.html#method.incoming_chunk_headers
+    NodeIpc,
+
+    /// Each message is followed by a newline, i.e. newline-delimited JSON. A carriage return
+    /// before the newline is ignored when receiving.
+    JsonLines,
+}
+
+impl Default for Framing {
+    fn default() -> Self {
+        Framing::LengthPrefixed
+    }
+}
+
+impl Framing {
+    /// Appends `message` to `out`, framed so that the receiving side can find where it ends.
+    pub fn encode(self, message: &[u8], out: &mut Vec<u8>) {
+        match self {
+            Framing::NodeIpc => {
+                out.extend_from_slice(message);
+                out.push(0xC);
+            }
+
+            Framing::JsonLines => {
+                out.extend_from_slice(message);
+                out.push(b'\n');
+            }
+
+            Framing::LengthPrefixed => {
+                let len = message.len() as u32;
+                out.extend_from_slice(&[
+                    (len >> 24) as u8,
+                    (len >> 16) as u8,
+                    (len >> 8) as u8,
+                    len as u8,
+                ]);
+                out.extend_from_slice(message);
+            }
+        }
+    }
+
+    /// Finds the first complete message in `buffer`.
+    ///
+    /// Returns the range of the message's bytes within `buffer`, along with the number of bytes
+    /// to remove from the front of `buffer` once the message has been handled. Returns `None`
+    /// if `buffer` doesn't contain a complete message yet.
+    pub fn decode(self, buffer: &[u8]) -> Option<(Range<usize>, usize)> {
+        match self {
+            Framing::NodeIpc => {
+                let index = buffer.iter().position(|&byte| byte == 0xC)?;
+                Some((0..index, index + 1))
+            }
+
+            Framing::JsonLines => {
+                let index = buffer.iter().position(|&byte| byte == b'\n')?;
+                let end = if index > 0 && buffer[index - 1] == b'\r' {
+                    index - 1
+                } else {
+                    index
+                };
+                Some((0..end, index + 1))
+            }
+
+            Framing::LengthPrefixed => {
+                let len = length_prefix(buffer)?;
+                if buffer.len() < 4 + len {
+                    return None;
+                }
+
+                Some((4..4 + len, 4 + len))
+            }
+        }
+    }
+
+    /// Whether the incomplete message at the start of `buffer` is larger than
+    /// `MAX_MESSAGE_LEN`, meaning that it should be discarded.
+    ///
+    /// Only meaningful once `decode` has returned `None` for `buffer`.
+    pub(crate) fn is_oversized(self, buffer: &[u8]) -> bool {
+        match self {
+            Framing::NodeIpc | Framing::JsonLines => buffer.len() > MAX_MESSAGE_LEN,
+
+            // NOTE: The length prefix tells how large the message is going to be before all of
+            // it has arrived, so bogus lengths are caught right away.
+            Framing::LengthPrefixed => {
+                length_prefix(buffer).map_or(false, |len| len > MAX_MESSAGE_LEN)
+            }
+        }
+    }
+}
+
+/// Reads the length prefix at the start of `buffer`, if all of it has arrived.
+fn length_prefix(buffer: &[u8]) -> Option<usize> {
+    if buffer.len() < 4 {
+        return None;
+    }
+
+    Some(
+        buffer[..4]
+            .iter()
+            .fold(0, |len, &byte| len << 8 | byte as usize),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for &framing in &[
+            Framing::NodeIpc,
+            Framing::JsonLines,
+            Framing::LengthPrefixed,
+        ] {
+            let mut buffer = Vec::new();
+            framing.encode(b"{\"a\":1}", &mut buffer);
+            framing.encode(b"{}", &mut buffer);
+
+            // A partial message isn't decoded until the rest of it arrives.
+            assert_eq!(framing.decode(&buffer[..3]), None);
+
+            let (message, consumed) = framing.decode(&buffer).unwrap();
+            assert_eq!(&buffer[message], b"{\"a\":1}");
+            buffer.drain(..consumed);
+
+            let (message, consumed) = framing.decode(&buffer).unwrap();
+            assert_eq!(&buffer[message], b"{}");
+            buffer.drain(..consumed);
+            assert!(buffer.is_empty());
+        }
+    }
+
+    #[test]
+    fn oversized_messages_are_detected() {
+        // A node-ipc message sent to a game expecting length prefixes starts with `{"ty`, which
+        // reads as a length of almost 2GB.
+        assert!(Framing::LengthPrefixed.is_oversized(b"{\"type\":\"Heartbeat\"}"));
+        assert!(!Framing::LengthPrefixed.is_oversized(&[0, 0, 1, 0, b'{']));
+        assert!(!Framing::NodeIpc.is_oversized(b"{\"type\""));
+        assert!(Framing::NodeIpc.is_oversized(&vec![b' '; MAX_MESSAGE_LEN + 1]));
+    }
+
+    #[test]
+    fn json_lines_ignores_carriage_return() {
+        let (message, consumed) = Framing::JsonLines.decode(b"{}\r\n").unwrap();
+        assert_eq!(message, 0..2);
+        assert_eq!(consumed, 4);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::capabilities::Capabilities;
+use crate::editor_meta::EditorMeta;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Information about the game's editor support, sent to the editor when a session starts.
+///
+/// The handshake is sent once when the game starts, and again whenever the editor sends a
+/// `Handshake` message (e.g. because the editor was started after the game).
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Handshake {
+    /// The ID of the bundle that sent the handshake, if there are several in the process.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+
+    /// The names of all registered components.
+    pub components: Vec<&'static str>,
+
+    /// The names of all registered resources.
+    pub resources: Vec<&'static str>,
+
+    /// The fields of each component or resource that are redacted and never sent.
+    pub redacted: BTreeMap<&'static str, Vec<String>>,
+
+    /// The fields of each component or resource that are sent but can't be edited.
+    pub read_only: BTreeMap<&'static str, Vec<String>>,
+
+    /// The components and resources that are only updated during fixed updates.
+    pub fixed_step: Vec<&'static str>,
+
+    /// The optional protocol features used by the game.
+    pub capabilities: Capabilities,
+
+    /// Custom message types that the game may send, along with a sample payload for each.
+    pub messages: BTreeMap<&'static str, Value>,
+
+    /// Settings stored by the editor for this game.
+    pub editor_meta: EditorMeta,
+
+    /// Whether the editor has to send an `Authenticate` message before its commands are
+    /// accepted.
+    pub auth_required: bool,
+}
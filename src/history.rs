@@ -0,0 +1,224 @@
+use amethyst::ecs::{Entity, Join, World};
+use crate::registry::Registry;
+use crate::serializable_entity::SerializableEntity;
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+
+/// The registered components and resources of a single frame, as recorded in the [`History`].
+#[derive(Debug, Clone)]
+pub(crate) struct HistoryFrame {
+    pub frame: u64,
+    pub entities: Vec<Entity>,
+    pub components: BTreeMap<&'static str, BTreeMap<Entity, Value>>,
+    pub resources: BTreeMap<&'static str, Value>,
+}
+
+/// A frame of the history as sent to the editor in a `"history"` message.
+#[derive(Debug, Serialize)]
+pub(crate) struct SerializedHistoryFrame {
+    frame: u64,
+    entities: Vec<SerializableEntity>,
+
+    /// The serialized components of each registered type, keyed by entity ID.
+    components: BTreeMap<&'static str, BTreeMap<u32, Value>>,
+    resources: BTreeMap<&'static str, Value>,
+}
+
+impl HistoryFrame {
+    /// Records the state of all registered types in `world`.
+    ///
+    /// Unlike data sent to the editor, the values are stored without redacting or truncating
+    /// them, so that they can be deserialized again when rewinding.
+    pub(crate) fn capture(frame: u64, world: &World, registry: &Registry) -> Self {
+        let entities = (&*world.entities()).join().collect::<Vec<_>>();
+
+        let mut components = BTreeMap::new();
+        for access in registry.components() {
+            let mut values = BTreeMap::new();
+            for &entity in &entities {
+                match access.serialize(world, entity) {
+                    Some(Ok(value)) => {
+                        values.insert(entity, value);
+                    }
+                    Some(Err(error)) => error!(
+                        "Failed to record component of type {}: {}",
+                        access.name(),
+                        error
+                    ),
+                    None => {}
+                }
+            }
+            components.insert(access.name(), values);
+        }
+
+        let mut resources = BTreeMap::new();
+        for access in registry.resources() {
+            match access.serialize(world) {
+                Some(Ok(value)) => {
+                    resources.insert(access.name(), value);
+                }
+                Some(Err(error)) => error!(
+                    "Failed to record resource of type {}: {}",
+                    access.name(),
+                    error
+                ),
+                None => {}
+            }
+        }
+
+        HistoryFrame {
+            frame,
+            entities,
+            components,
+            resources,
+        }
+    }
+
+    /// Prepares the frame to be sent to the editor, redacting and truncating the data the same
+    /// way as in snapshots.
+    pub(crate) fn serialize(&self, registry: &Registry) -> SerializedHistoryFrame {
+        let components = self
+            .components
+            .iter()
+            .map(|(&name, values)| {
+                let options = registry.options(name);
+                let values = values
+                    .iter()
+                    .map(|(entity, value)| {
+                        let mut value = value.clone();
+                        options.prepare_outgoing(&mut value);
+                        (entity.id(), value)
+                    })
+                    .collect();
+                (name, values)
+            })
+            .collect();
+
+        let resources = self
+            .resources
+            .iter()
+            .map(|(&name, value)| {
+                let mut value = value.clone();
+                registry.options(name).prepare_outgoing(&mut value);
+                (name, value)
+            })
+            .collect();
+
+        SerializedHistoryFrame {
+            frame: self.frame,
+            entities: self.entities.iter().map(|&entity| entity.into()).collect(),
+            components,
+            resources,
+        }
+    }
+
+    /// Writes the recorded state of the editable components and resources back into `world`.
+    ///
+    /// Components are only restored on entities that still exist. Entities created or deleted
+    /// since the frame was recorded are left alone, since a deleted entity can't be brought back
+    /// with the same ID.
+    pub(crate) fn restore(&self, world: &World, registry: &Registry) {
+        for access in registry.writable_components() {
+            let values = match self.components.get(access.name()) {
+                Some(values) => values,
+                None => continue,
+            };
+
+            for &entity in &self.entities {
+                if !world.is_alive(entity) {
+                    continue;
+                }
+
+                if let Some(Err(error)) = access.restore(world, entity, values.get(&entity)) {
+                    error!(
+                        "Failed to restore component of type {}: {}",
+                        access.name(),
+                        error
+                    );
+                }
+            }
+        }
+
+        for access in registry.writable_resources() {
+            if let Some(value) = self.resources.get(access.name()) {
+                if let Some(Err(error)) = access.restore(world, value) {
+                    error!(
+                        "Failed to restore resource of type {}: {}",
+                        access.name(),
+                        error
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The state recorded over the last few frames, oldest first.
+#[derive(Debug)]
+pub(crate) struct History {
+    frames: VecDeque<HistoryFrame>,
+    capacity: usize,
+}
+
+impl History {
+    pub(crate) fn new(capacity: usize) -> Self {
+        History {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds a frame, dropping the oldest one if the history is full.
+    pub(crate) fn push(&mut self, frame: HistoryFrame) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// The recorded frame numbered `frame`, if it's still in the history.
+    pub(crate) fn get(&self, frame: u64) -> Option<&HistoryFrame> {
+        self.frames.iter().find(|recorded| recorded.frame == frame)
+    }
+
+    /// The recorded frames numbered from `from` to `to`, inclusive.
+    pub(crate) fn range(&self, from: u64, to: u64) -> impl Iterator<Item = &HistoryFrame> {
+        self.frames
+            .iter()
+            .filter(move |recorded| recorded.frame >= from && recorded.frame <= to)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(frame: u64) -> HistoryFrame {
+        HistoryFrame {
+            frame,
+            entities: Vec::new(),
+            components: BTreeMap::new(),
+            resources: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn drops_oldest_frames() {
+        let mut history = History::new(3);
+        for number in 0..5 {
+            history.push(frame(number));
+        }
+
+        assert!(history.get(1).is_none());
+        assert!(history.get(2).is_some());
+        let frames = history
+            .range(0, 3)
+            .map(|recorded| recorded.frame)
+            .collect::<Vec<_>>();
+        assert_eq!(frames, vec![2, 3]);
+    }
+}
@@ -0,0 +1,131 @@
+use amethyst::core::nalgebra::{Matrix4, Point3};
+use amethyst::ecs::{BitSet, Entity, Join};
+use std::sync::{Arc, RwLock};
+
+/// The part of the world around the camera whose entities are fully synced with the editor.
+///
+/// See [`SyncEditorBundle::interest_area`] for details.
+///
+/// [`SyncEditorBundle::interest_area`]: ./struct.SyncEditorBundle.html#method.interest_area
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InterestArea {
+    /// Entities within the given distance of the camera.
+    Distance(f32),
+
+    /// Entities within the camera's view.
+    Frustum,
+}
+
+impl InterestArea {
+    /// Whether `point` is within the area, given the global transform of the camera and its
+    /// projection.
+    pub(crate) fn contains(
+        self,
+        camera: &Matrix4<f32>,
+        projection: &Matrix4<f32>,
+        point: &Point3<f32>,
+    ) -> bool {
+        match self {
+            InterestArea::Distance(distance) => {
+                let position = Point3::new(camera[(0, 3)], camera[(1, 3)], camera[(2, 3)]);
+                (point - position).norm() <= distance
+            }
+
+            InterestArea::Frustum => {
+                let view = match camera.try_inverse() {
+                    Some(view) => view,
+                    None => return false,
+                };
+                let clip = projection * view * point.to_homogeneous();
+                if clip.w <= 0.0 {
+                    return false;
+                }
+
+                let ndc = clip.xyz() / clip.w;
+                ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && ndc.z.abs() <= 1.0
+            }
+        }
+    }
+}
+
+/// The entities whose components are serialized, shared between the system that picks them
+/// and the systems that read components.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InterestFilter {
+    state: Arc<RwLock<InterestState>>,
+}
+
+#[derive(Debug, Default)]
+struct InterestState {
+    // `None` unless an interest area is set, meaning that every entity is serialized.
+    entities: Option<BitSet>,
+
+    // The IDs of the entities that the editor asked to always sync.
+    forced: BitSet,
+
+    // The number of times `entities` has changed.
+    changes: u64,
+}
+
+impl InterestFilter {
+    /// The IDs of the entities whose components are serialized, or `None` for all of them.
+    pub(crate) fn entities(&self) -> Option<BitSet> {
+        self.state
+            .read()
+            .expect("Interest filter poisoned")
+            .entities
+            .clone()
+    }
+
+    pub(crate) fn set_entities(&self, entities: BitSet) {
+        let mut state = self.state.write().expect("Interest filter poisoned");
+        let changed = match state.entities {
+            Some(ref previous) => !(&entities).join().eq(previous.join()),
+            None => true,
+        };
+        if changed {
+            state.entities = Some(entities);
+            state.changes += 1;
+        }
+    }
+
+    /// The number of times the entities whose components are serialized have changed, which
+    /// tells the systems that cache serialized components when to serialize them again.
+    pub(crate) fn changes(&self) -> u64 {
+        self.state.read().expect("Interest filter poisoned").changes
+    }
+
+    /// The IDs of the entities that are serialized wherever they are.
+    pub(crate) fn forced(&self) -> BitSet {
+        self.state
+            .read()
+            .expect("Interest filter poisoned")
+            .forced
+            .clone()
+    }
+
+    /// Replaces the entities that are serialized wherever they are.
+    pub(crate) fn set_forced(&self, entities: &[Entity]) {
+        let mut forced = BitSet::new();
+        for entity in entities {
+            forced.add(entity.id());
+        }
+        self.state.write().expect("Interest filter poisoned").forced = forced;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::core::nalgebra::Vector3;
+
+    #[test]
+    fn distance_is_measured_from_camera() {
+        let camera = Matrix4::new_translation(&Vector3::new(10.0, 0.0, 0.0));
+        let projection = Matrix4::identity();
+        let area = InterestArea::Distance(5.0);
+
+        assert!(area.contains(&camera, &projection, &Point3::new(12.0, 3.0, 0.0)));
+        assert!(!area.contains(&camera, &projection, &Point3::new(0.0, 0.0, 0.0)));
+    }
+}
@@ -0,0 +1,234 @@
+//! Local IPC connections to an editor running on the same machine.
+//!
+//! Editors built on node-ipc listen on a Unix domain socket on Linux and macOS, and on a named
+//! pipe on Windows. Both are reliable streams, so unlike UDP no data is ever dropped, though a
+//! message may be split across several reads.
+
+pub(crate) use self::platform::IpcStream;
+
+#[cfg(unix)]
+mod platform {
+    use std::io::{self, Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+
+    /// A connection to the Unix domain socket that the editor is listening on.
+    pub(crate) struct IpcStream {
+        stream: UnixStream,
+    }
+
+    impl IpcStream {
+        pub(crate) fn connect(path: &Path) -> io::Result<IpcStream> {
+            let stream = UnixStream::connect(path)?;
+
+            // NOTE: The stream is nonblocking for the same reason that the UDP socket is, so that
+            // reading doesn't block the game when the editor hasn't sent anything.
+            stream.set_nonblocking(true)?;
+            Ok(IpcStream { stream })
+        }
+
+        pub(crate) fn try_clone(&self) -> io::Result<IpcStream> {
+            Ok(IpcStream {
+                stream: self.stream.try_clone()?,
+            })
+        }
+
+        pub(crate) fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.stream.write(bytes)
+        }
+
+        pub(crate) fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.stream.read(buf)
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use crossbeam_channel::{self, Receiver, TryRecvError};
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::mem;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::path::Path;
+    use std::ptr;
+    use std::thread;
+    use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE};
+    use winapi::shared::winerror::ERROR_IO_PENDING;
+    use winapi::um::fileapi::{ReadFile, WriteFile};
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::ioapiset::GetOverlappedResult;
+    use winapi::um::minwinbase::OVERLAPPED;
+    use winapi::um::synchapi::CreateEventW;
+    use winapi::um::winbase::FILE_FLAG_OVERLAPPED;
+    use winapi::um::winnt::HANDLE;
+
+    /// A connection to the named pipe that the editor is listening on.
+    ///
+    /// The standard library can't read from a pipe without blocking, so incoming data is read
+    /// on a background thread and handed over through a channel.
+    ///
+    /// NOTE: The pipe is opened for overlapped I/O. Reads and writes on a pipe opened for
+    /// synchronous I/O are serialized, so the background thread waiting for the editor to send
+    /// something would block every write until it did.
+    pub(crate) struct IpcStream {
+        pipe: File,
+        incoming: Receiver<Vec<u8>>,
+
+        // Data received from the background thread that didn't fit in the last read.
+        leftover: Vec<u8>,
+    }
+
+    impl IpcStream {
+        pub(crate) fn connect(path: &Path) -> io::Result<IpcStream> {
+            let pipe = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .custom_flags(FILE_FLAG_OVERLAPPED)
+                .open(path)?;
+
+            let (sender, incoming) = crossbeam_channel::unbounded();
+            let reader = pipe.try_clone()?;
+            thread::spawn(move || {
+                let mut buf = [0; 4096];
+                loop {
+                    match read(&reader, &mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(bytes_read) => {
+                            if sender.send(buf[..bytes_read].to_vec()).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            });
+
+            Ok(IpcStream {
+                pipe,
+                incoming,
+                leftover: Vec::new(),
+            })
+        }
+
+        pub(crate) fn try_clone(&self) -> io::Result<IpcStream> {
+            Ok(IpcStream {
+                pipe: self.pipe.try_clone()?,
+                incoming: self.incoming.clone(),
+                leftover: Vec::new(),
+            })
+        }
+
+        pub(crate) fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            wait_for(&self.pipe, |handle, overlapped| unsafe {
+                WriteFile(
+                    handle,
+                    bytes.as_ptr() as *const _,
+                    bytes.len() as DWORD,
+                    ptr::null_mut(),
+                    overlapped,
+                )
+            })
+        }
+
+        /// Reads data received by the background thread, returning `Ok(0)` once the pipe has
+        /// been closed.
+        pub(crate) fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.leftover.is_empty() {
+                self.leftover = match self.incoming.try_recv() {
+                    Ok(bytes) => bytes,
+                    Err(TryRecvError::Empty) => return Err(io::ErrorKind::WouldBlock.into()),
+                    Err(TryRecvError::Disconnected) => return Ok(0),
+                };
+            }
+
+            let bytes_read = self.leftover.len().min(buf.len());
+            buf[..bytes_read].copy_from_slice(&self.leftover[..bytes_read]);
+            self.leftover.drain(..bytes_read);
+            Ok(bytes_read)
+        }
+    }
+
+    /// Reads from `pipe`, waiting until data arrives.
+    fn read(pipe: &File, buf: &mut [u8]) -> io::Result<usize> {
+        wait_for(pipe, |handle, overlapped| unsafe {
+            ReadFile(
+                handle,
+                buf.as_mut_ptr() as *mut _,
+                buf.len() as DWORD,
+                ptr::null_mut(),
+                overlapped,
+            )
+        })
+    }
+
+    /// Starts an overlapped read or write on `pipe` with `start`, and waits for it to complete,
+    /// returning the number of bytes transferred. Other operations on the pipe can go ahead
+    /// while it waits.
+    fn wait_for<F>(pipe: &File, start: F) -> io::Result<usize>
+    where
+        F: FnOnce(HANDLE, *mut OVERLAPPED) -> BOOL,
+    {
+        let handle = pipe.as_raw_handle() as HANDLE;
+        unsafe {
+            let event = CreateEventW(ptr::null_mut(), TRUE, FALSE, ptr::null());
+            if event.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut overlapped: OVERLAPPED = mem::zeroed();
+            overlapped.hEvent = event;
+            let mut result = Ok(());
+            if start(handle, &mut overlapped) == FALSE {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_IO_PENDING as i32) {
+                    result = Err(error);
+                }
+            }
+
+            // NOTE: The operation has to be waited for even if it completed right away, since
+            // the number of bytes transferred is only reported through the `OVERLAPPED`.
+            let mut transferred: DWORD = 0;
+            let result = result.and_then(|()| {
+                if GetOverlappedResult(handle, &mut overlapped, &mut transferred, TRUE) == FALSE {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(transferred as usize)
+                }
+            });
+
+            CloseHandle(event);
+            result
+        }
+    }
+}
+
+// Platforms without IPC support fail to connect.
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io;
+    use std::path::Path;
+
+    pub(crate) enum IpcStream {}
+
+    impl IpcStream {
+        pub(crate) fn connect(_path: &Path) -> io::Result<IpcStream> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "IPC transports aren't supported on this platform",
+            ))
+        }
+
+        pub(crate) fn try_clone(&self) -> io::Result<IpcStream> {
+            match *self {}
+        }
+
+        pub(crate) fn write(&mut self, _bytes: &[u8]) -> io::Result<usize> {
+            match *self {}
+        }
+
+        pub(crate) fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use amethyst::ecs::Entity;
+use crate::serializable_entity::SerializableEntity;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// The most issues kept for a single frame. Issues reported beyond this are dropped, so that a
+/// check failing for every entity can't flood the editor.
+const MAX_ISSUES_PER_FRAME: usize = 1024;
+
+/// How serious an issue reported through [`EditorConnection::report_issue`] is.
+///
+/// [`EditorConnection::report_issue`]: ./struct.EditorConnection.html#method.report_issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Worth knowing about, but not necessarily a problem.
+    Info,
+
+    /// Likely a problem, though the game can carry on.
+    Warning,
+
+    /// The game state is broken.
+    Error,
+}
+
+/// A problem with the game state reported by the game, sent to the editor in an `"issues"`
+/// message.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Issue {
+    pub severity: Severity,
+    pub category: String,
+    pub message: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity: Option<SerializableEntity>,
+}
+
+impl Issue {
+    pub(crate) fn new(
+        severity: Severity,
+        category: String,
+        message: String,
+        entity: Option<Entity>,
+    ) -> Self {
+        Issue {
+            severity,
+            category,
+            message,
+            entity: entity.map(SerializableEntity::new),
+        }
+    }
+}
+
+/// The issues reported by the game during the current frame, shared by all clones of an
+/// [`EditorConnection`].
+///
+/// [`EditorConnection`]: ./struct.EditorConnection.html
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IssueBuffer {
+    issues: Arc<Mutex<Vec<Issue>>>,
+}
+
+impl IssueBuffer {
+    pub(crate) fn push(&self, issue: Issue) {
+        let mut issues = self.issues.lock().expect("Issue buffer poisoned");
+        if issues.len() < MAX_ISSUES_PER_FRAME {
+            issues.push(issue);
+        } else {
+            warn_once!(
+                "More than {} issues were reported in one frame, dropping the rest",
+                MAX_ISSUES_PER_FRAME
+            );
+        }
+    }
+
+    /// Takes the issues reported since the last call.
+    pub(crate) fn take(&self) -> Vec<Issue> {
+        let mut issues = self.issues.lock().expect("Issue buffer poisoned");
+        mem::replace(&mut *issues, Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serializes_without_entity() {
+        let issue = Issue::new(
+            Severity::Warning,
+            "physics".into(),
+            "Body is asleep".into(),
+            None,
+        );
+        let value = serde_json::to_value(&issue).unwrap();
+        assert_eq!(value["severity"], "warning");
+        assert!(value.get("entity").is_none());
+    }
+}
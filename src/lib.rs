@@ -102,21 +102,119 @@
 //! [tap]: https://crates.io/crates/tap
 
 extern crate amethyst;
+extern crate backtrace;
+#[cfg(feature = "screen-capture")]
+extern crate base64;
 extern crate crossbeam_channel;
+#[cfg(feature = "screen-capture")]
+extern crate image;
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub extern crate inventory;
 #[macro_use]
 extern crate log;
 #[macro_use]
 extern crate log_once;
+extern crate rand;
+#[cfg(feature = "encryption")]
+extern crate ring;
+extern crate rmp_serde;
 #[macro_use]
 extern crate serde;
 extern crate serde_json;
+extern crate socket2;
+#[cfg(feature = "physics")]
+extern crate specs_physics;
+#[cfg(windows)]
+extern crate winapi;
 
-pub use crate::bundle::SyncEditorBundle;
+pub use crate::asset_paths::AssetPaths;
+pub use crate::bookmarks::Bookmarks;
+pub use crate::budget::SyncPriority;
+pub use crate::bundle::{SyncEditorBundle, SyncPoint};
+pub use crate::client::EditorClient;
+pub use crate::codec::Codec;
+pub use crate::connection_status::EditorConnectionStatus;
+pub use crate::dynamic_components::DynamicComponentStore;
 pub use crate::editor_log::EditorLogger;
+pub use crate::editor_uuid::{EditorUuid, ParseUuidError};
+pub use crate::entity_map::EntityIdMapping;
+pub use crate::entity_names::EntityNaming;
+pub use crate::fake_editor::{FakeEditor, FakeEditorHandle, SoakReport};
+pub use crate::framing::Framing;
+pub use crate::interest::InterestArea;
+pub use crate::issues::Severity;
+pub use crate::panic_hook::EditorPanicHook;
+pub use crate::profiler::{Profiled, SystemProfiler};
+pub use crate::resource_chunks::ResourceChunker;
+#[cfg(feature = "screen-capture")]
+pub use crate::screen_capture::{CaptureFormat, ScreenCapture};
+pub use crate::selection::EditorSelection;
 pub use crate::serializable_entity::SerializableEntity;
+pub use crate::snapshot::Snapshot;
+pub use crate::subscriptions::SyncLevel;
+pub use crate::sync_stats::SyncStats;
+pub use crate::sync_strategy::SyncStrategy;
+pub use crate::transport::Transport;
+pub use crate::types::EditorConnection;
+pub use crate::unknown_type::UnknownTypePolicy;
 
+mod asset_paths;
+mod auth;
+mod bookmarks;
+mod budget;
 mod bundle;
+mod capabilities;
+mod chunking;
+mod client;
+mod codec;
+mod connection_status;
+mod debug_draw;
+mod depth;
+mod dynamic_components;
 mod editor_log;
+mod editor_meta;
+mod editor_uuid;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod entity_map;
+mod entity_names;
+pub mod ext;
+mod fake_editor;
+mod framing;
+mod handshake;
+mod history;
+mod interest;
+mod ipc;
+mod issues;
+mod mask;
+mod message_reader;
+mod message_schema;
+pub mod names;
+mod packet_queue;
+mod pagination;
+mod panic_hook;
+mod profiler;
+pub mod protocol;
+mod query;
+mod redact;
+mod registry;
+mod resource_chunks;
+mod scene;
+#[cfg(feature = "screen-capture")]
+mod screen_capture;
+mod selection;
 mod serializable_entity;
+mod snapshot;
+mod subscriptions;
+mod sync_stats;
+mod sync_strategy;
+mod system_graph;
 mod systems;
+mod timing;
+mod transaction;
+mod transport;
 mod types;
+mod unknown_type;
+mod validation;
+mod worker;
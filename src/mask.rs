@@ -0,0 +1,109 @@
+use serde_json::{Map, Value};
+
+/// Fields of a registered type that can't be changed by the editor.
+///
+/// Masked fields are still sent to the editor, but updates to them are ignored. Fields are named
+/// by their serialized name, and nested fields can be named with a path separated by dots, e.g.
+/// `sprite.handle`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FieldMask {
+    fields: Vec<String>,
+}
+
+impl FieldMask {
+    pub(crate) fn new(fields: Vec<String>) -> Self {
+        FieldMask { fields }
+    }
+
+    pub(crate) fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Merges an update from the editor into the current serialized value, skipping any masked
+    /// fields.
+    pub(crate) fn merge(&self, current: &Value, updated: Value) -> Value {
+        merge(current, updated, &self.fields)
+    }
+}
+
+/// Merges `updated` into the current serialized value of a component or resource, skipping the
+/// given fields.
+///
+/// Objects are merged field by field, so the update only needs to contain the fields that
+/// changed. Any other value in the update replaces the current value.
+pub(crate) fn merge(current: &Value, updated: Value, skipped: &[String]) -> Value {
+    let mut merged = current.clone();
+    merge_into(&mut merged, updated, None, skipped);
+    merged
+}
+
+fn merge_into(target: &mut Value, updated: Value, path: Option<&str>, skipped: &[String]) {
+    match (target, updated) {
+        (Value::Object(target), Value::Object(updated)) => {
+            merge_objects(target, updated, path, skipped)
+        }
+        (target, updated) => *target = updated,
+    }
+}
+
+fn merge_objects(
+    target: &mut Map<String, Value>,
+    updated: Map<String, Value>,
+    path: Option<&str>,
+    skipped: &[String],
+) {
+    for (key, value) in updated {
+        let field = match path {
+            Some(path) => format!("{}.{}", path, key),
+            None => key.clone(),
+        };
+        if skipped.contains(&field) {
+            continue;
+        }
+
+        match target.get_mut(&key) {
+            Some(current) => merge_into(current, value, Some(&field), skipped),
+            None => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_skips_masked_fields() {
+        let mask = FieldMask::new(vec!["handle".into(), "sprite.sheet".into()]);
+        let current = json!({
+            "handle": 3,
+            "tint": [1.0, 1.0, 1.0],
+            "sprite": { "sheet": 1, "index": 0 },
+        });
+
+        let merged = mask.merge(
+            &current,
+            json!({
+                "handle": 7,
+                "tint": [1.0, 0.0, 0.0],
+                "sprite": { "sheet": 2, "index": 4 },
+            }),
+        );
+        assert_eq!(
+            merged,
+            json!({
+                "handle": 3,
+                "tint": [1.0, 0.0, 0.0],
+                "sprite": { "sheet": 1, "index": 4 },
+            }),
+        );
+
+        // Fields missing from the update keep their current value.
+        let merged = mask.merge(&current, json!({ "sprite": { "index": 2 } }));
+        assert_eq!(merged["tint"], current["tint"]);
+        assert_eq!(merged["sprite"], json!({ "sheet": 1, "index": 2 }));
+    }
+}
@@ -0,0 +1,184 @@
+//! Reads packets from the editor and decodes the messages in them.
+
+use crate::chunking::Reassembler;
+use crate::framing::Framing;
+use crate::transport::Transport;
+use crate::types::IncomingMessage;
+use std::io;
+use std::str;
+
+/// The default size of the buffer that packets from the editor are read into, which is large
+/// enough for any UDP datagram.
+const DEFAULT_RECEIVE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The smallest receive buffer allowed, in bytes, which fits the small messages that the editor
+/// sends most of the time, such as heartbeats and single edits.
+pub(crate) const MIN_RECEIVE_BUFFER_SIZE: usize = 1024;
+
+/// Reads packets from the editor and decodes the messages in them, shared by [`EditorClient`]
+/// and the bundle's receiver system.
+///
+/// [`EditorClient`]: ../struct.EditorClient.html
+pub(crate) struct MessageReader {
+    // Puts messages back together from chunks, if the editor starts each packet with a chunk
+    // header.
+    reassembler: Option<Reassembler>,
+
+    // Each packet is read into this buffer. Packets that don't fit are dropped by the transport.
+    receive_buffer: Vec<u8>,
+    incoming_buffer: Vec<u8>,
+}
+
+impl MessageReader {
+    pub(crate) fn new() -> Self {
+        MessageReader {
+            reassembler: None,
+            receive_buffer: vec![0; DEFAULT_RECEIVE_BUFFER_SIZE],
+            incoming_buffer: Vec::with_capacity(1024),
+        }
+    }
+
+    /// Sets whether every packet from the editor starts with a chunk header, in which case
+    /// messages are reassembled from their chunks regardless of the order they arrive in.
+    pub(crate) fn reassemble_chunks(&mut self, enabled: bool) {
+        self.reassembler = if enabled {
+            Some(Reassembler::default())
+        } else {
+            None
+        };
+    }
+
+    /// Sets the size of the buffer that each packet from the editor is read into, raising it to
+    /// `MIN_RECEIVE_BUFFER_SIZE` if it's smaller.
+    pub(crate) fn receive_buffer_size(&mut self, size: usize) {
+        if size < MIN_RECEIVE_BUFFER_SIZE {
+            warn!(
+                "Receive buffer of {} bytes is too small, using {} bytes instead",
+                size, MIN_RECEIVE_BUFFER_SIZE
+            );
+        }
+        self.receive_buffer = vec![0; size.max(MIN_RECEIVE_BUFFER_SIZE)];
+    }
+
+    /// Reads every packet waiting in `transport`, calling `on_packet` for each one.
+    pub(crate) fn read_packets<F: FnMut()>(&mut self, transport: &mut Transport, mut on_packet: F) {
+        loop {
+            let bytes_read = match transport.recv(&mut self.receive_buffer) {
+                Ok(res) => res,
+                Err(error) => {
+                    match error.kind() {
+                        // If the read would block, it means that there was no incoming data and we
+                        // should break from the loop.
+                        io::ErrorKind::WouldBlock => break,
+
+                        // This is an "error" that happens on Windows if no editor is running to
+                        // receive the state update we just sent. The OS gives a "connection was
+                        // forcibly closed" error when no socket receives the message, but we
+                        // don't care if that happens (in fact, we use UDP specifically so that
+                        // we can broadcast messages without worrying about establishing a
+                        // connection).
+                        io::ErrorKind::ConnectionReset => continue,
+
+                        // All other error kinds should be indicative of a genuine error. For our
+                        // purposes we still want to ignore them, but we'll at least log a warning
+                        // in case it helps debug an issue.
+                        _ => {
+                            warn!("Error reading incoming: {:?}", error);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            debug!("Packet: {:?}", &self.receive_buffer[..bytes_read]);
+            on_packet();
+
+            // Add the bytes from the incoming packet to the buffer. With chunk headers, nothing is
+            // added until the packet completes a message.
+            match self.reassembler {
+                Some(ref mut reassembler) => {
+                    if let Some(message) = reassembler.push(&self.receive_buffer[..bytes_read]) {
+                        self.incoming_buffer.extend_from_slice(&message);
+                    }
+                }
+                None => self
+                    .incoming_buffer
+                    .extend_from_slice(&self.receive_buffer[..bytes_read]),
+            }
+        }
+    }
+
+    /// Decodes the messages completed by the packets read so far, leaving out messages that
+    /// can't be parsed or that are addressed to an instance other than `instance`.
+    pub(crate) fn messages(
+        &mut self,
+        framing: Framing,
+        instance: Option<&str>,
+    ) -> Vec<IncomingMessage> {
+        let mut messages = Vec::new();
+        while let Some((message_range, consumed)) = framing.decode(&self.incoming_buffer) {
+            // HACK: Manually introduce a scope here so that the compiler can tell when we're done
+            // using borrowing the message bytes from `self.incoming_buffer`. This can be removed
+            // once NLL is stable.
+            {
+                let message_bytes = &self.incoming_buffer[message_range];
+                let result = str::from_utf8(message_bytes)
+                    .ok()
+                    .filter(|message| is_addressed_to(message, instance))
+                    .and_then(|message| {
+                        serde_json::from_str::<IncomingMessage>(message)
+                            .map_err(|error| {
+                                // Data nested deeper than serde_json's recursion limit can't be
+                                // parsed at all, which would otherwise drop the edit silently.
+                                if error.to_string().contains("recursion limit exceeded") {
+                                    warn!(
+                                        "Message from the editor is nested too deeply: {}",
+                                        error
+                                    );
+                                } else {
+                                    debug!("Failed to parse message from the editor: {}", error);
+                                }
+                            })
+                            .ok()
+                    });
+                debug!("Message str: {:?}", result);
+                messages.extend(result);
+            }
+
+            // Remove the message bytes from the beginning of the incoming buffer.
+            self.incoming_buffer.drain(..consumed);
+        }
+
+        // Bytes that can't be the start of a valid message would otherwise stay at the front of
+        // the buffer forever, blocking every message after them.
+        if framing.is_oversized(&self.incoming_buffer) {
+            warn!(
+                "Discarding {} invalid bytes from the editor, check that it uses the same framing",
+                self.incoming_buffer.len()
+            );
+            self.incoming_buffer.clear();
+        }
+
+        messages
+    }
+}
+
+/// The instance that a message from the editor is addressed to.
+#[derive(Deserialize)]
+struct MessageTarget {
+    #[serde(default)]
+    instance: Option<String>,
+}
+
+/// Checks whether a message from the editor is meant for the bundle with the given instance ID.
+///
+/// Messages without an instance ID are meant for every bundle, as are messages that can't be
+/// parsed, so that the error is reported when they're parsed in full.
+fn is_addressed_to(message: &str, instance: Option<&str>) -> bool {
+    match serde_json::from_str::<MessageTarget>(message) {
+        Ok(MessageTarget {
+            instance: Some(ref target),
+        }) => instance == Some(target.as_str()),
+        _ => true,
+    }
+}
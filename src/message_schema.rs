@@ -0,0 +1,151 @@
+//! Schemas for custom messages sent to the editor.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// Custom message types registered with the bundle, along with a sample payload describing the
+/// shape of each.
+///
+/// The samples are advertised to the editor in the handshake, and in debug builds outgoing
+/// messages of a registered type are checked against their sample.
+#[derive(Debug, Default)]
+pub(crate) struct MessageSchemas {
+    samples: RwLock<BTreeMap<&'static str, Value>>,
+}
+
+impl MessageSchemas {
+    pub(crate) fn register(&self, message_type: &'static str, sample: Value) {
+        self.samples
+            .write()
+            .expect("Message schemas poisoned")
+            .insert(message_type, sample);
+    }
+
+    /// All registered message types and their samples.
+    pub(crate) fn samples(&self) -> BTreeMap<&'static str, Value> {
+        self.samples
+            .read()
+            .expect("Message schemas poisoned")
+            .clone()
+    }
+
+    pub(crate) fn is_registered(&self, message_type: &str) -> bool {
+        self.samples
+            .read()
+            .expect("Message schemas poisoned")
+            .contains_key(message_type)
+    }
+
+    /// Checks that `data` has the same shape as the sample registered for `message_type`.
+    ///
+    /// Messages of unregistered types are always considered valid.
+    pub(crate) fn validate(&self, message_type: &str, data: &Value) -> Result<(), String> {
+        let samples = self.samples.read().expect("Message schemas poisoned");
+        match samples.get(message_type) {
+            Some(sample) => matches(sample, data, "data"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that `value` has the same shape as `sample`.
+///
+/// * A null in the sample marks an optional value, and matches anything.
+/// * Objects must have the same fields as the sample, though fields that are null in the sample
+///   may be omitted.
+/// * Each element of an array must match the first element of the sample array. An empty
+///   sample array matches any array.
+/// * Other values only need to be of the same type as the sample.
+fn matches(sample: &Value, value: &Value, path: &str) -> Result<(), String> {
+    match (sample, value) {
+        (Value::Null, _) => Ok(()),
+        (Value::Bool(_), Value::Bool(_)) => Ok(()),
+        (Value::Number(_), Value::Number(_)) => Ok(()),
+        (Value::String(_), Value::String(_)) => Ok(()),
+
+        (Value::Array(samples), Value::Array(values)) => match samples.first() {
+            Some(sample) => values.iter().enumerate().try_for_each(|(index, value)| {
+                matches(sample, value, &format!("{}[{}]", path, index))
+            }),
+            None => Ok(()),
+        },
+
+        (Value::Object(samples), Value::Object(fields)) => {
+            for (key, sample) in samples {
+                let field_path = format!("{}.{}", path, key);
+                match fields.get(key) {
+                    Some(value) => matches(sample, value, &field_path)?,
+                    None if sample.is_null() => {}
+                    None => return Err(format!("{} is missing", field_path)),
+                }
+            }
+
+            match fields.keys().find(|key| !samples.contains_key(*key)) {
+                Some(key) => Err(format!("{}.{} is not in the schema", path, key)),
+                None => Ok(()),
+            }
+        }
+
+        _ => Err(format!(
+            "{} should be {}, found {}",
+            path,
+            kind(sample),
+            kind(value)
+        )),
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matching_shapes() {
+        let sample = json!({ "name": "", "position": [0.0], "parent": null });
+        assert!(matches(&sample, &json!({ "name": "a", "position": [1, 2] }), "data").is_ok());
+        assert!(matches(
+            &sample,
+            &json!({ "name": "a", "position": [], "parent": 3 }),
+            "data"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn mismatched_shapes() {
+        let sample = json!({ "name": "", "position": [0.0] });
+        assert_eq!(
+            matches(&sample, &json!({ "name": 1, "position": [] }), "data"),
+            Err("data.name should be a string, found a number".to_owned()),
+        );
+        assert_eq!(
+            matches(&sample, &json!({ "name": "a" }), "data"),
+            Err("data.position is missing".to_owned()),
+        );
+        assert_eq!(
+            matches(&sample, &json!({ "name": "a", "position": ["x"] }), "data"),
+            Err("data.position[0] should be a number, found a string".to_owned()),
+        );
+        assert_eq!(
+            matches(
+                &sample,
+                &json!({ "name": "a", "position": [], "extra": 1 }),
+                "data"
+            ),
+            Err("data.extra is not in the schema".to_owned()),
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! The names of the systems added by [`SyncEditorBundle`].
+//!
+//! Game systems can use these as dependencies in order to run before or after specific parts of
+//! the editor integration. The systems added for each registered component and resource are
+//! named after the name the type was registered with, see e.g. [`read_component`]; constants
+//! are provided for the most commonly synced types. See [`SyncEditorBundle::apply_edits_at`]
+//! for controlling where edits made in the editor are applied relative to the game's own
+//! systems.
+//!
+//! [`SyncEditorBundle`]: ../struct.SyncEditorBundle.html
+//! [`SyncEditorBundle::apply_edits_at`]: ../struct.SyncEditorBundle.html#method.apply_edits_at
+//! [`read_component`]: ./fn.read_component.html
+
+/// The system that sends the serialized game state, logs and other messages to the editor.
+pub const SENDER: &str = "editor_sender_system";
+
+/// The system that receives messages from the editor and forwards them to the systems that
+/// handle them. All systems that apply edits depend on this.
+pub const RECEIVER: &str = "editor_receiver_system";
+
+/// The system that creates and destroys entities at the editor's request. The systems that
+/// write components and resources depend on this.
+pub const ENTITY_HANDLER: &str = "entity_creator";
+
+/// The system that duplicates entities at the editor's request.
+pub const CLONE_ENTITIES: &str = "editor_clone_entities";
+
+/// The system that keeps the `EditorSelection` resource in sync with the editor.
+pub const SELECTION: &str = "editor_selection";
+
+/// The system that keeps the `Bookmarks` resource in sync with the editor.
+pub const BOOKMARKS: &str = "editor_bookmarks";
+
+/// The system that exports entities to prefab files at the editor's request.
+pub const SCENE_EXPORT: &str = "scene_export";
+
+/// The system that answers entity queries from the editor.
+pub const ENTITY_QUERY: &str = "entity_query";
+
+/// The system that finds and purges orphaned entities at the editor's request.
+pub const ORPHANS: &str = "editor_orphans";
+
+/// The system that sends the values of fields watched by the editor.
+pub const WATCH: &str = "editor_watch";
+
+/// The system that holds fields pinned by the editor at their pinned values.
+pub const PIN: &str = "editor_pin_enforcement";
+
+/// The system that sends system timings, added when profiling is enabled.
+pub const PROFILER: &str = "editor_profiler";
+
+/// The system that alerts the editor of runaway entity counts, added when configured.
+pub const WATCHDOG: &str = "editor_watchdog";
+
+/// The system that reports component storage statistics, added when enabled.
+pub const STORAGE_STATS: &str = "editor_storage_stats";
+
+/// The system that changes the time scale and fixed timestep at the editor's request.
+pub const TIME_CONTROL: &str = "editor_time_control";
+
+/// The system that applies transform gizmo drags from the editor.
+pub const GIZMO_WRITE: &str = "gizmo_write";
+
+/// The system that answers requests to pick entities in the editor's viewport, added with the
+/// `renderer` feature.
+pub const PICKING: &str = "editor_picking";
+
+/// The system that names entities, added when entity naming is enabled.
+pub const ENTITY_NAMES: &str = "editor_entity_names";
+
+/// The system that gives entities UUIDs, added when entity UUIDs are enabled.
+pub const ENTITY_UUIDS: &str = "editor_entity_uuids";
+
+/// The system that pauses the game when a breakpoint set by the editor is hit. This runs in its
+/// own stage after all edits have been applied.
+pub const BREAKPOINTS: &str = "editor_breakpoints";
+
+/// The system that maps entities to their stable IDs, added when entity ID mapping is enabled.
+pub const ENTITY_ID_MAP: &str = "editor_entity_id_map";
+
+/// The system that spawns prefabs at the editor's request, added when prefab spawning is
+/// enabled.
+pub const PREFAB_SPAWN: &str = "editor_prefab_spawn";
+
+/// The system that passes console input from the editor on to the game's console handler,
+/// added when a handler is set.
+pub const CONSOLE: &str = "editor_console";
+
+/// The system that triggers a hot-reload of assets at the editor's request.
+pub const ASSET_RELOAD: &str = "editor_asset_reload";
+
+/// The system that streams frames captured by the game to the editor, added with the
+/// `screen-capture` feature when screen capture is enabled.
+pub const SCREEN_CAPTURE: &str = "editor_screen_capture";
+
+/// The system that sends the debug shapes drawn through `EditorConnection` during the frame.
+pub const DEBUG_DRAW: &str = "editor_debug_draw";
+
+/// The system that picks the entities within the interest area, added with the `renderer`
+/// feature when an interest area is set. It runs in its own stage, before the systems that read
+/// components.
+pub const INTEREST: &str = "editor_interest";
+
+/// The system that sends the issues reported through `EditorConnection` during the frame.
+pub const ISSUES: &str = "editor_issues";
+
+/// The system that sends the state of the game's audio, added when audio is synced.
+pub const AUDIO: &str = "editor_audio";
+
+/// The system that changes the volume of the game's audio at the editor's request, added when
+/// audio is synced.
+pub const AUDIO_VOLUME: &str = "editor_audio_volume";
+
+/// The system that sends the state of specs-physics bodies and colliders, added with the
+/// `physics` feature when physics is synced.
+pub const PHYSICS: &str = "editor_physics";
+
+/// The system that applies impulses and teleports requested by the editor, added with the
+/// `physics` feature when physics is synced.
+pub const PHYSICS_CONTROL: &str = "editor_physics_control";
+
+/// The system that sends the components in the `DynamicComponentStore`, added when dynamic
+/// components are synced.
+pub const DYNAMIC_COMPONENTS: &str = "editor_dynamic_components";
+
+/// The system that applies edits to the components in the `DynamicComponentStore`, added when
+/// dynamic components are synced. This depends on [`ENTITY_HANDLER`].
+///
+/// [`ENTITY_HANDLER`]: ./constant.ENTITY_HANDLER.html
+pub const WRITE_DYNAMIC_COMPONENTS: &str = "editor_write_dynamic_components";
+
+/// The system that records the state of registered types and rewinds to it at the editor's
+/// request, added when history is enabled.
+pub const HISTORY: &str = "editor_history";
+
+/// The system that serializes `Transform` components, when registered as `"Transform"`.
+pub const READ_TRANSFORM: &str = "editor_read_component:Transform";
+
+/// The system that applies edits to `Transform` components, when registered as `"Transform"`.
+pub const WRITE_TRANSFORM: &str = "editor_write_component:Transform";
+
+/// The system that serializes `Named` components, when registered as `"Named"`.
+pub const READ_NAMED: &str = "editor_read_component:Named";
+
+/// The system that applies edits to `Named` components, when registered as `"Named"`.
+pub const WRITE_NAMED: &str = "editor_write_component:Named";
+
+/// Returns the name of the system that serializes the component registered as `name`.
+pub fn read_component(name: &str) -> String {
+    format!("editor_read_component:{}", name)
+}
+
+/// Returns the name of the system that applies edits to the component registered as `name`.
+///
+/// This is only added for components registered with `sync_component`, and depends on
+/// [`ENTITY_HANDLER`](./constant.ENTITY_HANDLER.html).
+pub fn write_component(name: &str) -> String {
+    format!("editor_write_component:{}", name)
+}
+
+/// Returns the name of the system that serializes the resource registered as `name`.
+pub fn read_resource(name: &str) -> String {
+    format!("editor_read_resource:{}", name)
+}
+
+/// Returns the name of the system that applies edits to the resource registered as `name`.
+///
+/// This is only added for resources registered with `sync_resource`, and depends on
+/// [`ENTITY_HANDLER`](./constant.ENTITY_HANDLER.html).
+pub fn write_resource(name: &str) -> String {
+    format!("editor_write_resource:{}", name)
+}
+
+/// Returns the name of the system that lists the assets of type `name`, which is the asset
+/// type's `Asset::NAME`.
+///
+/// This is only added for asset types registered with `sync_assets`.
+pub fn read_assets(name: &str) -> String {
+    format!("editor_read_assets:{}", name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn constants_match_generated_names() {
+        assert_eq!(read_component("Transform"), READ_TRANSFORM);
+        assert_eq!(write_component("Transform"), WRITE_TRANSFORM);
+        assert_eq!(read_component("Named"), READ_NAMED);
+        assert_eq!(write_component("Named"), WRITE_NAMED);
+    }
+}
@@ -0,0 +1,561 @@
+//! The send pipeline shared by the bundle's sender system and [`EditorClient`], which frames
+//! messages, splits them into packets, and sends the packets with retries and rate limiting.
+//!
+//! [`EditorClient`]: ../struct.EditorClient.html
+
+use crate::chunking::{self, MessageIds, CHUNK_HEADER_LEN};
+use crate::codec::{Codec, EncodeError};
+use crate::sync_stats::SyncStats;
+use crate::transport::{
+    is_no_editor, is_transient, Transport, INITIAL_RETRY_DELAY_MS, MAX_SEND_RETRIES,
+};
+use crate::worker::{OutgoingPacket, SendEvent, SendThread};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// The default maximum packet size for an editor on the same machine, where packets don't
+/// leave the loopback interface and aren't limited by the MTU of a real network.
+const LOCAL_MAX_PACKET_SIZE: usize = 32 * 1024;
+
+/// The default maximum packet size for an editor on another machine, which stays below the MTU
+/// of almost every network once the IP and UDP headers are added, so that packets are never
+/// fragmented.
+const REMOTE_MAX_PACKET_SIZE: usize = 1200;
+
+/// How often errors that caused packets to be dropped are logged, in seconds. Errors that occur
+/// in between are counted and included in the next warning, so that a broken connection doesn't
+/// flood the log.
+const SEND_ERROR_REPORT_INTERVAL_SECS: u64 = 5;
+
+/// Limits how many bytes are sent to the editor each second, in order to test how the game and
+/// the editor cope with a low-bandwidth connection.
+///
+/// Packets that go over the limit stay queued until enough time has passed, the same way they
+/// would on a slow network.
+struct RateLimit {
+    bytes_per_second: u64,
+
+    // The number of bytes that can be sent right away, which builds up over time to at most one
+    // second's worth. It goes negative when a packet larger than the allowance is sent, so that
+    // packets larger than the allowance aren't held back forever.
+    allowance: f64,
+    updated_at: Instant,
+}
+
+impl RateLimit {
+    fn new(bytes_per_second: u64, now: Instant) -> Self {
+        RateLimit {
+            bytes_per_second,
+            allowance: bytes_per_second as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = duration_to_secs(now.duration_since(self.updated_at));
+        let limit = self.bytes_per_second as f64;
+        self.allowance = (self.allowance + elapsed * limit).min(limit);
+        self.updated_at = now;
+    }
+
+    /// Takes `bytes` out of the allowance, returning `false` if nothing can be sent right now.
+    fn spend(&mut self, bytes: usize) -> bool {
+        if self.allowance <= 0.0 {
+            return false;
+        }
+
+        self.allowance -= bytes as f64;
+        true
+    }
+}
+
+/// A packet waiting to be sent.
+struct Packet {
+    bytes: Vec<u8>,
+
+    /// Whether this is the last packet of its message.
+    last: bool,
+}
+
+/// Messages waiting to be sent to the editor, split into packets.
+///
+/// Messages are framed with the transport's framing, and split into packets no larger than the
+/// maximum packet size, each starting with a chunk header if chunk headers are enabled. Packets
+/// are always sent in the order they were queued, since the editor reassembles messages by
+/// concatenating packets.
+pub(crate) struct PacketQueue {
+    transport: Transport,
+    max_packet_size: usize,
+
+    // The IDs put in the chunk headers of messages, if chunk headers are enabled.
+    message_ids: Option<MessageIds>,
+
+    packets: VecDeque<Packet>,
+    rate_limit: Option<RateLimit>,
+
+    // The number of consecutive failed attempts to send the packet at the front of the queue,
+    // and when the next attempt can be made.
+    send_retries: u32,
+    retry_at: Option<Instant>,
+
+    // Whether a packet of the message at the front of the queue was dropped, in which case the
+    // editor won't be able to parse the message.
+    message_damaged: bool,
+
+    // Errors that caused packets to be dropped since they were last logged.
+    send_errors: SendErrors,
+
+    // The thread that packets are handed over to, if packets are sent in the background.
+    send_thread: Option<SendThread>,
+
+    scratch_message: Vec<u8>,
+    scratch_bytes: Vec<u8>,
+}
+
+impl PacketQueue {
+    /// Creates a queue that sends packets over `transport`, picking the maximum packet size
+    /// from the editor's address unless one is given.
+    pub(crate) fn new(
+        transport: Transport,
+        max_packet_size: Option<usize>,
+        chunk_headers: bool,
+    ) -> Self {
+        let max_packet_size = self::max_packet_size(max_packet_size, &transport, chunk_headers);
+        PacketQueue {
+            transport,
+            max_packet_size,
+            message_ids: if chunk_headers {
+                Some(MessageIds::default())
+            } else {
+                None
+            },
+            packets: VecDeque::new(),
+            rate_limit: None,
+            send_retries: 0,
+            retry_at: None,
+            message_damaged: false,
+            send_errors: SendErrors::new(),
+            send_thread: None,
+            scratch_message: Vec::with_capacity(max_packet_size),
+            scratch_bytes: Vec::with_capacity(max_packet_size),
+        }
+    }
+
+    /// Changes the maximum packet size and whether packets start with a chunk header, for
+    /// messages queued from now on.
+    pub(crate) fn configure(&mut self, max_packet_size: Option<usize>, chunk_headers: bool) {
+        self.max_packet_size =
+            self::max_packet_size(max_packet_size, &self.transport, chunk_headers);
+        if chunk_headers != self.message_ids.is_some() {
+            self.message_ids = if chunk_headers {
+                Some(MessageIds::default())
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Hands packets over to a background thread to be sent from now on, falling back to
+    /// sending them from the caller if the thread can't be started.
+    pub(crate) fn send_in_background(&mut self) {
+        match self.transport.try_clone().and_then(SendThread::new) {
+            Ok(send_thread) => self.send_thread = Some(send_thread),
+            Err(error) => error!(
+                "Failed to start sending thread, sending from the main thread instead: {}",
+                error
+            ),
+        }
+    }
+
+    /// Limits how many bytes are sent each second, or lifts the limit if `None`.
+    pub(crate) fn set_rate_limit(&mut self, bytes_per_second: Option<u64>, now: Instant) {
+        self.rate_limit = bytes_per_second.map(|limit| RateLimit::new(limit, now));
+    }
+
+    /// The send rate limit, if one is set.
+    pub(crate) fn rate_limit(&self) -> Option<u64> {
+        self.rate_limit.as_ref().map(|limit| limit.bytes_per_second)
+    }
+
+    pub(crate) fn max_packet_size(&self) -> usize {
+        self.max_packet_size
+    }
+
+    pub(crate) fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    /// The transport that packets are sent over, e.g. for receiving from the editor over the
+    /// same connection.
+    pub(crate) fn transport_mut(&mut self) -> &mut Transport {
+        &mut self.transport
+    }
+
+    /// The IDs put in chunk headers, if chunk headers are enabled.
+    pub(crate) fn message_ids(&self) -> Option<&MessageIds> {
+        self.message_ids.as_ref()
+    }
+
+    /// The number of packets waiting to be sent.
+    pub(crate) fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    /// Serializes `message` with `codec` and queues it to be sent.
+    pub(crate) fn queue_encoded<T: Serialize>(
+        &mut self,
+        codec: Codec,
+        message: &T,
+    ) -> Result<(), EncodeError> {
+        let result = codec.encode(message, &mut self.scratch_message);
+        if result.is_ok() {
+            self.queue_scratch_message();
+        }
+        self.scratch_message.clear();
+        result
+    }
+
+    /// Frames the message in `scratch_message` and queues it to be sent, breaking it up into
+    /// multiple packets if the message is too large.
+    fn queue_scratch_message(&mut self) {
+        // Frame the message so that the editor can tell where it ends once it's been reassembled.
+        self.transport
+            .framing()
+            .encode(&self.scratch_message, &mut self.scratch_bytes);
+
+        // With chunk headers, every packet starts with a header so that the editor can
+        // reassemble the message even if packets are reordered.
+        let id = self.message_ids.as_ref().map(MessageIds::next);
+        match chunking::split(&self.scratch_bytes, self.max_packet_size, id) {
+            Some(packets) => {
+                let count = packets.len();
+                for (index, bytes) in packets.into_iter().enumerate() {
+                    self.packets.push_back(Packet {
+                        bytes,
+                        last: index + 1 == count,
+                    });
+                }
+            }
+
+            None => warn!(
+                "Dropping message of {} bytes, which is too large to send in {} byte packets",
+                self.scratch_bytes.len(),
+                self.max_packet_size
+            ),
+        }
+
+        self.scratch_bytes.clear();
+    }
+
+    /// Sends up to `count` queued packets, recording the outcome in `stats` and returning the
+    /// number of messages that couldn't be sent in full.
+    ///
+    /// If sending fails with an error that's likely to be temporary, such as the OS send buffer
+    /// being full, the remaining packets stay queued and sending is retried on a later call,
+    /// waiting twice as long after each failed retry. Once `MAX_SEND_RETRIES` retries have failed
+    /// all queued packets are dropped, so that they don't keep piling up while the editor can't
+    /// keep up. Any other error only drops the packet that failed. Errors are logged at most
+    /// once every `SEND_ERROR_REPORT_INTERVAL_SECS` seconds, except for errors caused by there
+    /// being no editor listening, which aren't logged at all. Packets over the send rate limit,
+    /// if one is set, stay queued until a later call.
+    ///
+    /// If packets are sent in the background, they're handed over to the sending thread instead,
+    /// which retries and drops them the same way, and the outcome of the packets it handled since
+    /// the last call is recorded.
+    pub(crate) fn send(&mut self, now: Instant, count: usize, stats: &mut SyncStats) -> u64 {
+        let mut messages_dropped = 0;
+        if self.send_thread.is_some() {
+            self.hand_over(now, count, stats, &mut messages_dropped);
+        } else {
+            self.send_now(now, count, stats, &mut messages_dropped);
+        }
+        stats.packets_queued = self.packets.len();
+        messages_dropped
+    }
+
+    /// Sends up to `count` queued packets from the calling thread.
+    fn send_now(
+        &mut self,
+        now: Instant,
+        count: usize,
+        stats: &mut SyncStats,
+        messages_dropped: &mut u64,
+    ) {
+        if let Some(retry_at) = self.retry_at {
+            if now < retry_at {
+                return;
+            }
+        }
+
+        if let Some(ref mut limit) = self.rate_limit {
+            limit.refill(now);
+        }
+
+        for _ in 0..count {
+            let result = match self.packets.front() {
+                Some(packet) => {
+                    if let Some(ref mut limit) = self.rate_limit {
+                        if !limit.spend(packet.bytes.len()) {
+                            break;
+                        }
+                    }
+                    self.transport.send(&packet.bytes)
+                }
+                None => break,
+            };
+
+            match result {
+                Ok(()) => {
+                    let bytes = self.pop(false, messages_dropped);
+                    stats.packets_sent += 1;
+                    stats.bytes_sent += bytes as u64;
+                    self.send_retries = 0;
+                    self.retry_at = None;
+                }
+
+                Err(ref error) if is_transient(error.kind()) => {
+                    if self.send_retries < MAX_SEND_RETRIES {
+                        self.retry_at = Some(
+                            now + Duration::from_millis(
+                                INITIAL_RETRY_DELAY_MS << self.send_retries,
+                            ),
+                        );
+                        self.send_retries += 1;
+                        stats.send_retries += 1;
+                    } else {
+                        // NOTE: The editor may already have received the first packets of the
+                        // message being sent, so it will fail to parse that message. With chunk
+                        // headers it discards the incomplete message, and with a delimiting
+                        // framing it recovers once it receives the delimiter at the end of the
+                        // next message.
+                        let dropped = self.packets.len() as u64;
+                        self.send_errors.record(
+                            io::Error::new(
+                                error.kind(),
+                                format!("{} (after {} failed retries)", error, MAX_SEND_RETRIES),
+                            ),
+                            dropped,
+                        );
+                        stats.packets_dropped += dropped;
+                        while !self.packets.is_empty() {
+                            self.pop(true, messages_dropped);
+                        }
+                        self.send_retries = 0;
+                        self.retry_at = None;
+                    }
+                    break;
+                }
+
+                // There's nothing to report when the game runs without the editor, so the packet
+                // is dropped quietly and not counted as a dropped message.
+                Err(ref error) if is_no_editor(error.kind()) => {
+                    self.pop(false, messages_dropped);
+                    stats.packets_dropped += 1;
+                }
+
+                Err(error) => {
+                    self.send_errors.record(error, 1);
+                    self.pop(true, messages_dropped);
+                    stats.packets_dropped += 1;
+                }
+            }
+        }
+
+        self.send_errors.report(now);
+    }
+
+    /// Hands up to `count` queued packets over to the sending thread, recording what happened to
+    /// the packets it handled since the last call.
+    ///
+    /// If the thread has exited, packets are sent from the calling thread from then on.
+    fn hand_over(
+        &mut self,
+        now: Instant,
+        count: usize,
+        stats: &mut SyncStats,
+        messages_dropped: &mut u64,
+    ) {
+        if let Some(ref send_thread) = self.send_thread {
+            for event in send_thread.events() {
+                match event {
+                    SendEvent::Sent(bytes) => {
+                        stats.packets_sent += 1;
+                        stats.bytes_sent += bytes as u64;
+                    }
+                    SendEvent::Retried => stats.send_retries += 1,
+                    SendEvent::Dropped(error) => {
+                        self.send_errors.record(error, 1);
+                        stats.packets_dropped += 1;
+                    }
+                    SendEvent::NoEditor => stats.packets_dropped += 1,
+                    SendEvent::MessageDropped => *messages_dropped += 1,
+                }
+            }
+        }
+        self.send_errors.report(now);
+
+        if let Some(ref mut limit) = self.rate_limit {
+            limit.refill(now);
+        }
+
+        for _ in 0..count {
+            let packet = match self.packets.pop_front() {
+                Some(packet) => packet,
+                None => break,
+            };
+            if let Some(ref mut limit) = self.rate_limit {
+                if !limit.spend(packet.bytes.len()) {
+                    self.packets.push_front(packet);
+                    break;
+                }
+            }
+
+            let sent = match self.send_thread {
+                Some(ref send_thread) => send_thread.send(OutgoingPacket {
+                    bytes: packet.bytes,
+                    last: packet.last,
+                }),
+                None => break,
+            };
+            if let Err(packet) = sent {
+                error!("Sending thread has exited, sending from the main thread instead");
+                self.send_thread = None;
+                self.packets.push_front(Packet {
+                    bytes: packet.bytes,
+                    last: packet.last,
+                });
+            }
+        }
+    }
+
+    /// Removes the packet at the front of the queue, returning its size in bytes.
+    ///
+    /// A message is counted as dropped once its last packet has been removed, if any of its
+    /// packets were dropped.
+    fn pop(&mut self, dropped: bool, messages_dropped: &mut u64) -> usize {
+        let packet = match self.packets.pop_front() {
+            Some(packet) => packet,
+            None => return 0,
+        };
+
+        self.message_damaged |= dropped;
+        if packet.last && self.message_damaged {
+            self.message_damaged = false;
+            *messages_dropped += 1;
+        }
+
+        packet.bytes.len()
+    }
+}
+
+/// Errors that caused packets to be dropped, which are logged at most once every
+/// `SEND_ERROR_REPORT_INTERVAL_SECS` seconds.
+///
+/// NOTE: Errors caused by there being no editor listening aren't recorded, since the game is
+/// expected to run without the editor.
+struct SendErrors {
+    count: u64,
+    last: Option<io::Error>,
+    next_report: Instant,
+}
+
+impl SendErrors {
+    fn new() -> Self {
+        SendErrors {
+            count: 0,
+            last: None,
+            next_report: Instant::now(),
+        }
+    }
+
+    /// Records that `packets` packets were dropped because of `error`.
+    fn record(&mut self, error: io::Error, packets: u64) {
+        self.count += packets;
+        self.last = Some(error);
+    }
+
+    /// Logs the errors recorded since the last report, if it's time to do so.
+    fn report(&mut self, now: Instant) {
+        if now < self.next_report {
+            return;
+        }
+
+        if let Some(error) = self.last.take() {
+            warn!(
+                "Failed to send {} packets to the editor, most recently because of: {}",
+                self.count, error
+            );
+            self.count = 0;
+            self.next_report = now + Duration::from_secs(SEND_ERROR_REPORT_INTERVAL_SECS);
+        }
+    }
+}
+
+/// Picks the maximum size of the packets sent over `transport`, leaving room for the
+/// transport's own overhead.
+///
+/// NOTE: Packets need room for at least one byte of the message after the chunk header.
+fn max_packet_size(
+    max_packet_size: Option<usize>,
+    transport: &Transport,
+    chunk_headers: bool,
+) -> usize {
+    let max_packet_size = max_packet_size
+        .unwrap_or_else(|| default_max_packet_size(transport.editor_address()))
+        .saturating_sub(transport.packet_overhead());
+    if chunk_headers {
+        max_packet_size.max(CHUNK_HEADER_LEN + 1)
+    } else {
+        max_packet_size.max(1)
+    }
+}
+
+/// Picks the maximum packet size for an editor at `editor_address`, which is `None` for
+/// connections that aren't made over the network.
+fn default_max_packet_size(editor_address: Option<SocketAddr>) -> usize {
+    match editor_address {
+        Some(address) if !address.ip().is_loopback() => REMOTE_MAX_PACKET_SIZE,
+        _ => LOCAL_MAX_PACKET_SIZE,
+    }
+}
+
+fn duration_to_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn remote_editors_get_small_packets() {
+        assert_eq!(default_max_packet_size(None), LOCAL_MAX_PACKET_SIZE);
+        assert_eq!(
+            default_max_packet_size(Some(([127, 0, 0, 1], 8000).into())),
+            LOCAL_MAX_PACKET_SIZE
+        );
+        assert_eq!(
+            default_max_packet_size(Some(([192, 168, 1, 20], 8000).into())),
+            REMOTE_MAX_PACKET_SIZE
+        );
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        assert!(is_transient(io::ErrorKind::WouldBlock));
+        assert!(!is_transient(io::ErrorKind::ConnectionRefused));
+        assert!(!is_transient(io::ErrorKind::InvalidInput));
+        assert!(!is_transient(io::ErrorKind::PermissionDenied));
+    }
+
+    #[test]
+    fn missing_editor_is_not_an_error() {
+        assert!(is_no_editor(io::ErrorKind::ConnectionRefused));
+        assert!(is_no_editor(io::ErrorKind::ConnectionReset));
+        assert!(!is_no_editor(io::ErrorKind::WouldBlock));
+        assert!(!is_no_editor(io::ErrorKind::PermissionDenied));
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Where the components sent for a type are within all of the type's components, when component
+/// data is paginated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub(crate) struct ComponentPage {
+    /// The index of the page, starting from 0.
+    pub index: usize,
+
+    /// The number of components per page.
+    pub size: usize,
+
+    /// The number of components of the type across all pages.
+    pub total: usize,
+}
+
+/// The page of each component type that's sent to the editor, shared between the systems that
+/// read components and the receiver, which changes pages at the editor's request.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ComponentPages {
+    state: Arc<RwLock<PagesState>>,
+}
+
+#[derive(Debug, Default)]
+struct PagesState {
+    // `None` unless pagination is enabled, meaning that all components are sent.
+    page_size: Option<usize>,
+
+    // The page requested for each component type. Types that aren't listed show the first page.
+    pages: HashMap<String, usize>,
+
+    // The number of times a requested page has changed.
+    changes: u64,
+}
+
+impl ComponentPages {
+    pub(crate) fn set_page_size(&self, page_size: usize) {
+        let mut state = self.state.write().expect("Component pages poisoned");
+        state.page_size = Some(page_size.max(1));
+        state.changes += 1;
+    }
+
+    /// Requests page `index` of the component type registered as `name`.
+    pub(crate) fn set_page(&self, name: String, index: usize) {
+        let mut state = self.state.write().expect("Component pages poisoned");
+        if state.pages.insert(name, index) != Some(index) {
+            state.changes += 1;
+        }
+    }
+
+    /// The number of times the requested pages have changed, which tells the systems that cache
+    /// serialized components when to serialize them again.
+    pub(crate) fn changes(&self) -> u64 {
+        self.state.read().expect("Component pages poisoned").changes
+    }
+
+    /// Picks the components of type `name` that are sent to the editor out of all of them,
+    /// along with the page they're on if pagination is enabled.
+    ///
+    /// A page past the last one shows the last page instead, so that the editor isn't left with
+    /// an empty page when components are removed.
+    pub(crate) fn paginate<'a, T>(
+        &self,
+        name: &str,
+        data: &'a [T],
+    ) -> (&'a [T], Option<ComponentPage>) {
+        let state = self.state.read().expect("Component pages poisoned");
+        let size = match state.page_size {
+            Some(size) => size,
+            None => return (data, None),
+        };
+
+        let last = data.len().saturating_sub(1) / size;
+        let index = state.pages.get(name).cloned().unwrap_or(0).min(last);
+        let start = index * size;
+        let end = (start + size).min(data.len());
+        let page = ComponentPage {
+            index,
+            size,
+            total: data.len(),
+        };
+        (&data[start..end], Some(page))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all_components_are_sent_by_default() {
+        let pages = ComponentPages::default();
+        pages.set_page("Position".into(), 3);
+        assert_eq!(
+            pages.paginate("Position", &[1, 2, 3]),
+            (&[1, 2, 3][..], None)
+        );
+    }
+
+    #[test]
+    fn pages_are_clamped_to_the_last_page() {
+        let pages = ComponentPages::default();
+        pages.set_page_size(2);
+        let data = [1, 2, 3, 4, 5];
+        let page = |index, size, total| Some(ComponentPage { index, size, total });
+        assert_eq!(
+            pages.paginate("Position", &data),
+            (&data[..2], page(0, 2, 5))
+        );
+
+        let changes = pages.changes();
+        pages.set_page("Position".into(), 1);
+        assert_eq!(
+            pages.paginate("Position", &data),
+            (&data[2..4], page(1, 2, 5))
+        );
+        assert!(pages.changes() > changes);
+
+        pages.set_page("Position".into(), 7);
+        assert_eq!(
+            pages.paginate("Position", &data),
+            (&data[4..], page(2, 2, 5))
+        );
+        assert_eq!(
+            pages.paginate("Position", &[0; 0]),
+            (&[][..], page(0, 2, 0))
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use backtrace::Backtrace;
+use crate::chunking::{self, MessageIds};
+use crate::protocol::{OutgoingMessage, Packet, PacketData};
+use crate::transport::Transport;
+use crate::types::EditorConnection;
+use serde_json::Value;
+use std::panic::{self, PanicInfo};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for each packet of a crash report to be sent before giving up.
+const SEND_TIMEOUT_MS: u64 = 100;
+
+/// Sends a `"panic"` message to the editor whenever the game panics, so that the editor can show
+/// why the game died instead of just losing the connection.
+///
+/// The report includes the panic message, where the panic happened, the name of the thread that
+/// panicked, and a backtrace. Since the systems that normally send data to the editor may never
+/// run again after a panic, the report is sent straight away from the panicking thread, waiting
+/// briefly for it to go out. The panic hook that was set before is still called afterwards, so
+/// panics are printed as usual.
+///
+/// ```no_run
+/// # extern crate amethyst_editor_sync;
+/// # use amethyst_editor_sync::*;
+/// let bundle = SyncEditorBundle::default();
+/// EditorPanicHook::install(bundle.connection());
+/// ```
+pub struct EditorPanicHook {
+    connection: EditorConnection,
+}
+
+impl EditorPanicHook {
+    /// Installs the hook, reporting panics to the editor over `connection`.
+    ///
+    /// Reports can only be sent once the bundle has been built, and only while the editor is
+    /// connected.
+    pub fn install(connection: EditorConnection) {
+        let hook = EditorPanicHook { connection };
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            hook.report(info);
+            previous(info);
+        }));
+    }
+
+    fn report(&self, info: &PanicInfo) {
+        if !self.connection.presence().is_active() {
+            return;
+        }
+
+        let payload = info.payload();
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => (*message).to_owned(),
+            None => match payload.downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "Box<Any>".to_owned(),
+            },
+        };
+
+        let report = PanicReport {
+            message,
+            location: info.location().map(|location| PanicLocation {
+                file: location.file().to_owned(),
+                line: location.line(),
+                column: location.column(),
+            }),
+            thread: thread::current().name().map(str::to_owned),
+            backtrace: format!("{:?}", Backtrace::new()),
+        };
+        self.connection.send_crash_report(report);
+    }
+}
+
+/// Sent to the editor as a `"panic"` message.
+#[derive(Debug, Serialize)]
+pub(crate) struct PanicReport {
+    message: String,
+    location: Option<PanicLocation>,
+    thread: Option<String>,
+    backtrace: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PanicLocation {
+    file: String,
+    line: u32,
+    column: u32,
+}
+
+/// Sends messages to the editor directly over its own handle to the transport, bypassing the
+/// sender system.
+pub(crate) struct CrashReporter {
+    transport: Transport,
+    max_packet_size: usize,
+
+    // The source of chunk header IDs shared with the sender system, if chunk headers are enabled.
+    message_ids: Option<MessageIds>,
+
+    // The ID included in every message, if one is set.
+    instance_id: Option<String>,
+}
+
+impl CrashReporter {
+    pub(crate) fn new(
+        transport: Transport,
+        max_packet_size: usize,
+        message_ids: Option<MessageIds>,
+        instance_id: Option<String>,
+    ) -> Self {
+        CrashReporter {
+            transport,
+            max_packet_size,
+            message_ids,
+            instance_id,
+        }
+    }
+
+    /// Sends `report` as a `"panic"` message, sent on `frame`.
+    ///
+    /// NOTE: If the sender system was in the middle of sending a message over IPC, the editor
+    /// may not be able to parse either message. This is a best effort, since the game is going
+    /// down anyway.
+    pub(crate) fn send(&mut self, frame: u64, report: PanicReport) {
+        let report = match serde_json::to_value(report) {
+            Ok(report) => report,
+            Err(error) => {
+                error!("Failed to serialize crash report: {}", error);
+                return;
+            }
+        };
+
+        let packet: Packet = Packet {
+            ty: "message".to_owned(),
+            instance: self.instance_id.clone().map(Value::String),
+            frame,
+            absolute_time: None,
+            data: PacketData {
+                messages: vec![OutgoingMessage {
+                    ty: "panic".to_owned(),
+                    data: report,
+                }],
+                ..PacketData::default()
+            },
+        };
+        let serialized = serde_json::to_vec(&packet).expect("Failed to serialize crash report");
+        let mut bytes = Vec::new();
+        self.transport.framing().encode(&serialized, &mut bytes);
+
+        let id = self.message_ids.as_ref().map(MessageIds::next);
+        let packets = match chunking::split(&bytes, self.max_packet_size, id) {
+            Some(packets) => packets,
+            None => return,
+        };
+        for packet in packets {
+            if let Err(error) = self
+                .transport
+                .send_blocking(&packet, Duration::from_millis(SEND_TIMEOUT_MS))
+            {
+                error!("Failed to send crash report to the editor: {}", error);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use amethyst::ecs::{Resources, RunningTime, System};
+use crossbeam_channel::Sender;
+use std::time::{Duration, Instant};
+
+/// Measures how long the game's systems take to run, and streams the timings to the editor.
+///
+/// Created with [`SyncEditorBundle::profiler`]. Systems are only profiled if they're wrapped
+/// with [`wrap`] when they're added to the dispatcher. The bundle then sends the timings of
+/// all profiled systems to the editor in a `"profiler"` message once per frame, so that the
+/// editor can show where frame time goes.
+///
+/// ```
+/// # extern crate amethyst;
+/// # extern crate amethyst_editor_sync;
+/// # use amethyst::ecs::System;
+/// # use amethyst::prelude::*;
+/// # use amethyst_editor_sync::SyncEditorBundle;
+/// # struct MovementSystem;
+/// # impl<'a> System<'a> for MovementSystem {
+/// #     type SystemData = ();
+/// #     fn run(&mut self, _: ()) {}
+/// # }
+/// # fn main() -> Result<(), amethyst::Error> {
+/// let mut editor_bundle = SyncEditorBundle::new();
+/// let profiler = editor_bundle.profiler();
+///
+/// let game_data = GameDataBuilder::default()
+///     .with(profiler.wrap("movement", MovementSystem), "movement", &[])
+///     .with_bundle(editor_bundle)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`SyncEditorBundle::profiler`]: ./struct.SyncEditorBundle.html#method.profiler
+/// [`wrap`]: #method.wrap
+#[derive(Clone)]
+pub struct SystemProfiler {
+    sender: Sender<SystemTiming>,
+}
+
+impl SystemProfiler {
+    pub(crate) fn new(sender: Sender<SystemTiming>) -> Self {
+        SystemProfiler { sender }
+    }
+
+    /// Wraps `system` so that its run time is reported to the editor under `name`.
+    pub fn wrap<S>(&self, name: &'static str, system: S) -> Profiled<S> {
+        Profiled {
+            name,
+            system,
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+/// A system wrapped by a [`SystemProfiler`] to measure how long it takes to run.
+///
+/// [`SystemProfiler`]: ./struct.SystemProfiler.html
+pub struct Profiled<S> {
+    name: &'static str,
+    system: S,
+    sender: Sender<SystemTiming>,
+}
+
+impl<'a, S> System<'a> for Profiled<S>
+where
+    S: System<'a>,
+{
+    type SystemData = S::SystemData;
+
+    fn run(&mut self, data: Self::SystemData) {
+        let start = Instant::now();
+        self.system.run(data);
+        let duration = start.elapsed();
+
+        // NOTE: The profiler system may have been removed along with the rest of the bundle, in
+        // which case there's nothing to report the timing to.
+        let _ = self.sender.send(SystemTiming {
+            name: self.name,
+            start,
+            duration,
+        });
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.system.setup(res);
+    }
+}
+
+/// A single run of a profiled system.
+pub(crate) struct SystemTiming {
+    pub name: &'static str,
+    pub start: Instant,
+    pub duration: Duration,
+}
@@ -0,0 +1,913 @@
+//! The messages exchanged between the game and the editor.
+//!
+//! Each packet sent by the game is a JSON object (see [`Packet`]) framed as set by the
+//! transport, by default preceded by its length as a 4-byte big-endian integer. Packets carry
+//! the latest state snapshot along with a list of [`OutgoingMessage`]s, e.g. logs and
+//! acknowledgements of edits. The editor sends [`IncomingMessage`]s back, framed the same way
+//! and, over UDP, with a chunk header at the start of each datagram.
+//!
+//! The types in this module can be used by editors written in Rust to read and write messages
+//! directly. Editors written in other languages can generate bindings from the JSON Schema
+//! returned by [`schema`].
+//!
+//! [`Packet`]: ./struct.Packet.html
+//! [`OutgoingMessage`]: ./struct.OutgoingMessage.html
+//! [`IncomingMessage`]: ./enum.IncomingMessage.html
+//! [`schema`]: ./fn.schema.html
+
+use crate::editor_uuid::EditorUuid;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+pub use crate::query::{CompareOp, FieldCondition, JsonPathExpr};
+pub use crate::serializable_entity::{DeserializableEntity, IncomingEntity};
+pub use crate::timing::TimingInfo;
+pub use crate::types::{EditAck, EditStatus, IncomingMessage};
+
+/// How the parts of a [`PacketData`] are represented.
+///
+/// Editors read packets with [`Decoded`], the default, which uses the types in this module. The
+/// game itself builds packets out of data that has already been serialized, which is embedded
+/// as raw JSON so that it doesn't need to be parsed again.
+///
+/// [`PacketData`]: ./struct.PacketData.html
+/// [`Decoded`]: ./enum.Decoded.html
+pub trait PacketContents {
+    type Entity: Debug + Clone + PartialEq + Serialize;
+    type Component: Debug + Clone + PartialEq + Serialize;
+    type Resource: Debug + Clone + PartialEq + Serialize;
+    type Message: Debug + Clone + PartialEq + Serialize;
+
+    /// The stats and other sections of the snapshot.
+    type Value: Debug + Clone + PartialEq + Serialize;
+}
+
+/// Packet contents made up of the types in this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoded {}
+
+impl PacketContents for Decoded {
+    type Entity = EntityEntry;
+    type Component = ComponentData;
+    type Resource = ResourceData;
+    type Message = OutgoingMessage;
+    type Value = Value;
+}
+
+/// A packet sent by the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "PacketData<C>: Deserialize<'de>"))]
+pub struct Packet<C: PacketContents = Decoded> {
+    /// Always `"message"`.
+    #[serde(rename = "type")]
+    pub ty: String,
+
+    /// The ID of the game instance, if one was set with `SyncEditorBundle::instance_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<Value>,
+
+    /// The frame the packet was sent on.
+    pub frame: u64,
+
+    /// The time since the game started in seconds, when the packet was sent. Only missing from
+    /// crash reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute_time: Option<f64>,
+
+    pub data: PacketData<C>,
+}
+
+/// The contents of a [`Packet`].
+///
+/// Apart from `messages`, the fields are only included in packets carrying a state snapshot.
+///
+/// [`Packet`]: ./struct.Packet.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "",
+    deserialize = "C::Entity: DeserializeOwned, C::Component: DeserializeOwned, \
+                   C::Resource: DeserializeOwned, C::Message: DeserializeOwned, \
+                   C::Value: DeserializeOwned"
+))]
+pub struct PacketData<C: PacketContents = Decoded> {
+    /// All entities in the world, unless only their number is sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<C::Entity>>,
+
+    /// The number of entities in the world, sent instead of the entity list when the game is
+    /// configured to summarize entities.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_count: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<TimingInfo>,
+
+    /// Whether the snapshot only includes the components and resources that changed, rather
+    /// than all of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<C::Component>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Vec<C::Resource>>,
+
+    /// Size statistics for each registered type, if enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<C::Value>,
+
+    pub messages: Vec<C::Message>,
+
+    /// Other sections of the snapshot, e.g. `"physics"` or `"interest"`, keyed by name.
+    #[serde(flatten)]
+    pub sections: BTreeMap<String, C::Value>,
+}
+
+impl<C: PacketContents> Default for PacketData<C> {
+    fn default() -> Self {
+        PacketData {
+            entities: None,
+            entity_count: None,
+            time: None,
+            delta: None,
+            components: None,
+            resources: None,
+            stats: None,
+            messages: Vec::new(),
+            sections: BTreeMap::new(),
+        }
+    }
+}
+
+/// An entry in the entity list of a snapshot.
+///
+/// `S` is the type of the names, which the game borrows from the world when sending them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityEntry<S = String> {
+    #[serde(flatten)]
+    pub entity: DeserializableEntity,
+
+    /// The stable ID of the entity, if entity IDs are mapped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapped_id: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<S>,
+
+    /// The UUID of the entity, if entity UUIDs are enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<EditorUuid>,
+
+    /// The registered components that the entity has, if enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<S>>,
+}
+
+/// The serialized components of a registered type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentData {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    /// The components along with the entities they're attached to, sorted by entity ID.
+    pub data: Vec<ComponentEntry>,
+
+    /// The page that `data` is, if component data is paginated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<ComponentPage>,
+}
+
+/// Where the components in a [`ComponentData`] are within all components of the type.
+///
+/// [`ComponentData`]: ./struct.ComponentData.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentPage {
+    /// The index of the page, starting from 0.
+    pub index: usize,
+
+    /// The number of components per page.
+    pub size: usize,
+
+    /// The number of components of the type across all pages.
+    pub total: usize,
+}
+
+/// A serialized component along with the entity it's attached to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentEntry {
+    pub entity: DeserializableEntity,
+    pub data: Value,
+}
+
+/// The serialized value of a registered resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceData {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    pub data: Value,
+}
+
+/// A message sent by the game, e.g. `"log"` or `"edit_ack"`.
+///
+/// The shape of `data` depends on the type of the message. It can be deserialized into the
+/// matching type from this module where there is one, e.g. [`EditAck`] for `"edit_ack"`
+/// messages.
+///
+/// [`EditAck`]: ./struct.EditAck.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutgoingMessage {
+    #[serde(rename = "type")]
+    pub ty: String,
+
+    #[serde(default)]
+    pub data: Value,
+}
+
+/// The type of a field of an incoming message, used to build the schema.
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    String,
+    Strings,
+    Integer,
+    Integers,
+    Number,
+    Bool,
+    Any,
+    Values,
+    Entity,
+    Entities,
+    Vector(usize),
+    Enum(&'static [&'static str]),
+    Condition,
+    Query,
+    Operations,
+}
+
+impl Field {
+    fn schema(self) -> Value {
+        match self {
+            Field::String => json!({ "type": "string" }),
+            Field::Strings => json!({ "type": "array", "items": { "type": "string" } }),
+            Field::Integer => json!({ "type": "integer", "minimum": 0 }),
+            Field::Integers => json!({
+                "type": "array",
+                "items": { "type": "integer", "minimum": 0 },
+            }),
+            Field::Number => json!({ "type": "number" }),
+            Field::Bool => json!({ "type": "boolean" }),
+            Field::Any => json!({}),
+            Field::Values => json!({ "type": "array" }),
+            Field::Entity => json!({ "$ref": "#/definitions/IncomingEntity" }),
+            Field::Entities => json!({
+                "type": "array",
+                "items": { "$ref": "#/definitions/IncomingEntity" },
+            }),
+            Field::Vector(len) => json!({
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": len,
+                "maxItems": len,
+            }),
+            Field::Enum(values) => json!({ "enum": values }),
+            Field::Condition => json!({ "$ref": "#/definitions/FieldCondition" }),
+            Field::Query => json!({ "$ref": "#/definitions/JsonPathExpr" }),
+            Field::Operations => json!({
+                "type": "array",
+                "items": { "$ref": "#/definitions/IncomingMessage" },
+            }),
+        }
+    }
+}
+
+/// An incoming message type, along with its fields and whether each is required.
+struct MessageType {
+    name: &'static str,
+    fields: Vec<(&'static str, Field, bool)>,
+}
+
+impl MessageType {
+    fn new(name: &'static str, fields: &[(&'static str, Field, bool)]) -> Self {
+        MessageType {
+            name,
+            fields: fields.to_vec(),
+        }
+    }
+
+    /// Adds the optional `request_id` field.
+    fn acknowledged(mut self) -> Self {
+        self.fields.push(("request_id", Field::Integer, false));
+        self
+    }
+
+    fn schema(&self) -> Value {
+        let mut properties = Map::new();
+        properties.insert("type".into(), json!({ "const": self.name }));
+        let mut required = vec![json!("type")];
+        for &(name, field, is_required) in &self.fields {
+            properties.insert(name.into(), field.schema());
+            if is_required {
+                required.push(json!(name));
+            }
+        }
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
+/// All of the incoming message types supported with the enabled features.
+///
+/// NOTE: This must be kept in sync with `IncomingMessage`, which the tests below check in both
+/// directions.
+fn incoming_types() -> Vec<MessageType> {
+    use self::Field::*;
+
+    let types = vec![
+        MessageType::new(
+            "ComponentUpdate",
+            &[
+                ("id", String, true),
+                ("entity", Entity, true),
+                ("data", Any, true),
+            ],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "ComponentPatch",
+            &[
+                ("id", String, true),
+                ("entity", Entity, true),
+                ("data", Any, true),
+            ],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "ComponentUpdateBatch",
+            &[
+                ("id", String, true),
+                ("entities", Entities, true),
+                ("data", Any, true),
+                ("patch", Bool, false),
+            ],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "ResourceUpdate",
+            &[("id", String, true), ("data", Any, true)],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "RequestChunks",
+            &[("resource", String, true), ("ids", Values, true)],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "CreateEntities",
+            &[
+                ("amount", Integer, true),
+                ("temporary_ids", Integers, false),
+            ],
+        )
+        .acknowledged(),
+        MessageType::new("DestroyEntities", &[("entities", Entities, true)]).acknowledged(),
+        MessageType::new("CloneEntities", &[("entities", Entities, true)]).acknowledged(),
+        MessageType::new("SetSelection", &[("entities", Entities, true)]).acknowledged(),
+        MessageType::new(
+            "BookmarkEntity",
+            &[("name", String, true), ("entity", Entity, false)],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "ExportScene",
+            &[("path", String, true), ("entities", Entities, true)],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "QueryEntities",
+            &[
+                ("with_components", Strings, false),
+                ("where_clause", Query, false),
+            ],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "RequestComponentPage",
+            &[("id", String, true), ("page", Integer, true)],
+        )
+        .acknowledged(),
+        MessageType::new("FindOrphans", &[("anchors", Strings, false)]).acknowledged(),
+        MessageType::new(
+            "PurgeEntities",
+            &[("entities", Entities, true), ("anchors", Strings, false)],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "AddWatch",
+            &[
+                ("id", Integer, true),
+                ("component", String, true),
+                ("entity", Entity, true),
+                ("path", String, false),
+            ],
+        ),
+        MessageType::new("RemoveWatch", &[("id", Integer, true)]),
+        MessageType::new(
+            "PinComponentField",
+            &[
+                ("entity", Entity, true),
+                ("component", String, true),
+                ("path", String, false),
+                ("value", Any, true),
+            ],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "UnpinComponentField",
+            &[
+                ("entity", Entity, true),
+                ("component", String, true),
+                ("path", String, false),
+            ],
+        ),
+        MessageType::new(
+            "SetBreakpoint",
+            &[
+                ("id", Integer, true),
+                ("component", String, true),
+                ("entity", Entity, false),
+                ("condition", Condition, true),
+            ],
+        ),
+        MessageType::new("ClearBreakpoint", &[("id", Integer, true)]),
+        MessageType::new("SetTimeScale", &[("scale", Number, true)]).acknowledged(),
+        MessageType::new("SetFixedTimestep", &[("seconds", Number, true)]).acknowledged(),
+        MessageType::new("SetSendInterval", &[("milliseconds", Integer, true)]).acknowledged(),
+        MessageType::new("SetSendRateLimit", &[("bytes_per_second", Integer, false)])
+            .acknowledged(),
+        MessageType::new("ReloadAsset", &[("path", String, true)]).acknowledged(),
+        MessageType::new("SetMasterVolume", &[("volume", Number, true)]).acknowledged(),
+        MessageType::new("SpawnPrefab", &[("path", String, true)]).acknowledged(),
+        MessageType::new(
+            "TransformDrag",
+            &[
+                ("entity", Entity, true),
+                ("translation", Vector(3), false),
+                ("rotation", Vector(4), false),
+                ("scale", Vector(3), false),
+            ],
+        ),
+        MessageType::new("TransformDragEnd", &[("entity", Entity, true)]),
+        MessageType::new("Eval", &[("input", String, true)]).acknowledged(),
+        MessageType::new("Handshake", &[("capabilities", Strings, false)]),
+        MessageType::new("Heartbeat", &[]),
+        MessageType::new("Authenticate", &[("token", String, true)]).acknowledged(),
+        MessageType::new(
+            "SetEditorMeta",
+            &[("key", String, true), ("value", Any, true)],
+        ),
+        MessageType::new("Subscribe", &[("sections", Strings, false)]),
+        MessageType::new("ForceSync", &[("entities", Entities, true)]).acknowledged(),
+        MessageType::new(
+            "RequestHistory",
+            &[("from_frame", Integer, true), ("to_frame", Integer, true)],
+        )
+        .acknowledged(),
+        MessageType::new("RewindTo", &[("frame", Integer, true)]).acknowledged(),
+        MessageType::new(
+            "SetSyncLevel",
+            &[("level", Enum(&["full", "minimal"]), true)],
+        )
+        .acknowledged(),
+        MessageType::new("EnableSync", &[("id", String, true)]).acknowledged(),
+        MessageType::new("DisableSync", &[("id", String, true)]).acknowledged(),
+        MessageType::new("RequestFullSync", &[]),
+        MessageType::new("Transaction", &[("ops", Operations, true)]).acknowledged(),
+    ];
+
+    types
+        .into_iter()
+        .chain(physics_types())
+        .chain(renderer_types())
+        .collect()
+}
+
+#[cfg(feature = "physics")]
+fn physics_types() -> Vec<MessageType> {
+    use self::Field::*;
+
+    vec![
+        MessageType::new(
+            "ApplyImpulse",
+            &[
+                ("entity", Entity, true),
+                ("linear", Vector(3), false),
+                ("angular", Vector(3), false),
+            ],
+        )
+        .acknowledged(),
+        MessageType::new(
+            "Teleport",
+            &[("entity", Entity, true), ("position", Vector(3), true)],
+        )
+        .acknowledged(),
+    ]
+}
+
+#[cfg(not(feature = "physics"))]
+fn physics_types() -> Vec<MessageType> {
+    Vec::new()
+}
+
+#[cfg(feature = "renderer")]
+fn renderer_types() -> Vec<MessageType> {
+    vec![MessageType::new(
+        "PickRequest",
+        &[
+            ("screen_x", Field::Number, true),
+            ("screen_y", Field::Number, true),
+        ],
+    )
+    .acknowledged()]
+}
+
+#[cfg(not(feature = "renderer"))]
+fn renderer_types() -> Vec<MessageType> {
+    Vec::new()
+}
+
+/// Returns a JSON Schema (draft 7) describing the protocol.
+///
+/// The schema validates a [`Packet`] sent by the game. The messages the editor can send are
+/// described by the `IncomingMessage` definition, and the other types in this module have
+/// definitions of the same name. Only the incoming messages supported with the crate's enabled
+/// features are included.
+///
+/// [`Packet`]: ./struct.Packet.html
+pub fn schema() -> Value {
+    let incoming = incoming_types()
+        .iter()
+        .map(MessageType::schema)
+        .collect::<Vec<_>>();
+    let entity = json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer", "minimum": 0 },
+            "generation": { "type": "integer" },
+        },
+        "required": ["id", "generation"],
+    });
+    let optional_string = json!({ "type": ["string", "null"] });
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Packet",
+        "$ref": "#/definitions/Packet",
+        "definitions": {
+            "Entity": entity,
+            "IncomingEntity": {
+                "oneOf": [
+                    { "$ref": "#/definitions/Entity" },
+                    {
+                        "type": "object",
+                        "properties": { "mapped_id": { "type": "integer", "minimum": 0 } },
+                        "required": ["mapped_id"],
+                    },
+                    {
+                        "type": "object",
+                        "properties": { "temporary_id": { "type": "integer", "minimum": 0 } },
+                        "required": ["temporary_id"],
+                    },
+                ],
+            },
+            "FieldCondition": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "op": { "enum": ["eq", "ne", "lt", "le", "gt", "ge"] },
+                    "value": {},
+                },
+                "required": ["op", "value"],
+            },
+            "JsonPathExpr": {
+                "allOf": [
+                    { "$ref": "#/definitions/FieldCondition" },
+                    {
+                        "type": "object",
+                        "properties": { "component": { "type": "string" } },
+                        "required": ["component"],
+                    },
+                ],
+            },
+            "IncomingMessage": { "oneOf": incoming },
+            "Packet": {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "message" },
+                    "instance": {},
+                    "frame": { "type": "integer", "minimum": 0 },
+                    "absolute_time": { "type": "number" },
+                    "data": { "$ref": "#/definitions/PacketData" },
+                },
+                "required": ["type", "frame", "data"],
+            },
+            "PacketData": {
+                "type": "object",
+                "properties": {
+                    "entities": {
+                        "type": "array",
+                        "items": { "$ref": "#/definitions/EntityEntry" },
+                    },
+                    "entity_count": { "type": "integer", "minimum": 0 },
+                    "time": { "$ref": "#/definitions/TimingInfo" },
+                    "delta": { "type": "boolean" },
+                    "components": {
+                        "type": "array",
+                        "items": { "$ref": "#/definitions/ComponentData" },
+                    },
+                    "resources": {
+                        "type": "array",
+                        "items": { "$ref": "#/definitions/ResourceData" },
+                    },
+                    "stats": { "type": "object" },
+                    "messages": {
+                        "type": "array",
+                        "items": { "$ref": "#/definitions/OutgoingMessage" },
+                    },
+                },
+                "required": ["messages"],
+            },
+            "EntityEntry": {
+                "allOf": [
+                    { "$ref": "#/definitions/Entity" },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "mapped_id": { "type": "integer", "minimum": 0 },
+                            "name": { "type": "string" },
+                            "uuid": { "type": "string", "format": "uuid" },
+                            "components": { "type": "array", "items": { "type": "string" } },
+                        },
+                    },
+                ],
+            },
+            "TimingInfo": {
+                "type": "object",
+                "properties": {
+                    "frame": { "type": "integer", "minimum": 0 },
+                    "delta_seconds": { "type": "number" },
+                    "fixed_seconds": { "type": "number" },
+                    "fixed_step": { "type": "integer", "minimum": 0 },
+                    "interpolation_alpha": { "type": "number" },
+                },
+                "required": [
+                    "frame",
+                    "delta_seconds",
+                    "fixed_seconds",
+                    "fixed_step",
+                    "interpolation_alpha",
+                ],
+            },
+            "ComponentData": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "category": optional_string,
+                    "data": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "entity": { "$ref": "#/definitions/Entity" },
+                                "data": {},
+                            },
+                            "required": ["entity", "data"],
+                        },
+                    },
+                    "page": {
+                        "type": "object",
+                        "properties": {
+                            "index": { "type": "integer", "minimum": 0 },
+                            "size": { "type": "integer", "minimum": 1 },
+                            "total": { "type": "integer", "minimum": 0 },
+                        },
+                        "required": ["index", "size", "total"],
+                    },
+                },
+                "required": ["name", "data"],
+            },
+            "ResourceData": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "category": optional_string,
+                    "data": {},
+                },
+                "required": ["name", "data"],
+            },
+            "OutgoingMessage": {
+                "type": "object",
+                "properties": {
+                    "type": { "type": "string" },
+                    "data": {},
+                },
+                "required": ["type"],
+            },
+            "EditAck": {
+                "type": "object",
+                "properties": {
+                    "request_id": { "type": "integer", "minimum": 0 },
+                    "status": { "$ref": "#/definitions/EditStatus" },
+                },
+                "required": ["request_id", "status"],
+            },
+            "EditStatus": {
+                "enum": [
+                    "applied",
+                    "stale_entity",
+                    "unknown_type",
+                    "missing_component",
+                    "missing_resource",
+                    "invalid_data",
+                    "missing_frame",
+                    "unsupported",
+                    "limit_exceeded",
+                    "unauthorized",
+                    "failed",
+                ],
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Lists the names of the given `IncomingMessage` variants, failing to compile unless every
+    /// variant is listed.
+    macro_rules! variant_names {
+        ($($(#[$attr:meta])* $variant:ident,)*) => {{
+            #[allow(dead_code)]
+            fn exhaustive(message: &IncomingMessage) {
+                match *message {
+                    $($(#[$attr])* IncomingMessage::$variant { .. } => {})*
+                }
+            }
+
+            let mut names = Vec::<&str>::new();
+            $($(#[$attr])* names.push(stringify!($variant));)*
+            names
+        }};
+    }
+
+    /// Builds a message with only the required fields of `message_type`.
+    fn sample(message_type: &MessageType) -> Value {
+        let mut message = Map::new();
+        message.insert("type".into(), json!(message_type.name));
+        for &(name, field, required) in &message_type.fields {
+            if !required {
+                continue;
+            }
+
+            let value = match field {
+                Field::String => json!("a"),
+                Field::Strings => json!(["a"]),
+                Field::Integer => json!(1),
+                Field::Integers => json!([1]),
+                Field::Number => json!(0.5),
+                Field::Bool => json!(true),
+                Field::Any => json!({ "x": 1 }),
+                Field::Values => json!([1]),
+                Field::Entity => json!({ "id": 0, "generation": 1 }),
+                Field::Entities => json!([{ "id": 0, "generation": 1 }]),
+                Field::Vector(len) => json!(vec![0.0; len]),
+                Field::Enum(values) => json!(values[0]),
+                Field::Condition => json!({ "op": "eq", "value": 1 }),
+                Field::Query => json!({ "component": "a", "op": "eq", "value": 1 }),
+                Field::Operations => json!([{ "type": "CreateEntities", "amount": 1 }]),
+            };
+            message.insert(name.into(), value);
+        }
+        Value::Object(message)
+    }
+
+    #[test]
+    fn schema_matches_incoming_messages() {
+        for message_type in incoming_types() {
+            let message = sample(&message_type);
+            let parsed = serde_json::from_value::<IncomingMessage>(message.clone());
+            assert!(parsed.is_ok(), "{} doesn't match its schema", message);
+
+            // Messages must also survive being sent by editors written in Rust.
+            let reserialized = serde_json::to_value(parsed.unwrap()).unwrap();
+            assert_eq!(reserialized["type"], message["type"]);
+        }
+    }
+
+    #[test]
+    fn every_incoming_message_has_a_schema() {
+        let variants = variant_names!(
+            ComponentUpdate,
+            ComponentPatch,
+            ComponentUpdateBatch,
+            ResourceUpdate,
+            RequestChunks,
+            CreateEntities,
+            DestroyEntities,
+            CloneEntities,
+            SetSelection,
+            BookmarkEntity,
+            ExportScene,
+            QueryEntities,
+            FindOrphans,
+            PurgeEntities,
+            RequestComponentPage,
+            AddWatch,
+            RemoveWatch,
+            PinComponentField,
+            UnpinComponentField,
+            SetBreakpoint,
+            ClearBreakpoint,
+            SetTimeScale,
+            SetFixedTimestep,
+            SetSendInterval,
+            SetSendRateLimit,
+            ReloadAsset,
+            SetMasterVolume,
+            #[cfg(feature = "physics")]
+            ApplyImpulse,
+            #[cfg(feature = "physics")]
+            Teleport,
+            SpawnPrefab,
+            TransformDrag,
+            TransformDragEnd,
+            #[cfg(feature = "renderer")]
+            PickRequest,
+            Eval,
+            Handshake,
+            Authenticate,
+            Heartbeat,
+            SetEditorMeta,
+            Subscribe,
+            ForceSync,
+            RequestHistory,
+            RewindTo,
+            SetSyncLevel,
+            EnableSync,
+            DisableSync,
+            RequestFullSync,
+            Transaction,
+        );
+
+        let types = incoming_types();
+        for variant in variants {
+            assert!(
+                types
+                    .iter()
+                    .any(|message_type| message_type.name == variant),
+                "{} is missing from the schema",
+                variant
+            );
+        }
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        let packet = json!({
+            "type": "message",
+            "frame": 3,
+            "absolute_time": 0.5,
+            "data": {
+                "entities": [{ "id": 0, "generation": 1, "name": "player" }],
+                "delta": false,
+                "components": [{
+                    "name": "Position",
+                    "data": [{ "entity": { "id": 0, "generation": 1 }, "data": { "x": 1 } }],
+                }],
+                "physics": { "bodies": [] },
+                "messages": [{
+                    "type": "edit_ack",
+                    "data": { "request_id": 2, "status": "applied" },
+                }],
+            },
+        });
+
+        let parsed = serde_json::from_value::<Packet>(packet.clone()).unwrap();
+        assert_eq!(parsed.data.sections["physics"], json!({ "bodies": [] }));
+        let ack = serde_json::from_value::<EditAck>(parsed.data.messages[0].data.clone()).unwrap();
+        assert_eq!(ack.status, EditStatus::Applied);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), packet);
+    }
+}
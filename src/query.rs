@@ -0,0 +1,205 @@
+//! Entity queries requested by the editor.
+
+use amethyst::ecs::{Entity, Join, World};
+use crate::registry::Registry;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// A condition on a field of a component, e.g. `Health.current < 10`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonPathExpr {
+    /// The registered name of the component.
+    pub component: String,
+
+    #[serde(flatten)]
+    pub condition: FieldCondition,
+}
+
+/// A comparison of a field of a serialized value, e.g. `current < 10`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldCondition {
+    /// The dot-separated path of the field within the serialized component, e.g. `stats.hp`.
+    /// Array elements are addressed by index, e.g. `position.0`. An empty path compares the
+    /// whole component.
+    #[serde(default)]
+    pub path: String,
+
+    pub op: CompareOp,
+    pub value: Value,
+}
+
+/// The comparison used by a [`FieldCondition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl FieldCondition {
+    /// Checks whether a serialized value matches the condition.
+    ///
+    /// Numbers are compared numerically and strings lexicographically. Ordering comparisons
+    /// between any other values never match.
+    pub(crate) fn matches(&self, value: &Value) -> bool {
+        self.field(value).is_some()
+    }
+
+    /// Returns the compared field of a serialized value if the value matches the condition.
+    pub(crate) fn field<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        let field = lookup(value, &self.path)?;
+        if self.compare_field(field) {
+            Some(field)
+        } else {
+            None
+        }
+    }
+
+    fn compare_field(&self, field: &Value) -> bool {
+        match self.op {
+            CompareOp::Eq => field == &self.value,
+            CompareOp::Ne => field != &self.value,
+            CompareOp::Lt => compare(field, &self.value) == Some(Ordering::Less),
+            CompareOp::Le => match compare(field, &self.value) {
+                Some(Ordering::Less) | Some(Ordering::Equal) => true,
+                _ => false,
+            },
+            CompareOp::Gt => compare(field, &self.value) == Some(Ordering::Greater),
+            CompareOp::Ge => match compare(field, &self.value) {
+                Some(Ordering::Greater) | Some(Ordering::Equal) => true,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Looks up the field at a dot-separated path, where array elements are addressed by index.
+/// An empty path refers to the value itself.
+pub(crate) fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Object(object) => object.get(key),
+        Value::Array(array) => key.parse::<usize>().ok().and_then(|index| array.get(index)),
+        _ => None,
+    })
+}
+
+/// Looks up the field at a dot-separated path for modifying it, the same way as [`lookup`].
+///
+/// [`lookup`]: ./fn.lookup.html
+pub(crate) fn lookup_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Object(object) => object.get_mut(key),
+        Value::Array(array) => key
+            .parse::<usize>()
+            .ok()
+            .and_then(move |index| array.get_mut(index)),
+        _ => None,
+    })
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+/// Finds all entities that have every component in `with_components` and match `where_clause`.
+///
+/// The component named in `where_clause` doesn't need to be listed in `with_components`.
+/// Redacted fields are removed before the clause is evaluated, so they never match.
+pub(crate) fn find_entities(
+    world: &World,
+    registry: &Registry,
+    with_components: &[String],
+    where_clause: Option<&JsonPathExpr>,
+) -> Result<Vec<Entity>, String> {
+    let find = |name: &str| {
+        registry
+            .component(name)
+            .ok_or_else(|| format!("No component is registered as {:?}", name))
+    };
+    let required = with_components
+        .iter()
+        .map(|name| find(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let filter = match where_clause {
+        Some(clause) => Some((clause, find(&clause.component)?)),
+        None => None,
+    };
+
+    let mut entities = Vec::new();
+    for entity in (&*world.entities()).join() {
+        if !required.iter().all(|access| access.contains(world, entity)) {
+            continue;
+        }
+
+        if let Some((clause, access)) = filter {
+            let mut value = match access.serialize(world, entity) {
+                Some(Ok(value)) => value,
+                Some(Err(error)) => return Err(error.to_string()),
+                None => continue,
+            };
+            if let Some(redaction) = registry.redaction(access.name()) {
+                redaction.apply(&mut value);
+            }
+            if !clause.condition.matches(&value) {
+                continue;
+            }
+        }
+
+        entities.push(entity);
+    }
+    Ok(entities)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn expr(path: &str, op: CompareOp, value: Value) -> FieldCondition {
+        FieldCondition {
+            path: path.into(),
+            op,
+            value,
+        }
+    }
+
+    #[test]
+    fn compare_fields() {
+        let health = json!({ "current": 7, "name": "orc", "resist": [0.5, 1.0] });
+
+        assert!(expr("current", CompareOp::Lt, json!(10)).matches(&health));
+        assert!(expr("current", CompareOp::Le, json!(7.0)).matches(&health));
+        assert!(!expr("current", CompareOp::Gt, json!(7)).matches(&health));
+        assert!(expr("name", CompareOp::Eq, json!("orc")).matches(&health));
+        assert!(expr("name", CompareOp::Ge, json!("elf")).matches(&health));
+        assert!(expr("resist.1", CompareOp::Ge, json!(1)).matches(&health));
+
+        // Missing fields and mismatched types never match.
+        assert!(!expr("missing", CompareOp::Ne, json!(1)).matches(&health));
+        assert!(!expr("name", CompareOp::Lt, json!(10)).matches(&health));
+    }
+
+    #[test]
+    fn modify_nested_field() {
+        let mut transform = json!({ "translation": [1.0, 2.0, 3.0] });
+        *lookup_mut(&mut transform, "translation.1").unwrap() = json!(0.0);
+        assert_eq!(transform, json!({ "translation": [1.0, 0.0, 3.0] }));
+        assert!(lookup_mut(&mut transform, "rotation").is_none());
+    }
+}
@@ -0,0 +1,104 @@
+use serde_json::Value;
+
+/// Fields of a registered type that must never be sent to the editor.
+///
+/// Fields are named by their serialized name, and nested fields can be named with a path
+/// separated by dots, e.g. `account.email`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Redaction {
+    fields: Vec<String>,
+}
+
+impl Redaction {
+    pub(crate) fn new(fields: Vec<String>) -> Self {
+        Redaction { fields }
+    }
+
+    pub(crate) fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Removes the redacted fields from a serialized value.
+    pub(crate) fn apply(&self, value: &mut Value) {
+        for field in &self.fields {
+            let (parent, key) = split_path(field);
+            if let Some(Value::Object(object)) = lookup_mut(value, parent) {
+                object.remove(key);
+            }
+        }
+    }
+
+    /// Copies the redacted fields from `current` into `updated`.
+    ///
+    /// Updates from the editor never contain redacted fields, since the editor never sees them,
+    /// so they need to be filled back in before the update can be deserialized.
+    pub(crate) fn restore(&self, current: &Value, updated: &mut Value) {
+        for field in &self.fields {
+            let (parent, key) = split_path(field);
+            let current = match lookup(current, field) {
+                Some(current) => current,
+                None => continue,
+            };
+
+            if let Some(Value::Object(object)) = lookup_mut(updated, parent) {
+                object.insert(key.to_owned(), current.clone());
+            }
+        }
+    }
+}
+
+/// Splits a path into the path of the parent object and the name of the field.
+fn split_path(path: &str) -> (Option<&str>, &str) {
+    match path.rfind('.') {
+        Some(index) => (Some(&path[..index]), &path[index + 1..]),
+        None => (None, path),
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |value, key| value.as_object()?.get(key))
+}
+
+fn lookup_mut<'a>(value: &'a mut Value, path: Option<&str>) -> Option<&'a mut Value> {
+    match path {
+        Some(path) => path
+            .split('.')
+            .try_fold(value, |value, key| value.as_object_mut()?.get_mut(key)),
+        None => Some(value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redact_and_restore() {
+        let redaction = Redaction::new(vec!["token".into(), "account.email".into()]);
+        let current = json!({
+            "name": "player",
+            "token": "secret",
+            "account": { "id": 7, "email": "player@example.com" },
+        });
+
+        let mut redacted = current.clone();
+        redaction.apply(&mut redacted);
+        assert_eq!(
+            redacted,
+            json!({ "name": "player", "account": { "id": 7 } }),
+        );
+
+        let mut updated = json!({ "name": "renamed", "account": { "id": 8 } });
+        redaction.restore(&current, &mut updated);
+        assert_eq!(
+            updated,
+            json!({
+                "name": "renamed",
+                "token": "secret",
+                "account": { "id": 8, "email": "player@example.com" },
+            }),
+        );
+    }
+}
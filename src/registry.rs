@@ -0,0 +1,575 @@
+use amethyst::ecs::storage::{
+    BTreeStorage, DenseVecStorage, FlaggedStorage, HashMapStorage, MaskedStorage, NullStorage,
+    VecStorage,
+};
+use amethyst::ecs::{Component, Entity, Join, World};
+use amethyst::shred::Resource;
+use crate::budget::SyncPriority;
+use crate::depth::{self, DEFAULT_MAX_DEPTH};
+use crate::mask::{self, FieldMask};
+use crate::redact::Redaction;
+use crate::validation::Validator;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Type-erased access to a registered component type.
+///
+/// Most of the syncing work is done by systems specialized for each registered type, but some
+/// operations requested by the editor (e.g. exporting a scene) need to visit every registered
+/// component for a given entity. Those operations run with direct access to the `World` and use
+/// the registry to find the components.
+pub(crate) trait ComponentAccess: Send + Sync {
+    /// The name the component was registered with.
+    fn name(&self) -> &'static str;
+
+    /// Serializes the component attached to `entity`.
+    ///
+    /// Returns `None` if the entity doesn't have the component, or if the component's storage
+    /// hasn't been registered in the world.
+    fn serialize(
+        &self,
+        world: &World,
+        entity: Entity,
+    ) -> Option<Result<serde_json::Value, serde_json::Error>>;
+
+    /// Checks whether `entity` has the component.
+    fn contains(&self, world: &World, entity: Entity) -> bool;
+
+    /// Counts the entities that have the component.
+    ///
+    /// Returns `None` if the component's storage hasn't been registered in the world.
+    fn count(&self, world: &World) -> Option<usize>;
+
+    /// The kind of storage the component uses, e.g. `"DenseVecStorage"`.
+    fn storage(&self) -> &'static str;
+}
+
+/// Type-erased write access to a registered component type, which is only available for
+/// components that the editor can edit.
+pub(crate) trait ComponentWrite: Send + Sync {
+    /// The name the component was registered with.
+    fn name(&self) -> &'static str;
+
+    /// Copies the component attached to `source` onto `target`, by serializing and deserializing
+    /// it.
+    ///
+    /// Returns `None` if `source` doesn't have the component, or if the component's storage
+    /// hasn't been registered in the world.
+    fn clone_onto(
+        &self,
+        world: &World,
+        source: Entity,
+        target: Entity,
+    ) -> Option<Result<(), serde_json::Error>>;
+
+    /// Replaces the component attached to `entity` with one deserialized from `value`, or
+    /// removes it if `value` is `None`.
+    ///
+    /// Returns `None` if the component's storage hasn't been registered in the world.
+    fn restore(
+        &self,
+        world: &World,
+        entity: Entity,
+        value: Option<&Value>,
+    ) -> Option<Result<(), serde_json::Error>>;
+}
+
+/// Type-erased access to a registered resource type.
+pub(crate) trait ResourceAccess: Send + Sync {
+    /// The name the resource was registered with.
+    fn name(&self) -> &'static str;
+
+    /// Serializes the resource.
+    ///
+    /// Returns `None` if the resource hasn't been added to the world.
+    fn serialize(&self, world: &World) -> Option<Result<serde_json::Value, serde_json::Error>>;
+}
+
+/// Type-erased write access to a registered resource type, which is only available for
+/// resources that the editor can edit.
+pub(crate) trait ResourceWrite: Send + Sync {
+    /// The name the resource was registered with.
+    fn name(&self) -> &'static str;
+
+    /// Replaces the resource with one deserialized from `value`.
+    ///
+    /// Returns `None` if the resource hasn't been added to the world.
+    fn restore(&self, world: &World, value: &Value) -> Option<Result<(), serde_json::Error>>;
+}
+
+/// Settings for how a registered component or resource is sent to the editor.
+#[derive(Debug, Clone)]
+pub(crate) struct TypeOptions {
+    /// The category the type is grouped under in the editor.
+    pub category: Option<&'static str>,
+
+    /// Fields that must never be sent to the editor.
+    pub redaction: Option<Arc<Redaction>>,
+
+    /// Fields that the editor can't change.
+    pub mask: Option<Arc<FieldMask>>,
+
+    /// The maximum nesting depth of the serialized data, beyond which it's truncated.
+    pub max_depth: usize,
+
+    /// Whether the type is only updated during fixed updates.
+    pub fixed_step: bool,
+
+    /// How important the type is when snapshots don't fit within the byte budget.
+    pub priority: SyncPriority,
+}
+
+impl Default for TypeOptions {
+    fn default() -> Self {
+        TypeOptions {
+            category: None,
+            redaction: None,
+            mask: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            fixed_step: false,
+            priority: SyncPriority::default(),
+        }
+    }
+}
+
+impl TypeOptions {
+    /// Prepares serialized data to be sent to the editor, removing any redacted fields and
+    /// truncating it if it's nested too deeply.
+    ///
+    /// Returns `true` if the data was truncated.
+    pub(crate) fn prepare_outgoing(&self, value: &mut Value) -> bool {
+        if let Some(ref redaction) = self.redaction {
+            redaction.apply(value);
+        }
+        depth::truncate(value, self.max_depth)
+    }
+
+    /// Combines an update from the editor with the current serialized value, so that it can be
+    /// deserialized and applied.
+    ///
+    /// Redacted fields are filled back in from the current value, since the editor never sees
+    /// them. If the type has masked fields, the update is merged into the current value without
+    /// changing them.
+    pub(crate) fn prepare_update(&self, current: &Value, mut updated: Value) -> Value {
+        if let Some(ref redaction) = self.redaction {
+            redaction.restore(current, &mut updated);
+        }
+
+        match self.mask {
+            Some(ref mask) => mask.merge(current, updated),
+            None => updated,
+        }
+    }
+
+    /// Merges a patch from the editor into the current serialized value, leaving any fields
+    /// that the patch doesn't contain unchanged.
+    ///
+    /// Masked and redacted fields are never changed by a patch.
+    pub(crate) fn apply_patch(&self, current: &Value, patch: Value) -> Value {
+        let mut skipped = Vec::new();
+        if let Some(ref mask) = self.mask {
+            skipped.extend_from_slice(mask.fields());
+        }
+        if let Some(ref redaction) = self.redaction {
+            skipped.extend_from_slice(redaction.fields());
+        }
+        mask::merge(current, patch, &skipped)
+    }
+}
+
+/// All component types registered with a [`SyncEditorBundle`].
+///
+/// [`SyncEditorBundle`]: ./struct.SyncEditorBundle.html
+#[derive(Default)]
+pub(crate) struct Registry {
+    components: Vec<Box<dyn ComponentAccess>>,
+    writable_components: Vec<Box<dyn ComponentWrite>>,
+    resources: Vec<Box<dyn ResourceAccess>>,
+    writable_resources: Vec<Box<dyn ResourceWrite>>,
+    options: HashMap<&'static str, TypeOptions>,
+    max_depth: Option<usize>,
+
+    // Whether components are checked for NaN and infinite floats.
+    check_floats: bool,
+
+    // The `Vec<Validator<T>>` of each validated component type.
+    validators: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Registry {
+    pub(crate) fn register_component<T>(&mut self, name: &'static str)
+    where
+        T: Component + Serialize,
+    {
+        let access = TypedComponent::<T> {
+            name,
+            _marker: PhantomData,
+        };
+        self.components
+            .push(Box::new(access) as Box<dyn ComponentAccess>);
+    }
+
+    /// Allows the component registered as `name` to be written to with direct access to the
+    /// world, e.g. to copy it onto other entities.
+    pub(crate) fn register_writable_component<T>(&mut self, name: &'static str)
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        let access = TypedComponent::<T> {
+            name,
+            _marker: PhantomData,
+        };
+        self.writable_components
+            .push(Box::new(access) as Box<dyn ComponentWrite>);
+    }
+
+    pub(crate) fn register_resource<T>(&mut self, name: &'static str)
+    where
+        T: Resource + Serialize,
+    {
+        let access = TypedResource::<T> {
+            name,
+            _marker: PhantomData,
+        };
+        self.resources
+            .push(Box::new(access) as Box<dyn ResourceAccess>);
+    }
+
+    /// Allows the resource registered as `name` to be written to with direct access to the
+    /// world.
+    pub(crate) fn register_writable_resource<T>(&mut self, name: &'static str)
+    where
+        T: Resource + DeserializeOwned,
+    {
+        let access = TypedResource::<T> {
+            name,
+            _marker: PhantomData,
+        };
+        self.writable_resources
+            .push(Box::new(access) as Box<dyn ResourceWrite>);
+    }
+
+    /// Sets the fields of the component or resource registered as `name` that must never be
+    /// sent to the editor.
+    pub(crate) fn redact(&mut self, name: &'static str, fields: Vec<String>) {
+        self.options.entry(name).or_default().redaction = Some(Arc::new(Redaction::new(fields)));
+    }
+
+    /// Sets the fields of the component or resource registered as `name` that the editor can't
+    /// change.
+    pub(crate) fn mask(&mut self, name: &'static str, fields: Vec<String>) {
+        self.options.entry(name).or_default().mask = Some(Arc::new(FieldMask::new(fields)));
+    }
+
+    /// Sets the category that the component or resource registered as `name` is grouped under.
+    pub(crate) fn set_category(&mut self, name: &'static str, category: &'static str) {
+        self.options.entry(name).or_default().category = Some(category);
+    }
+
+    /// Marks the component or resource registered as `name` as only being updated during fixed
+    /// updates.
+    pub(crate) fn set_fixed_step(&mut self, name: &'static str) {
+        self.options.entry(name).or_default().fixed_step = true;
+    }
+
+    /// Sets the priority of the component or resource registered as `name`.
+    pub(crate) fn set_priority(&mut self, name: &'static str, priority: SyncPriority) {
+        self.options.entry(name).or_default().priority = priority;
+    }
+
+    /// Sets the maximum nesting depth of all components and resources.
+    pub(crate) fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth);
+    }
+
+    /// Enables checking all components for NaN and infinite floats.
+    pub(crate) fn set_check_floats(&mut self) {
+        self.check_floats = true;
+    }
+
+    pub(crate) fn checks_floats(&self) -> bool {
+        self.check_floats
+    }
+
+    /// Adds a check that components of type `T` are run through as they're read.
+    pub(crate) fn add_validator<T: 'static>(&mut self, validator: Validator<T>) {
+        self.validators
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Vec::<Validator<T>>::new()))
+            .downcast_mut::<Vec<Validator<T>>>()
+            .expect("Validators stored under the wrong type")
+            .push(validator);
+    }
+
+    /// The checks added for components of type `T`.
+    pub(crate) fn validators<T: 'static>(&self) -> Vec<Validator<T>> {
+        self.validators
+            .get(&TypeId::of::<T>())
+            .and_then(|validators| validators.downcast_ref::<Vec<Validator<T>>>())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn options(&self, name: &str) -> TypeOptions {
+        let mut options = self.options.get(name).cloned().unwrap_or_default();
+        if let Some(max_depth) = self.max_depth {
+            options.max_depth = max_depth;
+        }
+        options
+    }
+
+    pub(crate) fn redaction(&self, name: &str) -> Option<Arc<Redaction>> {
+        self.options
+            .get(name)
+            .and_then(|options| options.redaction.clone())
+    }
+
+    /// The redacted fields of each type, as advertised to the editor.
+    pub(crate) fn redacted_fields(&self) -> BTreeMap<&'static str, Vec<String>> {
+        self.options
+            .iter()
+            .filter_map(|(&name, options)| {
+                let redaction = options.redaction.as_ref()?;
+                Some((name, redaction.fields().to_vec()))
+            })
+            .collect()
+    }
+
+    /// The masked fields of each type, as advertised to the editor.
+    pub(crate) fn masked_fields(&self) -> BTreeMap<&'static str, Vec<String>> {
+        self.options
+            .iter()
+            .filter_map(|(&name, options)| {
+                let mask = options.mask.as_ref()?;
+                Some((name, mask.fields().to_vec()))
+            })
+            .collect()
+    }
+
+    /// The names of the types marked as fixed-step data, as advertised to the editor.
+    pub(crate) fn fixed_step_names(&self) -> Vec<&'static str> {
+        let mut names = self
+            .options
+            .iter()
+            .filter(|(_, options)| options.fixed_step)
+            .map(|(&name, _)| name)
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// The priority of each type that was given one, used to fit snapshots within the byte
+    /// budget.
+    pub(crate) fn priorities(&self) -> HashMap<&'static str, SyncPriority> {
+        self.options
+            .iter()
+            .map(|(&name, options)| (name, options.priority))
+            .collect()
+    }
+
+    pub(crate) fn component_names(&self) -> Vec<&'static str> {
+        self.components().map(|access| access.name()).collect()
+    }
+
+    pub(crate) fn resource_names(&self) -> Vec<&'static str> {
+        self.resources().map(|access| access.name()).collect()
+    }
+
+    pub(crate) fn component(&self, name: &str) -> Option<&dyn ComponentAccess> {
+        self.components().find(|access| access.name() == name)
+    }
+
+    pub(crate) fn writable_component(&self, name: &str) -> Option<&dyn ComponentWrite> {
+        self.writable_components()
+            .find(|access| access.name() == name)
+    }
+
+    pub(crate) fn components(&self) -> impl Iterator<Item = &dyn ComponentAccess> {
+        self.components.iter().map(|access| &**access)
+    }
+
+    pub(crate) fn writable_components(&self) -> impl Iterator<Item = &dyn ComponentWrite> {
+        self.writable_components.iter().map(|access| &**access)
+    }
+
+    pub(crate) fn resources(&self) -> impl Iterator<Item = &dyn ResourceAccess> {
+        self.resources.iter().map(|access| &**access)
+    }
+
+    pub(crate) fn writable_resources(&self) -> impl Iterator<Item = &dyn ResourceWrite> {
+        self.writable_resources.iter().map(|access| &**access)
+    }
+}
+
+struct TypedComponent<T> {
+    name: &'static str,
+
+    // NOTE: We use `fn() -> T` so that the accessor is `Send` and `Sync` regardless of `T`,
+    // since it never actually holds a `T`.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ComponentAccess for TypedComponent<T>
+where
+    T: Component + Serialize,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn serialize(
+        &self,
+        world: &World,
+        entity: Entity,
+    ) -> Option<Result<serde_json::Value, serde_json::Error>> {
+        if !world.res.has_value::<MaskedStorage<T>>() {
+            return None;
+        }
+
+        let storage = world.read_storage::<T>();
+        storage.get(entity).map(serde_json::to_value)
+    }
+
+    fn contains(&self, world: &World, entity: Entity) -> bool {
+        world.res.has_value::<MaskedStorage<T>>() && world.read_storage::<T>().contains(entity)
+    }
+
+    fn count(&self, world: &World) -> Option<usize> {
+        if !world.res.has_value::<MaskedStorage<T>>() {
+            return None;
+        }
+
+        let storage = world.read_storage::<T>();
+        Some((&storage).join().count())
+    }
+
+    fn storage(&self) -> &'static str {
+        storage_name::<T>()
+    }
+}
+
+/// The name of the storage used by `T`, e.g. `"DenseVecStorage"` or
+/// `"FlaggedStorage<VecStorage>"`, or `"custom"` for storages other than the ones provided by
+/// specs.
+fn storage_name<T: Component>() -> &'static str {
+    macro_rules! match_storage {
+        ($($storage:ident),*) => {{
+            let id = TypeId::of::<T::Storage>();
+            $(
+                if id == TypeId::of::<$storage<T>>() {
+                    return stringify!($storage);
+                }
+                if id == TypeId::of::<FlaggedStorage<T, $storage<T>>>() {
+                    return concat!("FlaggedStorage<", stringify!($storage), ">");
+                }
+            )*
+            "custom"
+        }};
+    }
+
+    match_storage!(
+        VecStorage,
+        DenseVecStorage,
+        HashMapStorage,
+        BTreeStorage,
+        NullStorage
+    )
+}
+
+impl<T> ComponentWrite for TypedComponent<T>
+where
+    T: Component + Serialize + DeserializeOwned,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn clone_onto(
+        &self,
+        world: &World,
+        source: Entity,
+        target: Entity,
+    ) -> Option<Result<(), serde_json::Error>> {
+        if !world.res.has_value::<MaskedStorage<T>>() {
+            return None;
+        }
+
+        let mut storage = world.write_storage::<T>();
+        let value = storage.get(source).map(serde_json::to_value)?;
+        let result = value
+            .and_then(serde_json::from_value::<T>)
+            .map(|component| {
+                // NOTE: Inserting only fails if `target` is no longer alive, in which case there's
+                // nothing to copy the component onto anyway.
+                let _ = storage.insert(target, component);
+            });
+        Some(result)
+    }
+
+    fn restore(
+        &self,
+        world: &World,
+        entity: Entity,
+        value: Option<&Value>,
+    ) -> Option<Result<(), serde_json::Error>> {
+        if !world.res.has_value::<MaskedStorage<T>>() {
+            return None;
+        }
+
+        let mut storage = world.write_storage::<T>();
+        let result = match value {
+            Some(value) => serde_json::from_value::<T>(value.clone()).map(|component| {
+                // NOTE: Inserting only fails if `entity` is no longer alive.
+                let _ = storage.insert(entity, component);
+            }),
+            None => {
+                storage.remove(entity);
+                Ok(())
+            }
+        };
+        Some(result)
+    }
+}
+
+struct TypedResource<T> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ResourceAccess for TypedResource<T>
+where
+    T: Resource + Serialize,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn serialize(&self, world: &World) -> Option<Result<serde_json::Value, serde_json::Error>> {
+        world
+            .res
+            .try_fetch::<T>()
+            .map(|resource| serde_json::to_value(&*resource))
+    }
+}
+
+impl<T> ResourceWrite for TypedResource<T>
+where
+    T: Resource + DeserializeOwned,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn restore(&self, world: &World, value: &Value) -> Option<Result<(), serde_json::Error>> {
+        let mut resource = world.res.try_fetch_mut::<T>()?;
+        let result = serde_json::from_value::<T>(value.clone()).map(|restored| {
+            *resource = restored;
+        });
+        Some(result)
+    }
+}
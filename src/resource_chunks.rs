@@ -0,0 +1,57 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::hash::Hash;
+
+/// Splits a resource into chunks that the editor can fetch individually, for resources that are
+/// too large to send in full, e.g. a tile map.
+///
+/// Register a chunker with [`SyncEditorBundle::sync_chunked_resource`]. The editor is sent the
+/// IDs of all chunks, and requests the chunks it wants to show, e.g. the regions of the map
+/// that are currently visible. Only the requested chunks are sent, and only when their data
+/// changes.
+///
+/// [`SyncEditorBundle::sync_chunked_resource`]: ./struct.SyncEditorBundle.html#method.sync_chunked_resource
+///
+/// # Examples
+///
+/// ```
+/// # extern crate amethyst_editor_sync;
+/// # use amethyst_editor_sync::ResourceChunker;
+/// struct TileMap {
+///     width: usize,
+///     tiles: Vec<u8>,
+/// }
+///
+/// /// Splits the map into rows.
+/// struct Rows;
+///
+/// impl ResourceChunker<TileMap> for Rows {
+///     type Id = usize;
+///     type Chunk = Vec<u8>;
+///
+///     fn chunk_ids(&self, map: &TileMap) -> Vec<usize> {
+///         (0..map.tiles.len() / map.width).collect()
+///     }
+///
+///     fn chunk(&self, map: &TileMap, &row: &usize) -> Option<Vec<u8>> {
+///         map.tiles
+///             .chunks(map.width)
+///             .nth(row)
+///             .map(|tiles| tiles.to_vec())
+///     }
+/// }
+/// # fn main() {}
+/// ```
+pub trait ResourceChunker<T>: Send + 'static {
+    /// Identifies a chunk, e.g. the coordinates of a region of a tile map.
+    type Id: Serialize + DeserializeOwned + Clone + Eq + Hash + Send;
+
+    /// The data of a single chunk.
+    type Chunk: Serialize;
+
+    /// The IDs of all chunks that `resource` is currently split into.
+    fn chunk_ids(&self, resource: &T) -> Vec<Self::Id>;
+
+    /// The data of the chunk with ID `id`, or `None` if there's no such chunk.
+    fn chunk(&self, resource: &T, id: &Self::Id) -> Option<Self::Chunk>;
+}
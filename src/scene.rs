@@ -0,0 +1,199 @@
+//! Exporting entities to Amethyst prefab files.
+
+use amethyst::ecs::{Entity, World};
+use crate::registry::Registry;
+use serde_json::Value;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Resolves a path sent by the editor against the directory that scenes are exported to.
+///
+/// Returns `None` for absolute paths and paths that use `..`, so that the editor can't write
+/// files outside of `dir`.
+pub(crate) fn resolve_export_path(dir: &Path, path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let mut components = path.components().peekable();
+    components.peek()?;
+    if components.any(|component| match component {
+        Component::Normal(_) | Component::CurDir => false,
+        Component::ParentDir | Component::RootDir | Component::Prefix(_) => true,
+    }) {
+        return None;
+    }
+
+    Some(dir.join(path))
+}
+
+/// Serializes the registered components of `entities` into a prefab file at `path`.
+///
+/// Each entity in the prefab has its components laid out as fields named after the snake cased
+/// name that the component was registered with, e.g. a component registered as
+/// `GlobalTransform` is written to the `global_transform` field. In order to load the exported
+/// prefab, the game's `PrefabData` type needs to use matching field names.
+///
+/// Redacted fields are left out of the prefab, the same as when sending data to the editor.
+pub(crate) fn export_prefab(
+    world: &World,
+    registry: &Registry,
+    path: &Path,
+    entities: &[Entity],
+) -> io::Result<()> {
+    let mut prefab = String::new();
+    prefab.push_str("#![enable(implicit_some)]\nPrefab(\n    entities: [\n");
+
+    for &entity in entities {
+        if !world.entities().is_alive(entity) {
+            debug!("Skipping dead entity {:?} in scene export", entity);
+            continue;
+        }
+
+        let mut data = serde_json::Map::new();
+        for component in registry.components() {
+            match component.serialize(world, entity) {
+                Some(Ok(mut value)) => {
+                    if let Some(redaction) = registry.redaction(component.name()) {
+                        redaction.apply(&mut value);
+                    }
+                    data.insert(snake_case(component.name()), value);
+                }
+
+                Some(Err(error)) => {
+                    warn!(
+                        "Failed to serialize {} for scene export: {:?}",
+                        component.name(),
+                        error
+                    );
+                }
+
+                None => {}
+            }
+        }
+
+        prefab.push_str("        (\n            data: ");
+        write_ron(&Value::Object(data), 3, &mut prefab);
+        prefab.push_str(",\n        ),\n");
+    }
+
+    prefab.push_str("    ],\n)\n");
+    fs::write(path, prefab)
+}
+
+/// Converts a type name like `GlobalTransform` into a field name like `global_transform`.
+fn snake_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if index != 0 {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Writes a JSON value as RON.
+///
+/// Objects whose keys are all valid identifiers are written as structs, since that's how serde
+/// represents structs in JSON. Other objects are written as maps.
+fn write_ron(value: &Value, indent: usize, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("None"),
+        Value::Bool(value) => write!(out, "{}", value).expect("Failed to write RON"),
+        Value::Number(value) => write!(out, "{}", value).expect("Failed to write RON"),
+        Value::String(value) => write!(out, "{:?}", value).expect("Failed to write RON"),
+
+        Value::Array(values) => {
+            out.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index != 0 {
+                    out.push_str(", ");
+                }
+                write_ron(value, indent, out);
+            }
+            out.push(']');
+        }
+
+        Value::Object(fields) => {
+            let is_struct = fields.keys().all(|key| is_identifier(key));
+            out.push_str(if is_struct { "(\n" } else { "{\n" });
+            for (key, value) in fields {
+                push_indent(indent + 1, out);
+                if is_struct {
+                    out.push_str(key);
+                } else {
+                    write!(out, "{:?}", key).expect("Failed to write RON");
+                }
+                out.push_str(": ");
+                write_ron(value, indent + 1, out);
+                out.push_str(",\n");
+            }
+            push_indent(indent, out);
+            out.push(if is_struct { ')' } else { '}' });
+        }
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn push_indent(indent: usize, out: &mut String) {
+    for _ in 0..indent {
+        out.push_str("    ");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn export_paths_stay_in_dir() {
+        let dir = Path::new("scenes");
+        assert_eq!(
+            resolve_export_path(dir, "level/boss.ron"),
+            Some(dir.join("level/boss.ron"))
+        );
+        assert_eq!(
+            resolve_export_path(dir, "./boss.ron"),
+            Some(dir.join("./boss.ron"))
+        );
+        assert_eq!(resolve_export_path(dir, ""), None);
+        assert_eq!(resolve_export_path(dir, "../boss.ron"), None);
+        assert_eq!(resolve_export_path(dir, "level/../../boss.ron"), None);
+        assert_eq!(resolve_export_path(dir, "/etc/passwd"), None);
+    }
+
+    #[test]
+    fn snake_case_names() {
+        assert_eq!(snake_case("Transform"), "transform");
+        assert_eq!(snake_case("GlobalTransform"), "global_transform");
+        assert_eq!(snake_case("UiText"), "ui_text");
+    }
+
+    #[test]
+    fn ron_structs_and_maps() {
+        let value = serde_json::json!({
+            "translation": [1.0, 2.0, 3.0],
+            "lookup": { "1": true },
+            "label": "foo",
+        });
+
+        let mut ron = String::new();
+        write_ron(&value, 0, &mut ron);
+        assert!(ron.starts_with("(\n"));
+        assert!(ron.contains("    translation: [1.0, 2.0, 3.0],\n"));
+        assert!(ron.contains("    lookup: {\n        \"1\": true,\n    },\n"));
+        assert!(ron.contains("    label: \"foo\",\n"));
+    }
+}
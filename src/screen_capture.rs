@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+/// The image format that captured frames are encoded in before they're sent to the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureFormat {
+    /// Lossless, but produces much more data than JPEG for most frames.
+    Png,
+
+    /// Lossy, with a quality between 1 and 100.
+    Jpeg { quality: u8 },
+}
+
+impl CaptureFormat {
+    /// The name of the format in `"screen_capture"` messages.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Jpeg { .. } => "jpeg",
+        }
+    }
+}
+
+/// Settings controlling how often frames are captured and how they're encoded.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CaptureSettings {
+    pub interval: Duration,
+    pub format: CaptureFormat,
+}
+
+/// A frame of the game's rendered output, as tightly packed 8-bit RGBA pixels starting from the
+/// top left.
+#[derive(Debug, Clone)]
+pub(crate) struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Hands frames rendered by the game over to the editor, for games that stream their viewport
+/// with [`SyncEditorBundle::capture_screen`].
+///
+/// Amethyst's renderer doesn't provide a way to read back the rendered frame, so the game has to
+/// do it, e.g. in a custom render pass. Reading back a frame is expensive, so the bundle only
+/// asks for a frame at the configured rate: the game should check [`wants_frame`] after
+/// rendering, and if it returns `true`, read back the frame and pass it to [`submit`].
+///
+/// [`SyncEditorBundle::capture_screen`]: ./struct.SyncEditorBundle.html#method.capture_screen
+/// [`wants_frame`]: #method.wants_frame
+/// [`submit`]: #method.submit
+#[derive(Debug, Clone, Default)]
+pub struct ScreenCapture {
+    requested: bool,
+    frame: Option<CapturedFrame>,
+}
+
+impl ScreenCapture {
+    /// Whether the editor is waiting for a frame.
+    pub fn wants_frame(&self) -> bool {
+        self.requested
+    }
+
+    /// Submits a frame to be sent to the editor, as tightly packed 8-bit RGBA pixels starting
+    /// from the top left.
+    ///
+    /// Frames that weren't asked for and frames whose size doesn't match `width` and `height`
+    /// are ignored.
+    pub fn submit(&mut self, width: u32, height: u32, rgba: Vec<u8>) {
+        if !self.requested {
+            return;
+        }
+
+        if rgba.len() != width as usize * height as usize * 4 {
+            warn_once!(
+                "Ignoring captured frame with {} bytes of pixels, which isn't {}x{} RGBA",
+                rgba.len(),
+                width,
+                height
+            );
+            return;
+        }
+
+        self.requested = false;
+        self.frame = Some(CapturedFrame {
+            width,
+            height,
+            rgba,
+        });
+    }
+
+    /// Asks the game for a new frame.
+    pub(crate) fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Takes the frame submitted by the game, if there is one.
+    pub(crate) fn take(&mut self) -> Option<CapturedFrame> {
+        self.frame.take()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_requested_frames_are_kept() {
+        let mut capture = ScreenCapture::default();
+        capture.submit(1, 1, vec![0; 4]);
+        assert!(capture.take().is_none());
+
+        capture.request();
+        assert!(capture.wants_frame());
+        capture.submit(2, 2, vec![0; 4]);
+        assert!(capture.wants_frame());
+
+        capture.submit(1, 1, vec![1, 2, 3, 4]);
+        assert!(!capture.wants_frame());
+        assert_eq!(capture.take().unwrap().rgba, vec![1, 2, 3, 4]);
+        assert!(capture.take().is_none());
+    }
+}
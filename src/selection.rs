@@ -0,0 +1,78 @@
+use amethyst::ecs::Entity;
+
+/// The entities selected in the editor, kept in sync in both directions.
+///
+/// When the editor changes its selection, the resource is updated to match. The game can also
+/// change the selection, e.g. to let the player click on entities in-game, and the change is
+/// sent back to the editor as a `"selection"` message. This way overlays and gizmos drawn by the
+/// game and by the editor always agree on what's selected.
+///
+/// Entities are kept in the order they were selected in. Deleted entities are removed from the
+/// selection automatically.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorSelection {
+    entities: Vec<Entity>,
+}
+
+impl EditorSelection {
+    /// The selected entities, in the order they were selected in.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Whether `entity` is selected.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    /// Adds `entity` to the selection, if it isn't already selected.
+    pub fn select(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            self.entities.push(entity);
+        }
+    }
+
+    /// Removes `entity` from the selection.
+    pub fn deselect(&mut self, entity: Entity) {
+        self.entities.retain(|&selected| selected != entity);
+    }
+
+    /// Replaces the selection with `entities`.
+    pub fn set<I: IntoIterator<Item = Entity>>(&mut self, entities: I) {
+        self.entities.clear();
+        for entity in entities {
+            self.select(entity);
+        }
+    }
+
+    /// Deselects all entities.
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+
+    /// Removes entities that don't satisfy `keep` from the selection.
+    pub(crate) fn retain<F: FnMut(Entity) -> bool>(&mut self, mut keep: F) {
+        self.entities.retain(|&entity| keep(entity));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::ecs::{Builder, World};
+
+    #[test]
+    fn keeps_selection_order_without_duplicates() {
+        let mut world = World::new();
+        let a = world.create_entity().build();
+        let b = world.create_entity().build();
+
+        let mut selection = EditorSelection::default();
+        selection.set(vec![b, a, b]);
+        assert_eq!(selection.entities(), &[b, a]);
+
+        selection.deselect(b);
+        selection.select(a);
+        assert_eq!(selection.entities(), &[a]);
+    }
+}
@@ -1,5 +1,6 @@
 use amethyst::ecs::world::Generation;
-use amethyst::ecs::Entity;
+use amethyst::ecs::{Entities, Entity};
+use crate::entity_map::EntityIdMap;
 use serde::ser::SerializeStruct;
 use serde::Serialize;
 use serde::Serializer;
@@ -76,10 +77,79 @@ impl From<SerializableEntity> for Entity {
     }
 }
 
-/// Secret struct for easy serialization/deserialization of `Entity` within
-/// `SerializableEntity`.
-#[derive(Debug, Clone, Copy, Deserialize)]
+/// The ID and generation of an entity, as sent to and from the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DeserializableEntity {
-    pub(crate) id: u32,
-    pub(crate) generation: i32,
+    pub id: u32,
+    pub generation: i32,
+}
+
+/// Reference to an entity in a message sent by the editor.
+///
+/// The editor may either address an entity directly by its ID and generation, by the
+/// stable ID provided by the registered [`EntityIdMapping`] component, or by the temporary ID it
+/// gave the entity when creating it. Temporary IDs can be used once the entities have been
+/// created, at the end of the frame that the `CreateEntities` message was received in.
+///
+/// [`EntityIdMapping`]: ./trait.EntityIdMapping.html
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IncomingEntity {
+    Entity(DeserializableEntity),
+    Mapped { mapped_id: u64 },
+    Temporary { temporary_id: u64 },
+}
+
+impl IncomingEntity {
+    /// Resolves the reference to a live entity.
+    ///
+    /// Returns `None` if the entity has a stale generation, or if there is no entity with
+    /// the given mapped or temporary ID.
+    pub(crate) fn resolve(self, entities: &Entities, id_map: &EntityIdMap) -> Option<Entity> {
+        match self {
+            IncomingEntity::Entity(entity_data) => {
+                let entity = entities.entity(entity_data.id);
+                if entity.gen().id() != entity_data.generation {
+                    debug!(
+                        "Entity {:?} had invalid generation {} (expected {})",
+                        entity_data,
+                        entity_data.generation,
+                        entity.gen().id()
+                    );
+                    return None;
+                }
+
+                Some(entity)
+            }
+
+            IncomingEntity::Mapped { mapped_id } => {
+                let entity = id_map.entity(mapped_id);
+                if entity.is_none() {
+                    debug!("No entity found with mapped ID {}", mapped_id);
+                }
+                entity
+            }
+
+            IncomingEntity::Temporary { temporary_id } => {
+                let entity = id_map
+                    .temporary(temporary_id)
+                    .filter(|&entity| entities.is_alive(entity));
+                if entity.is_none() {
+                    debug!("No entity found with temporary ID {}", temporary_id);
+                }
+                entity
+            }
+        }
+    }
+
+    /// Gets the ID of the referenced entity without checking its generation.
+    pub(crate) fn id(self, id_map: &EntityIdMap) -> Option<u32> {
+        match self {
+            IncomingEntity::Entity(entity_data) => Some(entity_data.id),
+            IncomingEntity::Mapped { mapped_id } => id_map.entity(mapped_id).map(|e| e.id()),
+            IncomingEntity::Temporary { temporary_id } => {
+                id_map.temporary(temporary_id).map(|e| e.id())
+            }
+        }
+    }
 }
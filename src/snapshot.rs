@@ -0,0 +1,84 @@
+use amethyst::ecs::{Entity, Join, World};
+use crate::registry::Registry;
+use crate::serializable_entity::SerializableEntity;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The state of all registered components and resources, serialized the same way as when it's
+/// sent to the editor.
+///
+/// Snapshots are created with [`SyncEditorBundle::capture_snapshot`], and allow tests and CI
+/// tooling to check the state of a world without connecting to an editor. Redacted fields are
+/// left out and deeply nested data is truncated, just as it would be for the editor.
+///
+/// [`SyncEditorBundle::capture_snapshot`]: ./struct.SyncEditorBundle.html#method.capture_snapshot
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Snapshot {
+    /// All entities in the world.
+    pub entities: Vec<SerializableEntity>,
+
+    /// The serialized components of each registered type, keyed by entity ID.
+    pub components: BTreeMap<&'static str, BTreeMap<u32, Value>>,
+
+    /// The serialized value of each registered resource that has been added to the world.
+    pub resources: BTreeMap<&'static str, Value>,
+}
+
+impl Snapshot {
+    pub(crate) fn capture(world: &World, registry: &Registry) -> Self {
+        let entities = (&*world.entities()).join().collect::<Vec<_>>();
+
+        let mut components = BTreeMap::new();
+        for access in registry.components() {
+            let options = registry.options(access.name());
+            let mut values = BTreeMap::new();
+            for &entity in &entities {
+                match access.serialize(world, entity) {
+                    Some(Ok(mut value)) => {
+                        options.prepare_outgoing(&mut value);
+                        values.insert(entity.id(), value);
+                    }
+                    Some(Err(error)) => error!(
+                        "Failed to serialize component of type {}: {}",
+                        access.name(),
+                        error
+                    ),
+                    None => {}
+                }
+            }
+            components.insert(access.name(), values);
+        }
+
+        let mut resources = BTreeMap::new();
+        for access in registry.resources() {
+            match access.serialize(world) {
+                Some(Ok(mut value)) => {
+                    registry.options(access.name()).prepare_outgoing(&mut value);
+                    resources.insert(access.name(), value);
+                }
+                Some(Err(error)) => error!(
+                    "Failed to serialize resource of type {}: {}",
+                    access.name(),
+                    error
+                ),
+                None => {}
+            }
+        }
+
+        Snapshot {
+            entities: entities.into_iter().map(Into::into).collect(),
+            components,
+            resources,
+        }
+    }
+
+    /// The serialized component of type `name` attached to `entity`, if any.
+    pub fn component(&self, name: &str, entity: Entity) -> Option<&Value> {
+        self.components.get(name)?.get(&entity.id())
+    }
+
+    /// The serialized value of the resource registered as `name`, if any.
+    pub fn resource(&self, name: &str) -> Option<&Value> {
+        self.resources.get(name)
+    }
+}
@@ -0,0 +1,180 @@
+//! The parts of the game state that the editor has subscribed to.
+//!
+//! By default everything is sent to the editor. Once the editor sends a `Subscribe` message,
+//! only the sections it listed are serialized and sent. Sections are named:
+//!
+//! * `"components"` and `"resources"` for all registered components or resources, or e.g.
+//!   `"components:Transform"` for the component registered as `"Transform"`.
+//! * `"log"` for log messages.
+//! * The type of any other message, e.g. `"profiler"`, or the name of a snapshot section, e.g.
+//!   `"physics"`.
+//!
+//! Handshakes and edit acknowledgements are always sent, since the protocol depends on them, as
+//! are crash reports. At [`SyncLevel::Minimal`], components, resources and snapshot sections
+//! aren't sent either way. The editor can also turn off syncing individual components and
+//! resources with a `DisableSync` message, e.g. for expensive types it doesn't need right now.
+//!
+//! [`SyncLevel::Minimal`]: ../enum.SyncLevel.html#variant.Minimal
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Messages that are sent regardless of the editor's subscriptions.
+const ALWAYS_SENT: &[&str] = &["handshake", "edit_ack", "transaction_ack", "panic"];
+
+/// How much of the game state is sent to the editor.
+///
+/// See [`SyncEditorBundle::sync_level`] for details.
+///
+/// [`SyncEditorBundle::sync_level`]: ./struct.SyncEditorBundle.html#method.sync_level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncLevel {
+    /// The full game state is sent.
+    Full,
+
+    /// Only the number of entities is sent with each snapshot, along with logs and other
+    /// messages. Components, resources and other snapshot sections aren't serialized at all.
+    Minimal,
+}
+
+impl Default for SyncLevel {
+    fn default() -> Self {
+        SyncLevel::Full
+    }
+}
+
+/// The sections the editor has subscribed to, shared between all systems that send data to it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Subscriptions {
+    // `None` until the editor subscribes to anything, meaning that everything is sent.
+    sections: Arc<RwLock<Option<Vec<String>>>>,
+
+    // Whether the sync level is `SyncLevel::Minimal`.
+    minimal: Arc<AtomicBool>,
+
+    // The names of the registered types that the editor turned off syncing for.
+    disabled: Arc<RwLock<HashSet<String>>>,
+}
+
+impl Subscriptions {
+    pub(crate) fn set_level(&self, level: SyncLevel) {
+        self.minimal
+            .store(level == SyncLevel::Minimal, Ordering::Relaxed);
+    }
+
+    /// Whether only the entity count, logs and messages are sent.
+    pub(crate) fn is_minimal(&self) -> bool {
+        self.minimal.load(Ordering::Relaxed)
+    }
+
+    /// Turns syncing the component or resource registered as `name` on or off.
+    pub(crate) fn set_enabled(&self, name: &str, enabled: bool) {
+        let mut disabled = self.disabled.write().expect("Subscriptions poisoned");
+        if enabled {
+            disabled.remove(name);
+        } else {
+            disabled.insert(name.to_owned());
+        }
+    }
+
+    /// Replaces the subscribed sections, or subscribes to everything if `sections` is `None`.
+    pub(crate) fn set(&self, sections: Option<Vec<String>>) {
+        *self.sections.write().expect("Subscriptions poisoned") = sections;
+    }
+
+    /// Whether the editor wants messages of type `message_type`, or the snapshot section with
+    /// that name.
+    pub(crate) fn wants(&self, section: &str) -> bool {
+        if ALWAYS_SENT.contains(&section) {
+            return true;
+        }
+
+        match *self.sections.read().expect("Subscriptions poisoned") {
+            Some(ref sections) => sections.iter().any(|subscribed| subscribed == section),
+            None => true,
+        }
+    }
+
+    /// Whether the editor wants the type registered as `name`, where `kind` is either
+    /// `"components"` or `"resources"`.
+    pub(crate) fn wants_type(&self, kind: &str, name: &str) -> bool {
+        if self.is_minimal()
+            || self
+                .disabled
+                .read()
+                .expect("Subscriptions poisoned")
+                .contains(name)
+        {
+            return false;
+        }
+
+        match *self.sections.read().expect("Subscriptions poisoned") {
+            Some(ref sections) => sections
+                .iter()
+                .any(|subscribed| match subscribed.find(':') {
+                    Some(index) => &subscribed[..index] == kind && &subscribed[index + 1..] == name,
+                    None => subscribed == kind,
+                }),
+            None => true,
+        }
+    }
+
+    /// Whether the editor wants the snapshot section with the given name.
+    pub(crate) fn wants_section(&self, section: &str) -> bool {
+        !self.is_minimal() && self.wants(section)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn everything_is_wanted_until_subscribed() {
+        let subscriptions = Subscriptions::default();
+        assert!(subscriptions.wants("log"));
+        assert!(subscriptions.wants_type("components", "Transform"));
+
+        subscriptions.set(Some(vec![
+            "components:Transform".to_owned(),
+            "resources".to_owned(),
+            "log".to_owned(),
+        ]));
+        assert!(subscriptions.wants("log"));
+        assert!(!subscriptions.wants("profiler"));
+        assert!(subscriptions.wants("edit_ack"));
+        assert!(subscriptions.wants_type("components", "Transform"));
+        assert!(!subscriptions.wants_type("components", "Named"));
+        assert!(subscriptions.wants_type("resources", "Time"));
+
+        subscriptions.set(None);
+        assert!(subscriptions.wants("profiler"));
+    }
+
+    #[test]
+    fn minimal_level_only_wants_messages() {
+        let subscriptions = Subscriptions::default();
+        subscriptions.set_level(SyncLevel::Minimal);
+        assert!(subscriptions.wants("log"));
+        assert!(subscriptions.wants("profiler"));
+        assert!(!subscriptions.wants_section("physics"));
+        assert!(!subscriptions.wants_type("components", "Transform"));
+
+        subscriptions.set_level(SyncLevel::Full);
+        assert!(subscriptions.wants_section("physics"));
+        assert!(subscriptions.wants_type("components", "Transform"));
+    }
+
+    #[test]
+    fn disabled_types_are_not_wanted() {
+        let subscriptions = Subscriptions::default();
+        subscriptions.set_enabled("Mesh", false);
+        assert!(!subscriptions.wants_type("components", "Mesh"));
+        assert!(subscriptions.wants_type("components", "Transform"));
+
+        subscriptions.set_enabled("Mesh", true);
+        assert!(subscriptions.wants_type("components", "Mesh"));
+    }
+}
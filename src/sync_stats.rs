@@ -0,0 +1,26 @@
+/// Statistics about the data sent to the editor, updated by the sender system every frame.
+///
+/// The bundle adds this resource to the world, so it can be read by the game's own systems,
+/// e.g. to display the state of the editor connection in a debug overlay.
+#[derive(Debug, Clone, Default)]
+pub struct SyncStats {
+    /// The number of packets sent to the editor.
+    pub packets_sent: u64,
+
+    /// The total size of the packets sent to the editor, in bytes.
+    pub bytes_sent: u64,
+
+    /// The number of times sending was retried because the OS send buffer was full.
+    pub send_retries: u64,
+
+    /// The number of packets that were dropped instead of being sent, either because the OS send
+    /// buffer stayed full or because sending failed.
+    pub packets_dropped: u64,
+
+    /// The number of packets waiting to be sent.
+    pub packets_queued: usize,
+
+    /// The number of times a component, resource or section was left out of a snapshot to keep
+    /// it within the byte budget.
+    pub types_deferred: u64,
+}
@@ -0,0 +1,98 @@
+use std::sync::{Arc, RwLock};
+
+/// Which component types are serialized and sent with each snapshot.
+///
+/// See [`SyncEditorBundle::sync_strategy`] for details.
+///
+/// [`SyncEditorBundle::sync_strategy`]: ./struct.SyncEditorBundle.html#method.sync_strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStrategy {
+    /// Every registered component type is sent with each snapshot.
+    Full,
+
+    /// Only the given number of component types are serialized for each snapshot, taking turns
+    /// through all registered component types.
+    RoundRobin(usize),
+}
+
+impl Default for SyncStrategy {
+    fn default() -> Self {
+        SyncStrategy::Full
+    }
+}
+
+/// The component types whose turn it is to be serialized, shared between the read systems and
+/// the sender system, which moves on to the next turn whenever it sends a snapshot.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SyncSchedule {
+    // `None` unless syncing round-robin, meaning that every type is serialized.
+    rotation: Arc<RwLock<Option<Rotation>>>,
+}
+
+#[derive(Debug)]
+struct Rotation {
+    names: Vec<&'static str>,
+    per_turn: usize,
+
+    // The index in `names` of the first type in the current turn.
+    start: usize,
+}
+
+impl SyncSchedule {
+    /// Takes turns through `names`, serializing `per_turn` of them for each snapshot.
+    pub(crate) fn round_robin(&self, names: Vec<&'static str>, per_turn: usize) {
+        *self.rotation.write().expect("Sync schedule poisoned") = Some(Rotation {
+            names,
+            per_turn: per_turn.max(1),
+            start: 0,
+        });
+    }
+
+    /// Whether only some of the types are serialized for each snapshot.
+    pub(crate) fn is_round_robin(&self) -> bool {
+        self.rotation
+            .read()
+            .expect("Sync schedule poisoned")
+            .is_some()
+    }
+
+    /// Whether the component type registered as `name` is serialized for the next snapshot.
+    pub(crate) fn is_due(&self, name: &str) -> bool {
+        let rotation = self.rotation.read().expect("Sync schedule poisoned");
+        let rotation = match *rotation {
+            Some(ref rotation) => rotation,
+            None => return true,
+        };
+
+        let count = rotation.names.len();
+        (0..rotation.per_turn.min(count))
+            .any(|offset| rotation.names[(rotation.start + offset) % count] == name)
+    }
+
+    /// Moves on to the next types, once a snapshot has been sent.
+    pub(crate) fn advance(&self) {
+        if let Some(ref mut rotation) = *self.rotation.write().expect("Sync schedule poisoned") {
+            if !rotation.names.is_empty() {
+                rotation.start = (rotation.start + rotation.per_turn) % rotation.names.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn takes_turns_through_types() {
+        let schedule = SyncSchedule::default();
+        assert!(schedule.is_due("A"));
+
+        schedule.round_robin(vec!["A", "B", "C"], 2);
+        assert!(schedule.is_due("A") && schedule.is_due("B") && !schedule.is_due("C"));
+        schedule.advance();
+        assert!(schedule.is_due("C") && schedule.is_due("A") && !schedule.is_due("B"));
+        schedule.advance();
+        assert!(schedule.is_due("B") && schedule.is_due("C") && !schedule.is_due("A"));
+    }
+}
@@ -0,0 +1,69 @@
+use amethyst::ecs::{DispatcherBuilder, System};
+
+/// The structure of the systems added by a [`SyncEditorBundle`], sent to the editor once in a
+/// `"system_graph"` message so that it can visualize execution order.
+///
+/// Systems are grouped into stages separated by barriers: all systems in a stage run after
+/// every system in the previous stage has finished. Within a stage, systems only wait on their
+/// dependencies.
+///
+/// [`SyncEditorBundle`]: ./struct.SyncEditorBundle.html
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct SystemGraph {
+    systems: Vec<SystemNode>,
+
+    #[serde(skip)]
+    stage: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SystemNode {
+    /// The name the system was registered with.
+    name: String,
+
+    dependencies: Vec<String>,
+
+    /// The stage that the system runs in, or `None` for systems that were reported by the game
+    /// and weren't added by the bundle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stage: Option<usize>,
+}
+
+impl SystemGraph {
+    /// Adds a system to the dispatcher and records it in the graph.
+    pub(crate) fn add<'a, 'b, S>(
+        &mut self,
+        dispatcher: &mut DispatcherBuilder<'a, 'b>,
+        system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        dispatcher.add(system, name, dependencies);
+        self.record(name.to_owned(), dependencies);
+    }
+
+    /// Adds a barrier to the dispatcher, starting a new stage.
+    pub(crate) fn add_barrier(&mut self, dispatcher: &mut DispatcherBuilder) {
+        dispatcher.add_barrier();
+        self.stage += 1;
+    }
+
+    /// Records a system that the game added itself.
+    pub(crate) fn report(&mut self, name: &str, dependencies: &[&str]) {
+        self.systems.push(SystemNode {
+            name: name.to_owned(),
+            dependencies: dependencies.iter().map(|&dep| dep.to_owned()).collect(),
+            stage: None,
+        });
+    }
+
+    fn record(&mut self, name: String, dependencies: &[&str]) {
+        self.systems.push(SystemNode {
+            name,
+            dependencies: dependencies.iter().map(|&dep| dep.to_owned()).collect(),
+            stage: Some(self.stage),
+        });
+    }
+}
@@ -0,0 +1,163 @@
+use amethyst::assets::{Asset, AssetStorage, Handle, HotReloadStrategy};
+use amethyst::ecs::{Join, Read, ReadStorage, System, Write};
+use crossbeam_channel::Receiver;
+use crate::asset_paths::AssetPaths;
+use crate::types::{EditStatus, EditorConnection};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// A request from the editor to reload the asset at `path`.
+pub(crate) struct AssetReload {
+    pub path: String,
+    pub request_id: Option<u64>,
+}
+
+/// Whether an asset has finished loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum LoadState {
+    Loading,
+    Loaded,
+}
+
+/// Entry in the list of assets sent to the editor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct AssetInfo {
+    id: u32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+
+    state: LoadState,
+
+    /// The number of entities with a handle to the asset.
+    users: usize,
+}
+
+/// Sent to the editor as an `"assets"` message whenever the assets of a type change.
+#[derive(Debug, Serialize)]
+struct AssetList<'a> {
+    kind: &'static str,
+    assets: &'a [AssetInfo],
+}
+
+/// A system that lists the assets of type `A` that are attached to entities, along with their
+/// paths and whether they've finished loading.
+///
+/// The list is only sent when it changes, since assets are loaded and unloaded rarely compared
+/// to how often the game state is sent.
+pub(crate) struct AssetSyncSystem<A> {
+    connection: EditorConnection,
+    last_sent: Option<Vec<AssetInfo>>,
+    resyncs: usize,
+    _marker: PhantomData<A>,
+}
+
+impl<A> AssetSyncSystem<A> {
+    pub(crate) fn new(connection: EditorConnection) -> Self {
+        AssetSyncSystem {
+            connection,
+            last_sent: None,
+            resyncs: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A: Asset> System<'a> for AssetSyncSystem<A> {
+    type SystemData = (
+        ReadStorage<'a, Handle<A>>,
+        Read<'a, AssetStorage<A>>,
+        Read<'a, AssetPaths>,
+    );
+
+    fn run(&mut self, (handles, storage, paths): Self::SystemData) {
+        // NOTE: The editor may have lost track of the assets while it was away, so the list is
+        // sent again once it's back.
+        if !self.connection.presence().is_active() {
+            self.last_sent = None;
+            return;
+        }
+
+        if self
+            .connection
+            .presence()
+            .resync_requested(&mut self.resyncs)
+        {
+            self.last_sent = None;
+        }
+
+        let mut users = BTreeMap::new();
+        for handle in (&handles).join() {
+            users.entry(handle.id()).or_insert((handle, 0)).1 += 1;
+        }
+
+        let assets = users
+            .into_iter()
+            .map(|(id, (handle, users))| AssetInfo {
+                id,
+                path: paths.get(handle).map(str::to_owned),
+                state: if storage.get(handle).is_some() {
+                    LoadState::Loaded
+                } else {
+                    LoadState::Loading
+                },
+                users,
+            })
+            .collect::<Vec<_>>();
+
+        if self.last_sent.as_ref() != Some(&assets) {
+            self.connection.send_message(
+                "assets",
+                AssetList {
+                    kind: A::NAME,
+                    assets: &assets,
+                },
+            );
+            self.last_sent = Some(assets);
+        }
+    }
+}
+
+/// A system that triggers a hot-reload of assets at the editor's request.
+///
+/// Amethyst reloads every asset whose file changed since it was loaded, so requesting a reload
+/// of one asset may reload others that changed too. Reloading requires the game to add a
+/// `HotReloadStrategy` resource, e.g. with `HotReloadBundle`.
+pub(crate) struct AssetReloadSystem {
+    receiver: Receiver<AssetReload>,
+    connection: EditorConnection,
+}
+
+impl AssetReloadSystem {
+    pub(crate) fn new(receiver: Receiver<AssetReload>, connection: EditorConnection) -> Self {
+        AssetReloadSystem {
+            receiver,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for AssetReloadSystem {
+    type SystemData = (Option<Write<'a, HotReloadStrategy>>, Read<'a, AssetPaths>);
+
+    fn run(&mut self, (mut strategy, paths): Self::SystemData) {
+        for AssetReload { path, request_id } in self.receiver.try_iter() {
+            match strategy {
+                Some(ref mut strategy) => {
+                    if !paths.contains_path(&path) {
+                        debug!("Reloading {:?}, which isn't a recorded asset path", path);
+                    }
+                    strategy.trigger();
+                    self.connection.send_ack(request_id, EditStatus::Applied);
+                }
+
+                None => {
+                    warn_once!("Can't reload assets without a `HotReloadStrategy` resource");
+                    self.connection
+                        .send_ack(request_id, EditStatus::MissingResource);
+                }
+            }
+        }
+    }
+}
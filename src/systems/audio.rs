@@ -0,0 +1,148 @@
+use amethyst::assets::Handle;
+use amethyst::audio::output::Output;
+use amethyst::audio::{AudioEmitter, AudioListener, AudioSink, Source};
+use amethyst::ecs::{Join, Read, ReadStorage, System};
+use crossbeam_channel::Receiver;
+use crate::types::{EditStatus, EditorConnection};
+use std::collections::HashSet;
+
+/// A request from the editor to change the volume of the game's audio sink.
+pub(crate) struct SetMasterVolume {
+    pub volume: f32,
+    pub request_id: Option<u64>,
+}
+
+/// The state of the audio sink that plays the game's music.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SinkState {
+    volume: f32,
+    paused: bool,
+
+    /// Whether the sink has anything left to play.
+    playing: bool,
+}
+
+/// Sent to the editor as an `"audio"` message whenever the audio state changes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct AudioState {
+    /// Whether an audio output device is available.
+    output: bool,
+
+    /// The `AudioSink` resource, if the game has added one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sink: Option<SinkState>,
+
+    emitters: usize,
+    listeners: usize,
+
+    /// The number of distinct sound sources attached to entities.
+    sources: usize,
+}
+
+/// A system that sends the state of the game's audio to the editor, so that sound issues can be
+/// debugged without attaching a debugger.
+///
+/// The state is only sent when it changes.
+pub(crate) struct AudioSyncSystem {
+    connection: EditorConnection,
+    last_sent: Option<AudioState>,
+    resyncs: usize,
+}
+
+impl AudioSyncSystem {
+    pub(crate) fn new(connection: EditorConnection) -> Self {
+        AudioSyncSystem {
+            connection,
+            last_sent: None,
+            resyncs: 0,
+        }
+    }
+}
+
+impl<'a> System<'a> for AudioSyncSystem {
+    type SystemData = (
+        Option<Read<'a, Output>>,
+        Option<Read<'a, AudioSink>>,
+        ReadStorage<'a, AudioEmitter>,
+        ReadStorage<'a, AudioListener>,
+        ReadStorage<'a, Handle<Source>>,
+    );
+
+    fn run(&mut self, (output, sink, emitters, listeners, sources): Self::SystemData) {
+        // NOTE: The editor may have lost track of the audio state while it was away, so it's
+        // sent again once the editor is back.
+        if !self.connection.presence().is_active() {
+            self.last_sent = None;
+            return;
+        }
+
+        if self
+            .connection
+            .presence()
+            .resync_requested(&mut self.resyncs)
+        {
+            self.last_sent = None;
+        }
+
+        let state = AudioState {
+            output: output.is_some(),
+            sink: sink.map(|sink| SinkState {
+                volume: sink.volume(),
+                paused: sink.is_paused(),
+                playing: !sink.empty(),
+            }),
+            emitters: (&emitters).join().count(),
+            listeners: (&listeners).join().count(),
+            sources: (&sources)
+                .join()
+                .map(Handle::id)
+                .collect::<HashSet<_>>()
+                .len(),
+        };
+
+        if self.last_sent.as_ref() != Some(&state) {
+            self.connection.send_message("audio", &state);
+            self.last_sent = Some(state);
+        }
+    }
+}
+
+/// A system that changes the volume of the game's audio sink at the editor's request.
+pub(crate) struct AudioVolumeSystem {
+    receiver: Receiver<SetMasterVolume>,
+    connection: EditorConnection,
+}
+
+impl AudioVolumeSystem {
+    pub(crate) fn new(receiver: Receiver<SetMasterVolume>, connection: EditorConnection) -> Self {
+        AudioVolumeSystem {
+            receiver,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for AudioVolumeSystem {
+    type SystemData = Option<Read<'a, AudioSink>>;
+
+    fn run(&mut self, sink: Self::SystemData) {
+        for SetMasterVolume { volume, request_id } in self.receiver.try_iter() {
+            match sink {
+                Some(ref sink) if volume.is_finite() && volume >= 0.0 => {
+                    sink.set_volume(volume);
+                    self.connection.send_ack(request_id, EditStatus::Applied);
+                }
+
+                Some(_) => {
+                    self.connection
+                        .send_ack(request_id, EditStatus::InvalidData);
+                }
+
+                None => {
+                    self.connection
+                        .send_ack(request_id, EditStatus::MissingResource);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use amethyst::core::Named;
+use amethyst::ecs::{Entities, Entity, Join, ReadStorage, System, Write};
+use crossbeam_channel::Receiver;
+use crate::bookmarks::Bookmarks;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::{EditStatus, EditorConnection};
+
+/// A request from the editor to bookmark an entity, or to remove a bookmark if `entity` is
+/// `None`.
+pub(crate) struct BookmarkEntity {
+    pub name: String,
+    pub entity: Option<Entity>,
+    pub request_id: Option<u64>,
+}
+
+/// Sent to the editor as a `"bookmarks"` message whenever the bookmarks change.
+#[derive(Debug, Serialize)]
+struct BookmarksMessage<'a> {
+    bookmarks: Vec<BookmarkEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct BookmarkEntry<'a> {
+    name: &'a str,
+
+    /// The bookmarked entity, or `None` while a named entity is waiting to be found again.
+    entity: Option<SerializableEntity>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    named: Option<&'a str>,
+}
+
+/// A system that keeps the [`Bookmarks`] resource in sync with the editor, and moves bookmarks
+/// of deleted entities to the entities that replace them.
+///
+/// [`Bookmarks`]: ../struct.Bookmarks.html
+pub(crate) struct BookmarkSystem {
+    receiver: Receiver<BookmarkEntity>,
+    connection: EditorConnection,
+    last_sent: Option<Bookmarks>,
+    resyncs: usize,
+}
+
+impl BookmarkSystem {
+    pub(crate) fn new(receiver: Receiver<BookmarkEntity>, connection: EditorConnection) -> Self {
+        BookmarkSystem {
+            receiver,
+            connection,
+            last_sent: None,
+            resyncs: 0,
+        }
+    }
+}
+
+impl<'a> System<'a> for BookmarkSystem {
+    type SystemData = (Entities<'a>, ReadStorage<'a, Named>, Write<'a, Bookmarks>);
+
+    fn run(&mut self, (entities, names, mut bookmarks): Self::SystemData) {
+        for request in self.receiver.try_iter() {
+            match request.entity {
+                Some(entity) => bookmarks.insert(request.name, entity),
+                None => bookmarks.remove(&request.name),
+            }
+            self.connection
+                .send_ack(request.request_id, EditStatus::Applied);
+        }
+
+        let mut lost = Vec::new();
+        for (name, bookmark) in bookmarks.entries_mut().iter_mut() {
+            match bookmark.entity {
+                // Remember the name of the entity while it's alive, so that it can be found
+                // again once it's been replaced.
+                Some(entity) if entities.is_alive(entity) => {
+                    if let Some(named) = names.get(entity) {
+                        if bookmark.named.as_ref().map(String::as_str) != Some(&*named.name) {
+                            bookmark.named = Some(named.name.to_string());
+                        }
+                    }
+                }
+
+                _ => match bookmark.named {
+                    Some(ref named) => {
+                        bookmark.entity = (&*entities, &names)
+                            .join()
+                            .find(|(_, candidate)| candidate.name == *named)
+                            .map(|(entity, _)| entity);
+                    }
+                    None => lost.push(name.clone()),
+                },
+            }
+        }
+        for name in lost {
+            bookmarks.remove(&name);
+        }
+
+        // NOTE: The editor may have lost track of the bookmarks while it was away, so they're
+        // sent again once the editor is back.
+        if !self.connection.presence().is_active() {
+            self.last_sent = None;
+            return;
+        }
+
+        if self
+            .connection
+            .presence()
+            .resync_requested(&mut self.resyncs)
+        {
+            self.last_sent = None;
+        }
+
+        if self.last_sent.as_ref() != Some(&*bookmarks) {
+            self.connection.send_message(
+                "bookmarks",
+                BookmarksMessage {
+                    bookmarks: bookmarks
+                        .entries()
+                        .iter()
+                        .map(|(name, bookmark)| BookmarkEntry {
+                            name,
+                            entity: bookmark.entity.map(Into::into),
+                            named: bookmark.named.as_ref().map(String::as_str),
+                        })
+                        .collect(),
+                },
+            );
+            self.last_sent = Some(bookmarks.clone());
+        }
+    }
+}
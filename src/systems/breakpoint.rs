@@ -0,0 +1,157 @@
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Entity, Join, LazyUpdate, Read, System, World};
+use crossbeam_channel::Receiver;
+use crate::query::FieldCondition;
+use crate::registry::Registry;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::EditorConnection;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+/// Changes to the set of breakpoints, requested by the editor.
+pub(crate) enum BreakpointMessage {
+    Set(Breakpoint),
+    Clear(u64),
+}
+
+/// A condition on a component that pauses the game when it becomes true.
+#[derive(Debug, Clone)]
+pub(crate) struct Breakpoint {
+    pub id: u64,
+    pub component: String,
+
+    /// The entity to check, or `None` to check every entity with the component.
+    pub entity: Option<Entity>,
+    pub condition: FieldCondition,
+
+    /// Whether the condition held the last time it was checked. Breakpoints only trigger when
+    /// their condition becomes true, so that the game can be resumed while it still holds.
+    pub triggered: bool,
+}
+
+/// Sent to the editor as a `"breakpoint"` message when a breakpoint triggers.
+#[derive(Debug, Serialize)]
+struct BreakpointHit {
+    id: u64,
+    entity: SerializableEntity,
+    value: Value,
+}
+
+/// A system that checks the breakpoints set by the editor after all edits have been applied,
+/// and pauses the game by setting the time scale to 0 when one of them triggers.
+///
+/// Breakpoints can target any registered component, so they're checked at the end of the
+/// frame, when they can access the world directly.
+pub(crate) struct BreakpointSystem {
+    receiver: Receiver<BreakpointMessage>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+    breakpoints: Arc<Mutex<Vec<Breakpoint>>>,
+}
+
+impl BreakpointSystem {
+    pub(crate) fn new(
+        receiver: Receiver<BreakpointMessage>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        BreakpointSystem {
+            receiver,
+            registry,
+            connection,
+            breakpoints: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl<'a> System<'a> for BreakpointSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        let mut breakpoints = self.breakpoints.lock().expect("Breakpoints poisoned");
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                BreakpointMessage::Set(breakpoint) => {
+                    if self.registry.component(&breakpoint.component).is_none() {
+                        debug!(
+                            "Can't set breakpoint on unregistered component {:?}",
+                            breakpoint.component
+                        );
+                    }
+                    breakpoints.retain(|existing| existing.id != breakpoint.id);
+                    breakpoints.push(breakpoint);
+                }
+
+                BreakpointMessage::Clear(id) => breakpoints.retain(|existing| existing.id != id),
+            }
+        }
+
+        if breakpoints.is_empty() {
+            return;
+        }
+
+        let shared = self.breakpoints.clone();
+        let registry = self.registry.clone();
+        let connection = self.connection.clone();
+        lazy.exec(move |world| {
+            let mut breakpoints = shared.lock().expect("Breakpoints poisoned");
+            let mut hit = false;
+            for breakpoint in breakpoints.iter_mut() {
+                let found = find_match(world, &registry, breakpoint);
+                let was_triggered = breakpoint.triggered;
+                breakpoint.triggered = found.is_some();
+                if was_triggered {
+                    continue;
+                }
+
+                if let Some((entity, value)) = found {
+                    info!("Breakpoint {} hit by {:?}", breakpoint.id, entity);
+                    connection.send_message(
+                        "breakpoint",
+                        BreakpointHit {
+                            id: breakpoint.id,
+                            entity: entity.into(),
+                            value,
+                        },
+                    );
+                    hit = true;
+                }
+            }
+
+            if hit {
+                match world.res.try_fetch_mut::<Time>() {
+                    Some(mut time) => time.set_time_scale(0.0),
+                    None => {
+                        warn!("Can't pause the game for a breakpoint without a `Time` resource")
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Finds an entity whose component matches the breakpoint's condition, returning the entity and
+/// the value of the compared field.
+///
+/// Redacted fields are removed before the condition is checked, so they never match.
+fn find_match(
+    world: &World,
+    registry: &Registry,
+    breakpoint: &Breakpoint,
+) -> Option<(Entity, Value)> {
+    let access = registry.component(&breakpoint.component)?;
+    let redaction = registry.redaction(access.name());
+    let check = |entity: Entity| {
+        let mut value = access.serialize(world, entity)?.ok()?;
+        if let Some(ref redaction) = redaction {
+            redaction.apply(&mut value);
+        }
+        let field = breakpoint.condition.field(&value)?.clone();
+        Some((entity, field))
+    };
+
+    match breakpoint.entity {
+        Some(entity) => check(entity),
+        None => (&*world.entities()).join().filter_map(check).next(),
+    }
+}
@@ -0,0 +1,214 @@
+use amethyst::ecs::{Read, System};
+use amethyst::shred::Resource;
+use crossbeam_channel::Receiver;
+use crate::resource_chunks::ResourceChunker;
+use crate::types::{EditStatus, EditorConnection};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// A request from the editor for the chunks with the given IDs, replacing any chunks requested
+/// before.
+pub(crate) struct ChunkRequest {
+    pub ids: Vec<Value>,
+    pub request_id: Option<u64>,
+}
+
+/// Sent to the editor as a `"resource_chunk_index"` message whenever the set of chunks changes.
+#[derive(Debug, Serialize)]
+struct ChunkIndex<'a, I: 'a> {
+    name: &'static str,
+    chunks: &'a [I],
+}
+
+/// Sent to the editor as a `"resource_chunks"` message with the requested chunks that changed.
+#[derive(Debug, Serialize)]
+struct ChunkUpdate<'a, I: 'a> {
+    name: &'static str,
+    chunks: Vec<ChunkData<'a, I>>,
+
+    /// Requested chunks that no longer exist.
+    removed: Vec<&'a I>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChunkData<'a, I: 'a> {
+    id: &'a I,
+    data: &'a Value,
+}
+
+/// A system that sends the chunks of a resource that the editor requested, rather than the
+/// whole resource.
+///
+/// Each requested chunk is only sent again once its data changes. Chunks aren't sent at all
+/// until the editor requests them, but the IDs of all chunks are sent whenever they change so
+/// that the editor knows what it can request.
+pub(crate) struct ChunkedResourceSystem<T, C: ResourceChunker<T>> {
+    name: &'static str,
+    chunker: C,
+    receiver: Receiver<ChunkRequest>,
+    connection: EditorConnection,
+    requested: Vec<C::Id>,
+    sent: HashMap<C::Id, Value>,
+    sent_index: Option<Value>,
+    resyncs: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T, C: ResourceChunker<T>> ChunkedResourceSystem<T, C> {
+    pub(crate) fn new(
+        name: &'static str,
+        chunker: C,
+        receiver: Receiver<ChunkRequest>,
+        connection: EditorConnection,
+    ) -> Self {
+        ChunkedResourceSystem {
+            name,
+            chunker,
+            receiver,
+            connection,
+            requested: Vec::new(),
+            sent: HashMap::new(),
+            sent_index: None,
+            resyncs: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replaces the requested chunks, returning the status to acknowledge the request with.
+    fn request(&mut self, ids: Vec<Value>) -> EditStatus {
+        let ids = match ids
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<C::Id>, _>>()
+        {
+            Ok(ids) => ids,
+            Err(error) => {
+                debug!("Invalid chunk IDs for resource {:?}: {}", self.name, error);
+                return EditStatus::InvalidData;
+            }
+        };
+
+        // NOTE: Chunks that are requested again are sent again, in case the editor discarded
+        // them since they were first requested.
+        self.sent.clear();
+        self.requested = ids;
+        EditStatus::Applied
+    }
+}
+
+impl<'a, T, C> System<'a> for ChunkedResourceSystem<T, C>
+where
+    T: Resource,
+    C: ResourceChunker<T>,
+{
+    type SystemData = Option<Read<'a, T>>;
+
+    fn run(&mut self, resource: Self::SystemData) {
+        let requests = self.receiver.try_iter().collect::<Vec<_>>();
+        let resource = match resource {
+            Some(resource) => resource,
+            None => {
+                warn_once!(
+                    "Resource named {:?} wasn't registered and will not show up in the editor",
+                    self.name
+                );
+                for request in requests {
+                    self.connection
+                        .send_ack(request.request_id, EditStatus::MissingResource);
+                }
+                return;
+            }
+        };
+
+        for ChunkRequest { ids, request_id } in requests {
+            let status = self.request(ids);
+            self.connection.send_ack(request_id, status);
+        }
+
+        // NOTE: The editor may have lost track of the chunks while it was away, so everything
+        // is sent again once it's back.
+        if !self.connection.presence().is_active() {
+            self.sent.clear();
+            self.sent_index = None;
+            return;
+        }
+
+        if self
+            .connection
+            .presence()
+            .resync_requested(&mut self.resyncs)
+        {
+            self.sent.clear();
+            self.sent_index = None;
+        }
+
+        let ids = self.chunker.chunk_ids(&resource);
+        match serde_json::to_value(&ids) {
+            Ok(index) => {
+                if self.sent_index.as_ref() != Some(&index) {
+                    self.connection.send_message(
+                        "resource_chunk_index",
+                        ChunkIndex {
+                            name: self.name,
+                            chunks: &ids,
+                        },
+                    );
+                    self.sent_index = Some(index);
+                }
+            }
+            Err(error) => error!(
+                "Failed to serialize chunk IDs of resource {}: {}",
+                self.name, error
+            ),
+        }
+
+        let mut changed = Vec::new();
+        let mut removed = Vec::new();
+        for id in &self.requested {
+            let chunk = match self.chunker.chunk(&resource, id) {
+                Some(chunk) => chunk,
+                None => {
+                    if self.sent.remove(id).is_some() {
+                        removed.push(id);
+                    }
+                    continue;
+                }
+            };
+
+            match serde_json::to_value(&chunk) {
+                Ok(data) => {
+                    if self.sent.get(id) != Some(&data) {
+                        self.sent.insert(id.clone(), data);
+                        changed.push(id);
+                    }
+                }
+                Err(error) => error!(
+                    "Failed to serialize chunk of resource {}: {}",
+                    self.name, error
+                ),
+            }
+        }
+
+        if changed.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        let sent = &self.sent;
+        let chunks = changed
+            .into_iter()
+            .map(|id| ChunkData {
+                id,
+                data: &sent[id],
+            })
+            .collect();
+        self.connection.send_message(
+            "resource_chunks",
+            ChunkUpdate {
+                name: self.name,
+                chunks,
+                removed,
+            },
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use amethyst::ecs::{Builder, Entity, LazyUpdate, Read, System};
+use crossbeam_channel::Receiver;
+use crate::dynamic_components::DynamicComponentStore;
+use crate::registry::Registry;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::{EditStatus, EditorConnection};
+use std::sync::Arc;
+
+/// A request from the editor to duplicate a set of entities.
+pub(crate) struct CloneEntities {
+    pub entities: Vec<Entity>,
+    pub request_id: Option<u64>,
+}
+
+/// Sent to the editor as an `"entities_cloned"` message once the entities have been duplicated.
+#[derive(Debug, Serialize)]
+struct ClonedEntities {
+    clones: Vec<ClonedEntity>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClonedEntity {
+    source: SerializableEntity,
+    clone: SerializableEntity,
+}
+
+/// A system that duplicates entities at the editor's request.
+///
+/// Every component that the editor can edit is copied onto the new entities by serializing and
+/// deserializing it, so components don't need to implement `Clone`. Read-only components can't
+/// be deserialized, and are left out. Since the copy needs to visit every registered component
+/// type, it's deferred until the end of the frame, when it can access the world directly.
+pub(crate) struct CloneEntitiesSystem {
+    receiver: Receiver<CloneEntities>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+}
+
+impl CloneEntitiesSystem {
+    pub(crate) fn new(
+        receiver: Receiver<CloneEntities>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        CloneEntitiesSystem {
+            receiver,
+            registry,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for CloneEntitiesSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        while let Ok(CloneEntities {
+            entities,
+            request_id,
+        }) = self.receiver.try_recv()
+        {
+            let registry = self.registry.clone();
+            let connection = self.connection.clone();
+            lazy.exec_mut(move |world| {
+                let mut clones = Vec::with_capacity(entities.len());
+                for source in entities {
+                    // NOTE: The source may have been deleted by the game since the request was
+                    // received, in which case it's left out of the response.
+                    if !world.is_alive(source) {
+                        continue;
+                    }
+
+                    let clone = world.create_entity().build();
+                    for access in registry.writable_components() {
+                        if let Some(Err(error)) = access.clone_onto(world, source, clone) {
+                            error!(
+                                "Failed to copy component {} onto cloned entity: {}",
+                                access.name(),
+                                error
+                            );
+                        }
+                    }
+
+                    if let Some(mut store) = world.res.try_fetch_mut::<DynamicComponentStore>() {
+                        store.clone_entity(source, clone);
+                    }
+
+                    clones.push(ClonedEntity {
+                        source: source.into(),
+                        clone: clone.into(),
+                    });
+                }
+
+                connection.send_message("entities_cloned", ClonedEntities { clones, request_id });
+                connection.send_ack(request_id, EditStatus::Applied);
+            });
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use amethyst::ecs::{LazyUpdate, Read, System, World};
+use crossbeam_channel::Receiver;
+use crate::types::EditorConnection;
+use std::sync::Arc;
+
+/// A function that evaluates input typed into the editor's console, returning the output to
+/// show in the editor.
+pub(crate) type ConsoleHandler = Arc<dyn Fn(&mut World, &str) -> String + Send + Sync>;
+
+/// A request from the editor to evaluate console input.
+pub(crate) struct ConsoleInput {
+    pub input: String,
+    pub request_id: Option<u64>,
+}
+
+/// Sent back to the editor with the output of the console handler.
+#[derive(Debug, Clone, Serialize)]
+struct ConsoleResult {
+    input: String,
+    output: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// A system that passes console input from the editor on to the game's console handler.
+///
+/// The handler is given mutable access to the whole world, so it runs when the world is
+/// maintained at the end of the frame rather than during the dispatch.
+pub(crate) struct ConsoleSystem {
+    receiver: Receiver<ConsoleInput>,
+    handler: ConsoleHandler,
+    connection: EditorConnection,
+}
+
+impl ConsoleSystem {
+    pub(crate) fn new(
+        receiver: Receiver<ConsoleInput>,
+        handler: ConsoleHandler,
+        connection: EditorConnection,
+    ) -> Self {
+        ConsoleSystem {
+            receiver,
+            handler,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for ConsoleSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        for ConsoleInput { input, request_id } in self.receiver.try_iter() {
+            debug!("Evaluating console input {:?}", input);
+
+            let handler = self.handler.clone();
+            let connection = self.connection.clone();
+            lazy.exec_mut(move |world| {
+                let output = handler(world, &input);
+                connection.send_message(
+                    "console_result",
+                    ConsoleResult {
+                        input,
+                        output,
+                        request_id,
+                    },
+                );
+            });
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use amethyst::ecs::System;
+use crate::debug_draw::DebugShape;
+use crate::types::{EditorConnection, SerializedData};
+
+/// The `"debug_draw"` section of the snapshot.
+#[derive(Serialize)]
+struct DebugDrawSection {
+    shapes: Vec<DebugShape>,
+}
+
+/// A system that sends the debug shapes drawn through [`EditorConnection`] during the frame.
+///
+/// The shapes are sent as a section of the snapshot rather than as a message, so that when
+/// several frames pass between snapshots only the shapes of the latest frame are sent.
+///
+/// [`EditorConnection`]: ./struct.EditorConnection.html
+pub(crate) struct DebugDrawSystem {
+    connection: EditorConnection,
+}
+
+impl DebugDrawSystem {
+    pub(crate) fn new(connection: EditorConnection) -> Self {
+        DebugDrawSystem { connection }
+    }
+}
+
+impl<'a> System<'a> for DebugDrawSystem {
+    type SystemData = ();
+
+    fn run(&mut self, (): Self::SystemData) {
+        let section = DebugDrawSection {
+            shapes: self.connection.debug_draw().take(),
+        };
+        if !self.connection.presence().is_active()
+            || !self.connection.subscriptions().wants_section("debug_draw")
+        {
+            return;
+        }
+
+        match serde_json::value::to_raw_value(&section) {
+            Ok(serialized) => self
+                .connection
+                .send_serialized(SerializedData::Section("debug_draw", serialized)),
+            Err(error) => error!("Failed to serialize debug shapes: {}", error),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use amethyst::ecs::{BitSet, Entities, LazyUpdate, Read, System};
+use crossbeam_channel::Receiver;
+use crate::dynamic_components::DynamicComponentStore;
+use crate::mask;
+use crate::types::{
+    ComponentEntry, EditStatus, EditorConnection, IncomingComponent, SerializedComponent,
+    SerializedData,
+};
+use std::collections::HashMap;
+
+/// An edit from the editor to a component in the [`DynamicComponentStore`].
+pub(crate) struct DynamicComponentEdit {
+    pub name: String,
+    pub component: IncomingComponent,
+}
+
+/// A system that sends the components in the [`DynamicComponentStore`] to the editor, as if
+/// each name was a registered component type.
+pub(crate) struct DynamicComponentSyncSystem {
+    connection: EditorConnection,
+
+    // NOTE: Serialized components are identified by a `&'static str` everywhere else, so the
+    // name of each dynamic component is leaked once, the first time it's seen. Games only use
+    // a handful of distinct names, so this doesn't grow in practice.
+    names: HashMap<String, &'static str>,
+}
+
+impl DynamicComponentSyncSystem {
+    pub(crate) fn new(connection: EditorConnection) -> Self {
+        DynamicComponentSyncSystem {
+            connection,
+            names: HashMap::new(),
+        }
+    }
+
+    fn intern(&mut self, name: &str) -> &'static str {
+        if let Some(&interned) = self.names.get(name) {
+            return interned;
+        }
+
+        let interned: &'static str = Box::leak(name.to_owned().into_boxed_str());
+        self.names.insert(name.to_owned(), interned);
+        interned
+    }
+}
+
+impl<'a> System<'a> for DynamicComponentSyncSystem {
+    type SystemData = (Entities<'a>, Read<'a, DynamicComponentStore>);
+
+    fn run(&mut self, (entities, store): Self::SystemData) {
+        if !self.connection.presence().is_active() {
+            return;
+        }
+
+        for name in store.names() {
+            if !self
+                .connection
+                .subscriptions()
+                .wants_type("components", name)
+            {
+                continue;
+            }
+
+            let mut mask = BitSet::new();
+            let data = store
+                .iter(name)
+                .filter(|&(entity, _)| entities.is_alive(entity))
+                .map(|(entity, value)| {
+                    mask.add(entity.id());
+                    ComponentEntry {
+                        entity: entity.into(),
+                        data: value,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let name = self.intern(name);
+            let (data, page) = self.connection.pages().paginate(name, &data);
+            let serialized = serde_json::value::to_raw_value(&SerializedComponent {
+                name,
+                category: None,
+                data,
+                page,
+            });
+            match serialized {
+                Ok(serialized) => self.connection.send_serialized(SerializedData::Component(
+                    name,
+                    serialized,
+                    data.len(),
+                    mask,
+                )),
+                Err(error) => error!("Failed to serialize dynamic component {}: {}", name, error),
+            }
+        }
+    }
+}
+
+/// A system that applies edits from the editor to the [`DynamicComponentStore`].
+pub(crate) struct DynamicComponentWriteSystem {
+    receiver: Receiver<DynamicComponentEdit>,
+    connection: EditorConnection,
+}
+
+impl DynamicComponentWriteSystem {
+    pub(crate) fn new(
+        receiver: Receiver<DynamicComponentEdit>,
+        connection: EditorConnection,
+    ) -> Self {
+        DynamicComponentWriteSystem {
+            receiver,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for DynamicComponentWriteSystem {
+    // NOTE: The store is only borrowed when edits are applied, for the same reason as in
+    // `DeferredWriteComponentSystem`.
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        let edits: Vec<_> = self.receiver.try_iter().collect();
+        if edits.is_empty() {
+            return;
+        }
+
+        let connection = self.connection.clone();
+        lazy.exec(move |world| {
+            let mut store = world.write_resource::<DynamicComponentStore>();
+            for DynamicComponentEdit { name, component } in edits {
+                let request_id = component.request_id;
+                let status = apply_edit(&mut store, &name, component);
+                connection.send_ack(request_id, status);
+            }
+        });
+    }
+}
+
+/// Applies a single edit from the editor to the store, returning the status to acknowledge the
+/// edit with.
+fn apply_edit(
+    store: &mut DynamicComponentStore,
+    name: &str,
+    component: IncomingComponent,
+) -> EditStatus {
+    debug!(
+        "Got incoming message for dynamic component {}: {:?}",
+        name, component.data
+    );
+
+    if !store.contains_name(name) {
+        return EditStatus::UnknownType;
+    }
+
+    // NOTE: Every entity is checked before any are changed, so that edits to several entities
+    // are applied atomically.
+    if component
+        .entities
+        .iter()
+        .any(|&entity| store.get(entity, name).is_none())
+    {
+        return EditStatus::MissingComponent;
+    }
+
+    for &entity in &component.entities {
+        if let Some(current) = store.get_mut(entity, name) {
+            *current = if component.patch {
+                mask::merge(current, component.data.clone(), &[])
+            } else {
+                component.data.clone()
+            };
+        }
+    }
+    EditStatus::Applied
+}
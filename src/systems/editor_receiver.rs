@@ -1,168 +1,1093 @@
-use amethyst::ecs::{Entities, System};
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Entities, Entity, Read, System, Write};
 use crossbeam_channel::Sender;
-use std::io;
-use std::net::UdpSocket;
-use std::str;
-use crate::types::{ComponentMap, EntityMessage, IncomingComponent, IncomingMessage, ResourceMap};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::auth::{AuthSession, AuthToken};
+use crate::capabilities::Capabilities;
+use crate::dynamic_components::DynamicComponentStore;
+use crate::message_reader::MessageReader;
+use crate::connection_status::EditorConnectionStatus;
+use crate::entity_map::EntityIdMap;
+use crate::handshake::Handshake;
+use crate::registry::Registry;
+use crate::serializable_entity::IncomingEntity;
+#[cfg(feature = "physics")]
+use crate::systems::PhysicsCommand;
+#[cfg(feature = "renderer")]
+use crate::systems::PickRequest;
+use crate::systems::{
+    AssetReload, BookmarkEntity, Breakpoint, BreakpointMessage, ChunkRequest, CloneEntities,
+    ConsoleInput, DynamicComponentEdit, EntityLimits, EntityQuery, GizmoMessage, HistoryRequest,
+    OrphanRequest, Pin, PinMessage, PrefabSpawn, SceneExport, SetMasterVolume, SetSelection,
+    TimeControl, Watch, WatchMessage, create_entities,
+};
+use crate::transport::Transport;
+use crate::unknown_type::{TypeKind, UnknownTypes};
+use crate::types::{
+    ChannelMap, ComponentMap, EditStatus, EditorConnection, EntityMessage, IncomingComponent,
+    IncomingMessage, IncomingResource, ResourceMap,
+};
 
-/// The system in charge of reading and dispatching incoming messages from
-/// the editor.
-pub struct EditorReceiverSystem {
-    socket: UdpSocket,
+/// The longest send interval the editor can ask for, in milliseconds. Longer intervals are
+/// rejected, since they would stop the state from being sent for the rest of the session anyway.
+const MAX_SEND_INTERVAL_MS: u64 = 60 * 60 * 1000;
 
+/// Channels used to pass incoming messages on to the systems that handle them.
+pub(crate) struct MessageRoutes {
     // Map containing channels used to send incoming serialized component/resource data from the
     // editor. Incoming data is sent to specialized systems that deserialize the data and update
     // the corresponding local data.
-    component_map: ComponentMap,
-    resource_map: ResourceMap,
-    entity_handler: Sender<EntityMessage>,
-    incoming_buffer: Vec<u8>,
+    pub component_map: ComponentMap,
+    pub resource_map: ResourceMap,
+    pub chunk_map: ChannelMap<ChunkRequest>,
+
+    // IDs of the types registered for debug builds only, which were left out of this build.
+    pub dev_only_components: HashSet<&'static str>,
+    pub dev_only_resources: HashSet<&'static str>,
+
+    pub entity_handler: Sender<EntityMessage>,
+    pub scene_export: Sender<SceneExport>,
+    pub clone_entities: Sender<CloneEntities>,
+    pub selection: Sender<SetSelection>,
+    pub bookmarks: Sender<BookmarkEntity>,
+    pub query: Sender<EntityQuery>,
+    pub orphans: Sender<OrphanRequest>,
+    pub watch: Sender<WatchMessage>,
+    pub pin: Sender<PinMessage>,
+    pub breakpoint: Sender<BreakpointMessage>,
+    pub time_control: Sender<TimeControl>,
+    pub gizmo: Sender<GizmoMessage>,
+    pub send_interval: Sender<Duration>,
+    pub rate_limit: Sender<Option<u64>>,
+    pub asset_reload: Sender<AssetReload>,
+
+    #[cfg(feature = "renderer")]
+    pub picking: Sender<PickRequest>,
+
+    // Only present if a prefab type has been registered for spawning.
+    pub prefab_spawn: Option<Sender<PrefabSpawn>>,
+
+    // Only present if a console handler has been set.
+    pub console: Option<Sender<ConsoleInput>>,
+
+    // Only present if dynamic components are synced.
+    pub dynamic_components: Option<Sender<DynamicComponentEdit>>,
+
+    // Only present if audio is synced.
+    pub audio_volume: Option<Sender<SetMasterVolume>>,
+
+    // Only present if history is enabled.
+    pub history: Option<Sender<HistoryRequest>>,
+
+    // Only present if physics is synced.
+    #[cfg(feature = "physics")]
+    pub physics: Option<Sender<PhysicsCommand>>,
+}
+
+/// The system in charge of reading and dispatching incoming messages from
+/// the editor.
+pub struct EditorReceiverSystem {
+    transport: Transport,
+    routes: MessageRoutes,
+    connection: EditorConnection,
+    handshake: Handshake,
+    unknown_types: UnknownTypes,
+
+    // The registered types, which the IDs in the editor's sync toggles and page requests are
+    // checked against.
+    registry: Arc<Registry>,
+
+    // How long the editor can go without sending anything before we stop sending state to it,
+    // if heartbeats are enabled.
+    heartbeat_timeout: Option<Duration>,
+
+    // Checked when creating the entities of a transaction, which bypasses the entity handler.
+    entity_limits: EntityLimits,
+
+    // Whether the editor has sent the token required before its commands are accepted, if one
+    // is required.
+    auth: AuthSession,
+
+    reader: MessageReader,
 }
 
 impl EditorReceiverSystem {
-    pub fn new(
-        component_map: ComponentMap,
-        resource_map: ResourceMap,
-        entity_handler: Sender<EntityMessage>,
-        socket: UdpSocket,
+    pub(crate) fn new(
+        routes: MessageRoutes,
+        connection: EditorConnection,
+        handshake: Handshake,
+        unknown_types: UnknownTypes,
+        registry: Arc<Registry>,
+        heartbeat_timeout: Option<Duration>,
+        entity_limits: EntityLimits,
+        transport: Transport,
     ) -> EditorReceiverSystem {
-        // Create the socket used for communicating with the editor.
-        //
-        // NOTE: We set the socket to nonblocking so that we don't block if there are no incoming
-        // messages to read. We `expect` on the call to `set_nonblocking` because the game will
-        // hang if the socket is still set to block when the game runs.
         EditorReceiverSystem {
-            socket,
-            component_map,
-            resource_map,
-            entity_handler,
-            incoming_buffer: Vec::with_capacity(1024),
+            transport,
+            routes,
+            connection,
+            handshake,
+            unknown_types,
+            registry,
+            heartbeat_timeout,
+            entity_limits,
+            auth: AuthSession::default(),
+            reader: MessageReader::new(),
         }
     }
+
+    /// Ignores all commands from the editor until it has sent `token` in an `Authenticate`
+    /// message.
+    pub(crate) fn require_auth_token(&mut self, token: AuthToken) {
+        self.auth.require(token);
+    }
+
+    /// Sets whether every packet from the editor starts with a chunk header, in which case
+    /// messages are reassembled from their chunks regardless of the order they arrive in.
+    pub(crate) fn reassemble_chunks(&mut self, enabled: bool) {
+        self.reader.reassemble_chunks(enabled);
+    }
+
+    /// Sets the size of the buffer that each packet from the editor is read into.
+    pub(crate) fn receive_buffer_size(&mut self, size: usize) {
+        self.reader.receive_buffer_size(size);
+    }
 }
 
 impl<'a> System<'a> for EditorReceiverSystem {
-    type SystemData = Entities<'a>;
+    type SystemData = (
+        Entities<'a>,
+        Write<'a, EntityIdMap>,
+        Read<'a, Time>,
+        Write<'a, EditorConnectionStatus>,
+        Option<Read<'a, DynamicComponentStore>>,
+    );
 
-    fn run(&mut self, entities: Self::SystemData) {
-        let editor_address = ([127, 0, 0, 1], 8000).into();
+    fn run(&mut self, (entities, mut id_map, time, mut status, dynamic): Self::SystemData) {
+        // NOTE: The receiver is the first of the bundle's systems to run each frame. Logs written
+        // by game systems that run before it are tagged with the previous frame.
+        self.connection.set_frame(time.frame_number());
 
         // Read any incoming messages from the editor process.
-        let mut buf = [0; 1024];
-        loop {
-            // TODO: Verify that the incoming address matches the editor process address.
-            let (bytes_read, addr) = match self.socket.recv_from(&mut buf[..]) {
-                Ok(res) => res,
-                Err(error) => {
-                    match error.kind() {
-                        // If the read would block, it means that there was no incoming data and we
-                        // should break from the loop.
-                        io::ErrorKind::WouldBlock => break,
-
-                        // This is an "error" that happens on Windows if no editor is running to
-                        // receive the state update we just sent. The OS gives a "connection was
-                        // forcibly closed" error when no socket receives the message, but we
-                        // don't care if that happens (in fact, we use UDP specifically so that
-                        // we can broadcast messages without worrying about establishing a
-                        // connection).
-                        io::ErrorKind::ConnectionReset => continue,
-
-                        // All other error kinds should be indicative of a genuine error. For our
-                        // purposes we still want to ignore them, but we'll at least log a warning
-                        // in case it helps debug an issue.
-                        _ => {
-                            warn!("Error reading incoming: {:?}", error);
-                            continue;
-                        }
+        let editor_address = self.transport.editor_address();
+        let was_connected = status.is_connected();
+        self.reader
+            .read_packets(&mut self.transport, || status.heard_from(editor_address));
+
+        // An editor that was silent for longer than the connection timeout may have been
+        // replaced by another process, so it starts a new session that has to be authenticated
+        // again.
+        if !was_connected && status.is_connected() {
+            self.auth.end();
+        }
+
+        // Start or stop sending state depending on whether the editor is still sending heartbeats.
+        // When the editor comes back it may have lost track of the game entirely, so the
+        // handshake is sent again.
+        if let Some(timeout) = self.heartbeat_timeout {
+            status.set_timeout(timeout);
+            let connected = status.is_connected();
+            let presence = self.connection.presence();
+            if connected != presence.is_active() {
+                presence.set_active(connected);
+                if connected {
+                    info!("Editor connected from {:?}", status.editor_address());
+
+                    // NOTE: Editors that have to authenticate get the handshake once they have.
+                    if !self.auth.is_required() {
+                        self.connection.send_message("handshake", &self.handshake);
                     }
+                } else {
+                    info!("Editor timed out, pausing sync until it reconnects");
+                    self.auth.end();
                 }
+            }
+        }
+
+        // Handle the messages completed by the packets that were just read.
+        let framing = self.transport.framing();
+        let instance = self.handshake.instance.as_ref().map(String::as_str);
+        let dynamic = dynamic.as_ref().map(|store| &**store);
+        for message in self.reader.messages(framing, instance) {
+            debug!("Message: {:#?}", message);
+            match message {
+                ref message if !self.auth.accepts(message) => {
+                    warn_once!("Ignoring commands from unauthenticated editor");
+                    debug!(
+                        "Ignoring message from unauthenticated editor: {:?}",
+                        message
+                    );
+                }
+                IncomingMessage::Transaction { ops, request_id } => {
+                    self.handle_transaction(ops, request_id, &entities, &mut id_map, dynamic)
+                }
+                message => self.handle_message(message, &entities, &id_map, dynamic),
+            }
+        }
+
+        status.record_errors(self.unknown_types.take_errors());
+    }
+}
+
+impl EditorReceiverSystem {
+    /// Negotiates the capabilities listed by the editor, and answers with the game's handshake.
+    fn send_handshake(&mut self, capabilities: Option<Vec<String>>) {
+        if let Some(names) = capabilities {
+            let editor = Capabilities::from_names(names.iter().map(String::as_str));
+            let negotiated = self.handshake.capabilities.intersection(editor);
+            debug!("Negotiated editor capabilities: {:?}", negotiated);
+            self.connection.capabilities().set(negotiated);
+        }
+        self.connection.send_message("handshake", &self.handshake);
+    }
+
+    /// Passes the operations of a transaction on to the systems that handle them, which all run
+    /// later in the same frame.
+    ///
+    /// Entities are created right away rather than by the entity handler, so that the following
+    /// operations can address them by their temporary IDs.
+    fn handle_transaction(
+        &mut self,
+        mut ops: Vec<IncomingMessage>,
+        request_id: Option<u64>,
+        entities: &Entities,
+        id_map: &mut EntityIdMap,
+        dynamic: Option<&DynamicComponentStore>,
+    ) {
+        // NOTE: Every operation needs a unique request ID so that its acknowledgement can be
+        // matched to the transaction, including those that are rejected.
+        let transactions = self.connection.transactions().clone();
+        let mut ids = Vec::with_capacity(ops.len());
+        for op in &mut ops {
+            let id = match op.operation_request_id() {
+                Some(op_request_id) => match *op_request_id {
+                    Some(id) if !ids.contains(&id) => id,
+                    _ => {
+                        let id = transactions.generate_id();
+                        *op_request_id = Some(id);
+                        id
+                    }
+                },
+                None => transactions.generate_id(),
             };
+            ids.push(id);
+        }
+        if let Some(ack) = transactions.begin(request_id, &ids) {
+            self.connection.send_transaction_ack(ack);
+        }
 
-            if addr != editor_address {
-                trace!("Packet received from unknown address {:?}", addr);
+        for (mut op, id) in ops.into_iter().zip(ids) {
+            if op.operation_request_id().is_none() {
+                debug!("Message can't be part of a transaction: {:?}", op);
+                self.connection.send_ack(Some(id), EditStatus::Unsupported);
                 continue;
             }
 
-            debug!("Packet: {:?}", &buf[..bytes_read]);
-
-            // Add the bytes from the incoming packet to the buffer.
-            self.incoming_buffer.extend_from_slice(&buf[..bytes_read]);
+            match op {
+                IncomingMessage::CreateEntities {
+                    amount,
+                    temporary_ids,
+                    request_id,
+                } => {
+                    if self.entity_limits.check_create_in_transaction(
+                        entities,
+                        amount,
+                        request_id,
+                        &self.connection,
+                    ) {
+                        create_entities(
+                            entities,
+                            id_map,
+                            amount,
+                            temporary_ids,
+                            request_id,
+                            &self.connection,
+                        );
+                    }
+                }
+                op => self.handle_message(op, entities, id_map, dynamic),
+            }
         }
+    }
 
-        // Check the incoming buffer to see if any completed messages have been received.
-        while let Some(index) = self.incoming_buffer.iter().position(|&byte| byte == 0xC) {
-            // HACK: Manually introduce a scope here so that the compiler can tell when we're done
-            // using borrowing the message bytes from `self.incoming_buffer`. This can be removed
-            // once NLL is stable.
-            {
-                let message_bytes = &self.incoming_buffer[..index];
-                let result = str::from_utf8(message_bytes)
-                    .ok()
-                    .and_then(|message| serde_json::from_str(message).ok());
-                debug!("Message str: {:?}", result);
+    fn handle_message(
+        &mut self,
+        message: IncomingMessage,
+        entities: &Entities,
+        id_map: &EntityIdMap,
+        dynamic: Option<&DynamicComponentStore>,
+    ) {
+        match message {
+            IncomingMessage::ComponentUpdate {
+                id,
+                entity,
+                data,
+                request_id,
+            } => {
+                if let Some(entity) = self.resolve_edited(&entity, request_id, entities, id_map) {
+                    self.send_component(
+                        &id,
+                        IncomingComponent {
+                            entities: vec![entity],
+                            data,
+                            request_id,
+                            patch: false,
+                        },
+                    );
+                }
+            }
 
-                if let Some(message) = result {
-                    debug!("Message: {:#?}", message);
+            IncomingMessage::ComponentPatch {
+                id,
+                entity,
+                data,
+                request_id,
+            } => {
+                if let Some(entity) = self.resolve_edited(&entity, request_id, entities, id_map) {
+                    self.send_component(
+                        &id,
+                        IncomingComponent {
+                            entities: vec![entity],
+                            data,
+                            request_id,
+                            patch: true,
+                        },
+                    );
+                }
+            }
 
-                    match message {
-                        IncomingMessage::ComponentUpdate {
-                            id,
-                            entity: entity_data,
+            IncomingMessage::ComponentUpdateBatch {
+                id,
+                entities: targets,
+                data,
+                patch,
+                request_id,
+            } => {
+                let resolved = targets
+                    .iter()
+                    .map(|entity| entity.resolve(entities, id_map))
+                    .collect::<Option<Vec<_>>>();
+                match resolved {
+                    Some(resolved) => self.send_component(
+                        &id,
+                        IncomingComponent {
+                            entities: resolved,
                             data,
-                        } => {
-                            let entity = entities.entity(entity_data.id);
-
-                            // Skip the update if the entity is no longer valid.
-                            if entity.gen().id() != entity_data.generation {
-                                debug!(
-                                    "Entity {:?} had invalid generation {} (expected {})",
-                                    entity_data,
-                                    entity_data.generation,
-                                    entity.gen().id()
-                                );
-                                continue;
-                            }
-
-                            if let Some(sender) = self.component_map.get(&*id) {
-                                // TODO: Should we do something to prevent this from blocking?
-                                sender
-                                    .send(IncomingComponent { entity, data })
-                                    .expect("Disconnected from component system");
-                            } else {
-                                debug!("No deserializer found for component {:?}", id);
-                            }
-                        }
+                            request_id,
+                            patch,
+                        },
+                    ),
 
-                        IncomingMessage::ResourceUpdate { id, data } => {
-                            // TODO: Should we do something if there was no deserialer system for the
-                            // specified ID?
-                            if let Some(sender) = self.resource_map.get(&*id) {
-                                // TODO: Should we do something to prevent this from blocking?
-                                sender
-                                    .send(data)
-                                    .expect("Disconnected from resource system");
-                            }
-                        }
+                    None => self
+                        .connection
+                        .send_ack(request_id, EditStatus::StaleEntity),
+                }
+            }
 
-                        IncomingMessage::CreateEntities { amount } => {
-                            self.entity_handler
-                                .send(EntityMessage::Create(amount))
-                                .expect("Disconnected from entity handler system");
-                        }
+            IncomingMessage::ResourceUpdate {
+                id,
+                data,
+                request_id,
+            } => {
+                if let Some(sender) = self.routes.resource_map.get(&*id) {
+                    // TODO: Should we do something to prevent this from blocking?
+                    sender
+                        .send(IncomingResource { data, request_id })
+                        .expect("Disconnected from resource system");
+                } else if self.routes.dev_only_resources.contains(&*id) {
+                    debug!("Ignoring update for dev-only resource {:?}", id);
+                    self.connection
+                        .send_ack(request_id, EditStatus::UnknownType);
+                } else {
+                    let known = known_ids(&self.routes.resource_map);
+                    self.unknown_types
+                        .handle(TypeKind::Resource, &id, known, &self.connection);
+                    self.connection
+                        .send_ack(request_id, EditStatus::UnknownType);
+                }
+            }
 
-                        IncomingMessage::DestroyEntities { entities } => {
-                            self.entity_handler
-                                .send(EntityMessage::Destroy(
-                                    entities.iter().map(|e| e.id).collect(),
-                                ))
-                                .expect("Disconnected from entity handler system");
+            IncomingMessage::RequestChunks {
+                resource,
+                ids,
+                request_id,
+            } => {
+                if let Some(sender) = self.routes.chunk_map.get(&*resource) {
+                    sender
+                        .send(ChunkRequest { ids, request_id })
+                        .expect("Disconnected from chunked resource system");
+                } else {
+                    let known = known_ids(&self.routes.chunk_map);
+                    self.unknown_types.handle(
+                        TypeKind::Resource,
+                        &resource,
+                        known,
+                        &self.connection,
+                    );
+                    self.connection
+                        .send_ack(request_id, EditStatus::UnknownType);
+                }
+            }
+
+            IncomingMessage::CreateEntities {
+                amount,
+                temporary_ids,
+                request_id,
+            } => {
+                self.routes
+                    .entity_handler
+                    .send(EntityMessage::Create(amount, temporary_ids, request_id))
+                    .expect("Disconnected from entity handler system");
+            }
+
+            IncomingMessage::DestroyEntities {
+                entities,
+                request_id,
+            } => {
+                self.routes
+                    .entity_handler
+                    .send(EntityMessage::Destroy(
+                        entities.iter().filter_map(|e| e.id(id_map)).collect(),
+                        request_id,
+                    ))
+                    .expect("Disconnected from entity handler system");
+            }
+
+            IncomingMessage::CloneEntities {
+                entities: selected,
+                request_id,
+            } => {
+                let entities = selected
+                    .iter()
+                    .filter_map(|entity| entity.resolve(entities, id_map))
+                    .collect();
+                self.routes
+                    .clone_entities
+                    .send(CloneEntities {
+                        entities,
+                        request_id,
+                    })
+                    .expect("Disconnected from clone entities system");
+            }
+
+            IncomingMessage::SetSelection {
+                entities: selected,
+                request_id,
+            } => {
+                let entities = selected
+                    .iter()
+                    .filter_map(|entity| entity.resolve(entities, id_map))
+                    .collect();
+                self.routes
+                    .selection
+                    .send(SetSelection {
+                        entities,
+                        request_id,
+                    })
+                    .expect("Disconnected from selection system");
+            }
+
+            IncomingMessage::BookmarkEntity {
+                name,
+                entity,
+                request_id,
+            } => {
+                let entity = match entity {
+                    Some(entity) => {
+                        match self.resolve_edited(&entity, request_id, entities, id_map) {
+                            Some(entity) => Some(entity),
+                            None => return,
                         }
                     }
+                    None => None,
+                };
+                self.routes
+                    .bookmarks
+                    .send(BookmarkEntity {
+                        name,
+                        entity,
+                        request_id,
+                    })
+                    .expect("Disconnected from bookmark system");
+            }
+
+            IncomingMessage::ExportScene {
+                path,
+                entities: selected,
+                request_id,
+            } => {
+                let entities = selected
+                    .iter()
+                    .filter_map(|entity| entity.resolve(entities, id_map))
+                    .collect();
+                self.routes
+                    .scene_export
+                    .send(SceneExport {
+                        path,
+                        entities,
+                        request_id,
+                    })
+                    .expect("Disconnected from scene export system");
+            }
+
+            IncomingMessage::QueryEntities {
+                with_components,
+                where_clause,
+                request_id,
+            } => {
+                self.routes
+                    .query
+                    .send(EntityQuery {
+                        with_components,
+                        where_clause,
+                        request_id,
+                    })
+                    .expect("Disconnected from entity query system");
+            }
+
+            IncomingMessage::FindOrphans {
+                anchors,
+                request_id,
+            } => {
+                self.routes
+                    .orphans
+                    .send(OrphanRequest::Find {
+                        anchors,
+                        request_id,
+                    })
+                    .expect("Disconnected from orphan system");
+            }
+
+            IncomingMessage::PurgeEntities {
+                entities: selected,
+                anchors,
+                request_id,
+            } => {
+                let entities = selected
+                    .iter()
+                    .filter_map(|entity| entity.resolve(entities, id_map))
+                    .collect();
+                self.routes
+                    .orphans
+                    .send(OrphanRequest::Purge {
+                        entities,
+                        anchors,
+                        request_id,
+                    })
+                    .expect("Disconnected from orphan system");
+            }
+
+            IncomingMessage::AddWatch {
+                id,
+                component,
+                entity,
+                path,
+            } => {
+                if let Some(entity) = entity.resolve(entities, id_map) {
+                    self.routes
+                        .watch
+                        .send(WatchMessage::Add(Watch {
+                            id,
+                            component,
+                            entity,
+                            path,
+                        }))
+                        .expect("Disconnected from watch system");
+                }
+            }
+
+            IncomingMessage::RemoveWatch { id } => {
+                self.routes
+                    .watch
+                    .send(WatchMessage::Remove(id))
+                    .expect("Disconnected from watch system");
+            }
+
+            IncomingMessage::PinComponentField {
+                entity,
+                component,
+                path,
+                value,
+                request_id,
+            } => {
+                if let Some(entity) = self.resolve_edited(&entity, request_id, entities, id_map) {
+                    self.routes
+                        .pin
+                        .send(PinMessage::Pin(Pin {
+                            entity,
+                            component,
+                            path,
+                            value,
+                            request_id,
+                        }))
+                        .expect("Disconnected from pin enforcement system");
+                }
+            }
+
+            IncomingMessage::UnpinComponentField {
+                entity,
+                component,
+                path,
+            } => {
+                if let Some(entity) = entity.resolve(entities, id_map) {
+                    self.routes
+                        .pin
+                        .send(PinMessage::Unpin {
+                            entity,
+                            component,
+                            path,
+                        })
+                        .expect("Disconnected from pin enforcement system");
+                }
+            }
+
+            IncomingMessage::SetBreakpoint {
+                id,
+                component,
+                entity,
+                condition,
+            } => {
+                // Ignore breakpoints on entities that no longer exist, rather than checking
+                // every entity instead.
+                let entity = match entity {
+                    Some(entity) => match entity.resolve(entities, id_map) {
+                        Some(entity) => Some(entity),
+                        None => return,
+                    },
+                    None => None,
+                };
+                self.routes
+                    .breakpoint
+                    .send(BreakpointMessage::Set(Breakpoint {
+                        id,
+                        component,
+                        entity,
+                        condition,
+                        triggered: false,
+                    }))
+                    .expect("Disconnected from breakpoint system");
+            }
+
+            IncomingMessage::ClearBreakpoint { id } => {
+                self.routes
+                    .breakpoint
+                    .send(BreakpointMessage::Clear(id))
+                    .expect("Disconnected from breakpoint system");
+            }
+
+            IncomingMessage::SetTimeScale { scale, request_id } => {
+                self.routes
+                    .time_control
+                    .send(TimeControl::SetTimeScale { scale, request_id })
+                    .expect("Disconnected from time control system");
+            }
+
+            IncomingMessage::SetFixedTimestep {
+                seconds,
+                request_id,
+            } => {
+                self.routes
+                    .time_control
+                    .send(TimeControl::SetFixedTimestep {
+                        seconds,
+                        request_id,
+                    })
+                    .expect("Disconnected from time control system");
+            }
+
+            IncomingMessage::SetSendInterval {
+                milliseconds,
+                request_id,
+            } => {
+                if milliseconds > MAX_SEND_INTERVAL_MS {
+                    warn!(
+                        "Editor asked for a send interval of {}ms, longer than the maximum of {}ms",
+                        milliseconds, MAX_SEND_INTERVAL_MS,
+                    );
+                    self.connection
+                        .send_ack(request_id, EditStatus::InvalidData);
+                    return;
+                }
+
+                self.routes
+                    .send_interval
+                    .send(Duration::from_millis(milliseconds))
+                    .expect("Disconnected from editor sender system");
+                self.connection.send_ack(request_id, EditStatus::Applied);
+            }
+
+            IncomingMessage::SetSendRateLimit {
+                bytes_per_second,
+                request_id,
+            } => {
+                self.routes
+                    .rate_limit
+                    .send(bytes_per_second)
+                    .expect("Disconnected from editor sender system");
+                self.connection.send_ack(request_id, EditStatus::Applied);
+            }
+
+            IncomingMessage::ReloadAsset { path, request_id } => {
+                self.routes
+                    .asset_reload
+                    .send(AssetReload { path, request_id })
+                    .expect("Disconnected from asset reload system");
+            }
+
+            IncomingMessage::SetMasterVolume { volume, request_id } => {
+                match self.routes.audio_volume {
+                    Some(ref sender) => {
+                        sender
+                            .send(SetMasterVolume { volume, request_id })
+                            .expect("Disconnected from audio volume system");
+                    }
+
+                    None => {
+                        debug!("Audio isn't synced, ignoring volume change");
+                        self.connection
+                            .send_ack(request_id, EditStatus::UnknownType);
+                    }
+                }
+            }
+
+            #[cfg(feature = "physics")]
+            IncomingMessage::ApplyImpulse {
+                entity,
+                linear,
+                angular,
+                request_id,
+            } => {
+                if let Some(entity) = self.resolve_edited(&entity, request_id, entities, id_map) {
+                    self.send_physics(
+                        PhysicsCommand::ApplyImpulse {
+                            entity,
+                            linear,
+                            angular,
+                            request_id,
+                        },
+                        request_id,
+                    );
                 }
             }
 
-            // Remove the message bytes from the beginning of the incoming buffer.
-            self.incoming_buffer.drain(..=index);
+            #[cfg(feature = "physics")]
+            IncomingMessage::Teleport {
+                entity,
+                position,
+                request_id,
+            } => {
+                if let Some(entity) = self.resolve_edited(&entity, request_id, entities, id_map) {
+                    self.send_physics(
+                        PhysicsCommand::Teleport {
+                            entity,
+                            position,
+                            request_id,
+                        },
+                        request_id,
+                    );
+                }
+            }
+
+            IncomingMessage::SpawnPrefab { path, request_id } => match self.routes.prefab_spawn {
+                Some(ref sender) => {
+                    sender
+                        .send(PrefabSpawn { path, request_id })
+                        .expect("Disconnected from prefab spawn system");
+                }
+
+                None => {
+                    debug!("No prefab type registered for spawning {:?}", path);
+                    self.connection
+                        .send_ack(request_id, EditStatus::UnknownType);
+                }
+            },
+
+            IncomingMessage::TransformDrag {
+                entity,
+                translation,
+                rotation,
+                scale,
+            } => {
+                if let Some(entity) = entity.resolve(entities, id_map) {
+                    self.routes
+                        .gizmo
+                        .send(GizmoMessage::Drag {
+                            entity,
+                            translation,
+                            rotation,
+                            scale,
+                        })
+                        .expect("Disconnected from gizmo system");
+                }
+            }
+
+            IncomingMessage::TransformDragEnd { entity } => {
+                if let Some(entity) = entity.resolve(entities, id_map) {
+                    self.routes
+                        .gizmo
+                        .send(GizmoMessage::EndDrag(entity))
+                        .expect("Disconnected from gizmo system");
+                }
+            }
+
+            #[cfg(feature = "renderer")]
+            IncomingMessage::PickRequest {
+                screen_x,
+                screen_y,
+                request_id,
+            } => {
+                self.routes
+                    .picking
+                    .send(PickRequest {
+                        screen_x,
+                        screen_y,
+                        request_id,
+                    })
+                    .expect("Disconnected from picking system");
+            }
+
+            IncomingMessage::Eval { input, request_id } => match self.routes.console {
+                Some(ref sender) => {
+                    sender
+                        .send(ConsoleInput { input, request_id })
+                        .expect("Disconnected from console system");
+                }
+
+                None => {
+                    debug!("No console handler set for evaluating {:?}", input);
+                    self.connection
+                        .send_ack(request_id, EditStatus::UnknownType);
+                }
+            },
+
+            // NOTE: A handshake starts a new session, which is only answered once the editor has
+            // authenticated if a token is required.
+            IncomingMessage::Handshake { capabilities } => {
+                if let Some(capabilities) = self.auth.handshake(capabilities) {
+                    self.send_handshake(capabilities);
+                }
+            }
+
+            // NOTE: The editor's address was already recorded when the packet arrived.
+            IncomingMessage::Heartbeat => {}
+
+            IncomingMessage::Authenticate { token, request_id } => {
+                let (status, pending_handshake) = self.auth.authenticate(&token);
+                self.connection.send_ack(request_id, status);
+                if let Some(capabilities) = pending_handshake {
+                    self.send_handshake(capabilities);
+                }
+            }
+
+            IncomingMessage::SetEditorMeta { key, value } => {
+                self.handshake.editor_meta.set(key, value);
+            }
+
+            IncomingMessage::Subscribe { sections } => {
+                debug!("Editor subscribed to {:?}", sections);
+                self.connection.subscriptions().set(sections);
+
+                // NOTE: The editor doesn't have any of the data it just subscribed to yet.
+                self.connection.presence().request_resync();
+            }
+
+            IncomingMessage::ForceSync {
+                entities: forced,
+                request_id,
+            } => {
+                let forced = forced
+                    .iter()
+                    .filter_map(|entity| entity.resolve(entities, id_map))
+                    .collect::<Vec<_>>();
+                self.connection.interest().set_forced(&forced);
+                self.connection.send_ack(request_id, EditStatus::Applied);
+            }
+
+            IncomingMessage::RequestHistory {
+                from_frame,
+                to_frame,
+                request_id,
+            } => self.send_history_request(
+                HistoryRequest::Range {
+                    from_frame,
+                    to_frame,
+                    request_id,
+                },
+                request_id,
+            ),
+
+            IncomingMessage::RewindTo { frame, request_id } => {
+                self.send_history_request(HistoryRequest::Rewind { frame, request_id }, request_id)
+            }
+
+            IncomingMessage::SetSyncLevel { level, request_id } => {
+                debug!("Editor changed the sync level to {:?}", level);
+                self.connection.subscriptions().set_level(level);
+
+                // NOTE: The editor doesn't have any of the state left out at the minimal level.
+                self.connection.presence().request_resync();
+                self.connection.send_ack(request_id, EditStatus::Applied);
+            }
+
+            IncomingMessage::EnableSync { ref id, request_id }
+            | IncomingMessage::DisableSync { ref id, request_id }
+                if !self.is_synced_type(id, dynamic) =>
+            {
+                debug!("Editor toggled syncing of unknown type {:?}", id);
+                self.connection
+                    .send_ack(request_id, EditStatus::UnknownType);
+            }
+
+            IncomingMessage::EnableSync { id, request_id } => {
+                debug!("Editor resumed syncing {}", id);
+                self.connection.subscriptions().set_enabled(&id, true);
+
+                // NOTE: The editor doesn't have the latest data for the type, since nothing was
+                // sent while it was turned off.
+                self.connection.presence().request_resync();
+                self.connection.send_ack(request_id, EditStatus::Applied);
+            }
+
+            IncomingMessage::DisableSync { id, request_id } => {
+                debug!("Editor stopped syncing {}", id);
+                self.connection.subscriptions().set_enabled(&id, false);
+                self.connection.send_ack(request_id, EditStatus::Applied);
+            }
+
+            IncomingMessage::RequestComponentPage {
+                ref id, request_id, ..
+            } if !self.is_synced_component(id, dynamic) => {
+                debug!("Editor requested a page of unknown component {:?}", id);
+                self.connection
+                    .send_ack(request_id, EditStatus::UnknownType);
+            }
+
+            IncomingMessage::RequestComponentPage {
+                id,
+                page,
+                request_id,
+            } => {
+                debug!("Editor requested page {} of {}", page, id);
+                self.connection.pages().set_page(id, page);
+                self.connection.send_ack(request_id, EditStatus::Applied);
+            }
+
+            IncomingMessage::RequestFullSync => {
+                debug!("Editor requested a full resync");
+                self.connection.presence().request_resync();
+            }
+
+            // NOTE: Transactions are handled by `handle_transaction` instead, which rejects
+            // nested transactions before they get here.
+            IncomingMessage::Transaction { request_id, .. } => {
+                debug!("Transactions can't be nested, ignoring transaction");
+                self.connection
+                    .send_ack(request_id, EditStatus::Unsupported);
+            }
+        }
+    }
+
+    /// Passes a request on to the history system, if history is enabled.
+    fn send_history_request(&self, request: HistoryRequest, request_id: Option<u64>) {
+        match self.routes.history {
+            Some(ref sender) => {
+                sender
+                    .send(request)
+                    .expect("Disconnected from history system");
+            }
+
+            None => {
+                debug!("History isn't enabled, ignoring history request");
+                self.connection
+                    .send_ack(request_id, EditStatus::UnknownType);
+            }
         }
     }
+
+    /// Resolves the entity targeted by an edit, acknowledging the edit as stale if the entity is
+    /// no longer valid.
+    fn resolve_edited(
+        &self,
+        entity: &IncomingEntity,
+        request_id: Option<u64>,
+        entities: &Entities,
+        id_map: &EntityIdMap,
+    ) -> Option<Entity> {
+        let resolved = entity.resolve(entities, id_map);
+        if resolved.is_none() {
+            self.connection
+                .send_ack(request_id, EditStatus::StaleEntity);
+        }
+        resolved
+    }
+
+    /// Passes a command on to the system that controls physics bodies, if physics is synced.
+    #[cfg(feature = "physics")]
+    fn send_physics(&self, command: PhysicsCommand, request_id: Option<u64>) {
+        match self.routes.physics {
+            Some(ref sender) => {
+                sender
+                    .send(command)
+                    .expect("Disconnected from physics control system");
+            }
+
+            None => {
+                debug!("Physics isn't synced, ignoring physics command");
+                self.connection
+                    .send_ack(request_id, EditStatus::UnknownType);
+            }
+        }
+    }
+
+    /// Whether `id` is a registered component or resource, or a dynamic component that's being
+    /// synced, rather than a name that the game doesn't know.
+    fn is_synced_type(&self, id: &str, dynamic: Option<&DynamicComponentStore>) -> bool {
+        self.is_synced_component(id, dynamic)
+            || self.registry.resources().any(|access| access.name() == id)
+    }
+
+    /// Whether `id` is a registered component, or a dynamic component that's being synced.
+    fn is_synced_component(&self, id: &str, dynamic: Option<&DynamicComponentStore>) -> bool {
+        self.registry.component(id).is_some()
+            || (self.routes.dynamic_components.is_some()
+                && dynamic.map_or(false, |store| store.contains_name(id)))
+    }
+
+    /// Passes an update on to the system that writes components of type `id`.
+    fn send_component(&mut self, id: &str, component: IncomingComponent) {
+        if let Some(sender) = self.routes.component_map.get(id) {
+            // TODO: Should we do something to prevent this from blocking?
+            sender
+                .send(component)
+                .expect("Disconnected from component system");
+        } else if self.routes.dev_only_components.contains(id) {
+            debug!("Ignoring update for dev-only component {:?}", id);
+            self.connection
+                .send_ack(component.request_id, EditStatus::UnknownType);
+        } else if let Some(ref sender) = self.routes.dynamic_components {
+            // NOTE: Dynamic components are only known to the store, so the system that writes
+            // them acknowledges edits to unknown names.
+            sender
+                .send(DynamicComponentEdit {
+                    name: id.to_owned(),
+                    component,
+                })
+                .expect("Disconnected from dynamic component system");
+        } else {
+            let known = known_ids(&self.routes.component_map);
+            self.unknown_types
+                .handle(TypeKind::Component, id, known, &self.connection);
+            self.connection
+                .send_ack(component.request_id, EditStatus::UnknownType);
+        }
+    }
+}
+
+/// The sorted IDs of the types that have a channel in `map`.
+fn known_ids<T>(map: &ChannelMap<T>) -> Vec<&'static str> {
+    let mut ids = map.keys().cloned().collect::<Vec<_>>();
+    ids.sort();
+    ids
 }
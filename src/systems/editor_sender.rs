@@ -1,55 +1,413 @@
-use amethyst::ecs::{Entities, Join, System};
+use amethyst::core::timing::Time;
+use amethyst::ecs::{BitSet, Entities, Join, Read, ReadStorage, System, Write};
 use crossbeam_channel::Receiver;
-use crate::serializable_entity::SerializableEntity;
-use std::cmp::min;
-use std::fmt::Write;
-use std::net::UdpSocket;
+use crate::budget::{ByteBudget, SyncPriority};
+use crate::capabilities::{Capability, SharedCapabilities};
+use crate::codec::Codec;
+use crate::connection_status::{EditorConnectionStatus, EditorPresence};
+use crate::editor_uuid::EditorUuid;
+use crate::entity_map::EntityIdMap;
+use crate::entity_names::EntityNames;
+use crate::packet_queue::PacketQueue;
+use crate::panic_hook::CrashReporter;
+use crate::protocol::{EntityEntry, Packet, PacketContents, PacketData};
+use crate::serializable_entity::DeserializableEntity;
+use crate::subscriptions::Subscriptions;
+use crate::sync_stats::SyncStats;
+use crate::sync_strategy::{SyncSchedule, SyncStrategy};
+use crate::timing::TimingInfo;
+use crate::transport::Transport;
+use serde::{Serialize, Serializer};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
 use std::time::{Duration, Instant};
-use crate::types::SerializedData;
+use crate::types::{serialize_message, EditorConnection, SerializedData};
 
-const MAX_PACKET_SIZE: usize = 32 * 1024;
+/// Settings controlling how and when the sender system transmits data.
+#[derive(Debug, Clone)]
+pub(crate) struct SenderSettings {
+    /// How often the full game state is sent.
+    pub send_interval: Duration,
+
+    /// The number of frames over which each state snapshot is transmitted.
+    pub send_frames: usize,
+
+    /// The maximum size of a single packet, or `None` to pick one based on the editor address.
+    pub max_packet_size: Option<usize>,
+
+    /// Start each packet with a header identifying the message it belongs to, or `None` to
+    /// decide based on the transport.
+    pub chunk_headers: Option<bool>,
+
+    /// How packets are encoded, once the editor supports the encoding.
+    pub codec: Codec,
+
+    /// Only send components and resources that changed since they were last sent.
+    pub delta_only: bool,
+
+    /// Only send the number of entities instead of the full entity list.
+    pub entity_summary: bool,
+
+    /// Include the number of components and the serialized size of each type in snapshots.
+    pub stats: bool,
+
+    /// Include the names of the components that each entity has in the entity list.
+    pub entity_components: bool,
+
+    /// The longest that log messages may be held back to avoid sending them in the same frame
+    /// as a state snapshot. `None` sends logs as soon as they're received.
+    pub max_log_delay: Option<Duration>,
+
+    /// How often a `"heartbeat"` message is sent, if heartbeats are enabled.
+    pub heartbeat_interval: Option<Duration>,
+
+    /// How often a `"transport_stats"` message is sent, if transport stats are enabled.
+    pub transport_stats_interval: Option<Duration>,
+
+    /// The most bytes sent each second, used to simulate a slow connection to the editor.
+    pub rate_limit: Option<u64>,
+
+    /// The ID included in every message to tell this bundle apart from others in the process.
+    pub instance_id: Option<String>,
+
+    /// Which component types are sent with each snapshot.
+    pub strategy: SyncStrategy,
+
+    /// The maximum size of the components, resources and sections in each snapshot, in bytes.
+    pub byte_budget: Option<usize>,
+
+    /// The priority of each type that was given one, used to fit snapshots within the budget.
+    pub priorities: HashMap<&'static str, SyncPriority>,
+
+    /// Hand packets over to a background thread to be sent, instead of sending them from the
+    /// sender system.
+    pub send_in_background: bool,
+}
+
+impl Default for SenderSettings {
+    fn default() -> Self {
+        SenderSettings {
+            send_interval: Duration::from_millis(200),
+            send_frames: 1,
+            max_packet_size: None,
+            chunk_headers: None,
+            codec: Codec::Json,
+            delta_only: false,
+            entity_summary: false,
+            stats: true,
+            entity_components: false,
+            max_log_delay: None,
+            heartbeat_interval: None,
+            transport_stats_interval: None,
+            rate_limit: None,
+            instance_id: None,
+            strategy: SyncStrategy::Full,
+            byte_budget: None,
+            priorities: HashMap::new(),
+            send_in_background: false,
+        }
+    }
+}
+
+/// Size statistics for each registered type, included in snapshots as the `"stats"` section.
+#[derive(Default, Serialize)]
+struct Stats {
+    components: BTreeMap<&'static str, TypeStats>,
+    resources: BTreeMap<&'static str, TypeStats>,
+}
+
+#[derive(Serialize)]
+struct TypeStats {
+    /// The number of components of the type, or `None` for resources.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<usize>,
+
+    /// The size of the type's serialized data in bytes, whether or not it was sent.
+    bytes: usize,
+}
+
+/// The contents of the packets sent by the sender system, which borrow the data serialized by
+/// other systems and embed it as is.
+struct Serialized<'a>(PhantomData<&'a ()>);
+
+impl<'a> PacketContents for Serialized<'a> {
+    type Entity = EntityEntry<&'a str>;
+    type Component = Fragment<'a>;
+    type Resource = Fragment<'a>;
+    type Message = Fragment<'a>;
+    type Value = Fragment<'a>;
+}
+
+/// JSON serialized by another system, which is written into packets without being parsed again.
+#[derive(Debug, Clone, Copy)]
+struct Fragment<'a>(&'a RawValue);
+
+impl<'a> PartialEq for Fragment<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.get() == other.0.get()
+    }
+}
+
+impl<'a> Serialize for Fragment<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Sent to the editor as a `"transport_stats"` message, so that bandwidth issues can be
+/// diagnosed from the editor. Totals are counted from when the game started.
+#[derive(Debug, Serialize)]
+struct TransportStats {
+    packets_sent: u64,
+    bytes_sent: u64,
+    packets_dropped: u64,
+    send_retries: u64,
+    packets_queued: usize,
+
+    /// Averaged over the time since the previous `"transport_stats"` message.
+    packets_per_second: f64,
+    bytes_per_second: f64,
+
+    /// The send rate limit, if one is set.
+    rate_limit: Option<u64>,
+}
 
 /// The system in charge of sending updated state data to the editor process.
 pub struct EditorSenderSystem {
     receiver: Receiver<SerializedData>,
-    socket: UdpSocket,
 
-    send_interval: Duration,
+    // Packets waiting to be sent. State snapshots are spread across `send_frames` frames in
+    // order to avoid a spike in frame time whenever the full state is sent.
+    queue: PacketQueue,
+    packets_per_frame: usize,
+
+    // Send intervals requested by the editor, which replace the configured interval.
+    interval_receiver: Receiver<Duration>,
+
+    // Send rate limits requested by the editor, which replace the configured limit.
+    rate_limit_receiver: Receiver<Option<u64>>,
+
+    settings: SenderSettings,
+    capabilities: SharedCapabilities,
+    schedule: SyncSchedule,
+    subscriptions: Subscriptions,
     next_send: Instant,
 
-    scratch_string: String,
+    // Whether the editor is connected, and whether it was last frame. Only heartbeats are sent
+    // while the editor is away.
+    presence: EditorPresence,
+    was_active: bool,
+    next_heartbeat: Instant,
+
+    // When the next `"transport_stats"` message is due, and when the previous one was sent
+    // along with the packet and byte totals at the time.
+    next_transport_stats: Instant,
+    last_transport_stats: (Instant, u64, u64),
+
+    // The number of full resyncs requested by the editor that have been handled.
+    resyncs: usize,
+
+    // Hashes of the component and resource data last sent to the editor, used to skip
+    // unchanged data when only sending deltas.
+    sent_components: HashMap<&'static str, u64>,
+    sent_resources: HashMap<&'static str, u64>,
+    sent_sections: HashMap<&'static str, u64>,
+
+    // The latest serialized data for each component and resource that hasn't been sent yet.
+    // Data is kept until the next snapshot, since components serialized in the background
+    // may arrive on a frame that doesn't send one.
+    latest_components: BTreeMap<&'static str, (Box<RawValue>, usize, BitSet)>,
+    latest_resources: BTreeMap<&'static str, Box<RawValue>>,
+    latest_sections: BTreeMap<&'static str, Box<RawValue>>,
+
+    // Picks the data left out of snapshots that would exceed the byte budget, if one is set.
+    budget: Option<ByteBudget>,
+
+    // Log messages that haven't been sent yet, along with the time they were received.
+    pending_logs: VecDeque<(Instant, Box<RawValue>)>,
+
+    // The parts of the last snapshot that haven't been sent yet, one per frame, when snapshots
+    // are split across several frames.
+    staged: VecDeque<SnapshotPart>,
+}
+
+/// A share of the components, resources and sections of a snapshot that's split across several
+/// frames.
+#[derive(Default)]
+struct SnapshotPart {
+    components: BTreeMap<&'static str, Box<RawValue>>,
+    resources: BTreeMap<&'static str, Box<RawValue>>,
+    sections: BTreeMap<&'static str, Box<RawValue>>,
+}
+
+impl SnapshotPart {
+    fn is_empty(&self) -> bool {
+        self.components.is_empty() && self.resources.is_empty() && self.sections.is_empty()
+    }
 }
 
 impl EditorSenderSystem {
     pub fn from_channel(
         receiver: Receiver<SerializedData>,
-        send_interval: Duration,
-        socket: UdpSocket,
+        interval_receiver: Receiver<Duration>,
+        rate_limit_receiver: Receiver<Option<u64>>,
+        mut settings: SenderSettings,
+        connection: &EditorConnection,
+        transport: Transport,
     ) -> Self {
-        // Create the socket used for communicating with the editor.
-        //
-        // NOTE: We set the socket to nonblocking so that we don't block if there are no incoming
-        // messages to read. We `expect` on the call to `set_nonblocking` because the game will
-        // hang if the socket is still set to block when the game runs.
-        let scratch_string = String::with_capacity(MAX_PACKET_SIZE);
+        settings.send_frames = settings.send_frames.max(1);
+
+        let chunk_headers = settings
+            .chunk_headers
+            .unwrap_or_else(|| transport.uses_chunk_headers());
+        let mut queue = PacketQueue::new(transport, settings.max_packet_size, chunk_headers);
+        queue.set_rate_limit(settings.rate_limit, Instant::now());
+        if settings.send_in_background {
+            queue.send_in_background();
+        }
+
+        let budget = settings
+            .byte_budget
+            .map(|bytes| ByteBudget::new(bytes, settings.priorities.clone()));
+
+        let presence = connection.presence().clone();
         EditorSenderSystem {
             receiver,
-            socket,
+            queue,
+            packets_per_frame: 1,
 
-            send_interval,
-            next_send: Instant::now() + send_interval,
+            interval_receiver,
 
-            scratch_string,
+            rate_limit_receiver,
+
+            next_send: Instant::now() + settings.send_interval,
+            settings,
+            capabilities: connection.capabilities().clone(),
+            schedule: connection.schedule().clone(),
+            subscriptions: connection.subscriptions().clone(),
+
+            was_active: presence.is_active(),
+            presence,
+            next_heartbeat: Instant::now(),
+
+            next_transport_stats: Instant::now(),
+            last_transport_stats: (Instant::now(), 0, 0),
+
+            resyncs: 0,
+
+            sent_components: HashMap::new(),
+            sent_resources: HashMap::new(),
+            sent_sections: HashMap::new(),
+
+            latest_components: BTreeMap::new(),
+            latest_resources: BTreeMap::new(),
+            latest_sections: BTreeMap::new(),
+
+            budget,
+
+            pending_logs: VecDeque::new(),
+
+            staged: VecDeque::new(),
         }
     }
+
+    /// Creates a reporter that sends crash reports straight to the editor, with the same
+    /// packet settings as this system.
+    pub(crate) fn crash_reporter(&self) -> io::Result<CrashReporter> {
+        Ok(CrashReporter::new(
+            self.queue.transport().try_clone()?,
+            self.queue.max_packet_size(),
+            self.queue.message_ids().cloned(),
+            self.settings.instance_id.clone(),
+        ))
+    }
 }
 
 impl<'a> System<'a> for EditorSenderSystem {
-    type SystemData = Entities<'a>;
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EntityIdMap>,
+        Read<'a, EntityNames>,
+        ReadStorage<'a, EditorUuid>,
+        Read<'a, Time>,
+        Write<'a, SyncStats>,
+        Write<'a, EditorConnectionStatus>,
+    );
 
-    fn run(&mut self, entities: Self::SystemData) {
-        // Determine if we should send full state data this frame.
+    fn run(
+        &mut self,
+        (entities, id_map, names, uuids, time, mut sync_stats, mut status): Self::SystemData,
+    ) {
         let now = Instant::now();
+
+        // Every message is tagged with the frame it was sent on, so that the editor can line up
+        // snapshots, logs and other messages on a single timeline.
+        let clock = (time.frame_number(), time.absolute_time_seconds());
+
+        // If the editor asked for a different send interval, switch to it right away rather than
+        // waiting out the rest of the old interval.
+        if let Some(interval) = self.interval_receiver.try_iter().last() {
+            debug!("Editor changed the send interval to {:?}", interval);
+            self.settings.send_interval = interval;
+            // NOTE: The receiver rejects intervals long enough to overflow the clock.
+            self.next_send = self.next_send.min(now + interval);
+        }
+
+        if let Some(limit) = self.rate_limit_receiver.try_iter().last() {
+            debug!("Editor changed the send rate limit to {:?}", limit);
+            self.queue.set_rate_limit(limit, now);
+        }
+
+        let heartbeat = match self.settings.heartbeat_interval {
+            Some(interval) if now >= self.next_heartbeat => {
+                self.next_heartbeat = now + interval;
+                serialize_message("heartbeat", ())
+            }
+
+            _ => None,
+        };
+
+        // While the editor is away, discard everything that would be sent to it and only send
+        // heartbeats, so that the editor can find the game again.
+        let active = self.presence.is_active();
+        if !active {
+            self.was_active = false;
+            while self.receiver.try_recv().is_ok() {}
+            self.latest_components.clear();
+            self.latest_resources.clear();
+            self.latest_sections.clear();
+            self.pending_logs.clear();
+            self.staged.clear();
+            if let Some(heartbeat) = heartbeat {
+                let messages = [heartbeat];
+                let data = PacketData {
+                    messages: fragments(&messages),
+                    ..PacketData::default()
+                };
+                self.queue_packet(clock, data);
+            }
+            self.send_packets(now, &mut sync_stats, &mut status);
+            return;
+        }
+
+        // The editor may have lost track of the game's state while it was away, or asked for the
+        // full state, so send the full state again right away.
+        let resync = self.presence.resync_requested(&mut self.resyncs);
+        if !self.was_active || resync {
+            self.was_active = true;
+            self.sent_components.clear();
+            self.sent_resources.clear();
+            self.sent_sections.clear();
+            self.next_send = now;
+        }
+
+        // Determine if we should send full state data this frame.
         let send_this_frame = now >= self.next_send;
 
         // Calculate when we should next send full state data.
@@ -61,85 +419,592 @@ impl<'a> System<'a> for EditorSenderSystem {
         // NOTE: We repeatedly add `send_interval` to `next_send` to ensure that the next send
         // time is after `now`. This is to avoid running into a death spiral if a frame spike
         // causes frame time to be so long that the next send time would still be in the past.
-        while self.next_send < now {
-            self.next_send += self.send_interval;
+        //
+        // NOTE: A send interval of zero means that we send every frame, in which case adding
+        // the interval would never move `next_send` past `now`.
+        if self.settings.send_interval == Duration::from_millis(0) {
+            self.next_send = now;
+        } else {
+            while self.next_send < now {
+                self.next_send += self.settings.send_interval;
+            }
         }
 
-        let mut components = Vec::new();
-        let mut resources = Vec::new();
+        // Optional snapshot features are only used if the editor supports them.
+        let capabilities = self.capabilities.get();
+        let delta_only = self.settings.delta_only && capabilities.contains(Capability::Deltas);
+
+        // Snapshots sent round-robin only contain some of the component types, so they're
+        // marked as deltas as well.
+        let round_robin =
+            self.schedule.is_round_robin() && capabilities.contains(Capability::Deltas);
+        let partial = delta_only || round_robin;
+
+        // Snapshots split across several frames only contain some of the types on each frame,
+        // so they can only be sent to editors that understand partial snapshots.
+        let split = self.settings.send_frames > 1 && capabilities.contains(Capability::Deltas);
+
+        // At the minimal sync level, snapshots only include the entity count. The read systems
+        // stop serializing state, but anything serialized before the switch is dropped here.
+        let minimal = self.subscriptions.is_minimal();
+        let entity_summary = minimal
+            || (self.settings.entity_summary && capabilities.contains(Capability::EntitySummary));
+        let send_stats =
+            !minimal && self.settings.stats && capabilities.contains(Capability::Stats);
+
         let mut messages = Vec::new();
         while let Ok(serialized) = self.receiver.try_recv() {
             match serialized {
-                SerializedData::Component(c) => components.push(c),
-                SerializedData::Resource(r) => resources.push(r),
+                SerializedData::Component(name, c, count, entities) => {
+                    self.latest_components.insert(name, (c, count, entities));
+                }
+                SerializedData::Resource(name, r) => {
+                    self.latest_resources.insert(name, r);
+                }
+                SerializedData::Section(name, s) => {
+                    self.latest_sections.insert(name, s);
+                }
                 SerializedData::Message(m) => messages.push(m),
+                SerializedData::Log(m) => self.pending_logs.push_back((now, m)),
             }
         }
 
-        let mut entity_data = Vec::<SerializableEntity>::new();
-        for (entity,) in (&*entities,).join() {
-            entity_data.push(entity.into());
+        // Components and resources are sorted by name, since the read systems run in parallel and
+        // may finish in any order. This keeps snapshots of identical state identical.
+        let mut components = BTreeMap::new();
+        let mut resources = BTreeMap::new();
+        let mut sections = BTreeMap::new();
+        let mut stats = Stats::default();
+        let mut component_entities = BTreeMap::new();
+        if send_this_frame {
+            let latest_components = mem::replace(&mut self.latest_components, BTreeMap::new());
+            for (name, (c, count, entities)) in latest_components {
+                component_entities.insert(name, entities);
+                stats.components.insert(
+                    name,
+                    TypeStats {
+                        count: Some(count),
+                        bytes: c.get().len(),
+                    },
+                );
+                if !delta_only || changed(&mut self.sent_components, name, c.get()) {
+                    components.insert(name, c);
+                }
+            }
+
+            let latest_resources = mem::replace(&mut self.latest_resources, BTreeMap::new());
+            for (name, r) in latest_resources {
+                stats.resources.insert(
+                    name,
+                    TypeStats {
+                        count: None,
+                        bytes: r.get().len(),
+                    },
+                );
+                if !delta_only || changed(&mut self.sent_resources, name, r.get()) {
+                    resources.insert(name, r);
+                }
+            }
+
+            let latest_sections = mem::replace(&mut self.latest_sections, BTreeMap::new());
+            for (name, s) in latest_sections {
+                if !delta_only || changed(&mut self.sent_sections, name, s.get()) {
+                    sections.insert(name, s);
+                }
+            }
+
+            // If the previous snapshot is still being sent, the parts that are left go out with
+            // this one instead, unless newer data replaced them.
+            let leftover = !self.staged.is_empty();
+            for part in self.staged.drain(..) {
+                for (name, c) in part.components {
+                    components.entry(name).or_insert(c);
+                }
+                for (name, r) in part.resources {
+                    resources.entry(name).or_insert(r);
+                }
+                for (name, s) in part.sections {
+                    sections.entry(name).or_insert(s);
+                }
+            }
+
+            sync_stats.types_deferred += self.apply_budget(
+                &mut components,
+                &mut resources,
+                &mut sections,
+                &stats,
+                &component_entities,
+            );
+
+            if round_robin {
+                self.schedule.advance();
+            }
+
+            // Only the first part of the snapshot is assembled and serialized this frame, and
+            // the rest on the frames that follow.
+            if split && !minimal && !leftover {
+                self.staged = split_snapshot(
+                    &mut components,
+                    &mut resources,
+                    &mut sections,
+                    self.settings.send_frames,
+                );
+            }
+        }
+
+        // Logs are normally sent right away, but when a maximum log delay is set they're held
+        // back on frames that send a snapshot and trickled out over the following frames, so
+        // that a burst of logs doesn't make a frame spike worse.
+        match self.settings.max_log_delay {
+            Some(max_delay) => {
+                let budget = if send_this_frame {
+                    0
+                } else {
+                    self.queue.max_packet_size()
+                };
+                take_logs(
+                    &mut self.pending_logs,
+                    now,
+                    max_delay,
+                    budget,
+                    &mut messages,
+                );
+            }
+
+            None => messages.extend(self.pending_logs.drain(..).map(|(_, log)| log)),
         }
-        let entity_string =
-            serde_json::to_string(&entity_data).expect("Failed to serialize entities");
+
+        messages.extend(heartbeat);
+        messages.extend(self.transport_stats(now, &sync_stats));
 
         // Create the message and serialize it to JSON. If we don't need to send the full state
         // data this frame, we discard entities, components, and resources, and only send the
         // messages (e.g. log output) from the current frame.
-        if send_this_frame {
-            write!(
-                self.scratch_string,
-                r#"{{
-                    "type": "message",
-                    "data": {{
-                        "entities": {},
-                        "components": [{}],
-                        "resources": [{}],
-                        "messages": [{}]
-                    }}
-                }}"#,
-                entity_string,
-                // Insert a comma between components so that it's valid JSON.
-                components.join(","),
-                resources.join(","),
-                messages.join(","),
-            )
-            .expect("Failed to write JSON string");
+        let stats = if send_this_frame && send_stats {
+            match serde_json::value::to_raw_value(&stats) {
+                Ok(stats) => Some(stats),
+                Err(error) => {
+                    error!("Failed to serialize stats: {}", error);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let part = if send_this_frame {
+            None
+        } else {
+            self.staged.pop_front()
+        };
+        let data: PacketData<Serialized> = if send_this_frame {
+            let (entity_list, entity_count) = if entity_summary {
+                (None, Some((&*entities).join().count()))
+            } else {
+                let mut entity_data = Vec::new();
+                for (entity,) in (&*entities,).join() {
+                    entity_data.push(EntityEntry {
+                        entity: DeserializableEntity {
+                            id: entity.id(),
+                            generation: entity.gen().id(),
+                        },
+                        mapped_id: id_map.mapped_id(entity.id()),
+                        name: names.get(entity),
+                        uuid: uuids.get(entity).cloned(),
+                        components: if self.settings.entity_components {
+                            Some(components_of(&component_entities, entity.id()))
+                        } else {
+                            None
+                        },
+                    });
+                }
+                (Some(entity_data), None)
+            };
+
+            if minimal {
+                PacketData {
+                    entity_count,
+                    time: Some(TimingInfo::from_time(&time)),
+                    messages: fragments(&messages),
+                    ..PacketData::default()
+                }
+            } else {
+                PacketData {
+                    entities: entity_list,
+                    entity_count,
+                    time: Some(TimingInfo::from_time(&time)),
+                    delta: Some(partial || !self.staged.is_empty()),
+                    components: Some(fragments(components.values())),
+                    resources: Some(fragments(resources.values())),
+                    stats: stats.as_ref().map(|stats| Fragment(stats)),
+                    messages: fragments(&messages),
+                    sections: sections
+                        .iter()
+                        .map(|(&name, s)| (name.to_owned(), Fragment(s)))
+                        .collect(),
+                }
+            }
+        } else if let Some(ref part) = part {
+            PacketData {
+                time: Some(TimingInfo::from_time(&time)),
+                delta: Some(true),
+                components: Some(fragments(part.components.values())),
+                resources: Some(fragments(part.resources.values())),
+                messages: fragments(&messages),
+                sections: part
+                    .sections
+                    .iter()
+                    .map(|(&name, s)| (name.to_owned(), Fragment(s)))
+                    .collect(),
+                ..PacketData::default()
+            }
+        } else {
+            PacketData {
+                messages: fragments(&messages),
+                ..PacketData::default()
+            }
+        };
+        self.queue_packet(clock, data);
+
+        // Spread the packets queued for a snapshot evenly over the configured number of frames.
+        // A snapshot that was split into parts is already spread out, so each part is sent on
+        // the frame it was serialized on.
+        if !self.staged.is_empty() || part.is_some() {
+            self.packets_per_frame = self.queue.len();
+        } else if send_this_frame {
+            let packets = self.queue.len();
+            let send_frames = self.settings.send_frames;
+            self.packets_per_frame = (packets + send_frames - 1) / send_frames;
+        }
+
+        self.send_packets(now, &mut sync_stats, &mut status);
+    }
+}
+
+impl EditorSenderSystem {
+    /// Wraps `data` in a packet tagged with the frame number and time in `clock`, and queues it
+    /// to be sent.
+    fn queue_packet(&mut self, (frame, absolute_time): (u64, f64), data: PacketData<Serialized>) {
+        let packet = Packet {
+            ty: "message".to_owned(),
+            instance: self.settings.instance_id.clone().map(Value::String),
+            frame,
+            absolute_time: Some(absolute_time),
+            data,
+        };
+
+        // Binary codecs are only used once the editor has said that it can decode them.
+        let codec = match self.settings.codec {
+            Codec::MessagePack if self.capabilities.contains(Capability::MessagePack) => {
+                Codec::MessagePack
+            }
+            _ => Codec::Json,
+        };
+        if let Err(error) = self.queue.queue_encoded(codec, &packet) {
+            error!("Failed to serialize message to the editor: {}", error);
+        }
+    }
+
+    /// Serializes a `"transport_stats"` message from the totals in `stats`, if one is due.
+    fn transport_stats(&mut self, now: Instant, stats: &SyncStats) -> Option<Box<RawValue>> {
+        let interval = self.settings.transport_stats_interval?;
+        if now < self.next_transport_stats || !self.subscriptions.wants("transport_stats") {
+            return None;
+        }
+        self.next_transport_stats = now + interval;
+
+        let (last_time, last_packets, last_bytes) = self.last_transport_stats;
+        self.last_transport_stats = (now, stats.packets_sent, stats.bytes_sent);
+        let elapsed = duration_to_secs(now.duration_since(last_time));
+        let per_second = |count: u64| {
+            if elapsed > 0.0 {
+                count as f64 / elapsed
+            } else {
+                0.0
+            }
+        };
+
+        serialize_message(
+            "transport_stats",
+            TransportStats {
+                packets_sent: stats.packets_sent,
+                bytes_sent: stats.bytes_sent,
+                packets_dropped: stats.packets_dropped,
+                send_retries: stats.send_retries,
+                packets_queued: self.queue.len(),
+                packets_per_second: per_second(stats.packets_sent - last_packets),
+                bytes_per_second: per_second(stats.bytes_sent - last_bytes),
+                rate_limit: self.queue.rate_limit(),
+            },
+        )
+    }
+
+    /// Leaves components, resources and sections out of the snapshot being sent until the rest
+    /// fit within the byte budget, if one is set, returning the number of types left out.
+    ///
+    /// The data left out is kept for the next snapshot unless newer data arrives first, and its
+    /// hash is forgotten so that it's sent even if it doesn't change in the meantime.
+    fn apply_budget(
+        &mut self,
+        components: &mut BTreeMap<&'static str, Box<RawValue>>,
+        resources: &mut BTreeMap<&'static str, Box<RawValue>>,
+        sections: &mut BTreeMap<&'static str, Box<RawValue>>,
+        stats: &Stats,
+        component_entities: &BTreeMap<&'static str, BitSet>,
+    ) -> u64 {
+        let entries = components
+            .iter()
+            .chain(resources.iter())
+            .chain(sections.iter())
+            .map(|(&name, data)| (name, data.get().len()))
+            .collect::<Vec<_>>();
+        let send = match self.budget {
+            Some(ref mut budget) => budget.select(&entries),
+            None => return 0,
+        };
+
+        let resources_start = components.len();
+        let sections_start = resources_start + resources.len();
+        let mut deferred = 0;
+        for (index, (&(name, _), send)) in entries.iter().zip(send).enumerate() {
+            if send {
+                continue;
+            }
+
+            if index < resources_start {
+                let c = components
+                    .remove(name)
+                    .expect("Deferred component not found");
+                let count = stats
+                    .components
+                    .get(name)
+                    .and_then(|stats| stats.count)
+                    .unwrap_or(0);
+                let entities = component_entities
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(BitSet::new);
+                self.latest_components.insert(name, (c, count, entities));
+                self.sent_components.remove(name);
+            } else if index < sections_start {
+                let r = resources.remove(name).expect("Deferred resource not found");
+                self.latest_resources.insert(name, r);
+                self.sent_resources.remove(name);
+            } else {
+                let s = sections.remove(name).expect("Deferred section not found");
+                self.latest_sections.insert(name, s);
+                self.sent_sections.remove(name);
+            }
+            deferred += 1;
+        }
+
+        deferred
+    }
+
+    /// Sends this frame's share of the queued packets, recording the outcome in `stats` and
+    /// `status`.
+    fn send_packets(
+        &mut self,
+        now: Instant,
+        stats: &mut SyncStats,
+        status: &mut EditorConnectionStatus,
+    ) {
+        let messages_dropped = self.queue.send(now, self.packets_per_frame.max(1), stats);
+        status.messages_dropped_by(messages_dropped);
+    }
+}
+
+fn duration_to_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
+}
+
+/// Moves components, resources and sections out of a snapshot into up to `parts - 1` further
+/// parts of roughly the same size, which are returned in the order they should be sent.
+///
+/// The snapshot keeps the first share of the data, so that it's never left empty.
+fn split_snapshot(
+    components: &mut BTreeMap<&'static str, Box<RawValue>>,
+    resources: &mut BTreeMap<&'static str, Box<RawValue>>,
+    sections: &mut BTreeMap<&'static str, Box<RawValue>>,
+    parts: usize,
+) -> VecDeque<SnapshotPart> {
+    let entries = components
+        .iter()
+        .chain(resources.iter())
+        .chain(sections.iter())
+        .map(|(&name, data)| (name, data.get().len()))
+        .collect::<Vec<_>>();
+    let total = entries.iter().map(|&(_, size)| size).sum::<usize>().max(1);
+
+    let resources_start = components.len();
+    let sections_start = resources_start + resources.len();
+    let mut rest = (1..parts)
+        .map(|_| SnapshotPart::default())
+        .collect::<Vec<_>>();
+    let mut offset = 0;
+    for (index, (name, size)) in entries.into_iter().enumerate() {
+        // Each entry goes in the part that its first byte falls in.
+        let part = offset * parts / total;
+        offset += size;
+        if part == 0 {
+            continue;
+        }
+
+        let part = &mut rest[part - 1];
+        if index < resources_start {
+            let c = components.remove(name).expect("Split component not found");
+            part.components.insert(name, c);
+        } else if index < sections_start {
+            let r = resources.remove(name).expect("Split resource not found");
+            part.resources.insert(name, r);
         } else {
-            write!(
-                self.scratch_string,
-                r#"{{
-                    "type": "message",
-                    "data": {{
-                        "messages": [{}]
-                    }}
-                }}"#,
-                // Insert a comma between components so that it's valid JSON.
-                messages.join(","),
-            )
-            .expect("Failed to write JSON string");
-        }
-
-        // NOTE: We need to append a page feed character after each message since that's
-        // what node-ipc expects to delimit messages.
-        self.scratch_string.push_str("\u{C}");
-
-        // Send the message, breaking it up into multiple packets if the message is too large.
-        let editor_address: std::net::SocketAddr = ([127, 0, 0, 1], 8000).into();
-        let mut bytes_sent = 0;
-        while bytes_sent < self.scratch_string.len() {
-            let bytes_to_send = min(self.scratch_string.len() - bytes_sent, MAX_PACKET_SIZE);
-            let end_offset = bytes_sent + bytes_to_send;
-
-            // Send the JSON message.
-            let bytes = self.scratch_string[bytes_sent..end_offset].as_bytes();
-            self.socket
-                .send_to(bytes, editor_address)
-                .expect("Failed to send message");
-
-            bytes_sent += bytes_to_send;
-        }
-
-        self.scratch_string.clear();
+            let s = sections.remove(name).expect("Split section not found");
+            part.sections.insert(name, s);
+        }
+    }
+
+    rest.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+/// Moves logs from `pending` to `messages`, up to `budget` bytes worth of logs.
+///
+/// Logs that have been waiting for at least `max_delay` are always taken, regardless of the
+/// budget. Logs are taken in the order they were received.
+fn take_logs(
+    pending: &mut VecDeque<(Instant, Box<RawValue>)>,
+    now: Instant,
+    max_delay: Duration,
+    mut budget: usize,
+    messages: &mut Vec<Box<RawValue>>,
+) {
+    while let Some(&(received, ref log)) = pending.front() {
+        let size = log.get().len();
+        let overdue = now.duration_since(received) >= max_delay;
+        if !overdue && size > budget {
+            break;
+        }
+
+        budget = budget.saturating_sub(size);
+        let (_, log) = pending.pop_front().unwrap();
+        messages.push(log);
+    }
+}
+
+/// The names of the components that the entity with the given ID has, in order of name.
+fn components_of(
+    component_entities: &BTreeMap<&'static str, BitSet>,
+    id: u32,
+) -> Vec<&'static str> {
+    component_entities
+        .iter()
+        .filter(|(_, entities)| entities.contains(id))
+        .map(|(&name, _)| name)
+        .collect()
+}
+
+/// Borrows the serialized `data` to be embedded in a packet.
+fn fragments<'a, I>(data: I) -> Vec<Fragment<'a>>
+where
+    I: IntoIterator<Item = &'a Box<RawValue>>,
+{
+    data.into_iter().map(|raw| Fragment(raw)).collect()
+}
+
+/// Records the hash of `data` as the last data sent for `name`, returning whether it differs
+/// from what was previously sent.
+fn changed(sent: &mut HashMap<&'static str, u64>, name: &'static str, data: &str) -> bool {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    sent.insert(name, hash) != Some(hash)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw(json: &str) -> Box<RawValue> {
+        RawValue::from_string(json.to_owned()).unwrap()
+    }
+
+    fn texts(messages: &[Box<RawValue>]) -> Vec<&str> {
+        messages.iter().map(|message| message.get()).collect()
+    }
+
+    #[test]
+    fn take_logs_within_budget() {
+        let now = Instant::now();
+        let mut pending = vec![(now, raw("[11]")), (now, raw("[22]"))]
+            .into_iter()
+            .collect::<VecDeque<_>>();
+
+        let mut messages = Vec::new();
+        take_logs(&mut pending, now, Duration::from_secs(1), 6, &mut messages);
+        assert_eq!(texts(&messages), vec!["[11]"]);
+
+        take_logs(&mut pending, now, Duration::from_secs(1), 0, &mut messages);
+        assert_eq!(texts(&messages), vec!["[11]"]);
+
+        take_logs(&mut pending, now, Duration::from_secs(1), 4, &mut messages);
+        assert_eq!(texts(&messages), vec!["[11]", "[22]"]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn serialized_data_is_embedded_as_is() {
+        let messages = [serialize_message("log", "hello").unwrap()];
+        let data = PacketData::<Serialized> {
+            messages: fragments(&messages),
+            ..PacketData::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&data).unwrap(),
+            r#"{"messages":[{"type":"log","data":"hello"}]}"#
+        );
+    }
+
+    #[test]
+    fn take_overdue_logs() {
+        let received = Instant::now();
+        let now = received + Duration::from_millis(100);
+        let mut pending = vec![(received, raw("[11]")), (now, raw("[22]"))]
+            .into_iter()
+            .collect::<VecDeque<_>>();
+
+        let mut messages = Vec::new();
+        take_logs(
+            &mut pending,
+            now,
+            Duration::from_millis(100),
+            0,
+            &mut messages,
+        );
+        assert_eq!(texts(&messages), vec!["[11]"]);
+        assert_eq!(pending.len(), 1);
+    }
+
+    #[test]
+    fn split_snapshot_by_size() {
+        let mut components = vec![("A", raw("[1111]")), ("B", raw("[2222]"))]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let mut resources = vec![("R", raw("[3333]"))].into_iter().collect();
+        let mut sections = vec![("S", raw("[4444]"))].into_iter().collect();
+
+        let parts = split_snapshot(&mut components, &mut resources, &mut sections, 2);
+        assert_eq!(components.keys().collect::<Vec<_>>(), vec![&"A", &"B"]);
+        assert!(resources.is_empty() && sections.is_empty());
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].components.is_empty());
+        assert_eq!(parts[0].resources["R"].get(), "[3333]");
+        assert_eq!(parts[0].sections["S"].get(), "[4444]");
+
+        // A part is never sent empty, even if there are more frames than types.
+        let mut resources = BTreeMap::new();
+        let mut sections = BTreeMap::new();
+        let parts = split_snapshot(&mut components, &mut resources, &mut sections, 4);
+        assert_eq!(components.keys().collect::<Vec<_>>(), vec![&"A"]);
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].components["B"].get(), "[2222]");
     }
 }
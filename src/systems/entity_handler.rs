@@ -1,51 +1,338 @@
-use amethyst::ecs::{Entities, System};
+use amethyst::ecs::{Entities, Join, System, Write};
 use crossbeam_channel::Receiver;
-use crate::types::EntityMessage;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use crate::entity_map::EntityIdMap;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::{EditStatus, EditorConnection, EntityMessage};
 
-/// A system that deserializes incoming updates for a resource and applies
-/// them to the world state.
+/// Sent to the editor as an `"entities_created"` message in response to a `CreateEntities`
+/// message.
+#[derive(Debug, Serialize)]
+struct EntitiesCreated {
+    entities: Vec<SerializableEntity>,
+
+    /// The temporary IDs that the editor gave the entities, in the same order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    temporary_ids: Vec<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// Sent to the editor as an `"error"` message when a request to create or destroy entities
+/// goes over the limits set with [`SyncEditorBundle::limit_entity_commands`].
+///
+/// [`SyncEditorBundle::limit_entity_commands`]: ./struct.SyncEditorBundle.html#method.limit_entity_commands
+#[derive(Debug, Serialize)]
+struct LimitExceeded {
+    /// The message that was rejected, either `"CreateEntities"` or `"DestroyEntities"`.
+    id: &'static str,
+    amount: usize,
+    error: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// Limits on the entity commands sent by the editor, which keep a buggy editor from freezing
+/// the game by asking for millions of entities at once.
+#[derive(Debug, Clone)]
+pub(crate) struct EntityLimits {
+    /// The maximum number of entities created in a single frame.
+    pub created_per_frame: usize,
+
+    /// The maximum number of entities destroyed in a single frame.
+    pub destroyed_per_frame: usize,
+
+    /// The maximum number of live entities that the editor can create entities up to.
+    pub max_entities: Option<usize>,
+
+    // The number of entities created by transactions this frame, shared by every copy of the
+    // limits. The entity handler counts them towards the frame's limit and then resets it.
+    created_by_transactions: Arc<AtomicUsize>,
+}
+
+impl Default for EntityLimits {
+    fn default() -> Self {
+        EntityLimits {
+            created_per_frame: 10_000,
+            destroyed_per_frame: 10_000,
+            max_entities: None,
+            created_by_transactions: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl EntityLimits {
+    /// Checks whether `amount` entities can be created, reporting the request back to the editor
+    /// if they can't.
+    pub(crate) fn check_create(
+        &self,
+        entities: &Entities,
+        amount: usize,
+        request_id: Option<u64>,
+        connection: &EditorConnection,
+    ) -> bool {
+        let error = if amount > self.created_per_frame {
+            format!(
+                "Can't create {} entities, at most {} can be created per frame",
+                amount, self.created_per_frame
+            )
+        } else {
+            match self.max_entities {
+                Some(max) => {
+                    let live = (&**entities).join().count();
+                    if live + amount <= max {
+                        return true;
+                    }
+                    format!(
+                        "Can't create {} entities, there are already {} of at most {}",
+                        amount, live, max
+                    )
+                }
+                None => return true,
+            }
+        };
+
+        report_exceeded(connection, "CreateEntities", amount, error, request_id);
+        false
+    }
+
+    /// Checks whether `amount` entities can be created by a transaction, reporting the request
+    /// back to the editor if they can't.
+    ///
+    /// Unlike other requests, transactions are applied as soon as they're received and can't be
+    /// held back until a later frame, so they're rejected if they would go over the frame's
+    /// limit together with the entities already created by transactions this frame.
+    pub(crate) fn check_create_in_transaction(
+        &self,
+        entities: &Entities,
+        amount: usize,
+        request_id: Option<u64>,
+        connection: &EditorConnection,
+    ) -> bool {
+        if !self.check_create(entities, amount, request_id, connection) {
+            return false;
+        }
+
+        let created = self.created_by_transactions.load(Ordering::SeqCst);
+        if created + amount > self.created_per_frame {
+            let error = format!(
+                "Can't create {} entities, {} of at most {} have already been created this frame",
+                amount, created, self.created_per_frame
+            );
+            report_exceeded(connection, "CreateEntities", amount, error, request_id);
+            return false;
+        }
+
+        self.created_by_transactions
+            .fetch_add(amount, Ordering::SeqCst);
+        true
+    }
+
+    /// Checks whether `amount` entities can be destroyed, reporting the request back to the
+    /// editor if they can't.
+    fn check_destroy(
+        &self,
+        amount: usize,
+        request_id: Option<u64>,
+        connection: &EditorConnection,
+    ) -> bool {
+        if amount <= self.destroyed_per_frame {
+            return true;
+        }
+
+        let error = format!(
+            "Can't destroy {} entities, at most {} can be destroyed per frame",
+            amount, self.destroyed_per_frame
+        );
+        report_exceeded(connection, "DestroyEntities", amount, error, request_id);
+        false
+    }
+}
+
+fn report_exceeded(
+    connection: &EditorConnection,
+    id: &'static str,
+    amount: usize,
+    error: String,
+    request_id: Option<u64>,
+) {
+    warn!("Rejected entity command from the editor: {}", error);
+    connection.send_message(
+        "error",
+        LimitExceeded {
+            id,
+            amount,
+            error,
+            request_id,
+        },
+    );
+    connection.send_ack(request_id, EditStatus::LimitExceeded);
+}
+
+/// A system that creates and destroys entities at the editor's request.
 ///
-/// An instance of this system is created for each writable resource registered
-/// with [`SyncEditorBundle`] by the player during setup for their game.
+/// Entities created for a `CreateEntities` message are reported back to the editor in an
+/// `"entities_created"` message, mapped to the temporary IDs the editor gave them, if any.
+/// Entities named in a `DestroyEntities` message are deleted, and the request is acknowledged
+/// with a `stale_entity` status if any of them no longer existed. A single instance of this
+/// system is added by [`SyncEditorBundle`].
+///
+/// Requests that go over the per-frame limits on their own are rejected. Requests that only go
+/// over them together with earlier requests in the same frame are held back until a later
+/// frame, so that requests are always handled in the order they were sent. Entities created by
+/// transactions, which the receiver creates itself, count towards the same per-frame limit.
 ///
 /// [`SyncEditorBundle`]: ./struct.SyncEditorBundle.html
 pub(crate) struct EntityHandlerSystem {
     receiver: Receiver<EntityMessage>,
+    connection: EditorConnection,
+    limits: EntityLimits,
+
+    // Requests held back because the limits for the frame were reached.
+    pending: VecDeque<EntityMessage>,
 }
 
 impl EntityHandlerSystem {
-    pub(crate) fn new(receiver: Receiver<EntityMessage>) -> Self {
-        EntityHandlerSystem { receiver }
+    pub(crate) fn new(
+        receiver: Receiver<EntityMessage>,
+        connection: EditorConnection,
+        limits: EntityLimits,
+    ) -> Self {
+        EntityHandlerSystem {
+            receiver,
+            connection,
+            limits,
+            pending: VecDeque::new(),
+        }
     }
 }
 
 impl<'a> System<'a> for EntityHandlerSystem {
-    type SystemData = Option<Entities<'a>>;
+    type SystemData = (Option<Entities<'a>>, Write<'a, EntityIdMap>);
 
-    fn run(&mut self, data: Self::SystemData) {
-        trace!("`CreateEntitiesSystem::run`");
+    fn run(&mut self, (data, mut id_map): Self::SystemData) {
+        trace!("`EntityHandlerSystem::run`");
 
         let entities = match data {
             Some(res) => res,
             None => return,
         };
 
-        while let Ok(message) = self.receiver.try_recv() {
+        self.pending.extend(self.receiver.try_iter());
+
+        // NOTE: The receiver runs first, so the entities created by transactions this frame
+        // have already been counted.
+        let mut created = self
+            .limits
+            .created_by_transactions
+            .swap(0, Ordering::SeqCst);
+        let mut destroyed = 0;
+        while let Some(message) = self.pending.pop_front() {
             match message {
-                EntityMessage::Create(amount) => {
-                    let mut ids = Vec::with_capacity(amount);
-                    for _ in 0..amount {
-                        ids.push(entities.create().id());
+                EntityMessage::Create(amount, temporary_ids, request_id) => {
+                    if !self
+                        .limits
+                        .check_create(&entities, amount, request_id, &self.connection)
+                    {
+                        continue;
                     }
+
+                    if created + amount > self.limits.created_per_frame {
+                        self.pending.push_front(EntityMessage::Create(
+                            amount,
+                            temporary_ids,
+                            request_id,
+                        ));
+                        break;
+                    }
+
+                    created += amount;
+                    create_entities(
+                        &entities,
+                        &mut id_map,
+                        amount,
+                        temporary_ids,
+                        request_id,
+                        &self.connection,
+                    );
                 }
-                EntityMessage::Destroy(ids) => {
+                EntityMessage::Destroy(ids, request_id) => {
+                    if !self
+                        .limits
+                        .check_destroy(ids.len(), request_id, &self.connection)
+                    {
+                        continue;
+                    }
+
+                    if destroyed + ids.len() > self.limits.destroyed_per_frame {
+                        self.pending
+                            .push_front(EntityMessage::Destroy(ids, request_id));
+                        break;
+                    }
+
+                    destroyed += ids.len();
+                    let mut status = EditStatus::Applied;
                     for id in ids {
                         let entity = entities.entity(id);
                         let result = entities.delete(entity);
                         trace!("Result of destroying entity {:?}: {:?}", id, result);
+                        if result.is_err() {
+                            status = EditStatus::StaleEntity;
+                        }
                     }
+                    self.connection.send_ack(request_id, status);
                 }
             }
         }
     }
 }
+
+/// Creates `amount` entities, assigning them the editor's temporary IDs in order, and sends
+/// them back to the editor.
+///
+/// Entities are created atomically, so this can be done from any system with access to
+/// `Entities`. This allows the receiver to create the entities of a transaction right away.
+pub(crate) fn create_entities(
+    entities: &Entities,
+    id_map: &mut EntityIdMap,
+    amount: usize,
+    mut temporary_ids: Vec<u64>,
+    request_id: Option<u64>,
+    connection: &EditorConnection,
+) {
+    if temporary_ids.len() > amount {
+        debug!(
+            "Got {} temporary IDs for {} new entities, ignoring the rest",
+            temporary_ids.len(),
+            amount
+        );
+        temporary_ids.truncate(amount);
+    }
+
+    if !temporary_ids.is_empty() {
+        id_map.retain_temporary(|entity| entities.is_alive(entity));
+    }
+
+    let mut created = Vec::with_capacity(amount);
+    for index in 0..amount {
+        let entity = entities.create();
+        if let Some(&temporary_id) = temporary_ids.get(index) {
+            id_map.insert_temporary(temporary_id, entity);
+        }
+        created.push(entity.into());
+    }
+
+    connection.send_message(
+        "entities_created",
+        EntitiesCreated {
+            entities: created,
+            temporary_ids,
+            request_id,
+        },
+    );
+    connection.send_ack(request_id, EditStatus::Applied);
+}
@@ -0,0 +1,34 @@
+use amethyst::ecs::{Entities, Join, ReadStorage, System, Write};
+use crate::entity_map::{EntityIdMap, EntityIdMapping};
+use std::marker::PhantomData;
+
+/// A system that rebuilds the [`EntityIdMap`] from the registered [`EntityIdMapping`]
+/// component each frame.
+///
+/// [`EntityIdMap`]: ../entity_map/struct.EntityIdMap.html
+/// [`EntityIdMapping`]: ../trait.EntityIdMapping.html
+pub(crate) struct EntityIdMapSystem<T> {
+    _phantom: PhantomData<T>,
+}
+
+impl<T> EntityIdMapSystem<T> {
+    pub(crate) fn new() -> Self {
+        EntityIdMapSystem {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> System<'a> for EntityIdMapSystem<T>
+where
+    T: EntityIdMapping,
+{
+    type SystemData = (Entities<'a>, ReadStorage<'a, T>, Write<'a, EntityIdMap>);
+
+    fn run(&mut self, (entities, mappings, mut id_map): Self::SystemData) {
+        id_map.clear();
+        for (entity, mapping) in (&*entities, &mappings).join() {
+            id_map.insert(entity, mapping.mapped_id());
+        }
+    }
+}
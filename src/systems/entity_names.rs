@@ -0,0 +1,70 @@
+use amethyst::core::Named;
+use amethyst::ecs::storage::MaskedStorage;
+use amethyst::ecs::{Entity, Join, LazyUpdate, Read, System, World};
+use crate::entity_names::{EntityNames, EntityNaming};
+use crate::registry::Registry;
+use std::sync::Arc;
+
+/// A system that keeps the [`EntityNames`] up to date, so that entities show up in the editor
+/// with a readable name.
+///
+/// Entities with a `Named` component use that name. Other entities are given a generated name
+/// the first time they're seen, which they keep for as long as they exist. Since generating
+/// names may need to check any registered component, the names are updated at the end of the
+/// frame, when the world can be accessed directly.
+///
+/// [`EntityNames`]: ../entity_names/struct.EntityNames.html
+pub(crate) struct EntityNameSystem {
+    naming: EntityNaming,
+    registry: Arc<Registry>,
+}
+
+impl EntityNameSystem {
+    pub(crate) fn new(naming: EntityNaming, registry: Arc<Registry>) -> Self {
+        EntityNameSystem { naming, registry }
+    }
+}
+
+impl<'a> System<'a> for EntityNameSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        let naming = self.naming;
+        let registry = self.registry.clone();
+        lazy.exec(move |world| {
+            let entities = world.entities();
+            let mut names = world.write_resource::<EntityNames>();
+            names.retain(|entity| entities.is_alive(entity));
+
+            let named = if world.res.has_value::<MaskedStorage<Named>>() {
+                Some(world.read_storage::<Named>())
+            } else {
+                None
+            };
+
+            for entity in (&*entities).join() {
+                if let Some(named) = named.as_ref().and_then(|named| named.get(entity)) {
+                    names.insert(entity, named.name.to_string());
+                } else if !names.contains(entity) {
+                    names.insert(entity, generate_name(world, &registry, naming, entity));
+                }
+            }
+        });
+    }
+}
+
+fn generate_name(
+    world: &World,
+    registry: &Registry,
+    naming: EntityNaming,
+    entity: Entity,
+) -> String {
+    let component = match naming {
+        EntityNaming::Id => None,
+        EntityNaming::DominantComponent => registry
+            .components()
+            .find(|access| access.contains(world, entity))
+            .map(|access| access.name()),
+    };
+    format!("{} {}", component.unwrap_or("Entity"), entity.id())
+}
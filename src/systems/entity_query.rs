@@ -0,0 +1,86 @@
+use amethyst::ecs::{LazyUpdate, Read, System};
+use crossbeam_channel::Receiver;
+use crate::query::{self, JsonPathExpr};
+use crate::registry::Registry;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::EditorConnection;
+use std::sync::Arc;
+
+/// A request from the editor to find the entities matching a query.
+pub(crate) struct EntityQuery {
+    pub with_components: Vec<String>,
+    pub where_clause: Option<JsonPathExpr>,
+    pub request_id: Option<u64>,
+}
+
+/// Result of an entity query, sent back to the editor.
+#[derive(Debug, Clone, Serialize)]
+struct QueryResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+
+    entities: Vec<SerializableEntity>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A system that answers entity queries from the editor, so that the editor can search for
+/// entities without having every component of every entity synced.
+///
+/// Like scene exports, queries need to visit arbitrary registered component types, so they're
+/// deferred until the end of the frame, when they can access the world directly.
+pub(crate) struct EntityQuerySystem {
+    receiver: Receiver<EntityQuery>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+}
+
+impl EntityQuerySystem {
+    pub(crate) fn new(
+        receiver: Receiver<EntityQuery>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        EntityQuerySystem {
+            receiver,
+            registry,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for EntityQuerySystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        while let Ok(query) = self.receiver.try_recv() {
+            let registry = self.registry.clone();
+            let connection = self.connection.clone();
+            lazy.exec(move |world| {
+                let result = query::find_entities(
+                    world,
+                    &registry,
+                    &query.with_components,
+                    query.where_clause.as_ref(),
+                );
+                if let Err(ref error) = result {
+                    debug!("Failed to run entity query: {}", error);
+                }
+
+                let (entities, error) = match result {
+                    Ok(entities) => (entities.into_iter().map(Into::into).collect(), None),
+                    Err(error) => (Vec::new(), Some(error)),
+                };
+                connection.send_message(
+                    "query_result",
+                    QueryResult {
+                        request_id: query.request_id,
+                        entities,
+                        error,
+                    },
+                );
+            });
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use amethyst::ecs::{Entities, Join, System, WriteStorage};
+use crate::editor_uuid::EditorUuid;
+
+/// A system that gives an [`EditorUuid`] to every entity that doesn't have one yet, so that the
+/// editor can recognize entities across runs of the game.
+///
+/// Entities that were created with a saved `EditorUuid` keep it.
+///
+/// [`EditorUuid`]: ../struct.EditorUuid.html
+pub(crate) struct EntityUuidSystem;
+
+impl<'a> System<'a> for EntityUuidSystem {
+    type SystemData = (Entities<'a>, WriteStorage<'a, EditorUuid>);
+
+    fn run(&mut self, (entities, mut uuids): Self::SystemData) {
+        let missing = (&*entities, !&uuids)
+            .join()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in missing {
+            uuids
+                .insert(entity, EditorUuid::new())
+                .expect("Entity is alive");
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use amethyst::core::Transform;
+use amethyst::ecs::storage::MaskedStorage;
+use amethyst::ecs::{Entities, Entity, LazyUpdate, Read, System};
+use crossbeam_channel::Receiver;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::EditorConnection;
+use std::collections::HashSet;
+
+/// Messages controlling a transform gizmo being dragged in the editor.
+pub(crate) enum GizmoMessage {
+    Drag {
+        entity: Entity,
+        translation: Option<[f32; 3]>,
+        rotation: Option<[f32; 4]>,
+        scale: Option<[f32; 3]>,
+    },
+
+    EndDrag(Entity),
+}
+
+/// Current transform of an entity being dragged, echoed back to the editor every frame.
+#[derive(Serialize)]
+struct TransformEcho<'a> {
+    entity: SerializableEntity,
+    transform: &'a Transform,
+}
+
+/// A system that applies transform changes from gizmos dragged in the editor.
+///
+/// Regular component updates are only reflected back to the editor once per send interval,
+/// which is too slow for interactive dragging. Instead, drag updates are applied every frame
+/// and the resulting transforms of all entities being dragged are sent back to the editor in a
+/// `"transform_echo"` message every frame until the drag ends.
+///
+/// Fields of the drag message use the same representation as the serialized `Transform`
+/// component, and fields that are omitted are left unchanged.
+///
+/// NOTE: Transforms are only written through `LazyUpdate` on frames with a drag in progress, so
+/// that the system doesn't hold up every other system that reads `Transform`, and so that it
+/// does nothing in worlds without a `Transform` storage.
+pub(crate) struct GizmoWriteSystem {
+    receiver: Receiver<GizmoMessage>,
+    connection: EditorConnection,
+    dragged: HashSet<Entity>,
+}
+
+impl GizmoWriteSystem {
+    pub(crate) fn new(receiver: Receiver<GizmoMessage>, connection: EditorConnection) -> Self {
+        GizmoWriteSystem {
+            receiver,
+            connection,
+            dragged: HashSet::new(),
+        }
+    }
+}
+
+impl<'a> System<'a> for GizmoWriteSystem {
+    type SystemData = (Entities<'a>, Read<'a, LazyUpdate>);
+
+    fn run(&mut self, (entities, lazy): Self::SystemData) {
+        let mut drags = Vec::new();
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                GizmoMessage::Drag {
+                    entity,
+                    translation,
+                    rotation,
+                    scale,
+                } => {
+                    self.dragged.insert(entity);
+                    drags.push((entity, translation, rotation, scale));
+                }
+
+                GizmoMessage::EndDrag(entity) => {
+                    self.dragged.remove(&entity);
+                }
+            }
+        }
+
+        self.dragged.retain(|&entity| entities.is_alive(entity));
+        if self.dragged.is_empty() && drags.is_empty() {
+            return;
+        }
+
+        let dragged = self.dragged.iter().cloned().collect::<Vec<_>>();
+        let connection = self.connection.clone();
+        lazy.exec(move |world| {
+            if !world.res.has_value::<MaskedStorage<Transform>>() {
+                warn_once!("Ignoring transform drags in a world without transforms");
+                return;
+            }
+
+            let mut transforms = world.write_storage::<Transform>();
+            for (entity, translation, rotation, scale) in drags {
+                let transform = match transforms.get_mut(entity) {
+                    Some(transform) => transform,
+                    None => continue,
+                };
+
+                if let Err(error) = apply_drag(transform, translation, rotation, scale) {
+                    debug!("Failed to apply transform drag: {:?}", error);
+                }
+            }
+
+            let echo = dragged
+                .into_iter()
+                .filter_map(|entity| {
+                    transforms.get(entity).map(|transform| TransformEcho {
+                        entity: entity.into(),
+                        transform,
+                    })
+                })
+                .collect::<Vec<_>>();
+            if !echo.is_empty() {
+                connection.send_message("transform_echo", echo);
+            }
+        });
+    }
+}
+
+/// Overwrites the given fields of `transform`, going through its serialized representation.
+fn apply_drag(
+    transform: &mut Transform,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+) -> Result<(), serde_json::Error> {
+    let mut value = serde_json::to_value(&*transform)?;
+    if let Some(translation) = translation {
+        value["translation"] = serde_json::to_value(translation)?;
+    }
+    if let Some(rotation) = rotation {
+        value["rotation"] = serde_json::to_value(rotation)?;
+    }
+    if let Some(scale) = scale {
+        value["scale"] = serde_json::to_value(scale)?;
+    }
+
+    *transform = serde_json::from_value(value)?;
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use amethyst::core::timing::Time;
+use amethyst::ecs::{LazyUpdate, Read, System};
+use crossbeam_channel::Receiver;
+use crate::history::{History, HistoryFrame, SerializedHistoryFrame};
+use crate::registry::Registry;
+use crate::types::{EditStatus, EditorConnection};
+use std::sync::{Arc, Mutex};
+
+/// A request from the editor concerning the recorded history.
+pub(crate) enum HistoryRequest {
+    /// Send the frames numbered from `from_frame` to `to_frame`, inclusive.
+    Range {
+        from_frame: u64,
+        to_frame: u64,
+        request_id: Option<u64>,
+    },
+
+    /// Restore the state recorded on `frame`.
+    Rewind { frame: u64, request_id: Option<u64> },
+}
+
+/// Sent to the editor as a `"history"` message in response to a `RequestHistory` message.
+#[derive(Debug, Serialize)]
+struct HistoryMessage {
+    frames: Vec<SerializedHistoryFrame>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// A system that records the state of all registered types every frame, and rewinds the world
+/// to a recorded frame at the editor's request.
+///
+/// Recording needs to visit every registered type, so it's deferred until the end of the frame,
+/// when it can access the world directly. Rewinding is deferred the same way, and happens before
+/// the frame is recorded, so the recorded frame shows the rewound state.
+pub(crate) struct HistorySystem {
+    receiver: Receiver<HistoryRequest>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+    history: Arc<Mutex<History>>,
+}
+
+impl HistorySystem {
+    pub(crate) fn new(
+        capacity: usize,
+        receiver: Receiver<HistoryRequest>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        HistorySystem {
+            receiver,
+            registry,
+            connection,
+            history: Arc::new(Mutex::new(History::new(capacity))),
+        }
+    }
+}
+
+impl<'a> System<'a> for HistorySystem {
+    type SystemData = (Read<'a, LazyUpdate>, Read<'a, Time>);
+
+    fn run(&mut self, (lazy, time): Self::SystemData) {
+        for request in self.receiver.try_iter() {
+            match request {
+                HistoryRequest::Range {
+                    from_frame,
+                    to_frame,
+                    request_id,
+                } => {
+                    let history = self.history.lock().expect("History poisoned");
+                    let frames = history
+                        .range(from_frame, to_frame)
+                        .map(|recorded| recorded.serialize(&self.registry))
+                        .collect();
+                    self.connection
+                        .send_message("history", HistoryMessage { frames, request_id });
+                }
+
+                HistoryRequest::Rewind { frame, request_id } => {
+                    let history = self.history.clone();
+                    let registry = self.registry.clone();
+                    let connection = self.connection.clone();
+                    lazy.exec(move |world| {
+                        let history = history.lock().expect("History poisoned");
+                        match history.get(frame) {
+                            Some(recorded) => {
+                                recorded.restore(world, &registry);
+                                connection.send_ack(request_id, EditStatus::Applied);
+                            }
+                            None => {
+                                debug!("Frame {} is no longer in the history", frame);
+                                connection.send_ack(request_id, EditStatus::MissingFrame);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+
+        let frame = time.frame_number();
+        let history = self.history.clone();
+        let registry = self.registry.clone();
+        lazy.exec(move |world| {
+            let recorded = HistoryFrame::capture(frame, world, &registry);
+            history.lock().expect("History poisoned").push(recorded);
+        });
+    }
+}
@@ -0,0 +1,87 @@
+use amethyst::core::nalgebra::Point3;
+use amethyst::core::GlobalTransform;
+use amethyst::ecs::{BitSet, Entities, Join, Read, ReadStorage, System};
+use amethyst::renderer::{ActiveCamera, Camera};
+use crate::interest::InterestArea;
+use crate::types::{EditorConnection, SerializedData};
+
+/// The `"interest"` section of the snapshot.
+#[derive(Serialize)]
+struct InterestSection {
+    /// The IDs of the entities whose components are included in the snapshot.
+    entities: Vec<u32>,
+}
+
+/// A system that picks the entities whose components are serialized, leaving out the ones
+/// outside of the interest area around the camera.
+///
+/// The area is centered on the active camera (or the first camera found, if there's no
+/// `ActiveCamera` resource). Entities without a `GlobalTransform` can't be placed, so they're
+/// always included, as are the entities the editor asked to always sync. If there's no camera,
+/// every entity is included.
+pub(crate) struct InterestSystem {
+    area: InterestArea,
+    connection: EditorConnection,
+}
+
+impl InterestSystem {
+    pub(crate) fn new(area: InterestArea, connection: EditorConnection) -> Self {
+        InterestSystem { area, connection }
+    }
+}
+
+impl<'a> System<'a> for InterestSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, GlobalTransform>,
+        Option<Read<'a, ActiveCamera>>,
+    );
+
+    fn run(&mut self, (entities, cameras, transforms, active_camera): Self::SystemData) {
+        if !self.connection.presence().is_active() {
+            return;
+        }
+
+        let camera = active_camera
+            .as_ref()
+            .map(|active| active.entity)
+            .or_else(|| (&*entities, &cameras).join().map(|(e, _)| e).next());
+        let camera =
+            camera.and_then(|camera| Some((transforms.get(camera)?, cameras.get(camera)?)));
+
+        let forced = self.connection.interest().forced();
+        let mut included = BitSet::new();
+        for entity in (&*entities).join() {
+            let inside = match (camera, transforms.get(entity)) {
+                (Some((camera_transform, camera)), Some(transform)) => {
+                    let position = Point3::new(
+                        transform.0[(0, 3)],
+                        transform.0[(1, 3)],
+                        transform.0[(2, 3)],
+                    );
+                    self.area
+                        .contains(&camera_transform.0, &camera.proj, &position)
+                }
+                _ => true,
+            };
+            if inside || forced.contains(entity.id()) {
+                included.add(entity.id());
+            }
+        }
+
+        if self.connection.subscriptions().wants_section("interest") {
+            let section = InterestSection {
+                entities: (&included).join().collect(),
+            };
+            match serde_json::value::to_raw_value(&section) {
+                Ok(serialized) => self
+                    .connection
+                    .send_serialized(SerializedData::Section("interest", serialized)),
+                Err(error) => error!("Failed to serialize interest area: {}", error),
+            }
+        }
+
+        self.connection.interest().set_entities(included);
+    }
+}
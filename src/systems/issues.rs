@@ -0,0 +1,39 @@
+use amethyst::ecs::System;
+use crate::issues::Issue;
+use crate::types::EditorConnection;
+
+/// Sent to the editor as an `"issues"` message.
+#[derive(Serialize)]
+struct IssuesMessage {
+    issues: Vec<Issue>,
+}
+
+/// A system that sends the issues reported through [`EditorConnection`] during the frame.
+///
+/// Unlike debug shapes, issues are sent as a message, so that issues reported on frames between
+/// snapshots aren't lost.
+///
+/// [`EditorConnection`]: ./struct.EditorConnection.html
+pub(crate) struct IssuesSystem {
+    connection: EditorConnection,
+}
+
+impl IssuesSystem {
+    pub(crate) fn new(connection: EditorConnection) -> Self {
+        IssuesSystem { connection }
+    }
+}
+
+impl<'a> System<'a> for IssuesSystem {
+    type SystemData = ();
+
+    fn run(&mut self, (): Self::SystemData) {
+        let issues = self.connection.issues().take();
+        if issues.is_empty() || !self.connection.presence().is_active() {
+            return;
+        }
+
+        self.connection
+            .send_message("issues", IssuesMessage { issues });
+    }
+}
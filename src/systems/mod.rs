@@ -1,15 +1,87 @@
+mod assets;
+mod audio;
+mod bookmarks;
+mod breakpoint;
+mod chunked_resource;
+mod clone_entities;
+mod console;
+mod debug_draw;
+mod dynamic_components;
 mod editor_receiver;
 mod editor_sender;
 mod entity_handler;
+mod entity_id_map;
+mod entity_names;
+mod entity_query;
+mod entity_uuids;
+mod gizmo_write;
+mod history;
+#[cfg(feature = "renderer")]
+mod interest;
+mod issues;
+mod orphans;
+#[cfg(feature = "physics")]
+mod physics;
+#[cfg(feature = "renderer")]
+mod picking;
+mod pin;
+mod prefab_spawn;
+mod profiler;
 mod read_component;
 mod read_resource;
+mod scene_export;
+#[cfg(feature = "screen-capture")]
+mod screen_capture;
+mod selection;
+mod storage_stats;
+mod time_control;
+mod watch;
+mod watchdog;
 mod write_component;
 mod write_resource;
 
-pub(crate) use self::editor_receiver::EditorReceiverSystem;
-pub(crate) use self::editor_sender::EditorSenderSystem;
-pub(crate) use self::entity_handler::EntityHandlerSystem;
-pub(crate) use self::read_component::ReadComponentSystem;
+pub(crate) use self::assets::{AssetReload, AssetReloadSystem, AssetSyncSystem};
+pub(crate) use self::audio::{AudioSyncSystem, AudioVolumeSystem, SetMasterVolume};
+pub(crate) use self::bookmarks::{BookmarkEntity, BookmarkSystem};
+pub(crate) use self::breakpoint::{Breakpoint, BreakpointMessage, BreakpointSystem};
+pub(crate) use self::chunked_resource::{ChunkRequest, ChunkedResourceSystem};
+pub(crate) use self::clone_entities::{CloneEntities, CloneEntitiesSystem};
+pub(crate) use self::console::{ConsoleHandler, ConsoleInput, ConsoleSystem};
+pub(crate) use self::debug_draw::DebugDrawSystem;
+pub(crate) use self::dynamic_components::{
+    DynamicComponentEdit, DynamicComponentSyncSystem, DynamicComponentWriteSystem,
+};
+pub(crate) use self::editor_receiver::{EditorReceiverSystem, MessageRoutes};
+pub(crate) use self::editor_sender::{EditorSenderSystem, SenderSettings};
+pub(crate) use self::entity_handler::{create_entities, EntityHandlerSystem, EntityLimits};
+pub(crate) use self::entity_id_map::EntityIdMapSystem;
+pub(crate) use self::entity_names::EntityNameSystem;
+pub(crate) use self::entity_uuids::EntityUuidSystem;
+pub(crate) use self::entity_query::{EntityQuery, EntityQuerySystem};
+pub(crate) use self::gizmo_write::{GizmoMessage, GizmoWriteSystem};
+pub(crate) use self::history::{HistoryRequest, HistorySystem};
+#[cfg(feature = "renderer")]
+pub(crate) use self::interest::InterestSystem;
+pub(crate) use self::issues::IssuesSystem;
+pub(crate) use self::orphans::{OrphanRequest, OrphanSystem};
+#[cfg(feature = "renderer")]
+pub(crate) use self::picking::{PickRequest, PickingSystem};
+#[cfg(feature = "physics")]
+pub(crate) use self::physics::{PhysicsCommand, PhysicsControlSystem, PhysicsSyncSystem};
+pub(crate) use self::pin::{Pin, PinEnforcementSystem, PinMessage};
+pub(crate) use self::prefab_spawn::{PrefabSpawn, PrefabSpawnSystem};
+pub(crate) use self::profiler::ProfilerSystem;
+pub(crate) use self::read_component::{
+    ReadBackgroundComponentSystem, ReadComponentSystem, ReadTrackedComponentSystem,
+};
 pub(crate) use self::read_resource::ReadResourceSystem;
-pub(crate) use self::write_component::WriteComponentSystem;
-pub(crate) use self::write_resource::WriteResourceSystem;
+pub(crate) use self::scene_export::{SceneExport, SceneExportSystem};
+pub(crate) use self::selection::{SelectionSystem, SetSelection};
+pub(crate) use self::storage_stats::StorageStatsSystem;
+#[cfg(feature = "screen-capture")]
+pub(crate) use self::screen_capture::ScreenCaptureSystem;
+pub(crate) use self::time_control::{TimeControl, TimeControlSystem};
+pub(crate) use self::watch::{Watch, WatchMessage, WatchSystem};
+pub(crate) use self::watchdog::{WatchdogSettings, WatchdogSystem};
+pub(crate) use self::write_component::{DeferredWriteComponentSystem, WriteComponentSystem};
+pub(crate) use self::write_resource::{DeferredWriteResourceSystem, WriteResourceSystem};
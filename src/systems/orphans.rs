@@ -0,0 +1,195 @@
+use amethyst::core::{Named, Parent, Transform};
+use amethyst::ecs::storage::MaskedStorage;
+use amethyst::ecs::{Component, Entity, Join, LazyUpdate, Read, ReadStorage, System, World};
+use crossbeam_channel::{Receiver, Sender};
+use crate::registry::Registry;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::{EditStatus, EditorConnection, EntityMessage};
+use std::sync::Arc;
+
+/// A request from the editor to find or delete entities that look orphaned.
+///
+/// `anchors` are the names of registered components that make an entity reachable. If it's
+/// `None`, entities with a `Transform`, `Parent` or `Named` component are considered reachable.
+pub(crate) enum OrphanRequest {
+    Find {
+        anchors: Option<Vec<String>>,
+        request_id: Option<u64>,
+    },
+    Purge {
+        entities: Vec<Entity>,
+        anchors: Option<Vec<String>>,
+        request_id: Option<u64>,
+    },
+}
+
+/// Sent to the editor as an `"orphans"` message in response to `FindOrphans`.
+#[derive(Debug, Serialize)]
+struct OrphansFound {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+
+    entities: Vec<Orphan>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Orphan {
+    #[serde(flatten)]
+    entity: SerializableEntity,
+
+    /// The registered components the entity has.
+    components: Vec<&'static str>,
+}
+
+/// A system that looks for entities that have registered components but none of the components
+/// that would make them reachable, which usually means they were leaked, and deletes them on
+/// request.
+///
+/// Purging goes through the entity handler like `DestroyEntities`, so it's subject to the same
+/// limits, and the entities are checked again before being deleted so that entities that were
+/// anchored in the meantime are kept.
+pub(crate) struct OrphanSystem {
+    receiver: Receiver<OrphanRequest>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+    entity_handler: Sender<EntityMessage>,
+}
+
+impl OrphanSystem {
+    pub(crate) fn new(
+        receiver: Receiver<OrphanRequest>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+        entity_handler: Sender<EntityMessage>,
+    ) -> Self {
+        OrphanSystem {
+            receiver,
+            registry,
+            connection,
+            entity_handler,
+        }
+    }
+}
+
+impl<'a> System<'a> for OrphanSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        while let Ok(request) = self.receiver.try_recv() {
+            let registry = self.registry.clone();
+            let connection = self.connection.clone();
+            let entity_handler = self.entity_handler.clone();
+            lazy.exec(move |world| match request {
+                OrphanRequest::Find {
+                    anchors,
+                    request_id,
+                } => {
+                    let candidates = (&*world.entities()).join().collect::<Vec<_>>();
+                    let result = find_orphans(world, &registry, anchors.as_ref(), candidates);
+                    let (entities, error) = match result {
+                        Ok(orphans) => (orphans, None),
+                        Err(error) => (Vec::new(), Some(error)),
+                    };
+                    connection.send_message(
+                        "orphans",
+                        OrphansFound {
+                            request_id,
+                            entities,
+                            error,
+                        },
+                    );
+                }
+
+                OrphanRequest::Purge {
+                    entities,
+                    anchors,
+                    request_id,
+                } => {
+                    let candidates = entities
+                        .into_iter()
+                        .filter(|&entity| world.entities().is_alive(entity))
+                        .collect();
+                    match find_orphans(world, &registry, anchors.as_ref(), candidates) {
+                        Ok(orphans) => entity_handler
+                            .send(EntityMessage::Destroy(
+                                orphans.iter().map(|orphan| orphan.entity.0.id()).collect(),
+                                request_id,
+                            ))
+                            .expect("Disconnected from entity handler system"),
+                        Err(error) => {
+                            debug!("Failed to purge entities: {}", error);
+                            connection.send_ack(request_id, EditStatus::UnknownType);
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Finds the entities among `candidates` that have at least one registered component but none
+/// of the anchor components.
+fn find_orphans(
+    world: &World,
+    registry: &Registry,
+    anchors: Option<&Vec<String>>,
+    candidates: Vec<Entity>,
+) -> Result<Vec<Orphan>, String> {
+    let anchors = match anchors {
+        Some(names) => Some(
+            names
+                .iter()
+                .map(|name| {
+                    registry
+                        .component(name)
+                        .ok_or_else(|| format!("No component is registered as {:?}", name))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => None,
+    };
+
+    let transforms = read_if_present::<Transform>(world);
+    let parents = read_if_present::<Parent>(world);
+    let names = read_if_present::<Named>(world);
+    let has_default_anchor = |entity| {
+        transforms.as_ref().map_or(false, |s| s.contains(entity))
+            || parents.as_ref().map_or(false, |s| s.contains(entity))
+            || names.as_ref().map_or(false, |s| s.contains(entity))
+    };
+
+    let mut orphans = Vec::new();
+    for entity in candidates {
+        let anchored = match anchors {
+            Some(ref anchors) => anchors.iter().any(|access| access.contains(world, entity)),
+            None => has_default_anchor(entity),
+        };
+        if anchored {
+            continue;
+        }
+
+        let components = registry
+            .components()
+            .filter(|access| access.contains(world, entity))
+            .map(|access| access.name())
+            .collect::<Vec<_>>();
+        if !components.is_empty() {
+            orphans.push(Orphan {
+                entity: entity.into(),
+                components,
+            });
+        }
+    }
+    Ok(orphans)
+}
+
+fn read_if_present<T: Component>(world: &World) -> Option<ReadStorage<T>> {
+    if world.res.has_value::<MaskedStorage<T>>() {
+        Some(world.read_storage::<T>())
+    } else {
+        None
+    }
+}
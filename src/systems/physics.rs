@@ -0,0 +1,223 @@
+use amethyst::ecs::{Entities, Entity, Join, ReadStorage, System, WriteStorage};
+use crossbeam_channel::Receiver;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::{EditStatus, EditorConnection, SerializedData};
+use specs_physics::math::{Isometry3, Vector3};
+use specs_physics::{PhysicsBody, PhysicsCollider, Position};
+use std::marker::PhantomData;
+
+/// Commands from the editor that act on physics bodies.
+pub(crate) enum PhysicsCommand {
+    /// Changes the velocity of a body as if the given impulses were applied at its center of
+    /// mass.
+    ApplyImpulse {
+        entity: Entity,
+        linear: [f32; 3],
+        angular: [f32; 3],
+        request_id: Option<u64>,
+    },
+
+    /// Moves an entity to a new position.
+    Teleport {
+        entity: Entity,
+        position: [f32; 3],
+        request_id: Option<u64>,
+    },
+}
+
+/// The `"physics"` section of the snapshot.
+#[derive(Serialize)]
+struct PhysicsSection {
+    bodies: Vec<BodyData>,
+}
+
+/// The physics state of an entity with a rigid body or a collider.
+#[derive(Serialize)]
+struct BodyData {
+    entity: SerializableEntity,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<[f32; 3]>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<BodyState>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    collider: Option<ColliderState>,
+}
+
+#[derive(Serialize)]
+struct BodyState {
+    status: String,
+    mass: f32,
+    linear_velocity: [f32; 3],
+    angular_velocity: [f32; 3],
+}
+
+#[derive(Serialize)]
+struct ColliderState {
+    sensor: bool,
+
+    /// The corners of the collider's axis-aligned bounding box, if the entity has a position.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aabb_min: Option<[f32; 3]>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aabb_max: Option<[f32; 3]>,
+}
+
+/// A system that sends the rigid bodies and colliders of specs-physics as the `"physics"`
+/// section of the snapshot, where `P` is the game's position component.
+pub(crate) struct PhysicsSyncSystem<P> {
+    connection: EditorConnection,
+    _marker: PhantomData<P>,
+}
+
+impl<P> PhysicsSyncSystem<P> {
+    pub(crate) fn new(connection: EditorConnection) -> Self {
+        PhysicsSyncSystem {
+            connection,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, P: Position<f32>> System<'a> for PhysicsSyncSystem<P> {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, PhysicsBody<f32>>,
+        ReadStorage<'a, PhysicsCollider<f32>>,
+        ReadStorage<'a, P>,
+    );
+
+    fn run(&mut self, (entities, bodies, colliders, positions): Self::SystemData) {
+        if !self.connection.presence().is_active()
+            || !self.connection.subscriptions().wants_section("physics")
+        {
+            return;
+        }
+
+        let mut section = PhysicsSection { bodies: Vec::new() };
+        for (entity, body, collider, position) in (
+            &*entities,
+            bodies.maybe(),
+            colliders.maybe(),
+            positions.maybe(),
+        )
+            .join()
+        {
+            if body.is_none() && collider.is_none() {
+                continue;
+            }
+
+            let position = position.map(|position| {
+                let (x, y, z) = position.position();
+                [x, y, z]
+            });
+
+            section.bodies.push(BodyData {
+                entity: entity.into(),
+                position,
+                body: body.map(|body| BodyState {
+                    status: format!("{:?}", body.body_status).to_lowercase(),
+                    mass: body.mass,
+                    linear_velocity: to_array(&body.velocity.linear),
+                    angular_velocity: to_array(&body.velocity.angular),
+                }),
+                collider: collider.map(|collider| {
+                    let aabb = position.map(|position| {
+                        let isometry =
+                            Isometry3::translation(position[0], position[1], position[2])
+                                * collider.offset_from_parent;
+                        collider.shape.handle().aabb(&isometry)
+                    });
+                    ColliderState {
+                        sensor: collider.sensor,
+                        aabb_min: aabb.as_ref().map(|aabb| to_array(&aabb.mins().coords)),
+                        aabb_max: aabb.as_ref().map(|aabb| to_array(&aabb.maxs().coords)),
+                    }
+                }),
+            });
+        }
+
+        match serde_json::value::to_raw_value(&section) {
+            Ok(serialized) => self
+                .connection
+                .send_serialized(SerializedData::Section("physics", serialized)),
+            Err(error) => error!("Failed to serialize physics data: {}", error),
+        }
+    }
+}
+
+/// A system that applies impulses and teleports requested by the editor.
+///
+/// specs-physics copies modified bodies and positions into the physics world on its next step.
+pub(crate) struct PhysicsControlSystem<P> {
+    receiver: Receiver<PhysicsCommand>,
+    connection: EditorConnection,
+    _marker: PhantomData<P>,
+}
+
+impl<P> PhysicsControlSystem<P> {
+    pub(crate) fn new(receiver: Receiver<PhysicsCommand>, connection: EditorConnection) -> Self {
+        PhysicsControlSystem {
+            receiver,
+            connection,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, P: Position<f32>> System<'a> for PhysicsControlSystem<P> {
+    type SystemData = (WriteStorage<'a, PhysicsBody<f32>>, WriteStorage<'a, P>);
+
+    fn run(&mut self, (mut bodies, mut positions): Self::SystemData) {
+        for command in self.receiver.try_iter() {
+            match command {
+                PhysicsCommand::ApplyImpulse {
+                    entity,
+                    linear,
+                    angular,
+                    request_id,
+                } => {
+                    let body = match bodies.get_mut(entity) {
+                        Some(body) => body,
+                        None => {
+                            self.connection
+                                .send_ack(request_id, EditStatus::MissingComponent);
+                            continue;
+                        }
+                    };
+
+                    if body.mass > 0.0 {
+                        body.velocity.linear += Vector3::from(linear) / body.mass;
+                    }
+                    if let Some(inverse_inertia) = body.angular_inertia.try_inverse() {
+                        body.velocity.angular += inverse_inertia * Vector3::from(angular);
+                    }
+                    self.connection.send_ack(request_id, EditStatus::Applied);
+                }
+
+                PhysicsCommand::Teleport {
+                    entity,
+                    position,
+                    request_id,
+                } => match positions.get_mut(entity) {
+                    Some(current) => {
+                        current.set_position(position[0], position[1], position[2]);
+                        self.connection.send_ack(request_id, EditStatus::Applied);
+                    }
+
+                    None => {
+                        self.connection
+                            .send_ack(request_id, EditStatus::MissingComponent);
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn to_array(vector: &Vector3<f32>) -> [f32; 3] {
+    [vector.x, vector.y, vector.z]
+}
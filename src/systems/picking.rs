@@ -0,0 +1,162 @@
+use amethyst::core::nalgebra::{Matrix4, Vector2, Vector4};
+use amethyst::core::GlobalTransform;
+use amethyst::ecs::{Entities, Entity, Join, Read, ReadStorage, System};
+use amethyst::renderer::{ActiveCamera, Camera, ScreenDimensions};
+use crossbeam_channel::Receiver;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::EditorConnection;
+
+/// Distance in pixels from the cursor within which an entity counts as being under it.
+const PICK_RADIUS: f32 = 16.0;
+
+/// A request from the editor for the entity at a position on the screen.
+pub(crate) struct PickRequest {
+    pub screen_x: f32,
+    pub screen_y: f32,
+    pub request_id: Option<u64>,
+}
+
+/// Result of a pick request, sent back to the editor.
+#[derive(Serialize)]
+struct PickResult {
+    screen_x: f32,
+    screen_y: f32,
+
+    /// The entity under the cursor, if any.
+    entity: Option<SerializableEntity>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// A system that answers pick requests from the editor, allowing entities to be selected by
+/// clicking on them in a viewport streamed from the game.
+///
+/// The position of every entity with a `GlobalTransform` is projected onto the screen using
+/// the active camera (or the first camera found, if there's no `ActiveCamera` resource). The
+/// entity closest to the requested position within a small radius is picked, preferring the one
+/// closest to the camera if several overlap. Since only the entity's origin is considered, this
+/// doesn't account for the size of the entity's mesh.
+///
+/// The result is sent to the editor as a `"pick_result"` message.
+pub(crate) struct PickingSystem {
+    receiver: Receiver<PickRequest>,
+    connection: EditorConnection,
+}
+
+impl PickingSystem {
+    pub(crate) fn new(receiver: Receiver<PickRequest>, connection: EditorConnection) -> Self {
+        PickingSystem {
+            receiver,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for PickingSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, GlobalTransform>,
+        Option<Read<'a, ActiveCamera>>,
+        Option<Read<'a, ScreenDimensions>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, cameras, transforms, active_camera, dimensions): Self::SystemData,
+    ) {
+        while let Ok(request) = self.receiver.try_recv() {
+            let entity = match dimensions {
+                Some(ref dimensions) => {
+                    let camera = active_camera
+                        .as_ref()
+                        .map(|active| active.entity)
+                        .or_else(|| (&*entities, &cameras).join().map(|(e, _)| e).next());
+                    camera.and_then(|camera| {
+                        pick(
+                            &request,
+                            camera,
+                            dimensions,
+                            &entities,
+                            &cameras,
+                            &transforms,
+                        )
+                    })
+                }
+
+                None => None,
+            };
+
+            self.connection.send_message(
+                "pick_result",
+                PickResult {
+                    screen_x: request.screen_x,
+                    screen_y: request.screen_y,
+                    entity: entity.map(Into::into),
+                    request_id: request.request_id,
+                },
+            );
+        }
+    }
+}
+
+fn pick(
+    request: &PickRequest,
+    camera: Entity,
+    dimensions: &ScreenDimensions,
+    entities: &Entities,
+    cameras: &ReadStorage<Camera>,
+    transforms: &ReadStorage<GlobalTransform>,
+) -> Option<Entity> {
+    let view = transforms.get(camera)?.0.try_inverse()?;
+    let view_proj = cameras.get(camera)?.proj * view;
+    let cursor = Vector2::new(request.screen_x, request.screen_y);
+
+    let mut picked: Option<(Entity, f32)> = None;
+    for (entity, transform) in (&**entities, transforms).join() {
+        if entity == camera {
+            continue;
+        }
+
+        let (screen, depth) = match project(&view_proj, &transform.0, dimensions) {
+            Some(projected) => projected,
+            None => continue,
+        };
+        if (screen - cursor).norm() > PICK_RADIUS {
+            continue;
+        }
+
+        if picked.map_or(true, |(_, closest)| depth < closest) {
+            picked = Some((entity, depth));
+        }
+    }
+
+    picked.map(|(entity, _)| entity)
+}
+
+/// Projects the origin of `transform` to screen coordinates, returning the position in pixels
+/// (from the top left of the screen) and the normalized depth.
+///
+/// Returns `None` if the point is outside the camera's view.
+fn project(
+    view_proj: &Matrix4<f32>,
+    transform: &Matrix4<f32>,
+    dimensions: &ScreenDimensions,
+) -> Option<(Vector2<f32>, f32)> {
+    let clip = view_proj * transform * Vector4::new(0.0, 0.0, 0.0, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc = clip.xyz() / clip.w;
+    if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 || ndc.z.abs() > 1.0 {
+        return None;
+    }
+
+    let screen = Vector2::new(
+        (ndc.x + 1.0) * 0.5 * dimensions.width(),
+        (1.0 - ndc.y) * 0.5 * dimensions.height(),
+    );
+    Some((screen, ndc.z))
+}
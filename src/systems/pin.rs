@@ -0,0 +1,195 @@
+use amethyst::ecs::{Entities, Entity, LazyUpdate, Read, System, World};
+use crossbeam_channel::Receiver;
+use crate::query;
+use crate::registry::Registry;
+use crate::types::{EditStatus, EditorConnection, WriteError};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+/// Changes to the set of pinned fields, requested by the editor.
+pub(crate) enum PinMessage {
+    Pin(Pin),
+    Unpin {
+        entity: Entity,
+        component: String,
+        path: String,
+    },
+}
+
+/// A field of a single component that's held at a fixed value.
+#[derive(Debug, Clone)]
+pub(crate) struct Pin {
+    pub entity: Entity,
+    pub component: String,
+    pub path: String,
+    pub value: Value,
+    pub request_id: Option<u64>,
+}
+
+impl Pin {
+    fn is_at(&self, entity: Entity, component: &str, path: &str) -> bool {
+        self.entity == entity && self.component == component && self.path == path
+    }
+}
+
+/// A system that keeps fields pinned by the editor at their pinned values, so that users can
+/// freeze part of the game state (e.g. lock an entity's height) while iterating on the rest.
+///
+/// Pinned values are written back at the end of every frame, after the game's systems have run,
+/// so they're what the rest of the game sees at the start of the next frame. Fields are only
+/// written when they differ from the pinned value, so that tracked storages don't see changes
+/// every frame. Read-only and redacted fields can't be pinned. A pin is removed when its entity
+/// is deleted, or when the pinned value can't be applied to the component.
+pub(crate) struct PinEnforcementSystem {
+    receiver: Receiver<PinMessage>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+    pins: Arc<Mutex<Vec<Pin>>>,
+}
+
+impl PinEnforcementSystem {
+    pub(crate) fn new(
+        receiver: Receiver<PinMessage>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        PinEnforcementSystem {
+            receiver,
+            registry,
+            connection,
+            pins: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl<'a> System<'a> for PinEnforcementSystem {
+    type SystemData = (Entities<'a>, Read<'a, LazyUpdate>);
+
+    fn run(&mut self, (entities, lazy): Self::SystemData) {
+        let mut pins = self.pins.lock().expect("Pins poisoned");
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                PinMessage::Pin(pin) => {
+                    if self.registry.writable_component(&pin.component).is_none() {
+                        debug!(
+                            "Can't pin field of unregistered component {:?}",
+                            pin.component
+                        );
+                        self.connection
+                            .send_ack(pin.request_id, EditStatus::UnknownType);
+                        continue;
+                    }
+
+                    if is_protected(&self.registry, &pin) {
+                        debug!(
+                            "Can't pin read-only field {:?} of {}",
+                            pin.path, pin.component
+                        );
+                        self.connection
+                            .send_ack(pin.request_id, EditStatus::InvalidData);
+                        continue;
+                    }
+
+                    pins.retain(|existing| !existing.is_at(pin.entity, &pin.component, &pin.path));
+                    self.connection
+                        .send_ack(pin.request_id, EditStatus::Applied);
+                    pins.push(pin);
+                }
+
+                PinMessage::Unpin {
+                    entity,
+                    component,
+                    path,
+                } => pins.retain(|pin| !pin.is_at(entity, &component, &path)),
+            }
+        }
+
+        pins.retain(|pin| entities.is_alive(pin.entity));
+        if pins.is_empty() {
+            return;
+        }
+
+        let pins = self.pins.clone();
+        let registry = self.registry.clone();
+        let connection = self.connection.clone();
+        lazy.exec(move |world| {
+            pins.lock()
+                .expect("Pins poisoned")
+                .retain(|pin| enforce(world, &registry, &connection, pin));
+        });
+    }
+}
+
+/// Whether the pinned field is read-only or redacted, or nested within such a field.
+fn is_protected(registry: &Registry, pin: &Pin) -> bool {
+    let options = registry.options(&pin.component);
+    let masked = options.mask.iter().flat_map(|mask| mask.fields());
+    let redacted = options
+        .redaction
+        .iter()
+        .flat_map(|redaction| redaction.fields());
+    masked.chain(redacted).any(|field| {
+        pin.path == *field
+            || (pin.path.starts_with(field.as_str()) && pin.path[field.len()..].starts_with('.'))
+    })
+}
+
+/// Writes the pinned value into its field if the field has changed, returning whether the pin
+/// should be kept.
+fn enforce(world: &World, registry: &Registry, connection: &EditorConnection, pin: &Pin) -> bool {
+    let (access, write) = match (
+        registry.component(&pin.component),
+        registry.writable_component(&pin.component),
+    ) {
+        (Some(access), Some(write)) => (access, write),
+        _ => return false,
+    };
+
+    // NOTE: The pin is kept while the entity doesn't have the component, in case it's added
+    // again later.
+    let current = match access.serialize(world, pin.entity) {
+        Some(Ok(current)) => current,
+        Some(Err(error)) => {
+            error!(
+                "Failed to serialize {} for pinning: {}",
+                access.name(),
+                error
+            );
+            return true;
+        }
+        None => return true,
+    };
+
+    let mut updated = current.clone();
+    match query::lookup_mut(&mut updated, &pin.path) {
+        Some(ref field) if **field == pin.value => return true,
+        Some(field) => *field = pin.value.clone(),
+        None => {
+            debug!("{} has no field {:?} to pin", access.name(), pin.path);
+            return false;
+        }
+    }
+
+    let updated = registry
+        .options(access.name())
+        .prepare_update(&current, updated);
+    match write.restore(world, pin.entity, Some(&updated)) {
+        Some(Err(error)) => {
+            debug!(
+                "Failed to apply pinned value to {}: {:?}",
+                access.name(),
+                error
+            );
+            connection.send_message(
+                "error",
+                WriteError {
+                    id: access.name(),
+                    entity: Some(pin.entity.into()),
+                    error: error.to_string(),
+                },
+            );
+            false
+        }
+        _ => true,
+    }
+}
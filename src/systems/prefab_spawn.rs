@@ -0,0 +1,184 @@
+use amethyst::assets::{AssetStorage, Handle, Loader, Prefab, ProgressCounter, RonFormat};
+use amethyst::core::Parent;
+use amethyst::ecs::prelude::*;
+use crossbeam_channel::Receiver;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::{EditStatus, EditorConnection};
+use serde::de::DeserializeOwned;
+use std::mem;
+
+/// A request from the editor to spawn a prefab.
+pub(crate) struct PrefabSpawn {
+    pub path: String,
+    pub request_id: Option<u64>,
+}
+
+/// Sent back to the editor once a prefab has been spawned.
+#[derive(Debug, Clone, Serialize)]
+struct PrefabSpawned {
+    path: String,
+
+    /// The entity the prefab was attached to, which is the root of the prefab.
+    entity: SerializableEntity,
+
+    /// Every entity created for the prefab, starting with the root.
+    entities: Vec<SerializableEntity>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// Sent to the editor as an `"error"` message when a prefab couldn't be loaded.
+#[derive(Debug, Serialize)]
+struct SpawnFailed {
+    path: String,
+    error: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// A prefab that has been requested by the editor but hasn't been instantiated yet.
+struct PendingSpawn<T> {
+    path: String,
+    request_id: Option<u64>,
+    root: Entity,
+    handle: Handle<Prefab<T>>,
+    progress: ProgressCounter,
+
+    // Set once the prefab is in the asset storage. The game's `PrefabLoaderSystem` instantiates
+    // it the next time it runs, so the prefab is reported the next time this system runs.
+    loaded: bool,
+}
+
+/// A system that loads prefabs requested by the editor and instantiates them.
+///
+/// The prefab is loaded through the asset [`Loader`] and attached to a newly created entity.
+/// The prefab itself is instantiated by the game's `PrefabLoaderSystem` once it has finished
+/// loading, so that system must also be registered for prefabs to show up in the world. This
+/// system keeps track of the prefabs that are still loading, and once a prefab has been
+/// instantiated it reports the root entity and all of its descendants back to the editor with
+/// a `"prefab_spawned"` message. If the prefab fails to load, the entity created for it is
+/// deleted again and the editor is sent an `"error"` message.
+///
+/// [`Loader`]: https://docs.rs/amethyst_assets/0.5/amethyst_assets/struct.Loader.html
+pub(crate) struct PrefabSpawnSystem<T> {
+    receiver: Receiver<PrefabSpawn>,
+    connection: EditorConnection,
+    pending: Vec<PendingSpawn<T>>,
+}
+
+impl<T> PrefabSpawnSystem<T> {
+    pub(crate) fn new(receiver: Receiver<PrefabSpawn>, connection: EditorConnection) -> Self {
+        PrefabSpawnSystem {
+            receiver,
+            connection,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T> System<'a> for PrefabSpawnSystem<T>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Prefab<T>>>,
+        ReadStorage<'a, Parent>,
+        WriteStorage<'a, Handle<Prefab<T>>>,
+    );
+
+    fn run(&mut self, (entities, loader, storage, parents, mut handles): Self::SystemData) {
+        while let Ok(PrefabSpawn { path, request_id }) = self.receiver.try_recv() {
+            debug!("Spawning prefab {:?}", path);
+
+            let mut progress = ProgressCounter::new();
+            let handle = loader.load(path.as_str(), RonFormat, (), &mut progress, &storage);
+            let root = entities.create();
+            if let Err(error) = handles.insert(root, handle.clone()) {
+                warn!("Failed to attach prefab {:?} to entity: {:?}", path, error);
+                let _ = entities.delete(root);
+                self.connection
+                    .send_ack(request_id, EditStatus::StaleEntity);
+                continue;
+            }
+
+            self.pending.push(PendingSpawn {
+                path,
+                request_id,
+                root,
+                handle,
+                progress,
+                loaded: false,
+            });
+        }
+
+        let pending = mem::replace(&mut self.pending, Vec::new());
+        for mut spawn in pending {
+            if !entities.is_alive(spawn.root) {
+                debug!(
+                    "Entity for prefab {:?} was deleted while loading",
+                    spawn.path
+                );
+                self.connection
+                    .send_ack(spawn.request_id, EditStatus::StaleEntity);
+                continue;
+            }
+
+            if spawn.progress.num_failed() > 0 {
+                warn!("Failed to load prefab {:?}", spawn.path);
+                let _ = entities.delete(spawn.root);
+                self.connection.send_message(
+                    "error",
+                    SpawnFailed {
+                        error: format!("Failed to load prefab {:?}", spawn.path),
+                        path: spawn.path,
+                        request_id: spawn.request_id,
+                    },
+                );
+                self.connection
+                    .send_ack(spawn.request_id, EditStatus::Failed);
+                continue;
+            }
+
+            if !spawn.loaded {
+                spawn.loaded = spawn.progress.is_complete() && storage.get(&spawn.handle).is_some();
+                self.pending.push(spawn);
+                continue;
+            }
+
+            let spawned = instantiated(spawn.root, &entities, &parents);
+            debug!("Spawned prefab {:?} as {:?}", spawn.path, spawned);
+            self.connection.send_message(
+                "prefab_spawned",
+                PrefabSpawned {
+                    path: spawn.path,
+                    entity: spawn.root.into(),
+                    entities: spawned.into_iter().map(Into::into).collect(),
+                    request_id: spawn.request_id,
+                },
+            );
+            self.connection
+                .send_ack(spawn.request_id, EditStatus::Applied);
+        }
+    }
+}
+
+/// Collects `root` and all of its descendants, as found through their `Parent` components.
+fn instantiated(root: Entity, entities: &Entities, parents: &ReadStorage<Parent>) -> Vec<Entity> {
+    let mut found = vec![root];
+    let mut next = 0;
+    while next < found.len() {
+        let parent = found[next];
+        found.extend(
+            (&**entities, parents)
+                .join()
+                .filter(|(_, child)| child.entity == parent)
+                .map(|(entity, _)| entity),
+        );
+        next += 1;
+    }
+    found
+}
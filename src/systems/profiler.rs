@@ -0,0 +1,76 @@
+use amethyst::ecs::System;
+use crossbeam_channel::Receiver;
+use crate::capabilities::Capability;
+use crate::profiler::SystemTiming;
+use crate::types::EditorConnection;
+use std::time::Duration;
+
+/// Timings for a single frame, sent to the editor as a `"profiler"` message.
+#[derive(Serialize)]
+struct ProfilerFrame {
+    systems: Vec<TimingData>,
+}
+
+#[derive(Serialize)]
+struct TimingData {
+    name: &'static str,
+
+    /// When the system started running, in microseconds since the first profiled system
+    /// started running this frame.
+    start_us: u64,
+
+    duration_us: u64,
+}
+
+/// A system that collects the timings of systems wrapped by a [`SystemProfiler`] and sends them
+/// to the editor once per frame.
+///
+/// [`SystemProfiler`]: ../struct.SystemProfiler.html
+pub(crate) struct ProfilerSystem {
+    receiver: Receiver<SystemTiming>,
+    connection: EditorConnection,
+}
+
+impl ProfilerSystem {
+    pub(crate) fn new(receiver: Receiver<SystemTiming>, connection: EditorConnection) -> Self {
+        ProfilerSystem {
+            receiver,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for ProfilerSystem {
+    type SystemData = ();
+
+    fn run(&mut self, _: Self::SystemData) {
+        let timings = self.receiver.try_iter().collect::<Vec<_>>();
+        if !self
+            .connection
+            .capabilities()
+            .contains(Capability::Profiler)
+        {
+            return;
+        }
+
+        let frame_start = match timings.iter().map(|timing| timing.start).min() {
+            Some(start) => start,
+            None => return,
+        };
+
+        let systems = timings
+            .into_iter()
+            .map(|timing| TimingData {
+                name: timing.name,
+                start_us: micros(timing.start - frame_start),
+                duration_us: micros(timing.duration),
+            })
+            .collect();
+        self.connection
+            .send_message("profiler", ProfilerFrame { systems });
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000 + u64::from(duration.subsec_micros())
+}
@@ -1,23 +1,42 @@
-use amethyst::ecs::{Component, Entities, Join, ReadStorage, System};
+use amethyst::ecs::storage::{ComponentEvent, Tracked};
+use amethyst::ecs::{
+    BitSet, Component, Entities, Entity, Join, ReadStorage, ReaderId, Resources, System,
+    SystemData, WriteStorage,
+};
+use crate::capabilities::Capability;
+use crate::depth::{self, COMPONENT_WRAPPER_DEPTH};
+use crate::pagination::ComponentPage;
+use crate::registry::TypeOptions;
 use serde::export::PhantomData;
 use serde::Serialize;
 use serde_json;
+use serde_json::value::RawValue;
+use std::mem;
 use std::str;
-use crate::types::{EditorConnection, SerializedComponent, SerializedData};
+use crate::types::{ComponentEntry, Diagnostic, EditorConnection, SerializedComponent, SerializedData};
+use crate::validation::ComponentValidator;
+use crate::worker::SerializationPool;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 /// A system that serializes all components of a specific type and sends them to the
 /// [`SyncEditorSystem`], which will sync them with the editor.
 pub struct ReadComponentSystem<T> {
-    name: &'static str,
-    connection: EditorConnection,
+    serializer: ComponentSerializer,
+    validator: Option<ComponentValidator<T>>,
     _phantom: PhantomData<T>,
 }
 
 impl<'a, T> ReadComponentSystem<T> {
-    pub fn new(name: &'static str, connection: EditorConnection) -> Self {
+    pub fn new(
+        name: &'static str,
+        connection: EditorConnection,
+        options: TypeOptions,
+        validator: Option<ComponentValidator<T>>,
+    ) -> Self {
         Self {
-            name,
-            connection,
+            serializer: ComponentSerializer::new(name, connection, options),
+            validator,
             _phantom: PhantomData,
         }
     }
@@ -30,19 +49,395 @@ where
     type SystemData = (Entities<'a>, ReadStorage<'a, T>);
 
     fn run(&mut self, (entities, components): Self::SystemData) {
-        let data = (&*entities, &components)
+        if !self.serializer.is_active() {
+            return;
+        }
+
+        let interest = self.serializer.connection.interest().entities();
+        let data = collect(&entities, &components, interest.as_ref());
+        if let Some(ref mut validator) = self.validator {
+            validator.validate(&data);
+        }
+        match self.serializer.serialize(&data) {
+            Ok((serialized, count)) => {
+                self.serializer
+                    .send(serialized, count, components.mask().clone())
+            }
+            Err(error) => self.serializer.report(&error),
+        }
+    }
+}
+
+/// A system that sends all components of a type with a tracked storage (e.g. a
+/// `FlaggedStorage`), reusing the last serialized data if no components have been inserted,
+/// modified, or removed since.
+///
+/// Note that tracked storages only flag components as modified when they're accessed mutably
+/// through the storage, so changes made through interior mutability aren't detected until the
+/// editor requests a full resync.
+pub(crate) struct ReadTrackedComponentSystem<T> {
+    serializer: ComponentSerializer,
+    reader: Option<ReaderId<ComponentEvent>>,
+    cached: Option<(Box<RawValue>, usize)>,
+    resyncs: usize,
+    interest_changes: u64,
+    page_changes: u64,
+    validator: Option<ComponentValidator<T>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> ReadTrackedComponentSystem<T> {
+    pub(crate) fn new(
+        name: &'static str,
+        connection: EditorConnection,
+        options: TypeOptions,
+        validator: Option<ComponentValidator<T>>,
+    ) -> Self {
+        Self {
+            serializer: ComponentSerializer::new(name, connection, options),
+            reader: None,
+            cached: None,
+            resyncs: 0,
+            interest_changes: 0,
+            page_changes: 0,
+            validator,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> System<'a> for ReadTrackedComponentSystem<T>
+where
+    T: Component + Serialize,
+    T::Storage: Tracked,
+{
+    type SystemData = (Entities<'a>, ReadStorage<'a, T>);
+
+    fn run(&mut self, (entities, components): Self::SystemData) {
+        // NOTE: All pending events need to be read, even though we only care whether there are
+        // any, so that they aren't reported again next frame.
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("`ReadTrackedComponentSystem::setup` was not called");
+        let changed = components.channel().read(reader).count() > 0;
+        let resync = self
+            .serializer
+            .connection
+            .presence()
+            .resync_requested(&mut self.resyncs);
+        let interest_changes = self.serializer.connection.interest().changes();
+        let interest_changed =
+            mem::replace(&mut self.interest_changes, interest_changes) != interest_changes;
+        let page_changes = self.serializer.connection.pages().changes();
+        let page_changed = mem::replace(&mut self.page_changes, page_changes) != page_changes;
+        if changed || resync || interest_changed || page_changed {
+            self.cached = None;
+        }
+
+        if !self.serializer.is_active() {
+            return;
+        }
+
+        // NOTE: Components are only validated when they change, since unchanged components
+        // can't have become invalid.
+        if self.cached.is_none() {
+            let interest = self.serializer.connection.interest().entities();
+            let data = collect(&entities, &components, interest.as_ref());
+            if let Some(ref mut validator) = self.validator {
+                validator.validate(&data);
+            }
+            match self.serializer.serialize(&data) {
+                Ok(serialized) => self.cached = Some(serialized),
+                Err(error) => {
+                    self.cached = None;
+                    self.serializer.report(&error);
+                }
+            }
+        }
+
+        if let Some((ref serialized, count)) = self.cached {
+            self.serializer
+                .send_copy(serialized, count, components.mask().clone());
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(WriteStorage::<T>::fetch(res).register_reader());
+    }
+}
+
+/// A system that copies all components of a type and serializes them on a background thread,
+/// so that the cost of serializing large storages isn't paid during the dispatch.
+///
+/// A new copy is only taken once the previous one has been serialized, so a background thread
+/// that falls behind never causes copies to pile up. Taking the copy still visits every
+/// component, so the cost during the dispatch grows with the size of the storage.
+pub(crate) struct ReadBackgroundComponentSystem<T> {
+    serializer: Arc<Mutex<ComponentSerializer>>,
+    pool: SerializationPool,
+    in_flight: Arc<AtomicBool>,
+    validator: Option<Arc<Mutex<ComponentValidator<T>>>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> ReadBackgroundComponentSystem<T> {
+    pub(crate) fn new(
+        name: &'static str,
+        connection: EditorConnection,
+        options: TypeOptions,
+        pool: SerializationPool,
+        validator: Option<ComponentValidator<T>>,
+    ) -> Self {
+        Self {
+            serializer: Arc::new(Mutex::new(ComponentSerializer::new(
+                name, connection, options,
+            ))),
+            pool,
+            in_flight: Arc::new(AtomicBool::new(false)),
+            validator: validator.map(|validator| Arc::new(Mutex::new(validator))),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> System<'a> for ReadBackgroundComponentSystem<T>
+where
+    T: Component + Clone + Serialize + Send,
+{
+    type SystemData = (Entities<'a>, ReadStorage<'a, T>);
+
+    fn run(&mut self, (entities, components): Self::SystemData) {
+        // NOTE: The serializer is only locked by the background thread while a copy is in
+        // flight, so checking `in_flight` first means this never waits on the lock.
+        if self.in_flight.load(Ordering::Acquire) || !lock(&self.serializer).is_active() {
+            return;
+        }
+
+        let interest = lock(&self.serializer).connection.interest().entities();
+        let copies = (&*entities, &components)
             .join()
-            .map(|(e, c)| (e.id(), c))
-            .collect();
+            .filter(|(entity, _)| is_included(interest.as_ref(), *entity))
+            .map(|(entity, component)| ComponentEntry {
+                entity: entity.into(),
+                data: component.clone(),
+            })
+            .collect::<Vec<_>>();
+        let mask = components.mask().clone();
+
+        let in_flight = InFlight::start(&self.in_flight);
+        let serializer = self.serializer.clone();
+        let validator = self.validator.clone();
+        self.pool.spawn(move || {
+            // NOTE: The copy stays in flight until the job is done or has panicked.
+            let _in_flight = in_flight;
+            let mut serializer = lock(&serializer);
+            let data = copies
+                .iter()
+                .map(|entry| ComponentEntry {
+                    entity: entry.entity,
+                    data: &entry.data,
+                })
+                .collect::<Vec<_>>();
+            if let Some(validator) = validator {
+                lock(&validator).validate(&data);
+            }
+            match serializer.serialize(&data) {
+                Ok((serialized, count)) => serializer.send(serialized, count, mask),
+                Err(error) => serializer.report(&error),
+            }
+        });
+    }
+}
+
+/// Marks a copy of the components as being serialized until it's dropped, including when the
+/// job serializing it panics, so that a failed job doesn't stop the components from being
+/// copied again.
+struct InFlight(Arc<AtomicBool>);
+
+impl InFlight {
+    fn start(in_flight: &Arc<AtomicBool>) -> InFlight {
+        in_flight.store(true, Ordering::Release);
+        InFlight(in_flight.clone())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Locks state shared with the background threads, recovering it if a job panicked while
+/// holding the lock.
+///
+/// NOTE: A panic partway through serializing or validating doesn't leave anything half-updated
+/// that later runs rely on, so it's safe to keep using the state.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Collects all components of a type along with the entities they're attached to, leaving
+/// out entities outside of the `interest` area, if one is set.
+///
+/// NOTE: Joining yields entities in order of ID, so identical state always serializes to the
+/// same string.
+fn collect<'s, T>(
+    entities: &Entities,
+    components: &'s ReadStorage<T>,
+    interest: Option<&BitSet>,
+) -> Vec<ComponentEntry<&'s T>>
+where
+    T: Component,
+{
+    (&**entities, components)
+        .join()
+        .filter(|(entity, _)| is_included(interest, *entity))
+        .map(|(entity, component)| ComponentEntry {
+            entity: entity.into(),
+            data: component,
+        })
+        .collect()
+}
+
+/// Whether the components of `entity` are serialized, given the entities in the interest area.
+fn is_included(interest: Option<&BitSet>, entity: Entity) -> bool {
+    interest.map_or(true, |interest| interest.contains(entity.id()))
+}
+
+/// Serializes all components of a type, shared by the systems that read components.
+struct ComponentSerializer {
+    name: &'static str,
+    connection: EditorConnection,
+    options: TypeOptions,
+    reported_truncation: bool,
+}
+
+impl ComponentSerializer {
+    fn new(name: &'static str, connection: EditorConnection, options: TypeOptions) -> Self {
+        ComponentSerializer {
+            name,
+            connection,
+            options,
+            reported_truncation: false,
+        }
+    }
+
+    /// Serializes all components of the type, or the requested page of them if component data
+    /// is paginated, returning the serialized data and the number of components serialized.
+    fn serialize<T>(
+        &mut self,
+        data: &[ComponentEntry<&T>],
+    ) -> Result<(Box<RawValue>, usize), serde_json::Error>
+    where
+        T: Serialize,
+    {
+        let (data, page) = self.connection.pages().paginate(self.name, data);
+        if self.options.redaction.is_some() {
+            return self.serialize_values(data, page);
+        }
+
+        let count = data.len();
         let serialize_data = SerializedComponent {
             name: self.name,
+            category: self.options.category,
             data,
+            page,
         };
-        if let Ok(serialized) = serde_json::to_string(&serialize_data) {
-            self.connection
-                .send_data(SerializedData::Component(serialized));
-        } else {
-            error!("Failed to serialize component of type {}", self.name);
+        match serde_json::value::to_raw_value(&serialize_data) {
+            // Components that are nested too deeply can't be parsed when the editor sends them
+            // back, so they need to be serialized again with the deep parts truncated.
+            Ok(ref serialized)
+                if depth::json_depth(serialized.get())
+                    > self.options.max_depth + COMPONENT_WRAPPER_DEPTH =>
+            {
+                self.serialize_values(data, page)
+            }
+
+            result => result.map(|serialized| (serialized, count)),
+        }
+    }
+
+    /// Serializes all components of the type by way of `serde_json::Value`, removing any redacted
+    /// fields and truncating components that are nested too deeply.
+    fn serialize_values<T>(
+        &mut self,
+        data: &[ComponentEntry<&T>],
+        page: Option<ComponentPage>,
+    ) -> Result<(Box<RawValue>, usize), serde_json::Error>
+    where
+        T: Serialize,
+    {
+        let mut values = Vec::with_capacity(data.len());
+        let mut truncated = false;
+        for entry in data {
+            let mut value = serde_json::to_value(entry.data)?;
+            truncated |= self.options.prepare_outgoing(&mut value);
+            values.push(ComponentEntry {
+                entity: entry.entity,
+                data: value,
+            });
+        }
+
+        if truncated && !self.reported_truncation {
+            self.reported_truncation = true;
+            warn!(
+                "Components of type {} are nested more than {} levels deep and were truncated",
+                self.name, self.options.max_depth
+            );
+            self.connection.send_message(
+                "diagnostic",
+                Diagnostic::DepthTruncated {
+                    name: self.name,
+                    max_depth: self.options.max_depth,
+                },
+            );
         }
+
+        let serialize_data = SerializedComponent {
+            name: self.name,
+            category: self.options.category,
+            data: &values,
+            page,
+        };
+        serde_json::value::to_raw_value(&serialize_data)
+            .map(|serialized| (serialized, values.len()))
+    }
+
+    /// Whether components should be serialized and sent this frame.
+    ///
+    /// When syncing round-robin, only the types whose turn it is are serialized, unless the
+    /// editor doesn't support the partial snapshots that this results in.
+    fn is_active(&self) -> bool {
+        self.connection.presence().is_active()
+            && self
+                .connection
+                .subscriptions()
+                .wants_type("components", self.name)
+            && (self.connection.schedule().is_due(self.name)
+                || !self
+                    .connection
+                    .capabilities()
+                    .get()
+                    .contains(Capability::Deltas))
+    }
+
+    fn send(&self, serialized: Box<RawValue>, count: usize, entities: BitSet) {
+        self.connection.send_serialized(SerializedData::Component(
+            self.name, serialized, count, entities,
+        ));
+    }
+
+    /// Sends a copy of `serialized`, which is kept around to be sent again.
+    fn send_copy(&self, serialized: &RawValue, count: usize, entities: BitSet) {
+        self.send(serialized.to_owned(), count, entities);
+    }
+
+    fn report(&self, error: &serde_json::Error) {
+        error!(
+            "Failed to serialize component of type {}: {}",
+            self.name, error
+        );
     }
 }
@@ -1,9 +1,12 @@
 use amethyst::ecs::*;
 use amethyst::shred::Resource;
 use serde::Serialize;
+use crate::depth::{self, RESOURCE_WRAPPER_DEPTH};
+use crate::registry::TypeOptions;
 use serde_json;
+use serde_json::value::RawValue;
 use std::marker::PhantomData;
-use crate::types::{EditorConnection, SerializedData, SerializedResource};
+use crate::types::{Diagnostic, EditorConnection, SerializedData, SerializedResource};
 
 /// A system that serializes a resource of a specific type and sends it to the
 /// [`SyncEditorSystem`].
@@ -16,14 +19,22 @@ use crate::types::{EditorConnection, SerializedData, SerializedResource};
 pub(crate) struct ReadResourceSystem<T> {
     name: &'static str,
     connection: EditorConnection,
+    options: TypeOptions,
+    reported_truncation: bool,
     _phantom: PhantomData<T>,
 }
 
 impl<T> ReadResourceSystem<T> {
-    pub(crate) fn new(name: &'static str, connection: EditorConnection) -> Self {
+    pub(crate) fn new(
+        name: &'static str,
+        connection: EditorConnection,
+        options: TypeOptions,
+    ) -> Self {
         Self {
             name,
             connection,
+            options,
+            reported_truncation: false,
             _phantom: PhantomData,
         }
     }
@@ -36,6 +47,15 @@ where
     type SystemData = Option<Read<'a, T>>;
 
     fn run(&mut self, resource: Self::SystemData) {
+        if !self.connection.presence().is_active()
+            || !self
+                .connection
+                .subscriptions()
+                .wants_type("resources", self.name)
+        {
+            return;
+        }
+
         let resource = match resource {
             Some(resource) => resource,
             None => {
@@ -47,16 +67,68 @@ where
             }
         };
 
-        let serialize_data = SerializedResource {
-            name: self.name,
-            data: &*resource,
+        let result = if self.options.redaction.is_some() {
+            self.serialize_value(&*resource)
+        } else {
+            serde_json::value::to_raw_value(&SerializedResource {
+                name: self.name,
+                category: self.options.category,
+                data: &*resource,
+            })
+            .and_then(|serialized| {
+                // Resources that are nested too deeply can't be parsed when the editor sends them
+                // back, so they need to be serialized again with the deep parts truncated.
+                if depth::json_depth(serialized.get())
+                    > self.options.max_depth + RESOURCE_WRAPPER_DEPTH
+                {
+                    self.serialize_value(&*resource)
+                } else {
+                    Ok(serialized)
+                }
+            })
         };
 
-        if let Ok(serialized) = serde_json::to_string(&serialize_data) {
-            self.connection
-                .send_data(SerializedData::Resource(serialized));
-        } else {
-            warn!("Failed to serialize resource of type {}", self.name);
+        match result {
+            Ok(serialized) => {
+                self.connection
+                    .send_serialized(SerializedData::Resource(self.name, serialized));
+            }
+
+            Err(error) => warn!(
+                "Failed to serialize resource of type {}: {}",
+                self.name, error
+            ),
         }
     }
 }
+
+impl<T> ReadResourceSystem<T> {
+    /// Serializes the resource by way of `serde_json::Value`, removing any redacted fields and
+    /// truncating the resource if it's nested too deeply.
+    fn serialize_value(&mut self, resource: &T) -> Result<Box<RawValue>, serde_json::Error>
+    where
+        T: Serialize,
+    {
+        let mut value = serde_json::to_value(resource)?;
+        if self.options.prepare_outgoing(&mut value) && !self.reported_truncation {
+            self.reported_truncation = true;
+            warn!(
+                "Resource {} is nested more than {} levels deep and was truncated",
+                self.name, self.options.max_depth
+            );
+            self.connection.send_message(
+                "diagnostic",
+                Diagnostic::DepthTruncated {
+                    name: self.name,
+                    max_depth: self.options.max_depth,
+                },
+            );
+        }
+
+        serde_json::value::to_raw_value(&SerializedResource {
+            name: self.name,
+            category: self.options.category,
+            data: &value,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+use amethyst::ecs::{Entity, LazyUpdate, Read, System};
+use crossbeam_channel::Receiver;
+use crate::registry::Registry;
+use crate::scene;
+use crate::types::{EditStatus, EditorConnection};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A request from the editor to export a set of entities to a prefab file.
+pub(crate) struct SceneExport {
+    pub path: String,
+    pub entities: Vec<Entity>,
+    pub request_id: Option<u64>,
+}
+
+/// Result of a scene export, sent back to the editor.
+#[derive(Debug, Clone, Serialize)]
+struct SceneExportResult {
+    path: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A system that exports the registered components of entities selected in the editor to an
+/// Amethyst prefab file.
+///
+/// Paths sent by the editor are resolved against the directory set with
+/// [`SyncEditorBundle::scene_export_dir`]. Exports are rejected if no directory has been set, or
+/// if the path would leave it.
+///
+/// Since the export needs to visit every registered component type, the export itself is
+/// deferred until the end of the frame, when it can access the world directly.
+///
+/// [`SyncEditorBundle::scene_export_dir`]: ../struct.SyncEditorBundle.html#method.scene_export_dir
+pub(crate) struct SceneExportSystem {
+    receiver: Receiver<SceneExport>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+    dir: Option<PathBuf>,
+}
+
+impl SceneExportSystem {
+    pub(crate) fn new(
+        receiver: Receiver<SceneExport>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+        dir: Option<PathBuf>,
+    ) -> Self {
+        SceneExportSystem {
+            receiver,
+            registry,
+            connection,
+            dir,
+        }
+    }
+
+    /// Reports an export that failed or was rejected to the editor.
+    fn report_error(
+        &self,
+        path: String,
+        error: String,
+        request_id: Option<u64>,
+        status: EditStatus,
+    ) {
+        warn!("Failed to export scene to {:?}: {}", path, error);
+        self.connection.send_message(
+            "scene_export",
+            SceneExportResult {
+                path,
+                error: Some(error),
+            },
+        );
+        self.connection.send_ack(request_id, status);
+    }
+}
+
+impl<'a> System<'a> for SceneExportSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        while let Ok(SceneExport {
+            path,
+            entities,
+            request_id,
+        }) = self.receiver.try_recv()
+        {
+            let resolved = match self.dir {
+                Some(ref dir) => scene::resolve_export_path(dir, &path),
+                None => {
+                    let error = "Scene export directory isn't set".to_owned();
+                    self.report_error(path, error, request_id, EditStatus::InvalidData);
+                    continue;
+                }
+            };
+            let resolved = match resolved {
+                Some(resolved) => resolved,
+                None => {
+                    let error = "Path must be relative and can't contain `..`".to_owned();
+                    self.report_error(path, error, request_id, EditStatus::InvalidData);
+                    continue;
+                }
+            };
+
+            let registry = self.registry.clone();
+            let connection = self.connection.clone();
+            lazy.exec(move |world| {
+                let result = scene::export_prefab(world, &registry, &resolved, &entities);
+                if let Err(ref error) = result {
+                    warn!("Failed to export scene to {:?}: {:?}", resolved, error);
+                }
+
+                let status = match result {
+                    Ok(()) => EditStatus::Applied,
+                    Err(_) => EditStatus::Failed,
+                };
+                connection.send_message(
+                    "scene_export",
+                    SceneExportResult {
+                        path,
+                        error: result.err().map(|error| error.to_string()),
+                    },
+                );
+                connection.send_ack(request_id, status);
+            });
+        }
+    }
+}
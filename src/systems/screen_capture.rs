@@ -0,0 +1,127 @@
+use amethyst::ecs::{System, Write};
+use crate::screen_capture::{CaptureFormat, CaptureSettings, CapturedFrame, ScreenCapture};
+use crate::types::EditorConnection;
+use image::jpeg::JPEGEncoder;
+use image::png::PNGEncoder;
+use image::ColorType;
+use std::io;
+use std::time::Instant;
+
+/// The most base64-encoded image data sent in a single message, in bytes.
+const MAX_CHUNK_SIZE: usize = 16 * 1024;
+
+/// A piece of an encoded frame, sent as a `"screen_capture"` message.
+///
+/// The editor decodes `data` as base64 and concatenates the chunks of a frame in order of
+/// `index` to get the encoded image.
+#[derive(Debug, Serialize)]
+struct CaptureChunk<'a> {
+    frame: u64,
+    format: &'static str,
+    width: u32,
+    height: u32,
+    index: usize,
+    count: usize,
+    data: &'a str,
+}
+
+/// A system that asks the game for a frame at the configured rate, and streams the frames it
+/// submits to the editor.
+///
+/// Each frame is encoded as an image and split into several messages, so that a large frame
+/// doesn't hold up log output and other messages for long. Frames are only requested while the
+/// editor is connected, and at most once per capture interval.
+pub(crate) struct ScreenCaptureSystem {
+    settings: CaptureSettings,
+    connection: EditorConnection,
+    next_capture: Instant,
+    next_frame_id: u64,
+}
+
+impl ScreenCaptureSystem {
+    pub(crate) fn new(settings: CaptureSettings, connection: EditorConnection) -> Self {
+        ScreenCaptureSystem {
+            settings,
+            connection,
+            next_capture: Instant::now(),
+            next_frame_id: 0,
+        }
+    }
+}
+
+impl<'a> System<'a> for ScreenCaptureSystem {
+    type SystemData = Write<'a, ScreenCapture>;
+
+    fn run(&mut self, mut capture: Self::SystemData) {
+        if let Some(frame) = capture.take() {
+            match encode(&frame, self.settings.format) {
+                Ok(encoded) => self.send(&frame, &encoded),
+                Err(error) => error!("Failed to encode captured frame: {}", error),
+            }
+        }
+
+        let now = Instant::now();
+        if !self.connection.presence().is_active() || now < self.next_capture {
+            return;
+        }
+
+        capture.request();
+        self.next_capture = now + self.settings.interval;
+    }
+}
+
+impl ScreenCaptureSystem {
+    fn send(&mut self, frame: &CapturedFrame, encoded: &[u8]) {
+        let id = self.next_frame_id;
+        self.next_frame_id += 1;
+
+        // NOTE: Base64 is pure ASCII, so the chunks can be split at any byte.
+        let data = base64::encode(encoded);
+        let count = (data.len() + MAX_CHUNK_SIZE - 1) / MAX_CHUNK_SIZE;
+        for (index, chunk) in data.as_bytes().chunks(MAX_CHUNK_SIZE).enumerate() {
+            self.connection.send_message(
+                "screen_capture",
+                CaptureChunk {
+                    frame: id,
+                    format: self.settings.format.name(),
+                    width: frame.width,
+                    height: frame.height,
+                    index,
+                    count,
+                    data: std::str::from_utf8(chunk).expect("Base64 produced invalid UTF-8"),
+                },
+            );
+        }
+    }
+}
+
+/// Encodes `frame` as an image in `format`.
+fn encode(frame: &CapturedFrame, format: CaptureFormat) -> io::Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    match format {
+        CaptureFormat::Png => {
+            PNGEncoder::new(&mut encoded).encode(
+                &frame.rgba,
+                frame.width,
+                frame.height,
+                ColorType::RGBA(8),
+            )?;
+        }
+
+        // NOTE: JPEG has no alpha channel, so it's dropped before encoding.
+        CaptureFormat::Jpeg { quality } => {
+            let rgb = frame
+                .rgba
+                .chunks(4)
+                .flat_map(|pixel| pixel[..3].iter().cloned())
+                .collect::<Vec<_>>();
+            JPEGEncoder::new_with_quality(&mut encoded, quality.max(1).min(100)).encode(
+                &rgb,
+                frame.width,
+                frame.height,
+                ColorType::RGB(8),
+            )?;
+        }
+    }
+    Ok(encoded)
+}
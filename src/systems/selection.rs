@@ -0,0 +1,86 @@
+use amethyst::ecs::{Entities, Entity, System, Write};
+use crossbeam_channel::Receiver;
+use crate::selection::EditorSelection;
+use crate::serializable_entity::SerializableEntity;
+use crate::types::{EditStatus, EditorConnection};
+
+/// A request from the editor to replace the selection.
+pub(crate) struct SetSelection {
+    pub entities: Vec<Entity>,
+    pub request_id: Option<u64>,
+}
+
+/// Sent to the editor as a `"selection"` message whenever the selection changes.
+#[derive(Debug, Serialize)]
+struct SelectionMessage {
+    entities: Vec<SerializableEntity>,
+}
+
+/// A system that keeps the [`EditorSelection`] resource in sync with the editor.
+///
+/// Selections made in the editor are applied to the resource, and any change to the resource
+/// is sent back to the editor, whichever side it came from. Echoing the editor's own changes
+/// back lets it see which of the entities it selected still exist.
+pub(crate) struct SelectionSystem {
+    receiver: Receiver<SetSelection>,
+    connection: EditorConnection,
+    last_sent: Option<EditorSelection>,
+    resyncs: usize,
+}
+
+impl SelectionSystem {
+    pub(crate) fn new(receiver: Receiver<SetSelection>, connection: EditorConnection) -> Self {
+        SelectionSystem {
+            receiver,
+            connection,
+            last_sent: None,
+            resyncs: 0,
+        }
+    }
+}
+
+impl<'a> System<'a> for SelectionSystem {
+    type SystemData = (Entities<'a>, Write<'a, EditorSelection>);
+
+    fn run(&mut self, (entities, mut selection): Self::SystemData) {
+        for SetSelection {
+            entities: selected,
+            request_id,
+        } in self.receiver.try_iter()
+        {
+            selection.set(selected);
+            self.connection.send_ack(request_id, EditStatus::Applied);
+        }
+
+        selection.retain(|entity| entities.is_alive(entity));
+
+        // NOTE: The editor may have lost track of the selection while it was away, so it's sent
+        // again once the editor is back.
+        if !self.connection.presence().is_active() {
+            self.last_sent = None;
+            return;
+        }
+
+        if self
+            .connection
+            .presence()
+            .resync_requested(&mut self.resyncs)
+        {
+            self.last_sent = None;
+        }
+
+        if self.last_sent.as_ref() != Some(&*selection) {
+            self.connection.send_message(
+                "selection",
+                SelectionMessage {
+                    entities: selection
+                        .entities()
+                        .iter()
+                        .map(|&entity| entity.into())
+                        .collect(),
+                },
+            );
+            self.last_sent = Some(selection.clone());
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use amethyst::ecs::{Join, LazyUpdate, Read, System};
+use crate::registry::Registry;
+use crate::types::EditorConnection;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Sent to the editor as a `"storage_stats"` message.
+#[derive(Debug, Serialize)]
+struct StorageStats {
+    /// The number of live entities.
+    entities: usize,
+
+    components: Vec<ComponentStats>,
+}
+
+#[derive(Debug, Serialize)]
+struct ComponentStats {
+    name: &'static str,
+
+    /// The kind of storage the component uses, e.g. `"DenseVecStorage"`.
+    storage: &'static str,
+
+    /// The number of entities with the component, or `None` if its storage hasn't been added
+    /// to the world.
+    count: Option<usize>,
+}
+
+/// A system that periodically reports how many entities have each registered component, along
+/// with the kind of storage each component uses.
+///
+/// This helps spot leaks, e.g. components that keep piling up because the entities holding them
+/// are never deleted. The report is sent to the editor as a `"storage_stats"` message.
+pub(crate) struct StorageStatsSystem {
+    interval: Duration,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+    next_report: Instant,
+}
+
+impl StorageStatsSystem {
+    pub(crate) fn new(
+        interval: Duration,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        StorageStatsSystem {
+            interval,
+            registry,
+            connection,
+            next_report: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for StorageStatsSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        let now = Instant::now();
+        if now < self.next_report {
+            return;
+        }
+        self.next_report = now + self.interval;
+
+        // NOTE: Counting components needs access to every registered storage, so it's done at
+        // the end of the frame with direct access to the world.
+        let registry = self.registry.clone();
+        let connection = self.connection.clone();
+        lazy.exec(move |world| {
+            let components = registry
+                .components()
+                .map(|access| ComponentStats {
+                    name: access.name(),
+                    storage: access.storage(),
+                    count: access.count(world),
+                })
+                .collect();
+            connection.send_message(
+                "storage_stats",
+                StorageStats {
+                    entities: (&*world.entities()).join().count(),
+                    components,
+                },
+            );
+        });
+    }
+}
@@ -0,0 +1,72 @@
+use amethyst::core::timing::Time;
+use amethyst::ecs::{System, Write};
+use crossbeam_channel::Receiver;
+use crate::types::{EditStatus, EditorConnection};
+
+/// Changes to the game's timing, requested by the editor.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum TimeControl {
+    SetTimeScale {
+        scale: f32,
+        request_id: Option<u64>,
+    },
+
+    SetFixedTimestep {
+        seconds: f32,
+        request_id: Option<u64>,
+    },
+}
+
+/// A system that applies changes to the `Time` resource requested by the editor, allowing the
+/// game to be slowed down, sped up, or have its fixed step changed while debugging.
+pub(crate) struct TimeControlSystem {
+    receiver: Receiver<TimeControl>,
+    connection: EditorConnection,
+}
+
+impl TimeControlSystem {
+    pub(crate) fn new(receiver: Receiver<TimeControl>, connection: EditorConnection) -> Self {
+        TimeControlSystem {
+            receiver,
+            connection,
+        }
+    }
+}
+
+impl<'a> System<'a> for TimeControlSystem {
+    type SystemData = Write<'a, Time>;
+
+    fn run(&mut self, mut time: Self::SystemData) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                TimeControl::SetTimeScale { scale, request_id } => {
+                    // NOTE: A time scale of 0 is valid, and pauses the game.
+                    if !scale.is_finite() || scale < 0.0 {
+                        debug!("Ignoring invalid time scale {}", scale);
+                        self.connection
+                            .send_ack(request_id, EditStatus::InvalidData);
+                        continue;
+                    }
+
+                    time.set_time_scale(scale);
+                    self.connection.send_ack(request_id, EditStatus::Applied);
+                }
+
+                TimeControl::SetFixedTimestep {
+                    seconds,
+                    request_id,
+                } => {
+                    if !seconds.is_finite() || seconds <= 0.0 {
+                        debug!("Ignoring invalid fixed timestep {}", seconds);
+                        self.connection
+                            .send_ack(request_id, EditStatus::InvalidData);
+                        continue;
+                    }
+
+                    time.set_fixed_seconds(seconds);
+                    self.connection.send_ack(request_id, EditStatus::Applied);
+                }
+            }
+        }
+    }
+}
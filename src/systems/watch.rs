@@ -0,0 +1,112 @@
+use amethyst::ecs::{Entity, LazyUpdate, Read, System, World};
+use crossbeam_channel::Receiver;
+use crate::query;
+use crate::registry::Registry;
+use crate::types::EditorConnection;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Changes to the set of watches, requested by the editor.
+pub(crate) enum WatchMessage {
+    Add(Watch),
+    Remove(u64),
+}
+
+/// A field of a single component that's sent to the editor every frame.
+#[derive(Debug, Clone)]
+pub(crate) struct Watch {
+    pub id: u64,
+    pub component: String,
+    pub entity: Entity,
+    pub path: String,
+}
+
+/// The current value of every watch, sent to the editor as a `"watch"` message.
+#[derive(Debug, Serialize)]
+struct WatchValues {
+    /// The value of each watch, keyed by its ID. Watches whose entity or component no longer
+    /// exists, or whose path doesn't match a field, have a null value.
+    values: BTreeMap<u64, Value>,
+}
+
+/// A system that evaluates the watches added by the editor every frame, so that the editor can
+/// monitor single fields without syncing their whole component at a high rate.
+///
+/// Watches can target any registered component, so they're evaluated at the end of the frame,
+/// when they can access the world directly.
+pub(crate) struct WatchSystem {
+    receiver: Receiver<WatchMessage>,
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+
+    // NOTE: The watches are shared with the closure that evaluates them, and only copied when
+    // a watch is added or removed.
+    watches: Arc<Vec<Watch>>,
+}
+
+impl WatchSystem {
+    pub(crate) fn new(
+        receiver: Receiver<WatchMessage>,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        WatchSystem {
+            receiver,
+            registry,
+            connection,
+            watches: Arc::new(Vec::new()),
+        }
+    }
+}
+
+impl<'a> System<'a> for WatchSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        while let Ok(message) = self.receiver.try_recv() {
+            let watches = Arc::make_mut(&mut self.watches);
+            match message {
+                WatchMessage::Add(watch) => {
+                    if self.registry.component(&watch.component).is_none() {
+                        debug!("Can't watch unregistered component {:?}", watch.component);
+                    }
+                    watches.retain(|existing| existing.id != watch.id);
+                    watches.push(watch);
+                }
+
+                WatchMessage::Remove(id) => watches.retain(|watch| watch.id != id),
+            }
+        }
+
+        if self.watches.is_empty() {
+            return;
+        }
+
+        let watches = self.watches.clone();
+        let registry = self.registry.clone();
+        let connection = self.connection.clone();
+        lazy.exec(move |world| {
+            let values = watches
+                .iter()
+                .map(|watch| {
+                    let value = evaluate(world, &registry, watch).unwrap_or(Value::Null);
+                    (watch.id, value)
+                })
+                .collect();
+            connection.send_message("watch", WatchValues { values });
+        });
+    }
+}
+
+/// Finds the current value of a watched field.
+///
+/// Redacted fields are removed before the path is looked up, so they can't be watched.
+fn evaluate(world: &World, registry: &Registry, watch: &Watch) -> Option<Value> {
+    let access = registry.component(&watch.component)?;
+    let mut value = access.serialize(world, watch.entity)?.ok()?;
+    if let Some(redaction) = registry.redaction(access.name()) {
+        redaction.apply(&mut value);
+    }
+    query::lookup(&value, &watch.path).cloned()
+}
@@ -0,0 +1,172 @@
+use amethyst::ecs::{Join, LazyUpdate, Read, System, World};
+use crate::registry::Registry;
+use crate::types::EditorConnection;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the watchdog counts entities and components.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Entity and component count thresholds monitored by the [`WatchdogSystem`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WatchdogSettings {
+    /// The maximum number of live entities.
+    pub max_entities: Option<usize>,
+
+    /// The maximum number of instances of each registered component type.
+    pub max_components: Vec<(&'static str, usize)>,
+}
+
+impl WatchdogSettings {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.max_entities.is_none() && self.max_components.is_empty()
+    }
+}
+
+/// Alert sent to the editor when a count exceeds its threshold.
+#[derive(Debug, Clone, Serialize)]
+struct Alert {
+    /// The component whose count exceeded its threshold, or `None` for the entity count.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component: Option<&'static str>,
+    count: usize,
+    threshold: usize,
+}
+
+/// A threshold along with whether it was exceeded the last time it was checked.
+#[derive(Debug, Clone)]
+struct Threshold {
+    component: Option<&'static str>,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl Threshold {
+    fn new(component: Option<&'static str>, limit: usize) -> Self {
+        Threshold {
+            component,
+            limit,
+            exceeded: false,
+        }
+    }
+
+    /// Updates the threshold with the latest count, returning whether the count has just gone
+    /// over the limit.
+    ///
+    /// Only crossing the limit is reported, so that a count that stays too high doesn't cause
+    /// an alert on every check. The threshold is re-armed once the count drops back to the
+    /// limit.
+    fn check(&mut self, count: usize) -> bool {
+        let was_exceeded = self.exceeded;
+        self.exceeded = count > self.limit;
+        self.exceeded && !was_exceeded
+    }
+}
+
+/// A system that periodically counts entities and registered components, and alerts the editor
+/// when a count exceeds its configured threshold.
+///
+/// This is intended to catch runaway spawners during long playtests. Each alert is sent to the
+/// editor as an `"alert"` message and logged locally as a warning.
+pub(crate) struct WatchdogSystem {
+    registry: Arc<Registry>,
+    connection: EditorConnection,
+    next_check: Instant,
+
+    // NOTE: The thresholds are checked at the end of the frame with direct access to the world,
+    // so they need to be shared with the deferred check.
+    thresholds: Arc<Mutex<Vec<Threshold>>>,
+}
+
+impl WatchdogSystem {
+    pub(crate) fn new(
+        settings: WatchdogSettings,
+        registry: Arc<Registry>,
+        connection: EditorConnection,
+    ) -> Self {
+        let mut thresholds = Vec::new();
+        if let Some(limit) = settings.max_entities {
+            thresholds.push(Threshold::new(None, limit));
+        }
+        for (name, limit) in settings.max_components {
+            if registry.component(name).is_none() {
+                warn!("Watchdog threshold set for unregistered component {}", name);
+                continue;
+            }
+            thresholds.push(Threshold::new(Some(name), limit));
+        }
+
+        WatchdogSystem {
+            registry,
+            connection,
+            next_check: Instant::now(),
+            thresholds: Arc::new(Mutex::new(thresholds)),
+        }
+    }
+}
+
+impl<'a> System<'a> for WatchdogSystem {
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        let now = Instant::now();
+        if now < self.next_check {
+            return;
+        }
+        self.next_check = now + CHECK_INTERVAL;
+
+        let registry = self.registry.clone();
+        let connection = self.connection.clone();
+        let thresholds = self.thresholds.clone();
+        lazy.exec(move |world| {
+            let mut thresholds = thresholds.lock().expect("Watchdog thresholds poisoned");
+            for threshold in thresholds.iter_mut() {
+                let count = match count(world, &registry, threshold.component) {
+                    Some(count) => count,
+                    None => continue,
+                };
+
+                if threshold.check(count) {
+                    warn!(
+                        "Watchdog: {} {} exceeds the threshold of {}",
+                        count,
+                        threshold.component.unwrap_or("entities"),
+                        threshold.limit
+                    );
+                    connection.send_message(
+                        "alert",
+                        Alert {
+                            component: threshold.component,
+                            count,
+                            threshold: threshold.limit,
+                        },
+                    );
+                }
+            }
+        });
+    }
+}
+
+fn count(world: &World, registry: &Registry, component: Option<&str>) -> Option<usize> {
+    match component {
+        Some(name) => registry
+            .component(name)
+            .and_then(|component| component.count(world)),
+        None => Some((&*world.entities()).join().count()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn threshold_alerts_once_per_crossing() {
+        let mut threshold = Threshold::new(None, 10);
+        assert!(!threshold.check(10));
+        assert!(threshold.check(11));
+        assert!(!threshold.check(12));
+        assert!(!threshold.check(5));
+        assert!(threshold.check(20));
+    }
+}
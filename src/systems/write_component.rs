@@ -1,15 +1,61 @@
 use amethyst::ecs::prelude::*;
+use amethyst::ecs::storage::MaskedStorage;
+use crate::registry::TypeOptions;
+use crate::serializable_entity::SerializableEntity;
 use serde::de::DeserializeOwned;
-use serde_json;
+use serde::Serialize;
+use serde_json::{self, Value};
 use std::marker::PhantomData;
-use crate::types::IncomingComponent;
+use std::sync::{Arc, Mutex};
+use crate::types::{EditStatus, EditorConnection, IncomingComponent, WriteError};
 
+/// A component as it was right after an edit from the editor was applied to it, which is
+/// compared with the component a frame later to find out whether the game overwrote the edit.
+struct AppliedEdit {
+    entity: Entity,
+    value: Value,
+
+    /// The paths of the fields that the edit set, which are the only ones checked. Fields that
+    /// the edit didn't touch are free to be changed by the game.
+    fields: Vec<String>,
+
+    request_id: Option<u64>,
+}
+
+/// Sent to the editor as an `"edit_overridden"` message when the game changed a component
+/// within a frame of an edit being applied to it, which usually means that a system sets the
+/// edited fields every frame.
+#[derive(Debug, Serialize)]
+struct EditOverridden {
+    id: &'static str,
+    entity: SerializableEntity,
+
+    /// The paths of the fields that no longer have the edited values, e.g. `"translation.x"`.
+    fields: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+/// A system that deserializes incoming updates for a component and applies them to the
+/// component storage as soon as it runs.
+///
+/// Used when edits are applied at [`SyncPoint::FrameStart`], so that the game's systems see
+/// them in the same frame.
+///
+/// [`SyncPoint::FrameStart`]: ../enum.SyncPoint.html#variant.FrameStart
 pub(crate) struct WriteComponentSystem<T>
 where
     T: Sync + Send + 'static,
 {
     id: &'static str,
     reader: crossbeam_channel::Receiver<IncomingComponent>,
+    connection: EditorConnection,
+    options: TypeOptions,
+
+    // The edits applied last frame, which are checked before this frame's edits are applied.
+    applied: Vec<AppliedEdit>,
+
     _marker: PhantomData<T>,
 }
 
@@ -20,10 +66,15 @@ where
     pub(crate) fn new(
         id: &'static str,
         reader: crossbeam_channel::Receiver<IncomingComponent>,
+        connection: EditorConnection,
+        options: TypeOptions,
     ) -> Self {
         WriteComponentSystem {
             id,
             reader,
+            connection,
+            options,
+            applied: Vec::new(),
             _marker: PhantomData,
         }
     }
@@ -31,27 +82,344 @@ where
 
 impl<'a, T> System<'a> for WriteComponentSystem<T>
 where
-    T: Component + DeserializeOwned + Send + Sync,
+    T: Component + Serialize + DeserializeOwned + Send + Sync,
 {
     type SystemData = WriteStorage<'a, T>;
 
     fn run(&mut self, mut storage: Self::SystemData) {
         trace!("`WriteComponentSystem::run` for {}", self.id);
 
+        // NOTE: The edits applied last frame are checked before this frame's edits are applied,
+        // once the game's systems have had a full frame to overwrite them.
+        for edit in self.applied.drain(..) {
+            check_edit(self.id, &self.connection, &storage, edit);
+        }
+
         while let Ok(event) = self.reader.try_recv() {
-            debug!("Got incoming message for {}: {:?}", self.id, event.data);
+            apply_edit(
+                self.id,
+                &self.options,
+                &self.connection,
+                &mut storage,
+                event,
+                &mut self.applied,
+            );
+        }
+    }
+}
+
+/// A system that deserializes incoming updates for a component and defers applying them until
+/// the world is maintained at the end of the frame.
+///
+/// Used when edits are applied at [`SyncPoint::FrameEnd`], where the game's systems only see
+/// edits in the next frame anyway. The storage is only borrowed on frames where there is
+/// something to apply or check, so this system doesn't contend with the game's systems for `T`
+/// on the vast majority of frames where the editor hasn't sent anything.
+///
+/// [`SyncPoint::FrameEnd`]: ../enum.SyncPoint.html#variant.FrameEnd
+pub(crate) struct DeferredWriteComponentSystem<T>
+where
+    T: Sync + Send + 'static,
+{
+    id: &'static str,
+    reader: crossbeam_channel::Receiver<IncomingComponent>,
+    connection: EditorConnection,
+    options: TypeOptions,
+
+    // The edits applied when the world was last maintained, which are checked before the next
+    // edits are applied. Shared with the deferred updates that fill it in.
+    applied: Arc<Mutex<Vec<AppliedEdit>>>,
+
+    _marker: PhantomData<T>,
+}
+
+impl<T> DeferredWriteComponentSystem<T>
+where
+    T: Sync + Send + 'static,
+{
+    pub(crate) fn new(
+        id: &'static str,
+        reader: crossbeam_channel::Receiver<IncomingComponent>,
+        connection: EditorConnection,
+        options: TypeOptions,
+    ) -> Self {
+        DeferredWriteComponentSystem {
+            id,
+            reader,
+            connection,
+            options,
+            applied: Arc::new(Mutex::new(Vec::new())),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> System<'a> for DeferredWriteComponentSystem<T>
+where
+    T: Component + Serialize + DeserializeOwned + Send + Sync,
+{
+    type SystemData = Read<'a, LazyUpdate>;
+
+    fn run(&mut self, lazy: Self::SystemData) {
+        trace!("`DeferredWriteComponentSystem::run` for {}", self.id);
+
+        let events: Vec<_> = self.reader.try_iter().collect();
+        if events.is_empty() && self.applied.lock().unwrap().is_empty() {
+            return;
+        }
+
+        let id = self.id;
+        let options = self.options.clone();
+        let connection = self.connection.clone();
+        let applied = self.applied.clone();
+        lazy.exec(move |world| {
+            let mut applied = applied.lock().unwrap();
+            if !world.res.has_value::<MaskedStorage<T>>() {
+                applied.clear();
+                for event in events {
+                    connection.send_ack(event.request_id, EditStatus::MissingComponent);
+                }
+                return;
+            }
+
+            let mut storage = world.write_storage::<T>();
+            for edit in applied.drain(..) {
+                check_edit(id, &connection, &storage, edit);
+            }
+            for event in events {
+                apply_edit(id, &options, &connection, &mut storage, event, &mut applied);
+            }
+        });
+    }
+}
+
+/// Applies a single edit from the editor to the component storage.
+///
+/// Edits to several entities are atomic: the updated components are prepared for every entity
+/// before any of them are changed, so that the edit can be rejected as a whole.
+fn apply_edit<T>(
+    id: &'static str,
+    options: &TypeOptions,
+    connection: &EditorConnection,
+    storage: &mut WriteStorage<T>,
+    event: IncomingComponent,
+    applied: &mut Vec<AppliedEdit>,
+) where
+    T: Component + Serialize + DeserializeOwned,
+{
+    debug!("Got incoming message for {}: {:?}", id, event.data);
+
+    let mut updates = Vec::with_capacity(event.entities.len());
+    for &entity in &event.entities {
+        match prepare_update(id, options, connection, storage, entity, &event) {
+            Ok(updated) => updates.push((entity, updated)),
+            Err(status) => {
+                connection.send_ack(event.request_id, status);
+                return;
+            }
+        }
+    }
 
-            let updated = match serde_json::from_value(event.data.clone()) {
-                Ok(updated) => updated,
-                Err(error) => {
-                    debug!("Failed to deserialize update for {}: {:?}", self.id, error);
-                    continue;
+    for (entity, updated) in updates {
+        if let Some(component) = storage.get_mut(entity) {
+            let previous = serde_json::to_value(&*component);
+            *component = updated;
+            if let Ok(value) = serde_json::to_value(&*component) {
+                // NOTE: Patches set exactly the fields they contain, whereas full updates are
+                // only considered to set the fields whose values they actually changed.
+                let mut fields = Vec::new();
+                if event.patch {
+                    leaf_fields(&event.data, "", &mut fields);
+                } else if let Ok(previous) = previous {
+                    changed_fields(&previous, &value, "", &mut fields);
                 }
-            };
 
-            if let Some(component) = storage.get_mut(event.entity) {
-                *component = updated;
+                if !fields.is_empty() {
+                    applied.push(AppliedEdit {
+                        entity,
+                        value,
+                        fields,
+                        request_id: event.request_id,
+                    });
+                }
             }
         }
     }
+    connection.send_ack(event.request_id, EditStatus::Applied);
+}
+
+/// Compares a component with its value right after an edit was applied, letting the editor know
+/// if the game has changed any of the fields that the edit set since.
+///
+/// Components that were removed in the meantime aren't reported, since the editor learns about
+/// those from the next snapshot anyway.
+fn check_edit<T>(
+    id: &'static str,
+    connection: &EditorConnection,
+    storage: &WriteStorage<T>,
+    edit: AppliedEdit,
+) where
+    T: Component + Serialize,
+{
+    let current = match storage.get(edit.entity).map(serde_json::to_value) {
+        Some(Ok(current)) => current,
+        _ => return,
+    };
+
+    let mut fields = Vec::new();
+    changed_fields(&edit.value, &current, "", &mut fields);
+    fields.retain(|field| edit.fields.iter().any(|edited| overlaps(field, edited)));
+    if fields.is_empty() {
+        return;
+    }
+
+    debug!(
+        "Edit to {} of {:?} was overridden by the game: {:?}",
+        id, edit.entity, fields
+    );
+    connection.send_message(
+        "edit_overridden",
+        EditOverridden {
+            id,
+            entity: edit.entity.into(),
+            fields,
+            request_id: edit.request_id,
+        },
+    );
+}
+
+/// Collects the paths of the fields that differ between `before` and `after`, descending into
+/// nested objects. Any other values are compared as a whole.
+fn changed_fields(before: &Value, after: &Value, path: &str, fields: &mut Vec<String>) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, value) in before {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match after.get(key) {
+                    Some(after) => changed_fields(value, after, &field, fields),
+                    None => fields.push(field),
+                }
+            }
+        }
+
+        _ => {
+            if before != after {
+                fields.push(path.to_owned());
+            }
+        }
+    }
+}
+
+/// Collects the paths of the fields set by `value`, descending into nested objects.
+fn leaf_fields(value: &Value, path: &str, fields: &mut Vec<String>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                leaf_fields(value, &field, fields);
+            }
+        }
+
+        _ => fields.push(path.to_owned()),
+    }
+}
+
+/// Whether the field at path `a` is the same as, or nested in, the field at path `b` or the
+/// other way around.
+fn overlaps(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner.starts_with(outer) && inner[outer.len()..].starts_with('.')
+    };
+    a == b || a.is_empty() || b.is_empty() || nested(a, b) || nested(b, a)
+}
+
+/// Deserializes the updated component for `entity`, returning the status to acknowledge the
+/// edit with if the component can't be updated.
+fn prepare_update<T>(
+    id: &'static str,
+    options: &TypeOptions,
+    connection: &EditorConnection,
+    storage: &WriteStorage<T>,
+    entity: Entity,
+    event: &IncomingComponent,
+) -> Result<T, EditStatus>
+where
+    T: Component + Serialize + DeserializeOwned,
+{
+    let current = storage.get(entity).ok_or(EditStatus::MissingComponent)?;
+
+    let data = if event.patch {
+        // Patches are merged into the current component, so that fields that were
+        // changed by the game in the meantime aren't overwritten.
+        match serde_json::to_value(current) {
+            Ok(current) => options.apply_patch(&current, event.data.clone()),
+            Err(error) => {
+                error!("Failed to serialize {} for patching: {}", id, error);
+                return Err(EditStatus::InvalidData);
+            }
+        }
+    } else if options.redaction.is_some() || options.mask.is_some() {
+        match serde_json::to_value(current) {
+            Ok(current) => options.prepare_update(&current, event.data.clone()),
+            Err(_) => event.data.clone(),
+        }
+    } else {
+        event.data.clone()
+    };
+
+    serde_json::from_value(data).map_err(|error| {
+        debug!("Failed to deserialize update for {}: {:?}", id, error);
+        connection.send_message(
+            "error",
+            WriteError {
+                id,
+                entity: Some(entity.into()),
+                error: error.to_string(),
+            },
+        );
+        EditStatus::InvalidData
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_changed_nested_fields() {
+        let before = serde_json::json!({ "translation": { "x": 1.0, "y": 2.0 }, "scale": 1.0 });
+        let after = serde_json::json!({ "translation": { "x": 1.0, "y": 5.0 }, "scale": 1.0 });
+
+        let mut fields = Vec::new();
+        changed_fields(&before, &after, "", &mut fields);
+        assert_eq!(fields, vec!["translation.y".to_owned()]);
+    }
+
+    #[test]
+    fn only_checks_edited_fields() {
+        let patch = serde_json::json!({ "translation": { "y": 5.0 } });
+        let mut edited = Vec::new();
+        leaf_fields(&patch, "", &mut edited);
+        assert_eq!(edited, vec!["translation.y".to_owned()]);
+
+        let before = serde_json::json!({ "translation": { "x": 1.0, "y": 5.0 }, "scale": 1.0 });
+        let after = serde_json::json!({ "translation": { "x": 3.0, "y": 5.0 }, "scale": 2.0 });
+        let mut fields = Vec::new();
+        changed_fields(&before, &after, "", &mut fields);
+        fields.retain(|field| edited.iter().any(|edited| overlaps(field, edited)));
+        assert!(fields.is_empty());
+
+        assert!(overlaps("translation", "translation.y"));
+        assert!(overlaps("translation.y", "translation"));
+        assert!(!overlaps("translation.y", "translation.x"));
+        assert!(!overlaps("scale", "scales"));
+    }
 }
@@ -1,9 +1,12 @@
 use amethyst::ecs::*;
 use amethyst::shred::Resource;
 use crossbeam_channel::Receiver;
+use crate::registry::TypeOptions;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json;
 use std::marker::PhantomData;
+use crate::types::{EditStatus, EditorConnection, IncomingResource, WriteError};
 
 /// A system that deserializes incoming updates for a resource and applies
 /// them to the world state.
@@ -14,15 +17,24 @@ use std::marker::PhantomData;
 /// [`SyncEditorBundle`]: ./struct.SyncEditorBundle.html
 pub(crate) struct WriteResourceSystem<T> {
     id: &'static str,
-    incoming: Receiver<serde_json::Value>,
+    incoming: Receiver<IncomingResource>,
+    connection: EditorConnection,
+    options: TypeOptions,
     _phantom: PhantomData<T>,
 }
 
 impl<T> WriteResourceSystem<T> {
-    pub(crate) fn new(id: &'static str, incoming: Receiver<serde_json::Value>) -> Self {
+    pub(crate) fn new(
+        id: &'static str,
+        incoming: Receiver<IncomingResource>,
+        connection: EditorConnection,
+        options: TypeOptions,
+    ) -> Self {
         WriteResourceSystem {
             id,
             incoming,
+            connection,
+            options,
             _phantom: PhantomData,
         }
     }
@@ -30,7 +42,7 @@ impl<T> WriteResourceSystem<T> {
 
 impl<'a, T> System<'a> for WriteResourceSystem<T>
 where
-    T: Resource + DeserializeOwned,
+    T: Resource + Serialize + DeserializeOwned,
 {
     type SystemData = Option<Write<'a, T>>;
 
@@ -39,21 +51,130 @@ where
 
         let mut resource = match data {
             Some(res) => res,
-            None => return,
+            None => {
+                while let Ok(incoming) = self.incoming.try_recv() {
+                    self.connection
+                        .send_ack(incoming.request_id, EditStatus::MissingResource);
+                }
+                return;
+            }
         };
 
         while let Ok(incoming) = self.incoming.try_recv() {
-            debug!("Got incoming message for {}: {:?}", self.id, incoming);
+            apply_update(
+                self.id,
+                &self.options,
+                &self.connection,
+                &mut *resource,
+                incoming,
+            );
+        }
+    }
+}
+
+/// A system that deserializes incoming updates for a resource and defers applying them until
+/// the world is maintained at the end of the frame.
+///
+/// Used instead of [`WriteResourceSystem`] when edits are applied at [`SyncPoint::FrameEnd`],
+/// so that the resource is only borrowed on frames where the editor has sent an update for it.
+///
+/// [`WriteResourceSystem`]: ./struct.WriteResourceSystem.html
+/// [`SyncPoint::FrameEnd`]: ../enum.SyncPoint.html#variant.FrameEnd
+pub(crate) struct DeferredWriteResourceSystem<T> {
+    id: &'static str,
+    incoming: Receiver<IncomingResource>,
+    connection: EditorConnection,
+    options: TypeOptions,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> DeferredWriteResourceSystem<T> {
+    pub(crate) fn new(
+        id: &'static str,
+        incoming: Receiver<IncomingResource>,
+        connection: EditorConnection,
+        options: TypeOptions,
+    ) -> Self {
+        DeferredWriteResourceSystem {
+            id,
+            incoming,
+            connection,
+            options,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> System<'a> for DeferredWriteResourceSystem<T>
+where
+    T: Resource + Serialize + DeserializeOwned,
+{
+    type SystemData = Read<'a, LazyUpdate>;
 
-            let updated = match serde_json::from_value(incoming) {
-                Ok(updated) => updated,
-                Err(error) => {
-                    debug!("Failed to deserialize update for {}: {:?}", self.id, error);
-                    continue;
+    fn run(&mut self, lazy: Self::SystemData) {
+        trace!("`DeferredWriteResourceSystem::run` for {}", self.id);
+
+        let updates: Vec<_> = self.incoming.try_iter().collect();
+        if updates.is_empty() {
+            return;
+        }
+
+        let id = self.id;
+        let options = self.options.clone();
+        let connection = self.connection.clone();
+        lazy.exec(move |world| {
+            let mut resource = match world.res.try_fetch_mut::<T>() {
+                Some(res) => res,
+                None => {
+                    for incoming in updates {
+                        connection.send_ack(incoming.request_id, EditStatus::MissingResource);
+                    }
+                    return;
                 }
             };
 
-            *resource = updated;
+            for incoming in updates {
+                apply_update(id, &options, &connection, &mut *resource, incoming);
+            }
+        });
+    }
+}
+
+/// Applies a single update from the editor to the resource.
+fn apply_update<T>(
+    id: &'static str,
+    options: &TypeOptions,
+    connection: &EditorConnection,
+    resource: &mut T,
+    mut incoming: IncomingResource,
+) where
+    T: Serialize + DeserializeOwned,
+{
+    debug!("Got incoming message for {}: {:?}", id, incoming);
+
+    if options.redaction.is_some() || options.mask.is_some() {
+        if let Ok(current) = serde_json::to_value(&*resource) {
+            incoming.data = options.prepare_update(&current, incoming.data);
         }
     }
+
+    let updated = match serde_json::from_value(incoming.data) {
+        Ok(updated) => updated,
+        Err(error) => {
+            debug!("Failed to deserialize update for {}: {:?}", id, error);
+            connection.send_message(
+                "error",
+                WriteError {
+                    id,
+                    entity: None,
+                    error: error.to_string(),
+                },
+            );
+            connection.send_ack(incoming.request_id, EditStatus::InvalidData);
+            return;
+        }
+    };
+
+    *resource = updated;
+    connection.send_ack(incoming.request_id, EditStatus::Applied);
 }
@@ -0,0 +1,67 @@
+use amethyst::core::timing::Time;
+
+/// Timing information included in each state snapshot as the `"time"` section.
+///
+/// Amethyst runs fixed updates zero or more times per frame, accumulating frame time until a full
+/// fixed step has elapsed. This lets the editor tell how far the fixed-step simulation has
+/// advanced, and interpolate data that's only updated in fixed updates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimingInfo {
+    /// The number of frames since the game started.
+    pub frame: u64,
+
+    /// The (scaled) duration of the last frame in seconds.
+    pub delta_seconds: f32,
+
+    /// The duration of each fixed step in seconds.
+    pub fixed_seconds: f32,
+
+    /// The number of fixed steps that have run since the game started.
+    pub fixed_step: u64,
+
+    /// How far the game is into the next fixed step, between 0 and 1.
+    pub interpolation_alpha: f32,
+}
+
+impl TimingInfo {
+    pub(crate) fn from_time(time: &Time) -> TimingInfo {
+        let (fixed_step, interpolation_alpha) =
+            fixed_steps(time.absolute_time_seconds(), time.fixed_seconds());
+        TimingInfo {
+            frame: time.frame_number(),
+            delta_seconds: time.delta_seconds(),
+            fixed_seconds: time.fixed_seconds(),
+            fixed_step,
+            interpolation_alpha,
+        }
+    }
+}
+
+/// Calculates the number of fixed steps that have run after `elapsed` seconds of (scaled) game
+/// time, along with the fraction of the next step that has elapsed.
+///
+/// NOTE: Amethyst doesn't expose its fixed-step accumulator, but since it accumulates the same
+/// scaled frame time that's added to the absolute time, the number of steps can be derived from
+/// the absolute time instead.
+fn fixed_steps(elapsed: f64, fixed_seconds: f32) -> (u64, f32) {
+    let fixed_seconds = f64::from(fixed_seconds);
+    if fixed_seconds <= 0.0 || elapsed <= 0.0 {
+        return (0, 0.0);
+    }
+
+    let steps = (elapsed / fixed_seconds).floor();
+    let alpha = (elapsed - steps * fixed_seconds) / fixed_seconds;
+    (steps as u64, alpha.max(0.0).min(1.0) as f32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixed_steps_and_alpha() {
+        assert_eq!(fixed_steps(0.0, 0.5), (0, 0.0));
+        assert_eq!(fixed_steps(1.25, 0.5), (2, 0.5));
+        assert_eq!(fixed_steps(1.0, 0.0), (0, 0.0));
+    }
+}
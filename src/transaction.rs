@@ -0,0 +1,193 @@
+//! Tracks the operations of transactions sent by the editor, so that the outcome of each
+//! operation can be reported together once the whole transaction has been handled.
+//!
+//! Each operation is handled by whichever system normally handles its message, and acknowledged
+//! the usual way through [`EditorConnection::send_ack`]. Acknowledgements of operations are
+//! collected here instead of being sent, and once every operation of a transaction has been
+//! acknowledged, a single `"transaction_ack"` message is sent in their place.
+//!
+//! [`EditorConnection::send_ack`]: ../types/struct.EditorConnection.html#method.send_ack
+
+use crate::types::EditStatus;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// The request IDs given to operations that the editor didn't give one, which are taken from
+/// the top of the range so that they don't collide with the editor's own.
+const GENERATED_ID_BASE: u64 = 1 << 63;
+
+/// The most transactions waiting for their operations to be acknowledged. Beyond this, the
+/// oldest transaction is dropped, so that operations that are never acknowledged can't leak.
+const MAX_PENDING: usize = 256;
+
+/// Sent to the editor as a `"transaction_ack"` message once all operations of a transaction
+/// have been handled.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TransactionAck {
+    pub request_id: u64,
+
+    /// The outcome of each operation, in the order they were sent.
+    pub results: Vec<EditStatus>,
+}
+
+/// The result of recording the outcome of an operation.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Recorded {
+    /// The request ID doesn't belong to an operation of a transaction.
+    NotTracked,
+
+    /// Other operations of the transaction are still being handled.
+    Pending,
+
+    /// This was the last operation of the transaction. Contains the acknowledgement for the
+    /// transaction, if the editor asked for one.
+    Complete(Option<TransactionAck>),
+}
+
+struct Pending {
+    key: u64,
+    request_id: Option<u64>,
+    results: Vec<Option<EditStatus>>,
+    remaining: usize,
+}
+
+#[derive(Default)]
+struct State {
+    next_key: u64,
+    next_generated_id: u64,
+    pending: VecDeque<Pending>,
+
+    // The transaction and position of each operation that hasn't been acknowledged yet.
+    operations: HashMap<u64, (u64, usize)>,
+}
+
+/// The transactions whose operations are still being handled, shared by all clones of an
+/// [`EditorConnection`].
+///
+/// [`EditorConnection`]: ./struct.EditorConnection.html
+#[derive(Clone, Default)]
+pub(crate) struct Transactions {
+    state: Arc<Mutex<State>>,
+}
+
+impl Transactions {
+    /// Generates a request ID for an operation that the editor didn't give one.
+    pub(crate) fn generate_id(&self) -> u64 {
+        let mut state = self.state.lock().expect("Transactions poisoned");
+        let id = GENERATED_ID_BASE + state.next_generated_id;
+        state.next_generated_id = (state.next_generated_id + 1) % GENERATED_ID_BASE;
+        id
+    }
+
+    /// Starts tracking a transaction whose operations have the given request IDs.
+    ///
+    /// Returns the acknowledgement right away if the transaction has no operations.
+    pub(crate) fn begin(
+        &self,
+        request_id: Option<u64>,
+        operations: &[u64],
+    ) -> Option<TransactionAck> {
+        if operations.is_empty() {
+            return request_id.map(|request_id| TransactionAck {
+                request_id,
+                results: Vec::new(),
+            });
+        }
+
+        let mut state = self.state.lock().expect("Transactions poisoned");
+        if state.pending.len() == MAX_PENDING {
+            if let Some(dropped) = state.pending.pop_front() {
+                warn!(
+                    "Too many transactions are waiting, dropping transaction {:?}",
+                    dropped.request_id
+                );
+                state
+                    .operations
+                    .retain(|_, &mut (key, _)| key != dropped.key);
+            }
+        }
+
+        let key = state.next_key;
+        state.next_key += 1;
+        for (index, &id) in operations.iter().enumerate() {
+            state.operations.insert(id, (key, index));
+        }
+        state.pending.push_back(Pending {
+            key,
+            request_id,
+            results: vec![None; operations.len()],
+            remaining: operations.len(),
+        });
+        None
+    }
+
+    /// Records the outcome of the operation with the given request ID, if it's part of a
+    /// transaction.
+    pub(crate) fn record(&self, request_id: u64, status: EditStatus) -> Recorded {
+        let mut state = self.state.lock().expect("Transactions poisoned");
+        let (key, index) = match state.operations.remove(&request_id) {
+            Some(operation) => operation,
+            None => return Recorded::NotTracked,
+        };
+
+        let position = match state.pending.iter().position(|pending| pending.key == key) {
+            Some(position) => position,
+            None => return Recorded::Pending,
+        };
+        {
+            let pending = &mut state.pending[position];
+            pending.results[index] = Some(status);
+            pending.remaining -= 1;
+            if pending.remaining > 0 {
+                return Recorded::Pending;
+            }
+        }
+
+        let done = state
+            .pending
+            .remove(position)
+            .expect("Pending transaction disappeared");
+        Recorded::Complete(done.request_id.map(|request_id| {
+            TransactionAck {
+                request_id,
+                results: done
+                    .results
+                    .into_iter()
+                    .map(|result| result.expect("Operation not acknowledged"))
+                    .collect(),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn completes_once_all_operations_are_acknowledged() {
+        let transactions = Transactions::default();
+        let generated = transactions.generate_id();
+        assert!(transactions.begin(Some(7), &[3, generated]).is_none());
+
+        assert_eq!(
+            transactions.record(4, EditStatus::Applied),
+            Recorded::NotTracked
+        );
+        assert_eq!(
+            transactions.record(generated, EditStatus::StaleEntity),
+            Recorded::Pending
+        );
+        assert_eq!(
+            transactions.record(3, EditStatus::Applied),
+            Recorded::Complete(Some(TransactionAck {
+                request_id: 7,
+                results: vec![EditStatus::Applied, EditStatus::StaleEntity],
+            }))
+        );
+        assert_eq!(
+            transactions.record(3, EditStatus::Applied),
+            Recorded::NotTracked
+        );
+    }
+}
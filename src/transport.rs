@@ -0,0 +1,707 @@
+#[cfg(feature = "encryption")]
+use crate::encryption::{self, Cipher, Role};
+use crate::framing::Framing;
+use crate::ipc::IpcStream;
+use rand::Rng;
+use socket2::Socket;
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The most data an IPC transport buffers while the editor isn't reading, in bytes. Beyond this,
+/// sending fails as if the OS send buffer was full.
+const MAX_UNSENT_BYTES: usize = 1024 * 1024;
+
+/// How long to wait, in milliseconds, before the first retry when the OS send buffer is full. The
+/// delay doubles with each failed retry.
+pub(crate) const INITIAL_RETRY_DELAY_MS: u64 = 1;
+
+/// The number of times sending is retried before the packets being sent are dropped.
+pub(crate) const MAX_SEND_RETRIES: u32 = 5;
+
+/// The connection used to exchange data with the editor.
+///
+/// By default [`SyncEditorBundle`] creates a UDP transport bound to the configured bind
+/// address. You can create a transport manually in order to configure it further, and then
+/// pass it to [`SyncEditorBundle::transport`].
+///
+/// [`SyncEditorBundle`]: ./struct.SyncEditorBundle.html
+/// [`SyncEditorBundle::transport`]: ./struct.SyncEditorBundle.html#method.transport
+pub struct Transport {
+    connection: Connection,
+    framing: Framing,
+    conditioner: Option<Conditioner>,
+    #[cfg(feature = "encryption")]
+    cipher: Option<Cipher>,
+}
+
+enum Connection {
+    Udp {
+        socket: UdpSocket,
+        editor_address: SocketAddr,
+    },
+
+    Ipc {
+        stream: IpcStream,
+
+        // Data that couldn't be written yet because the editor isn't keeping up. Since the
+        // stream has no packet boundaries, a packet that was partially written has to be
+        // finished before anything else is sent.
+        unsent: Vec<u8>,
+
+        // Whether the editor has closed the connection.
+        closed: bool,
+    },
+}
+
+impl Transport {
+    /// Creates a UDP transport bound to `bind_address`.
+    ///
+    /// Format should be `address:port`, e.g. `127.0.0.1:0` to bind to any free local port.
+    pub fn udp(bind_address: &str) -> io::Result<Transport> {
+        let socket = UdpSocket::bind(bind_address)?;
+
+        // NOTE: We set the socket to nonblocking so that we don't block if there are no incoming
+        // messages to read. The game will hang if the socket is still set to block when the
+        // game runs.
+        socket.set_nonblocking(true)?;
+
+        Ok(Transport {
+            connection: Connection::Udp {
+                socket,
+                editor_address: ([127, 0, 0, 1], 8000).into(),
+            },
+            framing: Framing::default(),
+            conditioner: None,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        })
+    }
+
+    /// Creates a transport connected to an editor running on the same machine, through the
+    /// Unix domain socket (on Linux and macOS) or named pipe (on Windows) at `path`.
+    ///
+    /// This is the kind of connection that node-ipc servers listen on, e.g.
+    /// `/tmp/app.amethyst-editor` or `\\.\pipe\/tmp/app.amethyst-editor`. Unlike UDP, no data
+    /// is lost between the game and the editor. The editor has to be running when the transport
+    /// is created, and once the editor closes the connection nothing more is sent or received.
+    ///
+    /// Editors built on node-ipc expect its delimiter, so pass [`Framing::NodeIpc`] to
+    /// [`with_framing`] when connecting to one.
+    ///
+    /// [`Framing::NodeIpc`]: ./enum.Framing.html#variant.NodeIpc
+    /// [`with_framing`]: #method.with_framing
+    pub fn ipc<P: AsRef<Path>>(path: P) -> io::Result<Transport> {
+        let stream = IpcStream::connect(path.as_ref())?;
+        Ok(Transport {
+            connection: Connection::Ipc {
+                stream,
+                unsent: Vec::new(),
+                closed: false,
+            },
+            framing: Framing::default(),
+            conditioner: None,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        })
+    }
+
+    /// Sets the address of the editor that messages are sent to.
+    ///
+    /// Defaults to `127.0.0.1:8000`. Packets received from any other address are ignored. Has
+    /// no effect on IPC transports.
+    pub fn with_editor_address(mut self, address: SocketAddr) -> Self {
+        if let Connection::Udp {
+            ref mut editor_address,
+            ..
+        } = self.connection
+        {
+            *editor_address = address;
+        }
+        self
+    }
+
+    /// Sets how messages are delimited, for both the messages sent to the editor and the
+    /// messages received from it.
+    ///
+    /// Defaults to [`Framing::LengthPrefixed`]. Editors that still delimit messages the way
+    /// node-ipc does need [`Framing::NodeIpc`].
+    ///
+    /// [`Framing::LengthPrefixed`]: ./enum.Framing.html#variant.LengthPrefixed
+    /// [`Framing::NodeIpc`]: ./enum.Framing.html#variant.NodeIpc
+    pub fn with_framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Sets the sizes of the socket's OS send and receive buffers, in bytes.
+    ///
+    /// Large state snapshots are sent as a burst of packets, which can overflow the default
+    /// send buffer on some platforms. Increasing the send buffer size reduces how often sending
+    /// has to be retried or packets dropped. Note that the OS may round the requested sizes or
+    /// clamp them to a system-wide limit.
+    ///
+    /// Only UDP transports have configurable buffers; this returns an error for IPC transports.
+    pub fn with_buffer_sizes(self, send_size: usize, recv_size: usize) -> io::Result<Self> {
+        match self.connection {
+            Connection::Udp { ref socket, .. } => {
+                // NOTE: The buffer sizes belong to the underlying socket, so setting them through
+                // a duplicate handle applies them to our socket too.
+                let socket = Socket::from(socket.try_clone()?);
+                socket.set_send_buffer_size(send_size)?;
+                socket.set_recv_buffer_size(recv_size)?;
+            }
+
+            Connection::Ipc { .. } => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "buffer sizes can only be set for UDP transports",
+                ));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Simulates a bad network connection by dropping and delaying packets.
+    ///
+    /// Each packet sent or received is dropped with probability `loss` (between 0 and 1), and
+    /// the remaining packets are delayed by `latency`, plus or minus a random amount up to
+    /// `jitter`. Since each packet gets its own delay, jitter will also cause packets to be
+    /// reordered.
+    ///
+    /// This is intended for testing how robust the game and the editor are against unreliable
+    /// networks, and shouldn't be enabled otherwise.
+    pub fn with_conditioning(mut self, loss: f32, latency: Duration, jitter: Duration) -> Self {
+        self.conditioner = Some(Conditioner {
+            loss,
+            latency,
+            jitter,
+            queue: VecDeque::new(),
+        });
+        self
+    }
+
+    /// Encrypts all packets exchanged with the editor with `key`, which the editor has to be
+    /// configured with as well, so that component data and commands aren't sent in plaintext
+    /// over untrusted networks.
+    ///
+    /// Each packet is encrypted separately with ChaCha20-Poly1305, using a separate key for each
+    /// direction derived from `key` with HMAC-SHA256 over the labels
+    /// `amethyst-editor-sync game to editor` and `amethyst-editor-sync editor to game`. A packet
+    /// is sent as an 8 byte big-endian sequence number, a random 12 byte nonce, the ciphertext
+    /// and the 16 byte authentication tag, with the sequence number as associated data.
+    ///
+    /// Packets received from the editor have to be encrypted the same way. Any packet that fails
+    /// to decrypt is dropped, as is any packet whose sequence number was already received or is
+    /// more than 64 behind the newest one, so that recorded packets can't be replayed. Sequence
+    /// numbers should start from the current time in microseconds, so that they keep increasing
+    /// across restarts. The key should be generated randomly and shared with the editor out of
+    /// band, e.g. through a file on the devkit. [`FakeEditor::with_encryption`] implements the
+    /// editor's side.
+    ///
+    /// [`FakeEditor::with_encryption`]: ./struct.FakeEditor.html#method.with_encryption
+    ///
+    /// Only UDP transports can be encrypted, since IPC connections never leave the machine;
+    /// this returns an error for IPC transports.
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption(mut self, key: [u8; 32]) -> io::Result<Self> {
+        if let Connection::Ipc { .. } = self.connection {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "only UDP transports can be encrypted",
+            ));
+        }
+
+        self.cipher = Some(Cipher::new(key, Role::Game));
+        Ok(self)
+    }
+
+    /// Returns the local address that the transport is bound to.
+    ///
+    /// This is the address that the editor needs to send messages to. IPC transports don't have
+    /// an address, and return an error.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self.connection {
+            Connection::Udp { ref socket, .. } => socket.local_addr(),
+            Connection::Ipc { .. } => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "IPC transports don't have a network address",
+            )),
+        }
+    }
+
+    /// Creates a new handle to the same underlying connection.
+    ///
+    /// Conditioning settings are copied, but each handle delays its packets independently.
+    pub(crate) fn try_clone(&self) -> io::Result<Transport> {
+        let connection = match self.connection {
+            Connection::Udp {
+                ref socket,
+                editor_address,
+            } => Connection::Udp {
+                socket: socket.try_clone()?,
+                editor_address,
+            },
+
+            Connection::Ipc {
+                ref stream, closed, ..
+            } => Connection::Ipc {
+                stream: stream.try_clone()?,
+                unsent: Vec::new(),
+                closed,
+            },
+        };
+
+        Ok(Transport {
+            connection,
+            framing: self.framing,
+            conditioner: self.conditioner.as_ref().map(|conditioner| Conditioner {
+                loss: conditioner.loss,
+                latency: conditioner.latency,
+                jitter: conditioner.jitter,
+                queue: VecDeque::new(),
+            }),
+            #[cfg(feature = "encryption")]
+            cipher: self.cipher.as_ref().map(Cipher::try_clone),
+        })
+    }
+
+    /// The address that messages to the editor are sent to, or `None` for IPC transports.
+    pub(crate) fn editor_address(&self) -> Option<SocketAddr> {
+        match self.connection {
+            Connection::Udp { editor_address, .. } => Some(editor_address),
+            Connection::Ipc { .. } => None,
+        }
+    }
+
+    /// How messages are delimited.
+    pub(crate) fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Whether packets exchanged with the editor start with chunk headers, unless configured
+    /// otherwise.
+    ///
+    /// Packets on IPC transports have no boundaries to put headers at, and editors that use
+    /// node-ipc's framing predate chunk headers.
+    pub(crate) fn uses_chunk_headers(&self) -> bool {
+        match self.connection {
+            Connection::Udp { .. } => self.framing != Framing::NodeIpc,
+            Connection::Ipc { .. } => false,
+        }
+    }
+
+    /// The number of bytes added to each packet by the transport, which has to be left free
+    /// when splitting messages into packets.
+    #[cfg(feature = "encryption")]
+    pub(crate) fn packet_overhead(&self) -> usize {
+        match self.cipher {
+            Some(_) => encryption::OVERHEAD,
+            None => 0,
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    pub(crate) fn packet_overhead(&self) -> usize {
+        0
+    }
+
+    /// Sends a single packet to the editor.
+    pub(crate) fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let sealed = self.seal(bytes)?;
+        let bytes = sealed.as_ref().map(Vec::as_slice).unwrap_or(bytes);
+        match self.conditioner {
+            Some(ref mut conditioner) => {
+                conditioner.push(bytes);
+                self.flush()
+            }
+
+            None => self.connection.send(bytes),
+        }
+    }
+
+    /// Sends a single packet to the editor, waiting up to `timeout` for the OS to accept all of
+    /// it, for messages that have to go out before the game exits.
+    ///
+    /// Conditioning is bypassed, since delayed packets would never be sent.
+    pub(crate) fn send_blocking(&mut self, bytes: &[u8], timeout: Duration) -> io::Result<()> {
+        let sealed = self.seal(bytes)?;
+        let bytes = sealed.as_ref().map(Vec::as_slice).unwrap_or(bytes);
+        let deadline = Instant::now() + timeout;
+        let mut queued = false;
+        loop {
+            let result = if queued {
+                self.connection.write_unsent()
+            } else {
+                self.connection.send(bytes)
+            };
+            match result {
+                Ok(()) => {
+                    queued = true;
+                    if self.connection.is_flushed() {
+                        return Ok(());
+                    }
+                }
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => {}
+                Err(error) => return Err(error),
+            }
+
+            if Instant::now() >= deadline {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Sends any delayed packets whose simulated latency has elapsed.
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        if let Some(ref mut conditioner) = self.conditioner {
+            while let Some(bytes) = conditioner.pop_ready() {
+                self.connection.send(&bytes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The time at which the next packet delayed to simulate latency is due to be sent, or
+    /// `None` if there are no delayed packets.
+    pub(crate) fn next_delayed(&self) -> Option<Instant> {
+        self.conditioner
+            .as_ref()
+            .and_then(|conditioner| conditioner.queue.front())
+            .map(|&(deliver_at, _)| deliver_at)
+    }
+
+    /// Receives a single packet from the editor, returning the number of bytes read.
+    ///
+    /// Behaves like `UdpSocket::recv` on a nonblocking socket, returning an error of kind
+    /// `WouldBlock` if no packet is available. Packets from anywhere other than the editor are
+    /// ignored, as are packets that fail to decrypt if the transport is encrypted.
+    pub(crate) fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let bytes_read = self.recv_packet(buf)?;
+            match self.open(&mut buf[..bytes_read]) {
+                Some(len) => return Ok(len),
+                None => debug!("Dropping packet that failed to decrypt"),
+            }
+        }
+    }
+
+    /// Encrypts a packet before it's sent, returning `None` if the transport isn't encrypted.
+    #[cfg(feature = "encryption")]
+    fn seal(&self, bytes: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        match self.cipher {
+            Some(ref cipher) => cipher.seal(bytes).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn seal(&self, _bytes: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Decrypts a received packet in place, returning the length of the decrypted data or
+    /// `None` if the packet should be dropped.
+    #[cfg(feature = "encryption")]
+    fn open(&self, packet: &mut [u8]) -> Option<usize> {
+        match self.cipher {
+            Some(ref cipher) => cipher.open(packet),
+            None => Some(packet.len()),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn open(&self, packet: &mut [u8]) -> Option<usize> {
+        Some(packet.len())
+    }
+
+    fn recv_packet(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let conditioner = match self.conditioner {
+            Some(ref mut conditioner) => conditioner,
+            None => return self.connection.recv(buf),
+        };
+
+        // Move all pending packets into the delay queue before checking for packets that are
+        // ready to be delivered.
+        loop {
+            match self.connection.recv(buf) {
+                Ok(bytes_read) => conditioner.push(&buf[..bytes_read]),
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        match conditioner.pop_ready() {
+            Some(bytes) => {
+                let bytes_read = bytes.len().min(buf.len());
+                buf[..bytes_read].copy_from_slice(&bytes[..bytes_read]);
+                Ok(bytes_read)
+            }
+
+            None => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl Connection {
+    fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match *self {
+            Connection::Udp {
+                ref socket,
+                editor_address,
+            } => socket.send_to(bytes, editor_address).map(|_| ()),
+
+            Connection::Ipc {
+                ref mut stream,
+                ref mut unsent,
+                ..
+            } => {
+                if unsent.len() >= MAX_UNSENT_BYTES {
+                    write_unsent(stream, unsent)?;
+                    if unsent.len() >= MAX_UNSENT_BYTES {
+                        return Err(io::ErrorKind::WouldBlock.into());
+                    }
+                }
+
+                unsent.extend_from_slice(bytes);
+                write_unsent(stream, unsent)
+            }
+        }
+    }
+
+    /// Writes data that an IPC stream couldn't take yet.
+    fn write_unsent(&mut self) -> io::Result<()> {
+        match *self {
+            Connection::Udp { .. } => Ok(()),
+            Connection::Ipc {
+                ref mut stream,
+                ref mut unsent,
+                ..
+            } => write_unsent(stream, unsent),
+        }
+    }
+
+    /// Whether all data has been handed over to the OS.
+    fn is_flushed(&self) -> bool {
+        match *self {
+            Connection::Udp { .. } => true,
+            Connection::Ipc { ref unsent, .. } => unsent.is_empty(),
+        }
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            Connection::Udp {
+                ref socket,
+                editor_address,
+            } => loop {
+                let (bytes_read, address) = match socket.recv_from(buf) {
+                    Ok(received) => received,
+                    Err(ref error) if is_truncated(error) => {
+                        warn_truncated(buf.len());
+                        continue;
+                    }
+                    Err(error) => return Err(error),
+                };
+                if address != editor_address {
+                    trace!("Packet received from unknown address {:?}", address);
+                    continue;
+                }
+
+                // NOTE: The OS silently cuts off the part of a datagram that doesn't fit in the
+                // buffer, so a packet that fills the buffer may have been truncated. What's left
+                // of it would corrupt the message it belongs to, so it's dropped instead.
+                if bytes_read == buf.len() {
+                    warn_truncated(buf.len());
+                    continue;
+                }
+
+                return Ok(bytes_read);
+            },
+
+            Connection::Ipc {
+                ref mut stream,
+                ref mut closed,
+                ..
+            } => {
+                if *closed {
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+
+                match stream.read(buf) {
+                    // NOTE: Reading nothing means that the editor closed the connection. We
+                    // report that as there being no data, since nothing more will arrive.
+                    Ok(0) => {
+                        warn!("The editor closed the IPC connection");
+                        *closed = true;
+                        Err(io::ErrorKind::WouldBlock.into())
+                    }
+
+                    result => result,
+                }
+            }
+        }
+    }
+}
+
+/// Writes as much of `unsent` to `stream` as it will take without blocking.
+fn write_unsent(stream: &mut IpcStream, unsent: &mut Vec<u8>) -> io::Result<()> {
+    while !unsent.is_empty() {
+        match stream.write(unsent) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(bytes_written) => {
+                unsent.drain(..bytes_written);
+            }
+            Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => break,
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(())
+}
+
+/// Simulated network conditions applied to packets passing through a [`Transport`].
+struct Conditioner {
+    loss: f32,
+    latency: Duration,
+    jitter: Duration,
+
+    // Delayed packets, along with the time at which they should be delivered.
+    queue: VecDeque<(Instant, Vec<u8>)>,
+}
+
+impl Conditioner {
+    fn push(&mut self, bytes: &[u8]) {
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f32>() < self.loss {
+            trace!("Dropping packet of {} bytes", bytes.len());
+            return;
+        }
+
+        let latency = duration_to_secs(self.latency);
+        let jitter = duration_to_secs(self.jitter);
+        let offset = if jitter > 0.0 {
+            rng.gen_range(-jitter, jitter)
+        } else {
+            0.0
+        };
+        let delay = secs_to_duration((latency + offset).max(0.0));
+
+        // Keep the queue sorted by delivery time so that jittered packets get reordered.
+        let deliver_at = Instant::now() + delay;
+        let index = self
+            .queue
+            .iter()
+            .position(|&(time, _)| time > deliver_at)
+            .unwrap_or_else(|| self.queue.len());
+        self.queue.insert(index, (deliver_at, bytes.to_owned()));
+    }
+
+    fn pop_ready(&mut self) -> Option<Vec<u8>> {
+        let ready = match self.queue.front() {
+            Some(&(deliver_at, _)) => deliver_at <= Instant::now(),
+            None => false,
+        };
+
+        if ready {
+            self.queue.pop_front().map(|(_, bytes)| bytes)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether sending may succeed if it's retried right away after failing with an error of kind
+/// `kind`, e.g. because the OS send buffer was full.
+pub(crate) fn is_transient(kind: io::ErrorKind) -> bool {
+    match kind {
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::TimedOut => true,
+        _ => false,
+    }
+}
+
+/// Whether sending failed with an error of kind `kind` because there's no editor listening,
+/// which is expected whenever the game runs without the editor.
+///
+/// NOTE: On some platforms, sending over UDP reports the ICMP "port unreachable" replies to
+/// earlier packets as errors of these kinds.
+pub(crate) fn is_no_editor(kind: io::ErrorKind) -> bool {
+    match kind {
+        io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::NotConnected => true,
+        _ => false,
+    }
+}
+
+/// Whether receiving failed because the datagram didn't fit in the buffer, which is reported as
+/// an error on Windows instead of truncating the datagram silently.
+#[cfg(windows)]
+fn is_truncated(error: &io::Error) -> bool {
+    // NOTE: `WSAEMSGSIZE`.
+    error.raw_os_error() == Some(10040)
+}
+
+#[cfg(not(windows))]
+fn is_truncated(_error: &io::Error) -> bool {
+    false
+}
+
+fn warn_truncated(buffer_size: usize) {
+    warn!(
+        "Dropping packet from the editor that doesn't fit in the {} byte receive buffer, see \
+         `SyncEditorBundle::receive_buffer_size`",
+        buffer_size
+    );
+}
+
+fn duration_to_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
+}
+
+fn secs_to_duration(secs: f64) -> Duration {
+    Duration::new(secs.trunc() as u64, (secs.fract() * 1e9) as u32)
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    #[test]
+    fn ipc_round_trip() {
+        let path = std::env::temp_dir().join("amethyst-editor-sync-ipc-test.sock");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let mut transport = Transport::ipc(&path).unwrap();
+        let (mut editor, _) = listener.accept().unwrap();
+
+        transport.send(b"hello").unwrap();
+        let mut buf = [0; 16];
+        let bytes_read = editor.read(&mut buf).unwrap();
+        assert_eq!(&buf[..bytes_read], b"hello");
+
+        editor.write_all(b"hi").unwrap();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(transport.recv(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"hi");
+
+        // Once the editor disconnects, there's just nothing left to read.
+        drop(editor);
+        thread::sleep(Duration::from_millis(10));
+        let error = transport.recv(&mut buf).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,12 +1,27 @@
-use amethyst::ecs::Entity;
+use amethyst::ecs::{BitSet, Entity};
 use crossbeam_channel::Sender;
+use crate::capabilities::{Capability, SharedCapabilities};
+use crate::connection_status::EditorPresence;
+use crate::debug_draw::{DebugDrawBuffer, DebugShape};
+use crate::interest::InterestFilter;
+use crate::issues::{Issue, IssueBuffer, Severity};
 use serde::Serialize;
-use crate::serializable_entity::DeserializableEntity;
+use crate::message_schema::MessageSchemas;
+use crate::pagination::{ComponentPage, ComponentPages};
+use crate::panic_hook::{CrashReporter, PanicReport};
+use crate::query::{FieldCondition, JsonPathExpr};
+use crate::serializable_entity::{IncomingEntity, SerializableEntity};
+use crate::subscriptions::{Subscriptions, SyncLevel};
+use crate::sync_strategy::SyncSchedule;
+use crate::transaction::{Recorded, TransactionAck, Transactions};
+use serde_json::value::RawValue;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 pub(crate) type ChannelMap<T> = HashMap<&'static str, Sender<T>>;
 pub(crate) type ComponentMap = ChannelMap<IncomingComponent>;
-pub(crate) type ResourceMap = ChannelMap<serde_json::Value>;
+pub(crate) type ResourceMap = ChannelMap<IncomingResource>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Message<T> {
@@ -15,57 +30,608 @@ pub(crate) struct Message<T> {
     data: T,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub(crate) struct SerializedComponent<'a, T: 'a> {
     pub name: &'static str,
-    pub data: HashMap<u32, &'a T>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<&'static str>,
+
+    /// The components along with the entities they're attached to, sorted by entity ID so
+    /// that identical state always serializes to the same string.
+    pub data: &'a [ComponentEntry<T>],
+
+    /// The page that `data` is, if component data is paginated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<ComponentPage>,
+}
+
+/// A component sent to the editor, along with the entity it's attached to.
+///
+/// The entity's generation is included so that the editor can tell the components of an entity
+/// apart from those of an earlier entity with the same ID.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ComponentEntry<T> {
+    pub entity: SerializableEntity,
+    pub data: T,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct SerializedResource<'a, T: 'a> {
     pub name: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<&'static str>,
+
     pub data: &'a T,
 }
 
+/// Error reported to the editor when an update it sent couldn't be applied.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct WriteError {
+    /// The registered name of the component or resource that failed to update.
+    pub id: &'static str,
+
+    /// The entity that the update targeted, if the update was for a component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity: Option<SerializableEntity>,
+
+    /// The error that occurred while applying the update.
+    pub error: String,
+}
+
+/// A problem with a registered type, reported to the editor as a `"diagnostic"` message.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Diagnostic {
+    /// The type's data was nested too deeply, and was truncated before being sent.
+    DepthTruncated {
+        name: &'static str,
+        max_depth: usize,
+    },
+
+    /// The editor sent an update for a component that isn't registered as editable.
+    UnknownComponent {
+        id: String,
+        known: Vec<&'static str>,
+    },
+
+    /// The editor sent an update for a resource that isn't registered as editable.
+    UnknownResource {
+        id: String,
+        known: Vec<&'static str>,
+    },
+}
+
+/// Acknowledgement sent to the editor once an edit it requested has been handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditAck {
+    pub request_id: u64,
+    pub status: EditStatus,
+}
+
+/// The outcome of an edit requested by the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditStatus {
+    /// The edit was applied to the world.
+    Applied,
+
+    /// The targeted entity no longer exists, or has been replaced by a newer generation.
+    StaleEntity,
+
+    /// No component or resource is registered with the given ID.
+    UnknownType,
+
+    /// The targeted entity doesn't have the component being edited.
+    MissingComponent,
+
+    /// The resource being edited hasn't been added to the world.
+    MissingResource,
+
+    /// The data sent by the editor couldn't be deserialized, or is out of range.
+    InvalidData,
+
+    /// The requested frame is no longer in the history, or was never recorded.
+    MissingFrame,
+
+    /// The message can't be sent as an operation of a transaction.
+    Unsupported,
+
+    /// The request went over the limits set on the editor's entity commands.
+    LimitExceeded,
+
+    /// The token sent in an `Authenticate` message doesn't match the one the game expects.
+    Unauthorized,
+
+    /// The request was valid, but the game failed to carry it out, e.g. because a file
+    /// couldn't be written.
+    Failed,
+}
+
+/// Data serialized for the editor, which the sender embeds into packets as is.
 pub enum SerializedData {
-    Resource(String),
-    Component(String),
-    Message(String),
+    Resource(&'static str, Box<RawValue>),
+
+    /// Serialized components of a type, along with the number of components serialized and the
+    /// IDs of the entities that have the component.
+    Component(&'static str, Box<RawValue>, usize, BitSet),
+    Message(Box<RawValue>),
+
+    /// A section of the snapshot with its own top-level key, e.g. `"physics"`, holding serialized
+    /// JSON. Only the latest data for each section is sent.
+    Section(&'static str, Box<RawValue>),
+
+    /// A `"log"` message, which the sender may hold back to avoid adding to a frame that's
+    /// already sending a state snapshot.
+    Log(Box<RawValue>),
 }
 
 pub enum EntityMessage {
-    Create(usize),
-    Destroy(Vec<u32>),
+    /// Create the given number of entities, assigning them the editor's temporary IDs in order.
+    Create(usize, Vec<u64>, Option<u64>),
+    Destroy(Vec<u32>, Option<u64>),
 }
 
 /// Messages sent from the editor to the game.
-#[derive(Debug, Clone, Deserialize)]
+///
+/// Messages that modify the world may include a `request_id`, in which case an `"edit_ack"`
+/// message is sent back once the edit has been handled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum IncomingMessage {
     ComponentUpdate {
         id: String,
-        entity: DeserializableEntity,
+        entity: IncomingEntity,
         data: serde_json::Value,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Update only the given fields of a component, leaving any other fields unchanged.
+    ///
+    /// Nested objects are merged field by field, while any other value replaces the current
+    /// value of the field.
+    ComponentPatch {
+        id: String,
+        entity: IncomingEntity,
+        data: serde_json::Value,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Apply the same update to the component of every entity in `entities`, e.g. to give all
+    /// selected entities the same scale. With `patch` set, only the given fields are changed,
+    /// as with `ComponentPatch`.
+    ///
+    /// The update is applied atomically: if any of the entities is stale or doesn't have the
+    /// component, none of them are updated.
+    ComponentUpdateBatch {
+        id: String,
+        entities: Vec<IncomingEntity>,
+        data: serde_json::Value,
+        #[serde(default)]
+        patch: bool,
+        #[serde(default)]
+        request_id: Option<u64>,
     },
 
     ResourceUpdate {
         id: String,
         data: serde_json::Value,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Request the chunks with the given IDs of a resource registered with
+    /// `sync_chunked_resource`, replacing any chunks of the resource requested before. The
+    /// requested chunks are sent as `"resource_chunks"` messages whenever they change.
+    RequestChunks {
+        resource: String,
+        ids: Vec<serde_json::Value>,
+        #[serde(default)]
+        request_id: Option<u64>,
     },
 
+    /// Create `amount` new entities, which are sent back in an `"entities_created"` message.
+    ///
+    /// The editor may give the new entities temporary IDs, in order, which can be used to
+    /// address them in later messages before the editor has learned their actual IDs.
     CreateEntities {
         amount: usize,
+        #[serde(default)]
+        temporary_ids: Vec<u64>,
+        #[serde(default)]
+        request_id: Option<u64>,
     },
 
     DestroyEntities {
-        entities: Vec<DeserializableEntity>,
+        entities: Vec<IncomingEntity>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Create a copy of each of `entities` with all of its editable components. The game
+    /// responds with an `"entities_cloned"` message listing the new entities.
+    CloneEntities {
+        entities: Vec<IncomingEntity>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Replace the entities selected in the `EditorSelection` resource. Entities that no longer
+    /// exist are left out of the selection.
+    SetSelection {
+        entities: Vec<IncomingEntity>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Bookmark `entity` as `name` in the `Bookmarks` resource, replacing any entity bookmarked
+    /// under that name, or remove the bookmark if `entity` is null.
+    BookmarkEntity {
+        name: String,
+        #[serde(default)]
+        entity: Option<IncomingEntity>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Export the registered components of `entities` to a prefab file at `path`, relative to
+    /// the directory the game allows scenes to be exported to.
+    ExportScene {
+        path: String,
+        entities: Vec<IncomingEntity>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Find the entities that have all of `with_components` and match `where_clause`. The game
+    /// responds with a `"query_result"` message listing the entities.
+    QueryEntities {
+        #[serde(default)]
+        with_components: Vec<String>,
+        #[serde(default)]
+        where_clause: Option<JsonPathExpr>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Find the entities that have registered components but none of the `anchors`
+    /// components, which usually means they were leaked. If `anchors` is omitted, entities with
+    /// a `Transform`, `Parent` or `Named` component are considered reachable. The game responds
+    /// with an `"orphans"` message listing the entities and their components.
+    FindOrphans {
+        #[serde(default)]
+        anchors: Option<Vec<String>>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Destroy those of `entities` that are still orphaned, as found by `FindOrphans` with the
+    /// same `anchors`. Entities that have become reachable since are kept.
+    PurgeEntities {
+        entities: Vec<IncomingEntity>,
+        #[serde(default)]
+        anchors: Option<Vec<String>>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Send page `page` of the components registered as `id`, counting from 0, instead of the
+    /// page sent so far. Only has an effect if component data is paginated.
+    RequestComponentPage {
+        id: String,
+        page: usize,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Send the value of the field at `path` of a component every frame, as part of a `"watch"`
+    /// message. Adding a watch with the same `id` as an existing watch replaces it.
+    AddWatch {
+        id: u64,
+        component: String,
+        entity: IncomingEntity,
+        #[serde(default)]
+        path: String,
+    },
+
+    /// Stop sending the value of a watch added with `AddWatch`.
+    RemoveWatch { id: u64 },
+
+    /// Hold the field at `path` of a component at `value`, writing it back at the end of every
+    /// frame if the game changes it. Pinning the same field again replaces its value.
+    PinComponentField {
+        entity: IncomingEntity,
+        component: String,
+        #[serde(default)]
+        path: String,
+        value: serde_json::Value,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Stop holding a field pinned with `PinComponentField`.
+    UnpinComponentField {
+        entity: IncomingEntity,
+        component: String,
+        #[serde(default)]
+        path: String,
+    },
+
+    /// Pause the game when a field of a component matches `condition`, checking either a single
+    /// entity or every entity with the component. Setting a breakpoint with the same `id` as an
+    /// existing breakpoint replaces it.
+    SetBreakpoint {
+        id: u64,
+        component: String,
+        #[serde(default)]
+        entity: Option<IncomingEntity>,
+        condition: FieldCondition,
+    },
+
+    /// Remove a breakpoint set with `SetBreakpoint`.
+    ClearBreakpoint { id: u64 },
+
+    /// Scale the speed at which game time passes, e.g. 0.5 for slow motion or 2 for fast
+    /// forward. A scale of 0 pauses the game.
+    SetTimeScale {
+        scale: f32,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Change the duration of each fixed update step, in seconds.
+    SetFixedTimestep {
+        seconds: f32,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Change how often the game state is sent, in milliseconds. An interval of 0 sends the state
+    /// every frame. Intervals longer than an hour are rejected as `InvalidData`.
+    SetSendInterval {
+        milliseconds: u64,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Limit how many bytes are sent to the editor each second, in order to test low-bandwidth
+    /// conditions, or remove the limit if `bytes_per_second` is `None`.
+    SetSendRateLimit {
+        #[serde(default)]
+        bytes_per_second: Option<u64>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Hot-reload the asset at `path`, along with any other assets whose files changed.
+    ReloadAsset {
+        path: String,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Change the volume of the game's `AudioSink`, where 1 is the original volume.
+    SetMasterVolume {
+        volume: f32,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Change the velocity of an entity's physics body as if `linear` and `angular` impulses
+    /// were applied at its center of mass.
+    #[cfg(feature = "physics")]
+    ApplyImpulse {
+        entity: IncomingEntity,
+        #[serde(default)]
+        linear: [f32; 3],
+        #[serde(default)]
+        angular: [f32; 3],
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Move an entity with a physics body to `position`.
+    #[cfg(feature = "physics")]
+    Teleport {
+        entity: IncomingEntity,
+        position: [f32; 3],
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Load the prefab at `path` and instantiate it.
+    SpawnPrefab {
+        path: String,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Move a transform gizmo, applied immediately and echoed back every frame.
+    TransformDrag {
+        entity: IncomingEntity,
+        #[serde(default)]
+        translation: Option<[f32; 3]>,
+        #[serde(default)]
+        rotation: Option<[f32; 4]>,
+        #[serde(default)]
+        scale: Option<[f32; 3]>,
+    },
+
+    /// Stop echoing the transform of an entity that was being dragged.
+    TransformDragEnd { entity: IncomingEntity },
+
+    /// Find the entity at a position on the screen, in pixels from the top left.
+    #[cfg(feature = "renderer")]
+    PickRequest {
+        screen_x: f32,
+        screen_y: f32,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Evaluate `input` with the game's console handler. The game responds with a
+    /// `"console_result"` message containing the handler's output.
+    Eval {
+        input: String,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Request that the game resend its handshake.
+    ///
+    /// The editor may list the capabilities it supports, in which case any optional features
+    /// that the editor doesn't support are disabled. Otherwise all features used by the game
+    /// stay enabled. If the game requires a token, the handshake starts a new session and is
+    /// only answered once the editor has sent an `Authenticate` message.
+    Handshake {
+        #[serde(default)]
+        capabilities: Option<Vec<String>>,
+    },
+
+    /// Authenticate the session by sending the token the game was configured with, if the game
+    /// requires one. Until then, all other messages apart from `Handshake` and `Heartbeat` are
+    /// ignored, and a `Handshake` that started the session is only answered afterwards.
+    Authenticate {
+        token: String,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Sent periodically by the editor when heartbeats are enabled, to let the game know that
+    /// the editor is still connected. Any other message from the editor has the same effect.
+    Heartbeat,
+
+    /// Store a setting for this game, or remove it if `value` is null.
+    SetEditorMeta {
+        key: String,
+        value: serde_json::Value,
+    },
+
+    /// Only send the listed sections of the game state to the editor, e.g.
+    /// `["components:Transform", "log", "profiler"]`, or everything again if `sections` is
+    /// omitted. Handshakes, acknowledgements and the entity list are always sent.
+    Subscribe {
+        #[serde(default)]
+        sections: Option<Vec<String>>,
+    },
+
+    /// Always sync the components of `entities`, even when they're outside of the interest
+    /// area, replacing the entities listed before. An empty list goes back to only syncing the
+    /// entities within the area.
+    ForceSync {
+        entities: Vec<IncomingEntity>,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Request the state recorded from `from_frame` to `to_frame`, inclusive, if history is
+    /// enabled. The game responds with a `"history"` message listing the recorded frames.
+    RequestHistory {
+        from_frame: u64,
+        to_frame: u64,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Restore the editable components and resources to the state recorded on `frame`, if
+    /// history is enabled.
+    RewindTo {
+        frame: u64,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Change how much of the game state is sent, e.g. `"minimal"` to only send the entity
+    /// count, logs and other messages.
+    SetSyncLevel {
+        level: SyncLevel,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Resume syncing the component or resource registered as `id` after it was turned off with
+    /// `DisableSync`.
+    EnableSync {
+        id: String,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Stop serializing and sending the component or resource registered as `id`, e.g. to turn
+    /// off expensive types that the editor doesn't need right now.
+    DisableSync {
+        id: String,
+        #[serde(default)]
+        request_id: Option<u64>,
+    },
+
+    /// Send the full game state again with the next snapshot, including anything that's
+    /// normally only sent when it changes, e.g. after the editor restarted and lost its state.
+    RequestFullSync,
+
+    /// Apply several edits together within the same frame, acknowledged with a single
+    /// `"transaction_ack"` message listing the outcome of each operation in order.
+    ///
+    /// Only edits to components, resources, and entities can be operations of a transaction.
+    /// Operations that fail don't undo the others. Entities created by the transaction can be
+    /// addressed by their temporary IDs in the operations that follow.
+    Transaction {
+        ops: Vec<IncomingMessage>,
+        #[serde(default)]
+        request_id: Option<u64>,
     },
 }
 
+impl IncomingMessage {
+    /// The request ID of a message that can be an operation of a transaction, or `None` if the
+    /// message can't be one.
+    pub(crate) fn operation_request_id(&mut self) -> Option<&mut Option<u64>> {
+        match *self {
+            IncomingMessage::ComponentUpdate {
+                ref mut request_id, ..
+            }
+            | IncomingMessage::ComponentPatch {
+                ref mut request_id, ..
+            }
+            | IncomingMessage::ComponentUpdateBatch {
+                ref mut request_id, ..
+            }
+            | IncomingMessage::ResourceUpdate {
+                ref mut request_id, ..
+            }
+            | IncomingMessage::CreateEntities {
+                ref mut request_id, ..
+            }
+            | IncomingMessage::DestroyEntities {
+                ref mut request_id, ..
+            }
+            | IncomingMessage::CloneEntities {
+                ref mut request_id, ..
+            } => Some(request_id),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct IncomingComponent {
-    pub entity: Entity,
+    /// The entities to update, which all receive the same data. Edits to several entities are
+    /// applied atomically: if any of the entities can't be updated, none of them are.
+    pub entities: Vec<Entity>,
     pub data: serde_json::Value,
+    pub request_id: Option<u64>,
+
+    /// Whether `data` only contains the fields to change.
+    pub patch: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct IncomingResource {
+    pub data: serde_json::Value,
+    pub request_id: Option<u64>,
 }
 
 /// A connection to an editor which allows sending messages via a [`SyncEditorSystem`].
@@ -74,33 +640,315 @@ pub struct IncomingComponent {
 #[derive(Clone)]
 pub struct EditorConnection {
     sender: Sender<SerializedData>,
+    schemas: Arc<MessageSchemas>,
+    capabilities: SharedCapabilities,
+    presence: EditorPresence,
+    debug_draw: DebugDrawBuffer,
+    issues: IssueBuffer,
+    subscriptions: Subscriptions,
+    schedule: SyncSchedule,
+    interest: InterestFilter,
+    pages: ComponentPages,
+
+    // The number of the frame currently running, for tagging data sent from outside of systems.
+    frame: Arc<AtomicUsize>,
+
+    // Sends crash reports straight to the editor, once the bundle has been built.
+    crash_reporter: Arc<Mutex<Option<CrashReporter>>>,
+
+    transactions: Transactions,
 }
 
 impl EditorConnection {
     /// Construct a connection to the editor via sending messages to the [`SyncEditorSystem`].
     pub(crate) fn new(sender: Sender<SerializedData>) -> Self {
-        Self { sender }
+        Self {
+            sender,
+            schemas: Arc::new(MessageSchemas::default()),
+            capabilities: SharedCapabilities::default(),
+            presence: EditorPresence::default(),
+            debug_draw: DebugDrawBuffer::default(),
+            issues: IssueBuffer::default(),
+            subscriptions: Subscriptions::default(),
+            schedule: SyncSchedule::default(),
+            interest: InterestFilter::default(),
+            pages: ComponentPages::default(),
+            frame: Arc::new(AtomicUsize::new(0)),
+            crash_reporter: Arc::new(Mutex::new(None)),
+            transactions: Transactions::default(),
+        }
+    }
+
+    /// The custom message types registered for this connection.
+    pub(crate) fn schemas(&self) -> &MessageSchemas {
+        &self.schemas
+    }
+
+    /// The capabilities negotiated with the editor.
+    pub(crate) fn capabilities(&self) -> &SharedCapabilities {
+        &self.capabilities
+    }
+
+    /// Whether state is currently being sent to the editor.
+    pub(crate) fn presence(&self) -> &EditorPresence {
+        &self.presence
+    }
+
+    /// The debug shapes drawn during the current frame.
+    pub(crate) fn debug_draw(&self) -> &DebugDrawBuffer {
+        &self.debug_draw
+    }
+
+    /// The issues reported during the current frame.
+    pub(crate) fn issues(&self) -> &IssueBuffer {
+        &self.issues
+    }
+
+    /// The parts of the game state that the editor subscribed to.
+    pub(crate) fn subscriptions(&self) -> &Subscriptions {
+        &self.subscriptions
+    }
+
+    /// The component types whose turn it is to be serialized.
+    pub(crate) fn schedule(&self) -> &SyncSchedule {
+        &self.schedule
+    }
+
+    /// The entities whose components are serialized.
+    pub(crate) fn interest(&self) -> &InterestFilter {
+        &self.interest
+    }
+
+    /// The page of each component type that's sent, if component data is paginated.
+    pub(crate) fn pages(&self) -> &ComponentPages {
+        &self.pages
+    }
+
+    /// The transactions whose operations are still being handled.
+    pub(crate) fn transactions(&self) -> &Transactions {
+        &self.transactions
+    }
+
+    /// The number of the frame currently running, as last recorded with [`set_frame`].
+    ///
+    /// [`set_frame`]: #method.set_frame
+    pub(crate) fn frame(&self) -> u64 {
+        self.frame.load(Ordering::Relaxed) as u64
+    }
+
+    /// Records the number of the frame that started running.
+    pub(crate) fn set_frame(&self, frame: u64) {
+        self.frame.store(frame as usize, Ordering::Relaxed);
+    }
+
+    /// Draws a line from `start` to `end` in the editor's view, with an RGBA `color`.
+    ///
+    /// Shapes are only drawn for a single frame, so they need to be drawn again every frame
+    /// that they should stay visible. All shapes drawn during a frame are sent together in the
+    /// `"debug_draw"` section of the next snapshot, which replaces the shapes sent before.
+    /// Nothing is recorded while no editor is connected.
+    pub fn draw_line(&self, start: [f32; 3], end: [f32; 3], color: [f32; 4]) {
+        if self.presence.is_active() {
+            self.debug_draw.push(DebugShape::Line { start, end, color });
+        }
+    }
+
+    /// Draws an axis-aligned box spanning from `min` to `max` in the editor's view, with an
+    /// RGBA `color`.
+    ///
+    /// See [`draw_line`] for how long shapes stay visible.
+    ///
+    /// [`draw_line`]: #method.draw_line
+    pub fn draw_aabb(&self, min: [f32; 3], max: [f32; 3], color: [f32; 4]) {
+        if self.presence.is_active() {
+            self.debug_draw.push(DebugShape::Aabb { min, max, color });
+        }
+    }
+
+    /// Reports a problem with the game state, such as an entity with a NaN transform, for the
+    /// editor to list with other problems.
+    ///
+    /// `category` groups related issues together, and `entity` lets the editor link the issue
+    /// to the entity it concerns. All issues reported during a frame are sent together in an
+    /// `"issues"` message. Nothing is recorded while no editor is connected.
+    pub fn report_issue<C, M>(
+        &self,
+        severity: Severity,
+        category: C,
+        message: M,
+        entity: Option<Entity>,
+    ) where
+        C: Into<String>,
+        M: Into<String>,
+    {
+        if self.presence.is_active() {
+            self.issues.push(Issue::new(
+                severity,
+                category.into(),
+                message.into(),
+                entity,
+            ));
+        }
+    }
+
+    /// Sends data of your own to the editor as a section of the next snapshot, under the
+    /// top-level key `section`.
+    ///
+    /// This is how systems added by other crates (see [`SyncSystemExt`]) sync their own state,
+    /// e.g. the paths found by a pathfinding system. Like the game state, only the latest data
+    /// sent for each section is sent with the snapshot, so there's no need to throttle sending.
+    /// Pick a section name that won't clash with the sections sent by this crate or other
+    /// plugins, e.g. by prefixing it with the name of your crate. Editors can subscribe to the
+    /// section by name. Nothing is sent while no editor is connected.
+    ///
+    /// [`SyncSystemExt`]: ./ext/trait.SyncSystemExt.html
+    pub fn send_data<T: Serialize>(&self, section: &'static str, data: T) {
+        if !self.presence.is_active() || !self.subscriptions.wants_section(section) {
+            return;
+        }
+
+        match serde_json::value::to_raw_value(&data) {
+            Ok(serialized) => self.send_serialized(SerializedData::Section(section, serialized)),
+            Err(error) => error!("Failed to serialize section {}: {}", section, error),
+        }
     }
 
     /// Send serialized data to the editor.
-    pub(crate) fn send_data(&self, data: SerializedData) {
-        self.sender
-            .send(data)
-            .expect("Disconnected from editor sync system");
+    ///
+    /// If the bundle was disabled because the connection to the editor couldn't be opened,
+    /// nothing receives the data and it's dropped. Data that the editor hasn't subscribed to is
+    /// dropped as well.
+    pub(crate) fn send_serialized(&self, data: SerializedData) {
+        let wanted = match data {
+            SerializedData::Component(name, ..) => {
+                self.subscriptions.wants_type("components", name)
+            }
+            SerializedData::Resource(name, _) => self.subscriptions.wants_type("resources", name),
+            SerializedData::Section(name, _) => self.subscriptions.wants_section(name),
+            SerializedData::Log(_) => self.subscriptions.wants("log"),
+
+            // NOTE: Messages are filtered by type in `send_message`, before they're serialized.
+            SerializedData::Message(_) => true,
+        };
+
+        if wanted {
+            let _ = self.sender.send(data);
+        }
+    }
+
+    /// Acknowledge an edit requested by the editor, if the editor asked for an acknowledgement.
+    ///
+    /// Edits that are operations of a transaction are acknowledged together with the rest of
+    /// the transaction, once all of them have been handled.
+    pub(crate) fn send_ack(&self, request_id: Option<u64>, status: EditStatus) {
+        let request_id = match request_id {
+            Some(request_id) => request_id,
+            None => return,
+        };
+
+        match self.transactions.record(request_id, status) {
+            Recorded::NotTracked => {
+                if self.capabilities.contains(Capability::EditAcks) {
+                    self.send_message("edit_ack", EditAck { request_id, status });
+                }
+            }
+            Recorded::Pending | Recorded::Complete(None) => {}
+            Recorded::Complete(Some(ack)) => self.send_transaction_ack(ack),
+        }
+    }
+
+    /// Acknowledge a transaction once all of its operations have been handled.
+    pub(crate) fn send_transaction_ack(&self, ack: TransactionAck) {
+        if self.capabilities.contains(Capability::EditAcks) {
+            self.send_message("transaction_ack", ack);
+        }
     }
 
     /// Send an arbitrary message to the editor.
     ///
     /// Note that the message types supported by the editor may differ between implementations.
+    /// In debug builds, messages of a type registered with
+    /// [`SyncEditorBundle::register_message`] are checked against the registered sample, and an
+    /// error is logged if they don't match.
+    ///
+    /// Once the editor has subscribed to a list of sections, messages of types it didn't list
+    /// are dropped.
+    ///
+    /// [`SyncEditorBundle::register_message`]: ./struct.SyncEditorBundle.html#method.register_message
     pub fn send_message<T: Serialize>(&self, message_type: &'static str, data: T) {
-        let serialize_data = Message {
-            ty: message_type,
-            data,
+        if !self.subscriptions.wants(message_type) {
+            return;
+        }
+
+        #[cfg(debug_assertions)]
+        self.validate(message_type, &data);
+
+        if let Some(serialized) = serialize_message(message_type, data) {
+            self.send_serialized(SerializedData::Message(serialized));
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn validate<T: Serialize>(&self, message_type: &'static str, data: &T) {
+        if !self.schemas.is_registered(message_type) {
+            return;
+        }
+
+        let result = serde_json::to_value(data)
+            .map_err(|error| error.to_string())
+            .and_then(|value| self.schemas.validate(message_type, &value));
+        if let Err(error) = result {
+            error!(
+                "{:?} message doesn't match its registered schema: {}",
+                message_type, error
+            );
+        }
+    }
+
+    /// Sets the reporter used by [`send_crash_report`].
+    ///
+    /// [`send_crash_report`]: #method.send_crash_report
+    pub(crate) fn set_crash_reporter(&self, reporter: CrashReporter) {
+        *self.crash_reporter.lock().expect("Crash reporter poisoned") = Some(reporter);
+    }
+
+    /// Sends a `"panic"` message to the editor right away, rather than waiting for the sender
+    /// system to run.
+    pub(crate) fn send_crash_report(&self, report: PanicReport) {
+        // NOTE: A previous panic while sending a report leaves the lock poisoned, but the
+        // reporter itself is still usable.
+        let mut reporter = match self.crash_reporter.lock() {
+            Ok(reporter) => reporter,
+            Err(poisoned) => poisoned.into_inner(),
         };
-        if let Ok(serialized) = serde_json::to_string(&serialize_data) {
-            self.send_data(SerializedData::Message(serialized));
-        } else {
-            error!("Failed to serialize message");
+        if let Some(ref mut reporter) = *reporter {
+            reporter.send(self.frame(), report);
+        }
+    }
+
+    /// Send a log record to the editor as a `"log"` message.
+    pub(crate) fn send_log<T: Serialize>(&self, record: T) {
+        if !self.subscriptions.wants("log") {
+            return;
+        }
+
+        if let Some(serialized) = serialize_message("log", record) {
+            self.send_serialized(SerializedData::Log(serialized));
         }
     }
 }
+
+pub(crate) fn serialize_message<T: Serialize>(
+    message_type: &'static str,
+    data: T,
+) -> Option<Box<RawValue>> {
+    let serialize_data = Message {
+        ty: message_type,
+        data,
+    };
+    let result = serde_json::value::to_raw_value(&serialize_data);
+    if result.is_err() {
+        error!("Failed to serialize message");
+    }
+    result.ok()
+}
@@ -0,0 +1,180 @@
+use crate::types::{Diagnostic, EditorConnection};
+use std::collections::HashSet;
+use std::mem;
+
+/// How the game responds to updates from the editor for components or resources that aren't
+/// registered as editable, set with [`SyncEditorBundle::unknown_type_policy`].
+///
+/// Such updates usually mean that the editor and the game disagree about which types are
+/// registered, e.g. because a type was renamed. The update is always dropped, and if the editor
+/// asked for an acknowledgement it's acknowledged with an `unknown_type` status.
+///
+/// [`SyncEditorBundle::unknown_type_policy`]: ./struct.SyncEditorBundle.html#method.unknown_type_policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownTypePolicy {
+    /// Only log the update at debug level.
+    Ignore,
+
+    /// Log a warning the first time an update is received for each unknown ID.
+    WarnOnce,
+
+    /// Log a warning the first time an update is received for each unknown ID, and send a
+    /// `"diagnostic"` message to the editor listing the IDs that can be edited, so that the
+    /// editor can correct its UI.
+    Report,
+
+    /// Log an error and send a `"diagnostic"` message every time an update is received for an
+    /// unknown ID, and record the error in [`EditorConnectionStatus::take_errors`]. Intended for
+    /// tests and CI, where a registration mismatch should fail the run. The game itself keeps
+    /// running, so it's up to the caller to check for the errors.
+    ///
+    /// [`EditorConnectionStatus::take_errors`]: ./struct.EditorConnectionStatus.html#method.take_errors
+    Strict,
+}
+
+impl Default for UnknownTypePolicy {
+    fn default() -> Self {
+        UnknownTypePolicy::WarnOnce
+    }
+}
+
+/// Whether an unknown ID was meant to be a component or a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TypeKind {
+    Component,
+    Resource,
+}
+
+/// Applies an [`UnknownTypePolicy`], remembering which unknown IDs have already been reported.
+#[derive(Debug)]
+pub(crate) struct UnknownTypes {
+    policy: UnknownTypePolicy,
+    reported: HashSet<(TypeKind, String)>,
+
+    // Errors recorded with the strict policy, which haven't been taken yet.
+    errors: Vec<String>,
+}
+
+impl UnknownTypes {
+    pub(crate) fn new(policy: UnknownTypePolicy) -> Self {
+        UnknownTypes {
+            policy,
+            reported: HashSet::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Takes the errors recorded with the strict policy since the last call.
+    pub(crate) fn take_errors(&mut self) -> Vec<String> {
+        mem::replace(&mut self.errors, Vec::new())
+    }
+
+    /// Handles an update for the unknown `id`, where `known` lists the editable IDs of the same
+    /// kind.
+    pub(crate) fn handle(
+        &mut self,
+        kind: TypeKind,
+        id: &str,
+        known: Vec<&'static str>,
+        connection: &EditorConnection,
+    ) {
+        let description = match kind {
+            TypeKind::Component => "component",
+            TypeKind::Resource => "resource",
+        };
+
+        match self.policy {
+            UnknownTypePolicy::Ignore => {
+                debug!("No deserializer found for {} {:?}", description, id);
+                return;
+            }
+
+            UnknownTypePolicy::Strict => {
+                let error = format!(
+                    "Received an update for unknown {} {:?}, known {}s are {:?}",
+                    description, id, description, known
+                );
+                error!("{}", error);
+                self.errors.push(error);
+                send_diagnostic(kind, id, known, connection);
+                return;
+            }
+
+            UnknownTypePolicy::WarnOnce | UnknownTypePolicy::Report => {}
+        }
+
+        if !self.reported.insert((kind, id.to_owned())) {
+            debug!("No deserializer found for {} {:?}", description, id);
+            return;
+        }
+
+        warn!(
+            "Received an update for unknown {} {:?}, known {}s are {:?}",
+            description, id, description, known
+        );
+
+        if self.policy == UnknownTypePolicy::Report {
+            send_diagnostic(kind, id, known, connection);
+        }
+    }
+}
+
+/// Sends a `"diagnostic"` message listing the IDs that can be edited instead of `id`.
+fn send_diagnostic(
+    kind: TypeKind,
+    id: &str,
+    known: Vec<&'static str>,
+    connection: &EditorConnection,
+) {
+    let id = id.to_owned();
+    let diagnostic = match kind {
+        TypeKind::Component => Diagnostic::UnknownComponent { id, known },
+        TypeKind::Resource => Diagnostic::UnknownResource { id, known },
+    };
+    connection.send_message("diagnostic", diagnostic);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::SerializedData;
+
+    #[test]
+    fn report_each_id_once() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let connection = EditorConnection::new(sender);
+        let mut unknown_types = UnknownTypes::new(UnknownTypePolicy::Report);
+
+        unknown_types.handle(TypeKind::Component, "Foo", vec!["Bar"], &connection);
+        unknown_types.handle(TypeKind::Component, "Foo", vec!["Bar"], &connection);
+        unknown_types.handle(TypeKind::Resource, "Foo", vec![], &connection);
+
+        let reports = receiver
+            .try_iter()
+            .map(|data| match data {
+                SerializedData::Message(message) => message.get().to_owned(),
+                _ => panic!("Expected a diagnostic message"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(reports.len(), 2);
+        assert!(reports[0].contains(r#""kind":"unknown_component""#));
+        assert!(reports[0].contains(r#""known":["Bar"]"#));
+        assert!(reports[1].contains(r#""kind":"unknown_resource""#));
+    }
+
+    #[test]
+    fn strict_records_every_update() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let connection = EditorConnection::new(sender);
+        let mut unknown_types = UnknownTypes::new(UnknownTypePolicy::Strict);
+
+        unknown_types.handle(TypeKind::Component, "Foo", vec!["Bar"], &connection);
+        unknown_types.handle(TypeKind::Component, "Foo", vec!["Bar"], &connection);
+
+        assert_eq!(receiver.try_iter().count(), 2);
+        let errors = unknown_types.take_errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains(r#"unknown component "Foo""#));
+        assert!(unknown_types.take_errors().is_empty());
+    }
+}
@@ -0,0 +1,484 @@
+//! Checks for invalid values in synced components, such as a transform that went NaN.
+//!
+//! NaN and infinite floats can't be found in the serialized JSON, since `serde_json` writes them
+//! as `null`, so the components are checked by serializing them into a [`FloatFinder`] instead.
+
+use amethyst::ecs::{BitSet, Entity};
+use crate::issues::Severity;
+use crate::types::{ComponentEntry, EditorConnection};
+use serde::ser::{self, Serialize};
+use std::error::Error as StdError;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+/// A check added with [`SyncEditorBundle::add_validator`], returning a description of the
+/// problem if the component is invalid.
+///
+/// [`SyncEditorBundle::add_validator`]: ./struct.SyncEditorBundle.html#method.add_validator
+pub(crate) type Validator<T> = Arc<dyn Fn(&T) -> Result<(), String> + Send + Sync>;
+
+/// Validates all components of a type as they're read, reporting invalid components to the
+/// editor as issues.
+///
+/// Each problem is reported once when a component becomes invalid, rather than every frame that
+/// it stays invalid.
+pub(crate) struct ComponentValidator<T> {
+    name: &'static str,
+    connection: EditorConnection,
+
+    // Whether to look for NaN and infinite floats.
+    check_floats: bool,
+    validators: Vec<Validator<T>>,
+
+    // The entities whose component was invalid when last validated.
+    invalid: BitSet,
+}
+
+impl<T: Serialize> ComponentValidator<T> {
+    /// Creates a validator, or returns `None` if there's nothing to check.
+    pub(crate) fn new(
+        name: &'static str,
+        connection: EditorConnection,
+        check_floats: bool,
+        validators: Vec<Validator<T>>,
+    ) -> Option<Self> {
+        if !check_floats && validators.is_empty() {
+            return None;
+        }
+
+        Some(ComponentValidator {
+            name,
+            connection,
+            check_floats,
+            validators,
+            invalid: BitSet::new(),
+        })
+    }
+
+    pub(crate) fn validate(&mut self, data: &[ComponentEntry<&T>]) {
+        let mut invalid = BitSet::new();
+        for entry in data {
+            let problems = self.problems(entry.data);
+            if problems.is_empty() {
+                continue;
+            }
+
+            let entity = entry.entity.0;
+            invalid.add(entity.id());
+            if !self.invalid.contains(entity.id()) {
+                for problem in problems {
+                    self.report(entity, problem);
+                }
+            }
+        }
+        self.invalid = invalid;
+    }
+
+    fn problems(&self, component: &T) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.check_floats {
+            let fields = non_finite_fields(component);
+            if !fields.is_empty() {
+                problems.push(format!(
+                    "{} has non-finite values in {}",
+                    self.name,
+                    fields.join(", ")
+                ));
+            }
+        }
+
+        for validator in &self.validators {
+            if let Err(problem) = validator(component) {
+                problems.push(format!("{}: {}", self.name, problem));
+            }
+        }
+        problems
+    }
+
+    fn report(&self, entity: Entity, problem: String) {
+        warn!("Invalid component on entity {}: {}", entity.id(), problem);
+        self.connection
+            .report_issue(Severity::Warning, "validation", problem, Some(entity));
+    }
+}
+
+/// The paths of all NaN or infinite floats in `value`, with fields and indices separated by
+/// dots, e.g. `translation.0`. A float at the top level has an empty path.
+pub(crate) fn non_finite_fields<T: ?Sized + Serialize>(value: &T) -> Vec<String> {
+    let mut finder = FloatFinder::default();
+    // NOTE: The finder never fails by itself, so any error comes from the type's own
+    // `Serialize` impl, which the read system reports when serializing it for real.
+    let _ = value.serialize(&mut finder);
+    finder.found
+}
+
+/// A serializer that discards everything but the paths of NaN and infinite floats.
+#[derive(Default)]
+struct FloatFinder {
+    path: Vec<String>,
+    found: Vec<String>,
+}
+
+impl FloatFinder {
+    fn check(&mut self, value: f64) -> Result<(), Error> {
+        if !value.is_finite() {
+            self.found.push(self.path.join("."));
+        }
+        Ok(())
+    }
+
+    fn visit<T: ?Sized + Serialize>(&mut self, segment: String, value: &T) -> Result<(), Error> {
+        self.path.push(segment);
+        let result = value.serialize(&mut *self);
+        self.path.pop();
+        result
+    }
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl StdError for Error {
+    fn description(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error(message.to_string())
+    }
+}
+
+/// Tracks the position within a sequence, map, or struct being searched.
+struct Compound<'a> {
+    finder: &'a mut FloatFinder,
+    index: usize,
+    key: String,
+
+    // Whether the variant name was pushed onto the path, and needs to be popped at the end.
+    variant: bool,
+}
+
+impl<'a> Compound<'a> {
+    fn new(finder: &'a mut FloatFinder, variant: Option<&'static str>) -> Self {
+        if let Some(variant) = variant {
+            finder.path.push(variant.to_owned());
+        }
+        Compound {
+            finder,
+            index: 0,
+            key: String::new(),
+            variant: variant.is_some(),
+        }
+    }
+
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let segment = self.index.to_string();
+        self.index += 1;
+        self.finder.visit(segment, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        if self.variant {
+            self.finder.path.pop();
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut FloatFinder {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, _: bool) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), Error> {
+        self.check(f64::from(value))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), Error> {
+        self.check(value)
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_str(self, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.visit(variant.to_owned(), value)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, None))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, None))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, Some(variant)))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, None))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, Some(variant)))
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeMap for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        // NOTE: Map keys are written the same way as in the JSON sent to the editor.
+        self.key = match serde_json::to_value(key) {
+            Ok(serde_json::Value::String(key)) => key,
+            Ok(key) => key.to_string(),
+            Err(_) => "?".to_owned(),
+        };
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.clone();
+        self.finder.visit(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.finder.visit(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.finder.visit(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::f32;
+
+    #[derive(Serialize)]
+    struct Body {
+        position: [f32; 3],
+        mass: f64,
+        tags: BTreeMap<&'static str, f32>,
+    }
+
+    #[test]
+    fn finds_non_finite_fields() {
+        let mut tags = BTreeMap::new();
+        tags.insert("drag", f32::INFINITY);
+        tags.insert("lift", 1.0);
+        let body = Body {
+            position: [0.0, f32::NAN, 1.0],
+            mass: 2.0,
+            tags,
+        };
+        assert_eq!(non_finite_fields(&body), vec!["position.1", "tags.drag"]);
+
+        let body = Body {
+            position: [0.0; 3],
+            mass: 2.0,
+            tags: BTreeMap::new(),
+        };
+        assert!(non_finite_fields(&body).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! Background threads that take serialization and socket I/O off the main thread.
+
+use crossbeam_channel::{self, Receiver, RecvTimeoutError, Sender};
+use crate::transport::{self, Transport, INITIAL_RETRY_DELAY_MS, MAX_SEND_RETRIES};
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A job queued on the pool.
+///
+/// NOTE: This stands in for `Box<dyn FnOnce()>`, which can't be called directly on the versions
+/// of Rust supported by Amethyst.
+trait Job: Send {
+    fn run(self: Box<Self>);
+}
+
+impl<F> Job for F
+where
+    F: FnOnce() + Send,
+{
+    fn run(self: Box<Self>) {
+        (*self)()
+    }
+}
+
+/// A fixed set of threads that run serialization jobs handed over by the read systems.
+///
+/// A job that panics is logged and doesn't take its thread down with it. If the threads can't
+/// be started, or have exited, jobs are run right away on the calling thread instead, so that
+/// syncing carries on at the cost of doing the work during the dispatch.
+///
+/// The threads exit once every handle to the pool has been dropped and all queued jobs have
+/// finished.
+#[derive(Clone)]
+pub(crate) struct SerializationPool {
+    jobs: Option<Sender<Box<dyn Job>>>,
+}
+
+impl SerializationPool {
+    pub(crate) fn new(threads: usize) -> SerializationPool {
+        let (jobs, receiver) = crossbeam_channel::unbounded::<Box<dyn Job>>();
+        let mut started = 0;
+        for index in 0..threads.max(1) {
+            let receiver = receiver.clone();
+            let result = thread::Builder::new()
+                .name(format!("editor-serializer-{}", index))
+                .spawn(move || {
+                    for job in receiver.iter() {
+                        run_job(job);
+                    }
+                });
+            match result {
+                Ok(_) => started += 1,
+                Err(error) => error!("Failed to start serialization thread: {}", error),
+            }
+        }
+
+        SerializationPool {
+            jobs: if started > 0 { Some(jobs) } else { None },
+        }
+    }
+
+    /// Queues `job` to be run on one of the pool's threads, or runs it right away if the
+    /// threads aren't running.
+    pub(crate) fn spawn<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let job = match self.jobs {
+            Some(ref jobs) => match jobs.send(Box::new(job)) {
+                Ok(()) => return,
+                Err(error) => error.into_inner(),
+            },
+            None => Box::new(job) as Box<dyn Job>,
+        };
+
+        warn_once!("Serialization threads aren't running, serializing on the main thread instead");
+        run_job(job);
+    }
+}
+
+/// Runs `job`, logging an error instead of unwinding if it panics.
+fn run_job(job: Box<dyn Job>) {
+    // NOTE: Jobs only share state through locks and atomics that they leave consistent, or
+    // that the systems recover from, so it's fine to carry on after one panics.
+    if panic::catch_unwind(AssertUnwindSafe(|| job.run())).is_err() {
+        error!("Serialization job panicked, its data won't be sent to the editor");
+    }
+}
+
+/// A packet handed over to a [`SendThread`].
+pub(crate) struct OutgoingPacket {
+    pub bytes: Vec<u8>,
+
+    /// Whether this is the last packet of its message.
+    pub last: bool,
+}
+
+/// What happened to the packets handed over to a [`SendThread`], reported back to the sender
+/// system so that it can keep its statistics.
+pub(crate) enum SendEvent {
+    /// A packet of the given size was sent.
+    Sent(usize),
+
+    /// Sending a packet failed with an error that's likely to be temporary, and is retried.
+    Retried,
+
+    /// A packet was dropped because sending it failed.
+    Dropped(io::Error),
+
+    /// A packet was dropped because there's no editor listening.
+    NoEditor,
+
+    /// The last packet of a message that had any of its packets dropped has been handled.
+    MessageDropped,
+}
+
+/// A thread that sends packets to the editor, so that encrypting packets and writing them to the
+/// socket doesn't happen on the main thread.
+///
+/// Packets are sent in the order they're handed over. Errors that are likely to be temporary are
+/// retried on the thread, waiting twice as long after each failed retry. Packets that fail
+/// because there's no editor listening are dropped quietly, and any other error drops the packet
+/// and is reported.
+pub(crate) struct SendThread {
+    packets: Sender<OutgoingPacket>,
+    events: Receiver<SendEvent>,
+}
+
+impl SendThread {
+    pub(crate) fn new(transport: Transport) -> io::Result<SendThread> {
+        let (packets, packet_receiver) = crossbeam_channel::unbounded();
+        let (event_sender, events) = crossbeam_channel::unbounded();
+        thread::Builder::new()
+            .name("editor-sender".into())
+            .spawn(move || send_packets(transport, &packet_receiver, &event_sender))?;
+
+        Ok(SendThread { packets, events })
+    }
+
+    /// Queues `packet` to be sent, handing it back if the thread has exited.
+    pub(crate) fn send(&self, packet: OutgoingPacket) -> Result<(), OutgoingPacket> {
+        self.packets
+            .send(packet)
+            .map_err(|error| error.into_inner())
+    }
+
+    /// Takes the events that happened since the last call.
+    pub(crate) fn events(&self) -> impl Iterator<Item = SendEvent> + '_ {
+        self.events.try_iter()
+    }
+}
+
+/// Sends the packets received from `packets` until the sender system is dropped.
+fn send_packets(
+    mut transport: Transport,
+    packets: &Receiver<OutgoingPacket>,
+    events: &Sender<SendEvent>,
+) {
+    let mut message_damaged = false;
+    loop {
+        // NOTE: Packets delayed to simulate latency are sent as their time comes, even while
+        // there's nothing new to send. Otherwise the thread sleeps until there is.
+        let packet = match transport.next_delayed() {
+            Some(deliver_at) => {
+                let now = Instant::now();
+                let timeout = if deliver_at > now {
+                    deliver_at - now
+                } else {
+                    Duration::from_secs(0)
+                };
+                match packets.recv_timeout(timeout) {
+                    Ok(packet) => packet,
+                    Err(RecvTimeoutError::Timeout) => {
+                        let _ = transport.flush();
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            None => match packets.recv() {
+                Ok(packet) => packet,
+                Err(_) => return,
+            },
+        };
+
+        let mut retries = 0;
+        let result = loop {
+            match transport.send(&packet.bytes) {
+                Err(ref error)
+                    if transport::is_transient(error.kind()) && retries < MAX_SEND_RETRIES =>
+                {
+                    thread::sleep(Duration::from_millis(INITIAL_RETRY_DELAY_MS << retries));
+                    retries += 1;
+                    let _ = events.send(SendEvent::Retried);
+                }
+                result => break result,
+            }
+        };
+
+        let event = match result {
+            Ok(()) => SendEvent::Sent(packet.bytes.len()),
+            Err(ref error) if transport::is_no_editor(error.kind()) => SendEvent::NoEditor,
+            Err(error) => {
+                message_damaged = true;
+                SendEvent::Dropped(error)
+            }
+        };
+        if events.send(event).is_err() {
+            return;
+        }
+
+        if packet.last && message_damaged {
+            message_damaged = false;
+            let _ = events.send(SendEvent::MessageDropped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn runs_jobs_on_background_threads() {
+        let pool = SerializationPool::new(2);
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        for value in 0..4 {
+            let sender = sender.clone();
+            pool.spawn(move || {
+                let name = thread::current().name().map(str::to_owned);
+                sender.send((value, name)).unwrap();
+            });
+        }
+
+        let mut values = Vec::new();
+        for _ in 0..4 {
+            let (value, name) = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(name.unwrap().starts_with("editor-serializer-"));
+            values.push(value);
+        }
+        values.sort();
+        assert_eq!(values, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn panicking_jobs_leave_the_threads_running() {
+        let pool = SerializationPool::new(1);
+        pool.spawn(|| panic!("job failed"));
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        pool.spawn(move || sender.send(()).unwrap());
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn sends_delayed_packets_when_they_are_due() {
+        let editor = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        editor
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let transport = Transport::udp("127.0.0.1:0")
+            .unwrap()
+            .with_editor_address(editor.local_addr().unwrap())
+            .with_conditioning(0.0, Duration::from_millis(50), Duration::from_millis(0));
+
+        // Nothing else is sent after the packet, so it's only delivered if the thread wakes up
+        // once its delay has passed.
+        let sent = Instant::now();
+        let thread = SendThread::new(transport).unwrap();
+        assert!(thread
+            .send(OutgoingPacket {
+                bytes: b"delayed".to_vec(),
+                last: true,
+            })
+            .is_ok());
+
+        let mut buf = [0; 16];
+        let len = editor.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"delayed");
+        assert!(sent.elapsed() >= Duration::from_millis(50));
+    }
+}
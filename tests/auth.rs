@@ -0,0 +1,100 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::{json, Value};
+use tap::*;
+
+/// Runs a game that requires the token `"secret"`, after the editor has sent `requests`, and
+/// returns the messages it sent back.
+fn run_with_requests(requests: &[&str], expected_entities: usize) -> amethyst::Result<Vec<Value>> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.require_auth_token("secret"))
+        .tap(|bundle| bundle.transport(transport));
+
+    for request in requests {
+        editor.send(request);
+    }
+
+    // Checks that the editor's requests created the expected number of entities.
+    let state = TestState::frames(5).check(move |world| {
+        let entities = (&*world.entities()).join().count();
+        assert_eq!(entities, expected_entities);
+    });
+    run(editor_sync_bundle, state)?;
+
+    Ok(editor.messages())
+}
+
+#[test]
+fn commands_are_ignored_until_authenticated() -> amethyst::Result<()> {
+    let messages = run_with_requests(
+        &[
+            r#"{"type":"CreateEntities","amount":5,"request_id":1}"#,
+            r#"{"type":"Authenticate","token":"guess","request_id":2}"#,
+            r#"{"type":"CreateEntities","amount":5,"request_id":3}"#,
+        ],
+        0,
+    )?;
+    assert_eq!(acks(&messages), vec![json!([2, "unauthorized"])]);
+
+    Ok(())
+}
+
+#[test]
+fn commands_are_accepted_once_authenticated() -> amethyst::Result<()> {
+    let messages = run_with_requests(
+        &[
+            r#"{"type":"Authenticate","token":"secret","request_id":1}"#,
+            r#"{"type":"CreateEntities","amount":5,"request_id":2}"#,
+        ],
+        5,
+    )?;
+    assert_eq!(
+        acks(&messages),
+        vec![json!([1, "applied"]), json!([2, "applied"])]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn handshake_is_answered_once_authenticated() -> amethyst::Result<()> {
+    // Only the handshake sent when the game starts goes out.
+    let messages = run_with_requests(&[r#"{"type":"Handshake"}"#], 0)?;
+    assert_eq!(of_type(&messages, "handshake").len(), 1);
+
+    let messages = run_with_requests(
+        &[
+            r#"{"type":"Handshake"}"#,
+            r#"{"type":"Authenticate","token":"secret","request_id":1}"#,
+        ],
+        0,
+    )?;
+    assert_eq!(of_type(&messages, "handshake").len(), 2);
+    assert_eq!(acks(&messages), vec![json!([1, "applied"])]);
+
+    Ok(())
+}
+
+#[test]
+fn handshake_starts_a_new_session() -> amethyst::Result<()> {
+    let messages = run_with_requests(
+        &[
+            r#"{"type":"Authenticate","token":"secret","request_id":1}"#,
+            r#"{"type":"Handshake"}"#,
+            r#"{"type":"CreateEntities","amount":5,"request_id":2}"#,
+        ],
+        0,
+    )?;
+    assert_eq!(acks(&messages), vec![json!([1, "applied"])]);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::{Component, VecStorage};
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::{Serialize, Serializer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use tap::*;
+
+/// A component that panics the first time it's serialized.
+#[derive(Clone)]
+struct Flaky;
+
+static PANICKED: AtomicBool = AtomicBool::new(false);
+
+impl Serialize for Flaky {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !PANICKED.swap(true, Ordering::SeqCst) {
+            panic!("Flaky failed to serialize");
+        }
+        serializer.serialize_unit_struct("Flaky")
+    }
+}
+
+impl Component for Flaky {
+    type Storage = VecStorage<Self>;
+}
+
+#[test]
+fn serialization_recovers_from_panics() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.read_component_in_background::<Flaky>("Flaky"))
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    let state = TestState::frames(20)
+        .setup(|world| {
+            world.register::<Flaky>();
+            world.create_entity().with(Flaky).build();
+        })
+        .each_frame(|_, _| thread::sleep(Duration::from_millis(5)));
+    run(editor_sync_bundle, state)?;
+
+    assert!(PANICKED.load(Ordering::SeqCst));
+    assert!(editor
+        .snapshots()
+        .iter()
+        .any(|snapshot| snapshot["components"][0]["name"] == "Flaky"));
+
+    Ok(())
+}
+
+#[test]
+fn packets_are_sent_in_background() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.send_in_background(true))
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    run(editor_sync_bundle, TestState::frames(10))?;
+
+    // The sending thread may still be working through the last packets.
+    thread::sleep(Duration::from_millis(100));
+    assert!(editor.snapshots().len() > 1);
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[test]
+fn component_update_batch() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ComponentUpdateBatch","id":"Label","entities":[{"id":0,"generation":1},{"id":1,"generation":1}],"data":{"text":"edited"},"request_id":3}"#);
+
+    let state = TestState::frames(10)
+        .setup(|world| {
+            for _ in 0..2 {
+                world
+                    .create_entity()
+                    .with(Label {
+                        text: "original".into(),
+                    })
+                    .build();
+            }
+        })
+        .check(|world| {
+            // Both labels should have been updated.
+            let labels = world.read_storage::<Label>();
+            let texts = (&labels)
+                .join()
+                .map(|label| &*label.text)
+                .collect::<Vec<_>>();
+            assert_eq!(texts, vec!["edited", "edited"]);
+        });
+    run(editor_sync_bundle, state)
+}
@@ -0,0 +1,59 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate tap;
+
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use tap::*;
+
+struct TestState {
+    connection: EditorConnection,
+    frames: usize,
+}
+
+impl SimpleState for TestState {
+    fn update(&mut self, data: &mut StateData<GameData>) -> SimpleTrans {
+        data.data.update(&data.world);
+        self.connection.send_message("frame", self.frames);
+
+        self.frames += 1;
+        if self.frames > 10 {
+            Trans::Quit
+        } else {
+            Trans::None
+        }
+    }
+}
+
+#[test]
+fn disabled_when_bind_fails() -> amethyst::Result<()> {
+    let editor_sync_bundle =
+        SyncEditorBundle::new().tap(|bundle| bundle.bind_address("not an address"));
+    let state = TestState {
+        connection: editor_sync_bundle.connection(),
+        frames: 0,
+    };
+
+    // The bundle disables itself instead of failing, and messages sent through its connection
+    // are dropped.
+    let game_data = GameDataBuilder::default().with_bundle(editor_sync_bundle)?;
+    let mut game = Application::build(".", state)?.build(game_data)?;
+    game.run();
+
+    Ok(())
+}
+
+#[test]
+fn try_new_binds_immediately() -> amethyst::Result<()> {
+    let editor_sync_bundle = SyncEditorBundle::try_new().expect("Failed to bind socket");
+    let state = TestState {
+        connection: editor_sync_bundle.connection(),
+        frames: 0,
+    };
+
+    let game_data = GameDataBuilder::default().with_bundle(editor_sync_bundle)?;
+    let mut game = Application::build(".", state)?.build(game_data)?;
+    game.run();
+
+    Ok(())
+}
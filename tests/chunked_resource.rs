@@ -0,0 +1,67 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use tap::*;
+
+struct TileMap {
+    width: usize,
+    tiles: Vec<u8>,
+}
+
+struct Rows;
+
+impl ResourceChunker<TileMap> for Rows {
+    type Id = usize;
+    type Chunk = Vec<u8>;
+
+    fn chunk_ids(&self, map: &TileMap) -> Vec<usize> {
+        (0..map.tiles.len() / map.width).collect()
+    }
+
+    fn chunk(&self, map: &TileMap, &row: &usize) -> Option<Vec<u8>> {
+        map.tiles
+            .chunks(map.width)
+            .nth(row)
+            .map(|tiles| tiles.to_vec())
+    }
+}
+
+#[test]
+fn sends_requested_chunks() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.sync_chunked_resource::<TileMap, _>("TileMap", Rows))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"RequestChunks","resource":"TileMap","ids":[2]}"#);
+
+    let state = TestState::frames(10).setup(|world| {
+        world.add_resource(TileMap {
+            width: 4,
+            tiles: (0..16).collect(),
+        });
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let indices = of_type(&messages, "resource_chunk_index");
+    assert_eq!(indices.len(), 1);
+    assert_eq!(indices[0]["data"]["chunks"], json!([0, 1, 2, 3]));
+
+    // The map never changes, so the requested row should only be sent once.
+    let chunks = of_type(&messages, "resource_chunks");
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(
+        chunks[0]["data"]["chunks"],
+        json!([{ "id": 2, "data": [8, 9, 10, 11] }])
+    );
+
+    Ok(())
+}
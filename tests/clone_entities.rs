@@ -0,0 +1,61 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[test]
+fn clone_entities() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"CloneEntities","entities":[{"id":0,"generation":1}],"request_id":3}"#);
+
+    let state = TestState::frames(10)
+        .setup(|world| {
+            world
+                .create_entity()
+                .with(Label {
+                    text: "original".into(),
+                })
+                .build();
+        })
+        .check(|world| {
+            // The clone should have a copy of the original's label.
+            let labels = world.read_storage::<Label>();
+            let texts = (&labels)
+                .join()
+                .map(|label| &*label.text)
+                .collect::<Vec<_>>();
+            assert_eq!(texts, vec!["original", "original"]);
+        });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let results = of_type(&messages, "entities_cloned");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["data"]["request_id"], 3);
+    assert_eq!(results[0]["data"]["clones"][0]["source"]["id"], 0);
+
+    Ok(())
+}
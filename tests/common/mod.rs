@@ -0,0 +1,306 @@
+//! A stand-in for the editor that's shared by the integration tests, so that each test only has
+//! to describe what the editor sends and what it expects to get back.
+
+// NOTE: Each test only uses some of the helpers.
+#![allow(dead_code)]
+
+use amethyst::ecs::World;
+use amethyst::prelude::*;
+use amethyst_editor_sync::{Framing, SyncEditorBundle, Transport};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The editor's end of a UDP connection to the game.
+pub struct MockEditor {
+    socket: UdpSocket,
+    game_address: SocketAddr,
+    framing: Framing,
+
+    // The IDs put in chunk headers, shared with clones so that no two messages get the same ID.
+    message_ids: Arc<AtomicUsize>,
+}
+
+impl MockEditor {
+    /// Binds a mock editor, along with a transport for the game that sends to it.
+    pub fn new() -> io::Result<(MockEditor, Transport)> {
+        MockEditor::with_framing(Framing::default())
+    }
+
+    /// Binds a mock editor that frames messages with `framing`, along with a transport for the
+    /// game that uses the same framing.
+    pub fn with_framing(framing: Framing) -> io::Result<(MockEditor, Transport)> {
+        let socket = UdpSocket::bind("127.0.0.1:0")?;
+        socket.set_nonblocking(true)?;
+        let transport = Transport::udp("127.0.0.1:0")?
+            .with_editor_address(socket.local_addr()?)
+            .with_framing(framing);
+        let editor = MockEditor {
+            socket,
+            game_address: transport.local_addr()?,
+            framing,
+            message_ids: Arc::new(AtomicUsize::new(0)),
+        };
+        Ok((editor, transport))
+    }
+
+    /// Creates another handle to the same socket, e.g. for sending from within a state.
+    pub fn try_clone(&self) -> io::Result<MockEditor> {
+        Ok(MockEditor {
+            socket: self.socket.try_clone()?,
+            game_address: self.game_address,
+            framing: self.framing,
+            message_ids: self.message_ids.clone(),
+        })
+    }
+
+    /// Sends a single message to the game in its own packet.
+    ///
+    /// The packet starts with a chunk header unless the editor uses node-ipc's framing, which
+    /// is what the game expects from a UDP editor by default.
+    pub fn send(&self, message: &str) {
+        let mut packet = Vec::new();
+        if self.framing != Framing::NodeIpc {
+            let id = self.message_ids.fetch_add(1, Ordering::Relaxed) as u32;
+            packet.extend_from_slice(&[
+                (id >> 24) as u8,
+                (id >> 16) as u8,
+                (id >> 8) as u8,
+                id as u8,
+            ]);
+
+            // The whole message is chunk 0 of 1.
+            packet.extend_from_slice(&[0, 0, 0, 1]);
+        }
+        self.framing.encode(message.as_bytes(), &mut packet);
+        self.send_packet(&packet);
+    }
+
+    /// Serializes `message` and sends it to the game in its own packet.
+    pub fn send_json(&self, message: &Value) {
+        self.send(&message.to_string());
+    }
+
+    /// Sends `packet` to the game as-is, without framing it.
+    pub fn send_packet(&self, packet: &[u8]) {
+        self.socket
+            .send_to(packet, self.game_address)
+            .expect("Failed to send packet to the game");
+    }
+
+    /// Reads every packet that the game has sent so far.
+    pub fn packets(&self) -> Vec<Vec<u8>> {
+        let mut packets = Vec::new();
+        let mut buf = [0; 64 * 1024];
+        while let Ok(bytes_read) = self.socket.recv(&mut buf) {
+            packets.push(buf[..bytes_read].to_owned());
+        }
+        packets
+    }
+
+    /// Reads every packet that the game has sent so far, and returns the complete packets
+    /// (`{ "type", "frame", "data", .. }`) that they contain.
+    pub fn envelopes(&self) -> Vec<Value> {
+        self.decode(&self.reassemble(&self.packets()))
+    }
+
+    /// Puts the framed messages split across `packets` back together, in the order the game
+    /// sent them.
+    ///
+    /// The game starts each packet with a chunk header unless the editor uses node-ipc's
+    /// framing, in which case the packets are simply concatenated. Messages that are missing
+    /// chunks are left out.
+    pub fn reassemble(&self, packets: &[Vec<u8>]) -> Vec<u8> {
+        if self.framing == Framing::NodeIpc {
+            return packets.concat();
+        }
+
+        let mut messages = BTreeMap::new();
+        for packet in packets {
+            let id = read_be(&packet[0..4]);
+            let count = read_be(&packet[6..8]) as usize;
+            let chunks = messages.entry(id).or_insert_with(|| vec![None; count]);
+            chunks[read_be(&packet[4..6]) as usize] = Some(&packet[8..]);
+        }
+
+        messages
+            .values()
+            .filter(|chunks| chunks.iter().all(Option::is_some))
+            .flat_map(|chunks| {
+                chunks
+                    .iter()
+                    .flatten()
+                    .flat_map(|chunk| chunk.iter().cloned())
+            })
+            .collect()
+    }
+
+    /// Decodes the complete packets (`{ "type", "frame", "data", .. }`) framed in `bytes`.
+    ///
+    /// Packets are decoded as JSON if they start with `{`, and as MessagePack otherwise.
+    pub fn decode(&self, bytes: &[u8]) -> Vec<Value> {
+        self.split(bytes)
+            .into_iter()
+            .map(|message| {
+                if message[0] == b'{' {
+                    serde_json::from_slice(message).expect("Invalid JSON packet")
+                } else {
+                    rmp_serde::from_slice(message).expect("Invalid MessagePack packet")
+                }
+            })
+            .collect()
+    }
+
+    /// Splits `bytes` into the complete messages framed in it.
+    pub fn split<'a>(&self, mut bytes: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut messages = Vec::new();
+        while let Some((message, consumed)) = self.framing.decode(bytes) {
+            messages.push(&bytes[message]);
+            bytes = &bytes[consumed..];
+        }
+        messages
+    }
+
+    /// Reads every packet that the game has sent so far, and returns the messages sent along
+    /// with the state, e.g. `"edit_ack"` messages.
+    pub fn messages(&self) -> Vec<Value> {
+        self.envelopes()
+            .into_iter()
+            .flat_map(|envelope| {
+                envelope["data"]["messages"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Reads every packet that the game has sent so far, and returns the data of every
+    /// snapshot of the state.
+    pub fn snapshots(&self) -> Vec<Value> {
+        self.envelopes()
+            .into_iter()
+            .map(|mut envelope| envelope["data"].take())
+            .filter(|data| data.get("components").is_some())
+            .collect()
+    }
+}
+
+/// Reads the big-endian integer in `bytes`.
+pub fn read_be(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, &byte| value << 8 | u32::from(byte))
+}
+
+/// Returns the messages of type `ty`.
+pub fn of_type<'a>(messages: &'a [Value], ty: &str) -> Vec<&'a Value> {
+    messages
+        .iter()
+        .filter(|message| message["type"] == ty)
+        .collect()
+}
+
+/// Returns the acknowledgements among `messages`, as `[request_id, status]` arrays.
+pub fn acks(messages: &[Value]) -> Vec<Value> {
+    of_type(messages, "edit_ack")
+        .into_iter()
+        .map(|message| json!([message["data"]["request_id"], message["data"]["status"]]))
+        .collect()
+}
+
+/// A state that runs the game for a fixed number of frames.
+///
+/// The test can set up the world when the state starts, act on every frame, and check the world
+/// once all of the frames have run.
+pub struct TestState {
+    frame: usize,
+    frames: usize,
+    setup: Option<Box<dyn FnMut(&mut World)>>,
+    each_frame: Option<Box<dyn FnMut(usize, &mut World)>>,
+    check: Option<Box<dyn FnMut(&mut World)>>,
+}
+
+impl TestState {
+    /// Runs the game for `frames` frames, and quits on the frame after.
+    pub fn frames(frames: usize) -> TestState {
+        TestState {
+            frame: 0,
+            frames,
+            setup: None,
+            each_frame: None,
+            check: None,
+        }
+    }
+
+    /// Calls `setup` when the state starts.
+    pub fn setup<F: FnMut(&mut World) + 'static>(mut self, setup: F) -> TestState {
+        self.setup = Some(Box::new(setup));
+        self
+    }
+
+    /// Calls `each_frame` with the number of each frame, starting from 1, after the frame has
+    /// been dispatched.
+    pub fn each_frame<F: FnMut(usize, &mut World) + 'static>(mut self, each_frame: F) -> TestState {
+        self.each_frame = Some(Box::new(each_frame));
+        self
+    }
+
+    /// Calls `check` on the last frame, once all of the frames have run.
+    pub fn check<F: FnMut(&mut World) + 'static>(mut self, check: F) -> TestState {
+        self.check = Some(Box::new(check));
+        self
+    }
+}
+
+impl SimpleState for TestState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        if let Some(ref mut setup) = self.setup {
+            setup(data.world);
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<GameData>) -> SimpleTrans {
+        data.data.update(&data.world);
+
+        self.frame += 1;
+        if let Some(ref mut each_frame) = self.each_frame {
+            each_frame(self.frame, data.world);
+        }
+
+        if self.frame <= self.frames {
+            return Trans::None;
+        }
+
+        if let Some(ref mut check) = self.check {
+            check(data.world);
+        }
+        Trans::Quit
+    }
+}
+
+/// Runs a game with `bundle` until `state` quits.
+pub fn run<S>(bundle: SyncEditorBundle, state: S) -> amethyst::Result<()>
+where
+    S: SimpleState<'static, 'static> + 'static,
+{
+    run_with(GameDataBuilder::default(), bundle, state)
+}
+
+/// Runs a game with the systems in `game_data` and `bundle` until `state` quits.
+pub fn run_with<S>(
+    game_data: GameDataBuilder<'static, 'static>,
+    bundle: SyncEditorBundle,
+    state: S,
+) -> amethyst::Result<()>
+where
+    S: SimpleState<'static, 'static> + 'static,
+{
+    let game_data = game_data.with_bundle(bundle)?;
+    let mut game = Application::build(".", state)?.build(game_data)?;
+    game.run();
+    Ok(())
+}
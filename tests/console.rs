@@ -0,0 +1,42 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::Join;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use tap::*;
+
+#[test]
+fn eval_console_input() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| {
+            bundle.console_handler(|world, input| match input {
+                "count" => world.entities().join().count().to_string(),
+                _ => format!("Unknown command {:?}", input),
+            })
+        })
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"Eval","input":"count","request_id":7}"#);
+
+    let state = TestState::frames(10).setup(|world| {
+        for _ in 0..3 {
+            world.create_entity().build();
+        }
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let results = of_type(&messages, "console_result");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["data"]["output"], "3");
+    assert_eq!(results[0]["data"]["request_id"], 7);
+
+    Ok(())
+}
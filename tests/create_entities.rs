@@ -0,0 +1,101 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Checks that the only label is `expected`.
+fn check_labels(expected: &'static str) -> impl FnMut(&mut World) {
+    move |world| {
+        let labels = world.read_storage::<Label>();
+        let texts = (&labels)
+            .join()
+            .map(|label| &*label.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec![expected]);
+    }
+}
+
+#[test]
+fn create_entities_with_temporary_ids() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"CreateEntities","amount":2,"temporary_ids":[100],"request_id":3}"#);
+
+    // Labels the entity created by the editor through its temporary ID, once it has been
+    // created.
+    let state_editor = editor.try_clone()?;
+    let state = TestState::frames(10)
+        .each_frame(move |frame, _| {
+            if frame == 3 {
+                state_editor.send_json(&json!({
+                    "type": "ComponentUpdate",
+                    "id": "Label",
+                    "entity": { "temporary_id": 100 },
+                    "data": { "text": "created" },
+                }));
+            }
+        })
+        .check(check_labels("created"));
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let results = of_type(&messages, "entities_created");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["data"]["request_id"], 3);
+    assert_eq!(results[0]["data"]["temporary_ids"], json!([100]));
+    assert_eq!(
+        results[0]["data"]["entities"].as_array().map(Vec::len),
+        Some(2)
+    );
+
+    Ok(())
+}
+
+/// Checks that the entity created by a transaction got the label set by the same transaction.
+#[test]
+fn transaction_creates_and_edits_entity() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send_json(&json!({
+        "type": "Transaction",
+        "ops": [
+            { "type": "CreateEntities", "amount": 1, "temporary_ids": [5] },
+            {
+                "type": "ComponentUpdate",
+                "id": "Label",
+                "entity": { "temporary_id": 5 },
+                "data": { "text": "transaction" },
+            },
+        ],
+        "request_id": 9,
+    }));
+
+    let state = TestState::frames(10).check(check_labels("transaction"));
+    run(editor_sync_bundle, state)
+}
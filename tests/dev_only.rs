@@ -0,0 +1,89 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Runs the game for 5 frames with a labelled entity, recording the errors in the connection
+/// status.
+fn label(errors: Arc<Mutex<Vec<String>>>) -> TestState {
+    TestState::frames(5)
+        .setup(|world| {
+            world.register::<Label>();
+            world
+                .create_entity()
+                .with(Label {
+                    text: "original".into(),
+                })
+                .build();
+        })
+        .each_frame(move |_, world| {
+            let mut status = world.write_resource::<EditorConnectionStatus>();
+            errors.lock().unwrap().extend(status.take_errors());
+        })
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn dev_only_edits_in_debug_builds() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.sync_component_dev_only::<Label>("Label"))
+        .tap(|bundle| bundle.unknown_type_policy(UnknownTypePolicy::Strict))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ComponentUpdate","id":"Label","entity":{"id":0,"generation":1},"data":{"text":"edited"},"request_id":1}"#);
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    run(editor_sync_bundle, label(errors.clone()))?;
+
+    let messages = editor.messages();
+    assert_eq!(acks(&messages), vec![json!([1, "applied"])]);
+    assert!(!editor.snapshots().is_empty());
+    assert!(errors.lock().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn dev_only_edits_in_release_builds() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.sync_component_dev_only::<Label>("Label"))
+        .tap(|bundle| bundle.unknown_type_policy(UnknownTypePolicy::Strict))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ComponentUpdate","id":"Label","entity":{"id":0,"generation":1},"data":{"text":"edited"},"request_id":1}"#);
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    run(editor_sync_bundle, label(errors.clone()))?;
+
+    // The edit is dropped without counting as an error under the strict policy.
+    let messages = editor.messages();
+    assert_eq!(acks(&messages), vec![json!([1, "unknown_type"])]);
+    assert!(of_type(&messages, "diagnostic").is_empty());
+    assert!(errors.lock().unwrap().is_empty());
+
+    Ok(())
+}
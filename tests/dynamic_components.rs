@@ -0,0 +1,55 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use tap::*;
+
+#[test]
+fn edit_dynamic_components() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(SyncEditorBundle::sync_dynamic_components)
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ComponentPatch","id":"Health","entity":{"id":0,"generation":1},"data":{"current":8}}"#);
+
+    let state = TestState::frames(10)
+        .setup(|world| {
+            let entity = world.create_entity().build();
+            world.write_resource::<DynamicComponentStore>().insert(
+                entity,
+                "Health",
+                json!({ "current": 5, "max": 10 }),
+            );
+        })
+        .check(|world| {
+            // The patch sent by the editor should have been merged into the component.
+            let entity = world.entities().entity(0);
+            let store = world.read_resource::<DynamicComponentStore>();
+            let health = store.get(entity, "Health").cloned();
+            assert_eq!(health, Some(json!({ "current": 8, "max": 10 })));
+        });
+    run(editor_sync_bundle, state)?;
+
+    let components = editor
+        .snapshots()
+        .into_iter()
+        .filter_map(|snapshot| snapshot["components"].as_array().cloned())
+        .flatten()
+        .filter(|component| component["name"] == "Health")
+        .collect::<Vec<_>>();
+    assert!(!components.is_empty());
+    assert_eq!(
+        components.last().unwrap()["data"][0]["data"],
+        json!({ "current": 8, "max": 10 })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Mover {
+    speed: f32,
+    distance: f32,
+}
+
+impl Component for Mover {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Moves every mover each frame, which changes `distance` but never `speed`.
+struct MoveSystem;
+
+impl<'a> System<'a> for MoveSystem {
+    type SystemData = WriteStorage<'a, Mover>;
+
+    fn run(&mut self, mut movers: Self::SystemData) {
+        for mover in (&mut movers).join() {
+            mover.distance += mover.speed;
+        }
+    }
+}
+
+/// Overwrites every label each frame, so that edits from the editor never stick.
+struct ResetLabelSystem;
+
+impl<'a> System<'a> for ResetLabelSystem {
+    type SystemData = WriteStorage<'a, Label>;
+
+    fn run(&mut self, mut labels: Self::SystemData) {
+        for label in (&mut labels).join() {
+            label.text = "original".into();
+        }
+    }
+}
+
+#[test]
+fn overwritten_edit_is_reported() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ComponentUpdate","id":"Label","entity":{"id":0,"generation":1},"data":{"text":"edited"},"request_id":5}"#);
+
+    let state = TestState::frames(10).setup(|world| {
+        world
+            .create_entity()
+            .with(Label {
+                text: "original".into(),
+            })
+            .build();
+    });
+    let game_data = GameDataBuilder::default().with(ResetLabelSystem, "reset_labels", &[]);
+    run_with(game_data, editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let notices = of_type(&messages, "edit_overridden");
+    assert_eq!(notices.len(), 1);
+    assert_eq!(notices[0]["data"]["id"], "Label");
+    assert_eq!(notices[0]["data"]["fields"], json!(["text"]));
+    assert_eq!(notices[0]["data"]["request_id"], 5);
+
+    Ok(())
+}
+
+#[test]
+fn untouched_fields_are_not_reported() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Mover))
+        .tap(|bundle| bundle.transport(transport));
+
+    // The patch only sets `speed`, while the game keeps changing `distance`.
+    editor.send(r#"{"type":"ComponentPatch","id":"Mover","entity":{"id":0,"generation":1},"data":{"speed":2.0},"request_id":1}"#);
+
+    let state = TestState::frames(10).setup(|world| {
+        world
+            .create_entity()
+            .with(Mover {
+                speed: 1.0,
+                distance: 0.0,
+            })
+            .build();
+    });
+    let game_data = GameDataBuilder::default().with(MoveSystem, "move", &[]);
+    run_with(game_data, editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    assert_eq!(acks(&messages), vec![json!([1, "applied"])]);
+    assert!(of_type(&messages, "edit_overridden").is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+
+mod common;
+
+use amethyst_editor_sync::protocol::{IncomingMessage, OutgoingMessage, Packet, PacketData};
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn client_exchanges_messages_without_a_bundle() -> io::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let mut client = EditorClient::new(transport).with_max_packet_size(64);
+
+    editor.send(r#"{"type":"CreateEntities","amount":2,"request_id":7}"#);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let commands = loop {
+        let commands = client.poll_commands();
+        if !commands.is_empty() || Instant::now() > deadline {
+            break commands;
+        }
+        thread::sleep(Duration::from_millis(1));
+    };
+    assert_eq!(commands.len(), 1);
+    match commands[0] {
+        IncomingMessage::CreateEntities {
+            amount, request_id, ..
+        } => {
+            assert_eq!(amount, 2);
+            assert_eq!(request_id, Some(7));
+        }
+        ref command => panic!("Unexpected command {:?}", command),
+    }
+
+    // The state is larger than the maximum packet size, so it's split across several packets.
+    let mut data = PacketData::default();
+    data.entity_count = Some(2);
+    data.messages.push(OutgoingMessage {
+        ty: "edit_ack".into(),
+        data: json!({ "request_id": 7, "status": "applied" }),
+    });
+    client.send_state(3, data.clone())?;
+
+    let mut packets = Vec::new();
+    let envelopes = loop {
+        packets.extend(editor.packets());
+        let envelopes = editor.decode(&editor.reassemble(&packets));
+        if !envelopes.is_empty() || Instant::now() > deadline {
+            break envelopes;
+        }
+        thread::sleep(Duration::from_millis(1));
+    };
+    assert!(packets.len() > 1);
+    assert!(packets.iter().all(|packet| packet.len() <= 64));
+    assert_eq!(envelopes.len(), 1);
+
+    let packet = serde_json::from_value::<Packet>(envelopes[0].clone())?;
+    assert_eq!(packet.ty, "message");
+    assert_eq!(packet.frame, 3);
+    assert_eq!(packet.data, data);
+
+    Ok(())
+}
+
+#[test]
+fn client_requires_authentication() -> io::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let mut client = EditorClient::new(transport).with_auth_token("secret");
+
+    editor.send(r#"{"type":"Handshake","capabilities":[]}"#);
+    editor.send(r#"{"type":"CreateEntities","amount":1}"#);
+    editor.send(r#"{"type":"Authenticate","token":"secret","request_id":1}"#);
+
+    // Only the handshake is passed on, once the editor has authenticated.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut commands = Vec::new();
+    while commands.is_empty() && Instant::now() < deadline {
+        commands.extend(client.poll_commands());
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(commands.len(), 1);
+    match commands[0] {
+        IncomingMessage::Handshake { .. } => {}
+        ref command => panic!("Unexpected command {:?}", command),
+    }
+
+    // The acknowledgement goes out with the next state.
+    client.send_state(1, PacketData::default())?;
+    assert_eq!(client.stats().packets_sent, 1);
+
+    let mut messages = Vec::new();
+    while messages.is_empty() && Instant::now() < deadline {
+        messages.extend(editor.messages());
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(acks(&messages), vec![json!([1, "applied"])]);
+
+    Ok(())
+}
+
+#[test]
+fn client_ends_session_after_timeout() -> io::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let mut client = EditorClient::new(transport)
+        .with_auth_token("secret")
+        .with_connection_timeout(Duration::from_millis(50));
+
+    // Polls until the client passes on a command or the deadline is reached.
+    let poll = |client: &mut EditorClient| {
+        let deadline = Instant::now() + Duration::from_millis(500);
+        let mut commands = Vec::new();
+        while commands.is_empty() && Instant::now() < deadline {
+            commands.extend(client.poll_commands());
+            thread::sleep(Duration::from_millis(1));
+        }
+        commands
+    };
+
+    editor.send(r#"{"type":"Authenticate","token":"secret"}"#);
+    editor.send(r#"{"type":"CreateEntities","amount":1}"#);
+    assert_eq!(poll(&mut client).len(), 1);
+    assert!(client.connection_status().is_connected());
+
+    // Once the editor has been silent for longer than the timeout, it has to authenticate
+    // again before its commands are passed on.
+    thread::sleep(Duration::from_millis(100));
+    assert!(!client.connection_status().is_connected());
+    editor.send(r#"{"type":"CreateEntities","amount":1}"#);
+    assert!(poll(&mut client).is_empty());
+
+    editor.send(r#"{"type":"Authenticate","token":"secret"}"#);
+    editor.send(r#"{"type":"CreateEntities","amount":1}"#);
+    assert_eq!(poll(&mut client).len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use tap::*;
+
+#[test]
+fn oversized_create_is_rejected() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.limit_entity_commands(10, 10))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"CreateEntities","amount":1000000,"request_id":1}"#);
+    editor.send(r#"{"type":"CreateEntities","amount":5,"request_id":2}"#);
+
+    let state = TestState::frames(10).check(|world| {
+        // Only the request within the limit should have created entities.
+        assert_eq!((&*world.entities()).join().count(), 5);
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let errors = of_type(&messages, "error");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["data"]["id"], "CreateEntities");
+    assert_eq!(errors[0]["data"]["request_id"], 1);
+
+    assert_eq!(
+        acks(&messages),
+        vec![json!([1, "limit_exceeded"]), json!([2, "applied"])]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transactions_count_towards_the_frame_limit() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.limit_entity_commands(10, 10))
+        .tap(|bundle| bundle.transport(transport));
+
+    // Each transaction is within the limit on its own, but not together with the first one.
+    for request_id in 1..=2 {
+        editor.send_json(&json!({
+            "type": "Transaction",
+            "ops": [{ "type": "CreateEntities", "amount": 8 }],
+            "request_id": request_id,
+        }));
+    }
+
+    // A plain request is held back until the next frame instead.
+    editor.send(r#"{"type":"CreateEntities","amount":8,"request_id":3}"#);
+
+    let state = TestState::frames(10).check(|world| {
+        assert_eq!((&*world.entities()).join().count(), 16);
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let errors = of_type(&messages, "error");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["data"]["id"], "CreateEntities");
+
+    let transactions: Vec<_> = of_type(&messages, "transaction_ack")
+        .into_iter()
+        .map(|message| json!([message["data"]["request_id"], message["data"]["results"]]))
+        .collect();
+    assert_eq!(
+        transactions,
+        vec![json!([1, ["applied"]]), json!([2, ["limit_exceeded"]])]
+    );
+    assert_eq!(acks(&messages), vec![json!([3, "applied"])]);
+
+    Ok(())
+}
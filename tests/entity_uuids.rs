@@ -0,0 +1,38 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use tap::*;
+
+const SAVED_UUID: &str = "0b9c3a4e-7d1f-4c52-9a3e-5f0e2d8c1b7a";
+
+#[test]
+fn entities_are_given_uuids() -> amethyst::Result<()> {
+    let (_editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(SyncEditorBundle::assign_entity_uuids)
+        .tap(|bundle| bundle.transport(transport));
+
+    let state = TestState::frames(3)
+        .setup(|world| {
+            // One entity restored with the UUID it was saved with, and one new entity.
+            let saved = SAVED_UUID.parse::<EditorUuid>().expect("Invalid UUID");
+            world.create_entity().with(saved).build();
+            world.create_entity().build();
+        })
+        .check(|world| {
+            let uuids = world.read_storage::<EditorUuid>();
+            let mut uuids = (&uuids).join().map(ToString::to_string).collect::<Vec<_>>();
+            assert_eq!(uuids.len(), 2);
+            assert!(uuids.iter().any(|uuid| uuid == SAVED_UUID));
+            uuids.dedup();
+            assert_eq!(uuids.len(), 2);
+        });
+    run(editor_sync_bundle, state)
+}
@@ -0,0 +1,87 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate tap;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use serde::*;
+use std::time::{Duration, Instant};
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Health {
+    current: u32,
+    max: u32,
+}
+
+impl Component for Health {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Settings {
+    difficulty: String,
+}
+
+struct SoakState {
+    started: Instant,
+}
+
+impl SimpleState for SoakState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        data.world.register::<Health>();
+        data.world.add_resource(Settings::default());
+        for _ in 0..20 {
+            data.world
+                .create_entity()
+                .with(Health {
+                    current: 10,
+                    max: 10,
+                })
+                .build();
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<GameData>) -> SimpleTrans {
+        data.data.update(&data.world);
+
+        if self.started.elapsed() > Duration::from_secs(2) {
+            Trans::Quit
+        } else {
+            Trans::None
+        }
+    }
+}
+
+#[test]
+fn soak_test() -> amethyst::Result<()> {
+    let editor = FakeEditor::bind("127.0.0.1:0")?
+        .with_edit_rate(100.0)
+        .with_create_rate(10.0)
+        .with_destroy_rate(10.0)
+        .with_ack_timeout(Duration::from_secs(1));
+    let transport = Transport::udp("127.0.0.1:0")?.with_editor_address(editor.local_addr()?);
+    let editor = editor.spawn();
+
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Health))
+        .tap(|bundle| sync_resources!(bundle, Settings))
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(20)))
+        .tap(|bundle| bundle.transport(transport));
+
+    let game_data = GameDataBuilder::default().with_bundle(editor_sync_bundle)?;
+    let state = SoakState {
+        started: Instant::now(),
+    };
+    let mut game = Application::build(".", state)?.build(game_data)?;
+    game.run();
+
+    let report = editor.stop();
+    assert!(report.is_ok(), "{:#?}", report);
+    assert!(report.snapshots > 0);
+    assert!(report.acks > 0);
+
+    Ok(())
+}
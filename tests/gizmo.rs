@@ -0,0 +1,66 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate tap;
+
+mod common;
+
+use amethyst::core::Transform;
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use tap::*;
+
+#[test]
+fn transform_drag() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default().tap(|bundle| bundle.transport(transport));
+
+    editor.send(
+        r#"{"type":"TransformDrag","entity":{"id":0,"generation":1},"translation":[1.0,2.0,3.0]}"#,
+    );
+
+    let state = TestState::frames(10)
+        .setup(|world| {
+            world.register::<Transform>();
+            world.create_entity().with(Transform::default()).build();
+        })
+        .check(|world| {
+            let transforms = world.read_storage::<Transform>();
+            let translation = (&transforms).join().next().unwrap().translation();
+            assert_eq!(
+                [translation.x, translation.y, translation.z],
+                [1.0, 2.0, 3.0]
+            );
+        });
+    run(editor_sync_bundle, state)?;
+
+    // The drag never ends, so the transform keeps being echoed back.
+    let messages = editor.messages();
+    let echoes = of_type(&messages, "transform_echo");
+    assert!(!echoes.is_empty());
+    assert_eq!(echoes[0]["data"][0]["entity"]["id"], 0);
+
+    Ok(())
+}
+
+#[test]
+fn transform_drag_without_transforms() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default().tap(|bundle| bundle.transport(transport));
+
+    // The world has no `Transform` storage, so the drag is ignored.
+    editor.send(
+        r#"{"type":"TransformDrag","entity":{"id":0,"generation":1},"translation":[1.0,2.0,3.0]}"#,
+    );
+
+    let state = TestState::frames(10).setup(|world| {
+        world.create_entity().build();
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    assert!(of_type(&messages, "transform_echo").is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::core::timing::Time;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Counter {
+    value: usize,
+}
+
+/// Counts frames in the `Counter` resource, and rewinds it to an earlier frame partway through.
+struct RewindState {
+    frames: usize,
+    editor: MockEditor,
+
+    // The number of the recorded frame to rewind to, and the count on that frame.
+    recorded: Option<(u64, usize)>,
+}
+
+impl SimpleState for RewindState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        data.world.add_resource(Counter::default());
+    }
+
+    fn update(&mut self, data: &mut StateData<GameData>) -> SimpleTrans {
+        self.frames += 1;
+
+        // NOTE: The rewind is requested on frame 6, received on frame 7 and applied at the end of
+        // it, so by frame 8 the counter is back to its recorded value.
+        if self.frames == 8 {
+            let (_, count) = self.recorded.expect("No frame recorded");
+            assert_eq!(data.world.read_resource::<Counter>().value, count);
+            return Trans::Quit;
+        }
+
+        data.world.write_resource::<Counter>().value += 1;
+        data.data.update(&data.world);
+
+        if self.frames == 3 {
+            let frame = data.world.read_resource::<Time>().frame_number();
+            let count = data.world.read_resource::<Counter>().value;
+            self.recorded = Some((frame, count));
+        }
+
+        if self.frames == 6 {
+            let (frame, _) = self.recorded.expect("No frame recorded");
+            self.editor
+                .send_json(&json!({ "type": "RewindTo", "frame": frame }));
+        }
+
+        Trans::None
+    }
+}
+
+#[test]
+fn rewind_restores_recorded_frame() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_resources!(bundle, Counter))
+        .tap(|bundle| bundle.history(10))
+        .tap(|bundle| bundle.transport(transport));
+
+    let state = RewindState {
+        frames: 0,
+        editor,
+        recorded: None,
+    };
+    run(editor_sync_bundle, state)
+}
@@ -0,0 +1,90 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use tap::*;
+
+/// Appends the `len` least significant bytes of `value` to `out`, in big-endian order.
+fn push_be(value: u32, len: usize, out: &mut Vec<u8>) {
+    for shift in (0..len).rev() {
+        out.push((value >> (shift * 8)) as u8);
+    }
+}
+
+/// Frames `message` with a length prefix and splits it into `count` chunks with chunk headers.
+fn chunks(id: u32, message: &str, count: usize) -> Vec<Vec<u8>> {
+    let mut framed = Vec::new();
+    push_be(message.len() as u32, 4, &mut framed);
+    framed.extend_from_slice(message.as_bytes());
+
+    let chunk_size = (framed.len() + count - 1) / count;
+    framed
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(index, payload)| {
+            let mut packet = Vec::new();
+            push_be(id, 4, &mut packet);
+            push_be(index as u32, 2, &mut packet);
+            push_be(count as u32, 2, &mut packet);
+            packet.extend_from_slice(payload);
+            packet
+        })
+        .collect()
+}
+
+#[test]
+fn chunked_messages_are_reassembled() -> amethyst::Result<()> {
+    // Length prefixes and chunk headers are both the default.
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default().tap(|bundle| bundle.transport(transport));
+
+    // The first message's chunks arrive out of order, and the second message is missing its
+    // last chunk.
+    let first = chunks(
+        0,
+        r#"{"type":"CreateEntities","amount":5,"request_id":1}"#,
+        3,
+    );
+    let second = chunks(
+        1,
+        r#"{"type":"CreateEntities","amount":7,"request_id":2}"#,
+        3,
+    );
+    for packet in &[&first[2], &second[0], &first[0], &second[1], &first[1]] {
+        editor.send_packet(packet);
+    }
+
+    let state = TestState::frames(5).check(|world| {
+        // Only the messages whose chunks all arrived are applied.
+        let entities = (&*world.entities()).join().count();
+        assert_eq!(entities, 5);
+    });
+    run(editor_sync_bundle, state)
+}
+
+#[test]
+fn node_ipc_compatibility_mode() -> amethyst::Result<()> {
+    // Editors that delimit messages with a form feed don't send chunk headers either.
+    let (editor, transport) = MockEditor::with_framing(Framing::NodeIpc)?;
+    let editor_sync_bundle = SyncEditorBundle::default().tap(|bundle| bundle.transport(transport));
+
+    let mut packet = br#"{"type":"CreateEntities","amount":3,"request_id":1}"#.to_vec();
+    packet.push(0xC);
+    editor.send_packet(&packet);
+
+    let state = TestState::frames(5).check(|world| {
+        let entities = (&*world.entities()).join().count();
+        assert_eq!(entities, 3);
+    });
+    run(editor_sync_bundle, state)?;
+
+    assert_eq!(acks(&editor.messages()), vec![json!([1, "applied"])]);
+    Ok(())
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "inventory")]
+
+extern crate amethyst;
+#[macro_use]
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate tap;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::ext::Registration;
+use amethyst_editor_sync::*;
+use serde::*;
+use tap::*;
+
+#[derive(Serialize, Deserialize)]
+struct Foo;
+
+impl Component for Foo {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Serialize)]
+struct Bar;
+
+submit_registrations!(
+    Registration::sync_component::<Foo>("Foo"),
+    Registration::read_resource::<Bar>("Bar"),
+);
+
+#[test]
+fn sync_registered_types() {
+    let editor_bundle = SyncEditorBundle::default().tap(SyncEditorBundle::sync_registered_types);
+
+    let _ = GameDataBuilder::default().with_bundle(editor_bundle);
+}
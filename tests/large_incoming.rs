@@ -0,0 +1,94 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Runs a game with the given receive buffer size, after the editor has sent a packet setting
+/// the label's text to each of `texts` in turn, and checks that the label ends up with
+/// `expected` as its text.
+fn run_with_updates(
+    receive_buffer_size: Option<usize>,
+    texts: &[&str],
+    expected: &str,
+) -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| {
+            if let Some(size) = receive_buffer_size {
+                bundle.receive_buffer_size(size);
+            }
+        })
+        .tap(|bundle| bundle.transport(transport));
+
+    for text in texts {
+        editor.send_json(&json!({
+            "type": "ComponentUpdate",
+            "id": "Label",
+            "entity": { "id": 0, "generation": 1 },
+            "data": { "text": text },
+        }));
+    }
+
+    let expected = expected.to_owned();
+    let state = TestState::frames(10)
+        .setup(|world| {
+            world
+                .create_entity()
+                .with(Label {
+                    text: "original".into(),
+                })
+                .build();
+        })
+        .check(move |world| {
+            let labels = world.read_storage::<Label>();
+            let label = (&labels).join().next().unwrap();
+            assert_eq!(label.text, expected);
+        });
+    run(editor_sync_bundle, state)
+}
+
+#[test]
+fn multi_kilobyte_update_is_applied() -> amethyst::Result<()> {
+    let text = "x".repeat(16 * 1024);
+    run_with_updates(None, &[&text], &text)
+}
+
+#[test]
+fn update_larger_than_receive_buffer_is_lost() -> amethyst::Result<()> {
+    let text = "x".repeat(16 * 1024);
+    run_with_updates(Some(1024), &[&text], "original")
+}
+
+#[test]
+fn message_after_truncated_packet_is_applied() -> amethyst::Result<()> {
+    // The truncated packet is dropped entirely, so that what's left of it can't corrupt the
+    // next message.
+    let text = "x".repeat(16 * 1024);
+    run_with_updates(Some(1024), &[&text, "short"], "short")
+}
+
+#[test]
+fn tiny_receive_buffer_is_raised_to_the_minimum() -> amethyst::Result<()> {
+    run_with_updates(Some(0), &["short"], "short")
+}
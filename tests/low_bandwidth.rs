@@ -0,0 +1,328 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Runs the game for 10 frames with 100 labelled entities.
+fn labels() -> TestState {
+    TestState::frames(10).setup(|world| {
+        world.register::<Label>();
+        for index in 0..100 {
+            world
+                .create_entity()
+                .with(Label {
+                    text: format!("Entity with a fairly long label number {}", index),
+                })
+                .build();
+        }
+    })
+}
+
+#[test]
+fn low_bandwidth_profile() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(SyncEditorBundle::low_bandwidth_profile)
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    run(editor_sync_bundle, labels())?;
+
+    let packets = editor.packets();
+    assert!(!packets.is_empty());
+    assert!(packets.iter().all(|packet| packet.len() <= 512));
+
+    // The editor never said that it can decode MessagePack, so packets are still sent as JSON.
+    let bytes = editor.reassemble(&packets);
+    assert!(editor
+        .split(&bytes)
+        .iter()
+        .all(|message| message[0] == b'{'));
+
+    let envelopes = editor.decode(&bytes);
+    let snapshots = envelopes
+        .iter()
+        .map(|envelope| &envelope["data"])
+        .filter(|data| data.get("components").is_some())
+        .collect::<Vec<_>>();
+    assert!(!snapshots.is_empty());
+    for snapshot in &snapshots {
+        assert!(snapshot.get("entities").is_none());
+        assert!(snapshot["entity_count"].as_u64().is_some());
+        assert_eq!(snapshot["delta"], true);
+    }
+
+    // The labels never change, so they should only be sent in the first snapshot.
+    let label_count = snapshots
+        .iter()
+        .filter(|snapshot| !snapshot["components"].as_array().unwrap().is_empty())
+        .count();
+    assert_eq!(label_count, 1);
+
+    // Every packet is tagged with the frame it was sent on.
+    let frames = envelopes
+        .iter()
+        .map(|envelope| envelope["frame"].as_u64().expect("Packet without a frame"))
+        .collect::<Vec<_>>();
+    assert!(frames.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(envelopes
+        .iter()
+        .all(|envelope| envelope["absolute_time"].as_f64().is_some()));
+
+    Ok(())
+}
+
+#[test]
+fn binary_codec() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(SyncEditorBundle::low_bandwidth_profile)
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send_json(&json!({
+        "type": "Handshake",
+        "capabilities": ["msgpack", "deltas", "entity_summary", "edit_acks"],
+    }));
+    run(editor_sync_bundle, labels())?;
+
+    // The editor's handshake is read before anything is sent, so every packet is MessagePack.
+    let bytes = editor.reassemble(&editor.packets());
+    let messages = editor.split(&bytes);
+    assert!(!messages.is_empty());
+    assert!(messages.iter().all(|message| message[0] != b'{'));
+
+    let envelopes = editor.decode(&bytes);
+    let sent = envelopes
+        .iter()
+        .flat_map(|envelope| envelope["data"]["messages"].as_array().cloned())
+        .flatten()
+        .collect::<Vec<_>>();
+    let handshakes = of_type(&sent, "handshake");
+    assert!(!handshakes.is_empty());
+    for handshake in handshakes {
+        let capabilities = handshake["data"]["capabilities"].as_array().unwrap();
+        assert!(capabilities.contains(&json!("msgpack")));
+    }
+
+    let snapshots = envelopes
+        .iter()
+        .filter(|envelope| envelope["data"].get("components").is_some())
+        .count();
+    assert!(snapshots > 0);
+
+    Ok(())
+}
+
+#[test]
+fn split_snapshots() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label, Position))
+        .tap(|bundle| bundle.send_interval(Duration::from_secs(60)))
+        .tap(|bundle| bundle.send_frames(2))
+        .tap(|bundle| bundle.transport(transport));
+
+    // The full sync makes the game send a snapshot on the first frame, rather than after the
+    // send interval.
+    editor.send_json(&json!({ "type": "Handshake", "capabilities": ["deltas"] }));
+    editor.send_json(&json!({ "type": "RequestFullSync" }));
+    let state = TestState::frames(10).setup(|world| {
+        world.register::<Label>();
+        world.register::<Position>();
+        for index in 0..100 {
+            world
+                .create_entity()
+                .with(Label {
+                    text: format!("Entity with a fairly long label number {}", index),
+                })
+                .build();
+        }
+        for index in 0..10 {
+            world
+                .create_entity()
+                .with(Position {
+                    x: index as f32,
+                    y: 0.0,
+                })
+                .build();
+        }
+    });
+    run(editor_sync_bundle, state)?;
+
+    // The labels make up most of the snapshot, so they're sent on the first frame and the
+    // positions on the next.
+    let parts = editor
+        .envelopes()
+        .into_iter()
+        .filter(|envelope| envelope["data"].get("components").is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(parts.len(), 2);
+    assert_eq!(
+        parts[1]["frame"].as_u64().unwrap(),
+        parts[0]["frame"].as_u64().unwrap() + 1
+    );
+
+    let names = |part: &Value| {
+        part["data"]["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|component| component["name"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&parts[0]), vec!["Label"]);
+    assert_eq!(names(&parts[1]), vec!["Position"]);
+
+    // Only the first part carries the entity list, and both are marked as partial.
+    assert!(parts[0]["data"]["entities"].is_array());
+    assert!(parts[1]["data"].get("entities").is_none());
+    for part in &parts {
+        assert_eq!(part["data"]["delta"], true);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn chunk_headers() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.max_packet_size(256))
+        .tap(|bundle| bundle.chunk_headers(true))
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    run(editor_sync_bundle, labels())?;
+
+    let packets = editor.packets();
+    assert!(!packets.is_empty());
+    assert!(packets.iter().all(|packet| packet.len() <= 256));
+
+    // Reassemble the messages from the packets in reverse order, which would corrupt every
+    // message split across several packets if it weren't for the headers.
+    let mut chunks = BTreeMap::new();
+    for packet in packets.iter().rev() {
+        let id = read_be(&packet[0..4]);
+        let index = read_be(&packet[4..6]);
+        let count = read_be(&packet[6..8]);
+        let message = chunks.entry(id).or_insert_with(Vec::new);
+        message.push((index, count, &packet[8..]));
+    }
+
+    let mut snapshots = 0;
+    for (_, mut message) in chunks {
+        message.sort_by_key(|&(index, _, _)| index);
+        assert_eq!(message.len(), message[0].1 as usize);
+
+        let bytes = message
+            .iter()
+            .flat_map(|&(_, _, payload)| payload.iter().cloned())
+            .collect::<Vec<_>>();
+        let envelopes = editor.decode(&bytes);
+        assert_eq!(envelopes.len(), 1);
+        if envelopes[0]["data"].get("components").is_some() {
+            snapshots += 1;
+        }
+    }
+    assert!(snapshots > 0);
+
+    Ok(())
+}
+
+#[test]
+fn full_sync_resends_unchanged_state() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(SyncEditorBundle::low_bandwidth_profile)
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    // Asks the game for a full resync partway through.
+    let state_editor = editor.try_clone()?;
+    let state = labels().each_frame(move |frame, _| {
+        if frame == 2 {
+            state_editor.send_json(&json!({ "type": "RequestFullSync" }));
+        }
+    });
+    run(editor_sync_bundle, state)?;
+
+    // The labels never change, but they're sent again after the resync.
+    let label_count = editor
+        .snapshots()
+        .iter()
+        .filter(|snapshot| !snapshot["components"].as_array().unwrap().is_empty())
+        .count();
+    assert_eq!(label_count, 2);
+
+    Ok(())
+}
+
+#[test]
+fn send_rate_limit() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.max_packet_size(512))
+        .tap(|bundle| bundle.limit_send_rate(2000))
+        .tap(|bundle| bundle.send_transport_stats(Duration::from_millis(0)))
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    run(editor_sync_bundle, labels())?;
+
+    // Without the limit, every frame would send a snapshot of all 100 labels.
+    let packets = editor.packets();
+    let bytes = packets.iter().map(Vec::len).sum::<usize>();
+    assert!(bytes > 0);
+    assert!(bytes < 10_000, "Sent {} bytes", bytes);
+
+    let messages = editor
+        .decode(&editor.reassemble(&packets))
+        .into_iter()
+        .flat_map(|envelope| envelope["data"]["messages"].as_array().cloned())
+        .flatten()
+        .collect::<Vec<_>>();
+    let stats = of_type(&messages, "transport_stats");
+    let stats = stats.first().expect("No transport stats received");
+    assert_eq!(stats["data"]["rate_limit"], 2000);
+    assert!(stats["data"]["packets_sent"].as_u64().is_some());
+
+    Ok(())
+}
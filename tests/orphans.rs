@@ -0,0 +1,74 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::core::Named;
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {
+    type Storage = VecStorage<Self>;
+}
+
+#[test]
+fn orphans_are_found_and_purged() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Position))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"FindOrphans","request_id":1}"#);
+    editor.send(r#"{"type":"PurgeEntities","entities":[{"id":0,"generation":1},{"id":1,"generation":1}],"request_id":2}"#);
+
+    let state = TestState::frames(5)
+        .setup(|world| {
+            world.register::<Named>();
+
+            // Entity 0 is orphaned, entity 1 is anchored by its name and entity 2 has no
+            // registered components at all.
+            world
+                .create_entity()
+                .with(Position { x: 0.0, y: 0.0 })
+                .build();
+            world
+                .create_entity()
+                .with(Position { x: 1.0, y: 0.0 })
+                .with(Named::new("player"))
+                .build();
+            world.create_entity().build();
+        })
+        .check(|world| {
+            let entities = (&*world.entities()).join().count();
+            assert_eq!(entities, 2);
+        });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let orphans = of_type(&messages, "orphans");
+    let orphans = orphans.first().expect("No orphans message was sent");
+    assert_eq!(
+        orphans["data"],
+        json!({
+            "request_id": 1,
+            "entities": [{ "id": 0, "generation": 1, "components": ["Position"] }],
+        })
+    );
+    assert_eq!(acks(&messages), vec![json!([2, "applied"])]);
+
+    Ok(())
+}
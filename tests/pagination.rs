@@ -0,0 +1,90 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {
+    type Storage = VecStorage<Self>;
+}
+
+#[test]
+fn requested_page_is_sent() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Position))
+        .tap(|bundle| bundle.component_page_size(2))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"RequestComponentPage","id":"Position","page":1}"#);
+
+    let state = TestState::frames(5).setup(|world| {
+        for index in 0..5 {
+            world
+                .create_entity()
+                .with(Position {
+                    x: index as f32,
+                    y: 0.0,
+                })
+                .build();
+        }
+    });
+    run(editor_sync_bundle, state)?;
+
+    let components = editor
+        .snapshots()
+        .into_iter()
+        .filter_map(|snapshot| snapshot["components"].as_array().cloned())
+        .flatten()
+        .filter(|component| component["name"] == "Position")
+        .collect::<Vec<_>>();
+    let last = components.last().expect("No components were sent");
+    assert_eq!(last["page"], json!({ "index": 1, "size": 2, "total": 5 }));
+
+    let xs = last["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["data"]["x"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(xs, vec![json!(2.0), json!(3.0)]);
+
+    Ok(())
+}
+
+#[test]
+fn page_of_unknown_component() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Position))
+        .tap(|bundle| bundle.component_page_size(2))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"RequestComponentPage","id":"Position","page":1,"request_id":1}"#);
+    editor.send(r#"{"type":"RequestComponentPage","id":"Missing","page":1,"request_id":2}"#);
+    run(editor_sync_bundle, TestState::frames(5))?;
+
+    let messages = editor.messages();
+    assert_eq!(
+        acks(&messages),
+        vec![json!([1, "applied"]), json!([2, "unknown_type"])]
+    );
+
+    Ok(())
+}
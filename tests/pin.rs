@@ -0,0 +1,64 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Moves every entity upwards each frame.
+struct RiseSystem;
+
+impl<'a> System<'a> for RiseSystem {
+    type SystemData = WriteStorage<'a, Position>;
+
+    fn run(&mut self, mut positions: Self::SystemData) {
+        for position in (&mut positions).join() {
+            position.x += 1.0;
+            position.y += 1.0;
+        }
+    }
+}
+
+#[test]
+fn pinned_field_keeps_its_value() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Position))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"PinComponentField","entity":{"id":0,"generation":1},"component":"Position","path":"y","value":0.0}"#);
+
+    let state = TestState::frames(10)
+        .setup(|world| {
+            world
+                .create_entity()
+                .with(Position { x: 0.0, y: 0.0 })
+                .build();
+        })
+        .check(|world| {
+            // The pinned field should stay put while the other one keeps rising.
+            let positions = world.read_storage::<Position>();
+            let position = (&positions).join().next().expect("No position");
+            assert_eq!(position.y, 0.0);
+            assert!(position.x > 5.0);
+        });
+    let game_data = GameDataBuilder::default().with(RiseSystem, "rise", &[]);
+    run_with(game_data, editor_sync_bundle, state)
+}
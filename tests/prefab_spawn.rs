@@ -0,0 +1,115 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::assets::{Handle, Prefab, PrefabData, PrefabError, PrefabLoaderSystem};
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::{json, Value};
+use std::thread;
+use std::time::Duration;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<'a> PrefabData<'a> for Label {
+    type SystemData = WriteStorage<'a, Label>;
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        labels: &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<(), PrefabError> {
+        labels.insert(entity, self.clone())?;
+        Ok(())
+    }
+}
+
+/// Spawns the prefab at `path`, checks the world with `check` once the game has run for a while,
+/// and returns the messages sent back to the editor.
+fn spawn<F>(path: &str, check: F) -> amethyst::Result<Vec<Value>>
+where
+    F: FnMut(&mut World) + 'static,
+{
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.spawn_prefabs::<Label>())
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(&format!(
+        r#"{{"type":"SpawnPrefab","path":"{}","request_id":1}}"#,
+        path
+    ));
+
+    // NOTE: Prefabs are loaded on the asset thread pool, so the frames are slowed down to give
+    // the load time to finish.
+    let state = TestState::frames(50)
+        .each_frame(|_, _| thread::sleep(Duration::from_millis(5)))
+        .check(check);
+
+    let game_data = GameDataBuilder::default().with(
+        PrefabLoaderSystem::<Label>::default(),
+        "prefab_loader",
+        &[],
+    );
+    run_with(game_data, editor_sync_bundle, state)?;
+
+    Ok(editor.messages())
+}
+
+#[test]
+fn spawn_prefab() -> amethyst::Result<()> {
+    let messages = spawn("tests/prefabs/labels.ron", |world| {
+        let labels = world.read_storage::<Label>();
+        let mut texts: Vec<_> = labels.join().map(|label| label.text.as_str()).collect();
+        texts.sort();
+        assert_eq!(texts, vec!["child", "grandchild", "root"]);
+    })?;
+
+    // The ack is only sent once the prefab has been instantiated, along with all of its entities.
+    assert_eq!(acks(&messages), vec![json!([1, "applied"])]);
+    let spawned = of_type(&messages, "prefab_spawned");
+    assert_eq!(spawned.len(), 1);
+    assert_eq!(spawned[0]["data"]["request_id"], 1);
+    assert_eq!(spawned[0]["data"]["entities"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        spawned[0]["data"]["entities"][0],
+        spawned[0]["data"]["entity"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn spawn_missing_prefab() -> amethyst::Result<()> {
+    // The entity created for the prefab is deleted again once loading fails.
+    let messages = spawn("tests/prefabs/missing.ron", |world| {
+        let handles = world.read_storage::<Handle<Prefab<Label>>>();
+        assert_eq!(handles.join().count(), 0);
+    })?;
+
+    assert_eq!(acks(&messages), vec![json!([1, "failed"])]);
+    assert!(of_type(&messages, "prefab_spawned").is_empty());
+
+    let errors = of_type(&messages, "error");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["data"]["path"], "tests/prefabs/missing.ron");
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use std::env;
+use tap::*;
+
+#[test]
+fn export_paths_outside_dir_are_rejected() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let dir = env::temp_dir().join("amethyst-editor-sync-scene-export");
+
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.scene_export_dir(&dir))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ExportScene","path":"../escaped.ron","entities":[],"request_id":1}"#);
+    editor.send(r#"{"type":"ExportScene","path":"/tmp/escaped.ron","entities":[],"request_id":2}"#);
+
+    run(editor_sync_bundle, TestState::frames(10))?;
+    let messages = editor.messages();
+    assert_eq!(
+        acks(&messages),
+        vec![json!([1, "invalid_data"]), json!([2, "invalid_data"])]
+    );
+    assert_eq!(of_type(&messages, "scene_export").len(), 2);
+    assert!(!dir.join("../escaped.ron").exists());
+
+    Ok(())
+}
+
+#[test]
+fn scenes_are_exported_into_dir() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let dir = env::temp_dir().join("amethyst-editor-sync-scene-export");
+    std::fs::create_dir_all(&dir)?;
+
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.scene_export_dir(&dir))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ExportScene","path":"empty.ron","entities":[],"request_id":1}"#);
+
+    run(editor_sync_bundle, TestState::frames(10))?;
+    assert_eq!(acks(&editor.messages()), vec![json!([1, "applied"])]);
+    assert!(dir.join("empty.ron").exists());
+
+    Ok(())
+}
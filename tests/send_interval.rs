@@ -0,0 +1,50 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use std::time::Duration;
+use tap::*;
+
+#[test]
+fn editor_changes_send_interval() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+
+    // The configured interval is far longer than the test runs, so any snapshots received were
+    // sent because of the editor's request.
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.send_interval(Duration::from_secs(60 * 60)))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"SetSendInterval","milliseconds":0}"#);
+
+    run(editor_sync_bundle, TestState::frames(10))?;
+    assert!(editor.snapshots().len() > 1);
+
+    Ok(())
+}
+
+#[test]
+fn huge_send_interval_is_rejected() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.send_interval(Duration::from_millis(0)))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(&format!(
+        r#"{{"type":"SetSendInterval","milliseconds":{},"request_id":1}}"#,
+        u64::max_value(),
+    ));
+
+    run(editor_sync_bundle, TestState::frames(10))?;
+    let messages = editor.messages();
+    assert_eq!(acks(&messages), vec![json!([1, "invalid_data"])]);
+
+    Ok(())
+}
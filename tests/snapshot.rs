@@ -0,0 +1,56 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+use amethyst::ecs::*;
+use amethyst_editor_sync::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Player {
+    name: String,
+    token: String,
+}
+
+impl Component for Player {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Score(u32);
+
+#[test]
+fn capture_snapshot() {
+    let mut world = World::new();
+    world.register::<Player>();
+    world.add_resource(Score(12));
+    let player = world
+        .create_entity()
+        .with(Player {
+            name: "player".into(),
+            token: "secret".into(),
+        })
+        .build();
+    let empty = world.create_entity().build();
+
+    let bundle = SyncEditorBundle::new().tap(|bundle| {
+        bundle.sync_component::<Player>("Player");
+        bundle.redact_fields("Player", &["token"]);
+        bundle.sync_resource::<Score>("Score");
+        bundle.read_resource::<String>("Missing");
+    });
+    let snapshot = bundle.capture_snapshot(&world);
+
+    assert_eq!(snapshot.entities.len(), 2);
+    assert_eq!(
+        snapshot.component("Player", player),
+        Some(&json!({ "name": "player" })),
+    );
+    assert_eq!(snapshot.component("Player", empty), None);
+    assert_eq!(snapshot.resource("Score"), Some(&json!(12)));
+    assert_eq!(snapshot.resource("Missing"), None);
+}
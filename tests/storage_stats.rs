@@ -0,0 +1,72 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use std::time::Duration;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {
+    type Storage = VecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Selected;
+
+impl Component for Selected {
+    type Storage = FlaggedStorage<Self, NullStorage<Self>>;
+}
+
+#[test]
+fn storage_stats_are_reported() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Position, Selected))
+        .tap(|bundle| bundle.report_storage_stats(Duration::from_secs(60)))
+        .tap(|bundle| bundle.transport(transport));
+
+    let state = TestState::frames(3).setup(|world| {
+        for index in 0..3 {
+            world
+                .create_entity()
+                .with(Position {
+                    x: index as f32,
+                    y: 0.0,
+                })
+                .build();
+        }
+        world.create_entity().with(Selected).build();
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    let reports = of_type(&messages, "storage_stats");
+
+    // The report is only sent once per interval.
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0]["data"]["entities"], 4);
+    assert_eq!(
+        reports[0]["data"]["components"],
+        json!([
+            { "name": "Position", "storage": "VecStorage", "count": 3 },
+            { "name": "Selected", "storage": "FlaggedStorage<NullStorage>", "count": 1 },
+        ])
+    );
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::core::timing::Time;
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+impl Component for Label {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The frames on which `WatchLabelSystem` first ran and first saw the edited label.
+#[derive(Debug, Default)]
+struct Sightings {
+    first_run: Option<u64>,
+    first_edited: Option<u64>,
+}
+
+struct WatchLabelSystem;
+
+impl<'a> System<'a> for WatchLabelSystem {
+    type SystemData = (ReadStorage<'a, Label>, Read<'a, Time>, Write<'a, Sightings>);
+
+    fn run(&mut self, (labels, time, mut sightings): Self::SystemData) {
+        let frame = time.frame_number();
+        sightings.first_run.get_or_insert(frame);
+        if sightings.first_edited.is_none() && labels.join().any(|label| label.text == "edited") {
+            sightings.first_edited = Some(frame);
+        }
+    }
+}
+
+#[test]
+fn edits_are_seen_in_the_same_frame_at_frame_start() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.apply_edits_at(SyncPoint::FrameStart))
+        .tap(|bundle| bundle.transport(transport));
+
+    // NOTE: The update arrives before the first frame, so it's read by the first frame's
+    // receiver.
+    editor.send(r#"{"type":"ComponentUpdate","id":"Label","entity":{"id":0,"generation":1},"data":{"text":"edited"}}"#);
+
+    let state = TestState::frames(5)
+        .setup(|world| {
+            world.add_resource(Sightings::default());
+            world
+                .create_entity()
+                .with(Label {
+                    text: "original".into(),
+                })
+                .build();
+        })
+        .check(|world| {
+            let sightings = world.read_resource::<Sightings>();
+            assert!(sightings.first_run.is_some());
+            assert_eq!(sightings.first_edited, sightings.first_run);
+        });
+
+    // NOTE: The bundle goes before the game's systems, so that its systems can run first.
+    let game_data = GameDataBuilder::default()
+        .with_bundle(editor_sync_bundle)?
+        .with(WatchLabelSystem, "watch_label", &[]);
+    let mut game = Application::build(".", state)?.build(game_data)?;
+    game.run();
+
+    Ok(())
+}
+
+#[test]
+fn edits_are_deferred_to_the_next_frame_at_frame_end() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Label))
+        .tap(|bundle| bundle.apply_edits_at(SyncPoint::FrameEnd))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ComponentUpdate","id":"Label","entity":{"id":0,"generation":1},"data":{"text":"edited"},"request_id":1}"#);
+
+    let state = TestState::frames(5)
+        .setup(|world| {
+            world.add_resource(Sightings::default());
+            world
+                .create_entity()
+                .with(Label {
+                    text: "original".into(),
+                })
+                .build();
+        })
+        .check(|world| {
+            // The edit is applied when the world is maintained at the end of the first frame.
+            let sightings = world.read_resource::<Sightings>();
+            assert_eq!(
+                sightings.first_edited,
+                sightings.first_run.map(|frame| frame + 1)
+            );
+        });
+
+    let game_data = GameDataBuilder::default().with(WatchLabelSystem, "watch_label", &[]);
+    run_with(game_data, editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    assert_eq!(acks(&messages), vec![json!([1, "applied"])]);
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst::ecs::*;
+use amethyst::prelude::*;
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde::*;
+use serde_json::json;
+use tap::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {
+    type Storage = VecStorage<Self>;
+}
+
+#[test]
+fn disable_sync() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| sync_components!(bundle, Position))
+        .tap(|bundle| bundle.transport(transport));
+
+    // Only registered types can be turned off.
+    editor.send(r#"{"type":"DisableSync","id":"Position","request_id":1}"#);
+    editor.send(r#"{"type":"DisableSync","id":"Missing","request_id":2}"#);
+    editor.send(r#"{"type":"EnableSync","id":"Missing","request_id":3}"#);
+
+    let state = TestState::frames(5).setup(|world| {
+        world
+            .create_entity()
+            .with(Position { x: 1.0, y: 2.0 })
+            .build();
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    assert_eq!(
+        acks(&messages),
+        vec![
+            json!([1, "applied"]),
+            json!([2, "unknown_type"]),
+            json!([3, "unknown_type"]),
+        ]
+    );
+
+    let snapshots = editor.snapshots();
+    let last = snapshots.last().expect("No snapshots were sent");
+    assert!(last["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|component| component["name"] != "Position"));
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+extern crate amethyst;
+extern crate amethyst_editor_sync;
+extern crate serde_json;
+extern crate tap;
+
+mod common;
+
+use amethyst_editor_sync::*;
+use crate::common::*;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tap::*;
+
+#[test]
+fn strict_policy_records_errors() -> amethyst::Result<()> {
+    let (editor, transport) = MockEditor::new()?;
+    let editor_sync_bundle = SyncEditorBundle::default()
+        .tap(|bundle| bundle.unknown_type_policy(UnknownTypePolicy::Strict))
+        .tap(|bundle| bundle.transport(transport));
+
+    editor.send(r#"{"type":"ResourceUpdate","id":"Missing","data":{},"request_id":1}"#);
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let recorded = errors.clone();
+    let state = TestState::frames(5).each_frame(move |_, world| {
+        let mut status = world.write_resource::<EditorConnectionStatus>();
+        recorded.lock().unwrap().extend(status.take_errors());
+    });
+    run(editor_sync_bundle, state)?;
+
+    let messages = editor.messages();
+    assert_eq!(acks(&messages), vec![json!([1, "unknown_type"])]);
+    assert_eq!(of_type(&messages, "diagnostic").len(), 1);
+
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains(r#"unknown resource "Missing""#));
+
+    Ok(())
+}